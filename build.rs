@@ -0,0 +1,20 @@
+// Hands the commit built from to the diagnostics, where the source is a git
+// checkout (a build from a package has none, and says so)
+use std::{path::Path, process::Command};
+
+fn main() {
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output();
+    if let Ok(output) = output {
+        if output.status.success() {
+            let hash = String::from_utf8_lossy(&output.stdout);
+            println!("cargo:rustc-env=NOLP_GIT_HASH={}", hash.trim());
+        }
+    }
+    // Built again when the checkout moves on, rather than on every change
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,231 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the exchanges of AT commands with a modem, each command
+* sent and the final result code received for it (or its timeout). The data is
+* split into lines as it arrives, so that a reply split across reads is still
+* read whole, and the lines received between exchanges are unsolicited.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Ok,
+    Error,
+    // The error with its code, as in `+CME ERROR: 10` (or `+CMS ERROR` for
+    // the SMS commands)
+    CmeError(String),
+    Timeout,
+    // Another command was sent before the result arrived
+    Interrupted,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exchange {
+    pub command: String,
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AtEvent {
+    Done(Exchange),
+    // A line received while no command was waiting (e.g., `+CREG: 1`)
+    Urc(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AtSession {
+    // The command waiting for its result, when it was sent, and whether its
+    // echo has been read
+    pending: Option<(String, Instant, bool)>,
+    // What arrived since the last line break
+    line: Vec<u8>,
+    history: VecDeque<Exchange>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const HISTORY_LENGTH: usize = 16;
+const LINE_LIMIT: usize = 512;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Verdict {
+    pub fn get_label(&self) -> String {
+        match self {
+            Verdict::Ok => String::from("OK"),
+            Verdict::Error => String::from("ERROR"),
+            Verdict::CmeError(code) => code.clone(),
+            Verdict::Timeout => String::from("TIMEOUT"),
+            Verdict::Interrupted => String::from("NO RESULT"),
+        }
+    }
+}
+
+impl AtSession {
+    pub fn get_history(&self) -> &VecDeque<Exchange> {
+        return &self.history;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        return self.pending.is_some();
+    }
+
+    // The command still waiting is ended first, since its result can no
+    // longer be told from that of the next
+    pub fn send(&mut self, command: &str, now: Instant) -> Option<Exchange> {
+        let interrupted = self.finish(Verdict::Interrupted, now);
+        self.pending = Some((String::from(command.trim()), now, false));
+        return interrupted;
+    }
+
+    pub fn check(&mut self, now: Instant, timeout: Duration) -> Option<Exchange> {
+        let (_, sent, _) = self.pending.as_ref()?;
+        if now.duration_since(*sent) < timeout {
+            return None;
+        }
+        return self.finish(Verdict::Timeout, now);
+    }
+
+    pub fn update(&mut self, data: &[u8], now: Instant) -> Vec<AtEvent> {
+        let mut events = Vec::new();
+        for value in data.iter().copied() {
+            if value != b'\r' && value != b'\n' {
+                if self.line.len() < LINE_LIMIT {
+                    self.line.push(value);
+                }
+                continue;
+            }
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.line)).trim().to_string();
+            if line.len() > 0 {
+                events.extend(self.read_line(line, now));
+            }
+        }
+        return events;
+    }
+
+    fn read_line(&mut self, line: String, now: Instant) -> Option<AtEvent> {
+        let (command, _, echoed) = match self.pending.as_mut() {
+            Some(pending) => pending,
+            // An echo that arrives late is not unsolicited either
+            None if is_command(&line) => return None,
+            None => return Some(AtEvent::Urc(line)),
+        };
+        // The echo of the command (with ATE1) is read once, so that it is
+        // not taken as a command of its own
+        if *echoed == false && line.eq_ignore_ascii_case(command) {
+            *echoed = true;
+            return None;
+        }
+        let verdict = match line.as_str() {
+            "OK" => Verdict::Ok,
+            "ERROR" => Verdict::Error,
+            _ if line.starts_with("+CME ERROR") || line.starts_with("+CMS ERROR") => Verdict::CmeError(line),
+            // The information of the reply (e.g., `+CSQ: 20,99`)
+            _ => return None,
+        };
+        return self.finish(verdict, now).map(AtEvent::Done);
+    }
+
+    fn finish(&mut self, verdict: Verdict, now: Instant) -> Option<Exchange> {
+        let (command, sent, _) = self.pending.take()?;
+        let exchange = Exchange {
+            command,
+            verdict,
+            elapsed: now.duration_since(sent),
+        };
+        self.history.push_back(exchange.clone());
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        return Some(exchange);
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn is_command(input: &str) -> bool {
+    let input = input.trim_start().as_bytes();
+    return input.len() >= 2 && input[..2].eq_ignore_ascii_case(b"AT");
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_command() {
+        assert!(is_command("AT"));
+        assert!(is_command("at+csq"));
+        assert!(is_command("  ATI"));
+        assert!(is_command("A") == false);
+        assert!(is_command("help") == false);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut session = AtSession::default();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // The echo and the information are part of the reply, which may be
+        // split across reads
+        assert_eq!(session.send("AT+CSQ", at(0)), None);
+        assert_eq!(session.update(b"AT+CSQ\r\r\n+CSQ: 20,99\r\n\r\nO", at(10)), vec![]);
+        let events = session.update(b"K\r\n", at(48));
+        let exchange = Exchange {
+            command: String::from("AT+CSQ"),
+            verdict: Verdict::Ok,
+            elapsed: Duration::from_millis(48),
+        };
+        assert_eq!(events, vec![AtEvent::Done(exchange)]);
+
+        // Lines between the exchanges are unsolicited, a late echo is not
+        let events = session.update(b"\r\n+CREG: 1\r\nRING\r\nAT+CSQ\r\n", at(60));
+        let urcs = vec![AtEvent::Urc(String::from("+CREG: 1")), AtEvent::Urc(String::from("RING"))];
+        assert_eq!(events, urcs);
+
+        // An echo is only skipped once, so a command echoed twice is not
+        // counted again, and the codes of an error are kept
+        session.send("at+cpin?", at(100));
+        let events = session.update(b"AT+CPIN?\r\nAT+CPIN?\r\n+CME ERROR: 10\r\n", at(130));
+        assert_eq!(events.len(), 1);
+        assert_eq!(session.get_history()[1].verdict, Verdict::CmeError(String::from("+CME ERROR: 10")));
+
+        session.send("AT+COPS=?", at(200));
+        assert_eq!(session.check(at(1000), Duration::from_secs(1)), None);
+        assert_eq!(session.check(at(1200), Duration::from_secs(1)).unwrap().verdict, Verdict::Timeout);
+        assert!(session.is_pending() == false);
+
+        session.send("ATD123;", at(2000));
+        let interrupted = session.send("ATH", at(2500)).unwrap();
+        assert_eq!((interrupted.verdict, interrupted.elapsed), (Verdict::Interrupted, Duration::from_millis(500)));
+        assert_eq!(session.update(b"ERROR\r\n", at(2510)).len(), 1);
+        assert_eq!(session.get_history().len(), 5);
+    }
+}
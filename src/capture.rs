@@ -0,0 +1,377 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the session capture, which writes the data sent and
+* received while connected to a file, on a thread of its own so that a slow
+* disk never stalls the terminal.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::{anyhow, Result};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::common::*;
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Debug)]
+pub struct Capture {
+    path: String,
+    sender: Sender<CaptureRecord>,
+    handle: thread::JoinHandle<io::Result<()>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureSource {
+    Rx,
+    Tx,
+    Bridge,
+    // A note on the session, rather than data (e.g., a manual clear)
+    Marker,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Internal Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+struct CaptureRecord {
+    time: SystemTime,
+    source: CaptureSource,
+    data: Vec<u8>,
+}
+
+// What is carried between records in the text format
+#[derive(Clone, Debug, PartialEq)]
+struct TextState {
+    source: Option<CaptureSource>,
+    line_start: bool,
+    after_cr: bool,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Capture {
+    pub fn get_path(&self) -> &str {
+        return &self.path;
+    }
+
+    pub fn record(&self, source: CaptureSource, data: &[u8]) {
+        // The writer only stops early on an error, reported once stopped
+        let record = CaptureRecord {
+            time: SystemTime::now(),
+            source,
+            data: data.to_vec(),
+        };
+        let _ = self.sender.send(record);
+    }
+
+    pub fn mark(&self, marker: &str) {
+        self.record(CaptureSource::Marker, marker.as_bytes());
+    }
+
+    pub fn stop(self) -> Result<String> {
+        // Whatever is still queued is written before the thread ends
+        drop(self.sender);
+        match self.handle.join() {
+            Ok(Ok(())) => return Ok(self.path),
+            Ok(Err(e)) => return Err(anyhow!("{}", e)),
+            Err(_) => return Err(anyhow!("Capture writer panicked")),
+        }
+    }
+}
+
+impl Default for TextState {
+    fn default() -> TextState {
+        TextState {
+            source: None,
+            line_start: true,
+            after_cr: false,
+        }
+    }
+}
+
+impl fmt::Display for CaptureSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureSource::Rx => write!(f, "RX"),
+            CaptureSource::Tx => write!(f, "TX"),
+            CaptureSource::Bridge => write!(f, "BRIDGE"),
+            CaptureSource::Marker => write!(f, "MARKER"),
+        }
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn start_capture(parameters: &PortParameters) -> Result<Capture> {
+    let path = match &parameters.capture_path {
+        Some(p) => p.clone(),
+        None => get_default_path(parameters, SystemTime::now(), "log"),
+    };
+    let file = File::create(&path).map_err(|e| anyhow!("{}: {}", path, e))?;
+    let format = parameters.capture_format.clone().unwrap_or(CaptureFormat::Text);
+    let (sender, receiver) = channel();
+    let handle = thread::spawn(move || write_records(BufWriter::new(file), receiver, format));
+    return Ok(Capture {
+        path,
+        sender,
+        handle,
+    });
+}
+
+pub fn get_default_path(parameters: &PortParameters, time: SystemTime, extension: &str) -> String {
+    // Named after the last part of the port (e.g., `ttyUSB0` of
+    // `/dev/ttyUSB0`), which may not be valid in a file name as is
+    let name = parameters.name.clone().unwrap_or_default();
+    let port: String = name
+        .rsplit(['/', '\\'])
+        .find(|part| part.len() > 0)
+        .unwrap_or("port")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+        .collect();
+    let (year, month, day, millis) = get_date(time);
+    let seconds = millis / 1000;
+    return format!(
+        "nolp-{}-{:04}{:02}{:02}-{:02}{:02}{:02}.{}",
+        port,
+        year,
+        month,
+        day,
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        extension
+    );
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_date(time: SystemTime) -> (u64, u64, u64, u64) {
+    // Days since the epoch to the civil date (H. Hinnant's algorithm), and
+    // the milliseconds into the day, all in UTC
+    let millis = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    let days = millis / 86_400_000;
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    return (year, month, day, millis % 86_400_000);
+}
+
+pub fn get_millis(timestamp: &str) -> Option<u64> {
+    // The time of a log line since the epoch (the inverse of the timestamp
+    // it was written with), where anything before the epoch is invalid
+    let field = |start: usize, end: usize| timestamp.get(start..end)?.parse::<u64>().ok();
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':'), (19, b'.'), (23, b'Z')];
+    let bytes = timestamp.as_bytes();
+    if bytes.len() != 24 || separators.iter().any(|(i, c)| bytes[*i] != *c) {
+        return None;
+    }
+    let (year, month, day) = (field(0, 4)?, field(5, 7)?, field(8, 10)?);
+    if year < 1970 || (1..=12).contains(&month) == false || (1..=31).contains(&day) == false {
+        return None;
+    }
+    let (hours, minutes, seconds, millis) = (field(11, 13)?, field(14, 16)?, field(17, 19)?, field(20, 23)?);
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    // The civil date to days since the epoch (H. Hinnant's algorithm)
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * month + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    return Some(days * 86_400_000 + ((hours * 60 + minutes) * 60 + seconds) * 1000 + millis);
+}
+
+fn get_log_line(record: &CaptureRecord) -> String {
+    let bytes: Vec<String> = match record.source {
+        CaptureSource::Marker => vec![String::from_utf8_lossy(&record.data).to_string()],
+        _ => record.data.iter().map(|b| format!("{:02X}", b)).collect(),
+    };
+    return format!(
+        "{} {} {}\n",
+        get_timestamp(record.time),
+        record.source,
+        bytes.join(" ")
+    );
+}
+
+fn get_text(record: &CaptureRecord, state: &mut TextState) -> Vec<u8> {
+    // Written as the Ascii text is shown, with the sent data on lines of its
+    // own and any other control byte as a dot
+    let mut text = Vec::new();
+    if state.source != Some(record.source) && state.source.is_some() && state.line_start == false {
+        text.push(b'\n');
+        state.line_start = true;
+    }
+    if record.source == CaptureSource::Marker {
+        // On a line of its own, as it is shown
+        if state.line_start == false {
+            text.push(b'\n');
+        }
+        text.extend_from_slice(&record.data);
+        text.push(b'\n');
+        state.line_start = true;
+        state.source = None;
+        return text;
+    }
+    state.source = Some(record.source);
+    for value in record.data.iter().copied() {
+        let after_cr = state.after_cr;
+        state.after_cr = value == b'\r';
+        match value {
+            b'\n' if after_cr => continue,
+            b'\r' | b'\n' => text.push(b'\n'),
+            b'\t' | 32..=126 => text.push(value),
+            _ => text.push(b'.'),
+        }
+        state.line_start = value == b'\r' || value == b'\n';
+    }
+    return text;
+}
+
+pub fn get_timestamp(time: SystemTime) -> String {
+    let (year, month, day, millis) = get_date(time);
+    let seconds = millis / 1000;
+    return format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        millis % 1000
+    );
+}
+
+fn write_records(
+    mut writer: impl Write,
+    receiver: Receiver<CaptureRecord>,
+    format: CaptureFormat,
+) -> io::Result<()> {
+    let mut state = TextState::default();
+    let mut write = |writer: &mut dyn Write, record: CaptureRecord| match format {
+        CaptureFormat::Text => writer.write_all(&get_text(&record, &mut state)),
+        CaptureFormat::Log => writer.write_all(get_log_line(&record).as_bytes()),
+    };
+    // Flushed whenever the queue runs dry, so the file stays current
+    while let Ok(record) = receiver.recv() {
+        write(&mut writer, record)?;
+        while let Ok(record) = receiver.try_recv() {
+            write(&mut writer, record)?;
+        }
+        writer.flush()?;
+    }
+    return writer.flush();
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(source: CaptureSource, data: &[u8]) -> CaptureRecord {
+        return CaptureRecord {
+            time: UNIX_EPOCH + Duration::from_millis(1_792_238_645_123),
+            source,
+            data: data.to_vec(),
+        };
+    }
+
+    #[test]
+    fn test_get_timestamp() {
+        assert_eq!(get_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let time = UNIX_EPOCH + Duration::from_millis(951_782_400_000);
+        assert_eq!(get_timestamp(time), "2000-02-29T00:00:00.000Z");
+        let time = record(CaptureSource::Rx, b"").time;
+        assert_eq!(get_timestamp(time), "2026-10-17T12:04:05.123Z");
+
+        let mut parameters = PortParameters::default().name(String::from("/dev/ttyUSB0"));
+        assert_eq!(get_default_path(&parameters, time, "log"), "nolp-ttyUSB0-20261017-120405.log");
+        parameters.name = Some(String::from("tcp://localhost:2000"));
+        assert_eq!(get_default_path(&parameters, time, "log"), "nolp-localhost-2000-20261017-120405.log");
+    }
+
+    #[test]
+    fn test_get_millis() {
+        // Read back as written, a leap day included
+        for millis in [0, 951_782_400_000, 951_868_799_999, 1_792_238_645_123] {
+            let time = UNIX_EPOCH + Duration::from_millis(millis);
+            assert_eq!(get_millis(&get_timestamp(time)), Some(millis));
+        }
+        assert_eq!(get_millis("2026-10-17T12:04:05.123"), None);
+        assert_eq!(get_millis("2026-13-17T12:04:05.123Z"), None);
+        assert_eq!(get_millis("1969-12-31T23:59:59.999Z"), None);
+        assert_eq!(get_millis("2026-10-17 12:04:05.123Z"), None);
+    }
+
+    #[test]
+    fn test_get_log_line() {
+        let line = get_log_line(&record(CaptureSource::Tx, b"AT\r"));
+        assert_eq!(line, "2026-10-17T12:04:05.123Z TX 41 54 0D\n");
+        let line = get_log_line(&record(CaptureSource::Marker, b"<CLEARED>"));
+        assert_eq!(line, "2026-10-17T12:04:05.123Z MARKER <CLEARED>\n");
+    }
+
+    #[test]
+    fn test_get_text() {
+        let mut state = TextState::default();
+        assert_eq!(get_text(&record(CaptureSource::Tx, b"AT"), &mut state), b"AT");
+        assert_eq!(get_text(&record(CaptureSource::Rx, b"OK\r"), &mut state), b"\nOK\n");
+        assert_eq!(get_text(&record(CaptureSource::Rx, b"\n\x1B!"), &mut state), b".!");
+        assert_eq!(get_text(&record(CaptureSource::Rx, b"\n"), &mut state), b"\n");
+        assert_eq!(get_text(&record(CaptureSource::Tx, b"q"), &mut state), b"q");
+
+        // A marker is put on a line of its own
+        assert_eq!(get_text(&record(CaptureSource::Marker, b"<CLEARED>"), &mut state), b"\n<CLEARED>\n");
+        assert_eq!(get_text(&record(CaptureSource::Rx, b"ok"), &mut state), b"ok");
+    }
+
+    #[test]
+    fn test_write_records() {
+        let (sender, receiver) = channel();
+        sender.send(record(CaptureSource::Rx, b"\x01")).unwrap();
+        sender.send(record(CaptureSource::Bridge, b"\xFF")).unwrap();
+        drop(sender);
+
+        let mut output = Vec::new();
+        write_records(&mut output, receiver, CaptureFormat::Log).unwrap();
+        let expected = "2026-10-17T12:04:05.123Z RX 01\n2026-10-17T12:04:05.123Z BRIDGE FF\n";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
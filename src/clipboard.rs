@@ -0,0 +1,142 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the copy of a text to the system clipboard, through the
+* terminal (an OSC 52 sequence, which reaches the clipboard of the machine the
+* terminal runs on, even over SSH) and through the clipboard command of the
+* platform, where either one is enough.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::{bail, Result};
+use crossterm::{execute, style::Print};
+use std::{
+    env,
+    io::{stdout, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// Tried in this order, the first found being used
+const MACOS_COMMANDS: [(&str, &[&str]); 1] = [("pbcopy", &[])];
+const WINDOWS_COMMANDS: [(&str, &[&str]); 1] = [("clip", &[])];
+const UNIX_COMMANDS: [(&str, &[&str]); 3] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn copy_text(text: &str) -> Result<()> {
+    // The terminal cannot say whether it took the sequence, so one that
+    // could is taken to have
+    let terminal = is_osc52_supported() && execute!(stdout(), Print(get_osc52(text))).is_ok();
+    let command = copy_with_command(text);
+    if terminal == false && command == false {
+        bail!("no clipboard (the terminal takes no OSC 52, and no clipboard command was found)");
+    }
+    return Ok(());
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn is_osc52_supported() -> bool {
+    // Neither the console of Linux nor a dumb terminal take it
+    let term = env::var("TERM").unwrap_or_default();
+    return stdout().is_terminal() && term != "dumb" && term != "linux";
+}
+
+fn get_osc52(text: &str) -> String {
+    // Wrapped for tmux (and screen), which would otherwise keep it to
+    // themselves rather than pass it on to the terminal
+    let sequence = format!("\x1b]52;c;{}\x07", get_base64(text.as_bytes()));
+    if env::var_os("TMUX").is_some() {
+        return format!("\x1bPtmux;\x1b{}\x1b\\", sequence);
+    }
+    if env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+        return format!("\x1bP{}\x1b\\", sequence);
+    }
+    return sequence;
+}
+
+fn get_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (i, b)| value | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(BASE64[(value >> (18 - 6 * i) & 0x3F) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    return encoded;
+}
+
+fn copy_with_command(text: &str) -> bool {
+    let commands: &[(&str, &[&str])] = if cfg!(windows) {
+        &WINDOWS_COMMANDS
+    } else if cfg!(target_os = "macos") {
+        &MACOS_COMMANDS
+    } else {
+        &UNIX_COMMANDS
+    };
+    for (name, args) in commands {
+        let child = Command::new(name)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        // One that is not installed is passed over for the next
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if child.wait().is_ok_and(|status| status.success()) && written {
+            return true;
+        }
+    }
+    return false;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_base64() {
+        assert_eq!(get_base64(b""), "");
+        assert_eq!(get_base64(b"f"), "Zg==");
+        assert_eq!(get_base64(b"fo"), "Zm8=");
+        assert_eq!(get_base64(b"foo"), "Zm9v");
+        assert_eq!(get_base64(b"/dev/ttyUSB0"), "L2Rldi90dHlVU0Iw");
+        assert_eq!(get_base64(&[0xFF, 0xFE, 0x00, 0x01]), "//4AAQ==");
+    }
+
+    #[test]
+    fn test_get_osc52() {
+        if env::var_os("TMUX").is_some() || env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+            return;
+        }
+        assert_eq!(get_osc52("COM4"), "\x1b]52;c;Q09NNA==\x07");
+    }
+}
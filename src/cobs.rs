@@ -0,0 +1,119 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the COBS framing (Consistent Overhead Byte Stuffing),
+* where each packet ends on a zero byte and the zeros within it are replaced
+* by the length of the block before them.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+pub const DELIMITER: u8 = 0x00;
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// A block without a zero after it holds at most this many bytes
+const BLOCK_LIMIT: u8 = 0xFF;
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn encode(packet: &[u8]) -> Vec<u8> {
+    // Each block starts with its code, the offset to the next zero (or to the
+    // next code, for a full block), and the frame ends on the delimiter
+    let mut frame = Vec::with_capacity(packet.len() + packet.len() / 254 + 2);
+    let mut code_index = 0;
+    let mut code = 1;
+    frame.push(0);
+    for value in packet.iter().copied() {
+        if value != 0 {
+            frame.push(value);
+            code += 1;
+        }
+        if value == 0 || code == BLOCK_LIMIT {
+            frame[code_index] = code;
+            code_index = frame.len();
+            code = 1;
+            frame.push(0);
+        }
+    }
+    frame[code_index] = code;
+    frame.push(DELIMITER);
+    return frame;
+}
+
+pub fn decode(frame: &[u8]) -> Result<Vec<u8>, String> {
+    // The bytes before the delimiter, where a zero or a block that runs past
+    // the end is not a packet that was encoded
+    let mut packet = Vec::with_capacity(frame.len());
+    let mut index = 0;
+    while index < frame.len() {
+        let code = frame[index];
+        if code == 0 {
+            return Err(String::from("zero within a frame"));
+        }
+        let end = index + usize::from(code);
+        if end > frame.len() {
+            return Err(String::from("frame ends within a block"));
+        }
+        if frame[index + 1..end].contains(&0) {
+            return Err(String::from("zero within a frame"));
+        }
+        packet.extend_from_slice(&frame[index + 1..end]);
+        index = end;
+        if code < BLOCK_LIMIT && index < frame.len() {
+            packet.push(0);
+        }
+    }
+    return Ok(packet);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(&[]), vec![0x01, 0x00]);
+        assert_eq!(encode(&[0x00]), vec![0x01, 0x01, 0x00]);
+        assert_eq!(encode(&[0x11, 0x22, 0x00, 0x33]), vec![0x03, 0x11, 0x22, 0x02, 0x33, 0x00]);
+        assert_eq!(encode(&[0x11, 0x00, 0x00, 0x00]), vec![0x02, 0x11, 0x01, 0x01, 0x01, 0x00]);
+
+        // A full block is followed by the code of the next, without a zero
+        let packet: Vec<u8> = (1..=254).collect();
+        let frame = encode(&packet);
+        assert_eq!(frame.len(), 257);
+        assert_eq!((frame[0], frame[255], frame[256]), (0xFF, 0x01, 0x00));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(&[0x01]), Ok(vec![]));
+        assert_eq!(decode(&[0x01, 0x01]), Ok(vec![0x00]));
+        assert_eq!(decode(&[0x03, 0x11, 0x22, 0x02, 0x33]), Ok(vec![0x11, 0x22, 0x00, 0x33]));
+
+        for length in [0, 1, 253, 254, 255, 600] {
+            let packet: Vec<u8> = (0..length).map(|i| (i % 7) as u8).collect();
+            let frame = encode(&packet);
+            assert_eq!(decode(&frame[..frame.len() - 1]), Ok(packet));
+        }
+
+        assert!(decode(&[0x05, 0x11, 0x22]).is_err());
+        assert!(decode(&[0x02, 0x11, 0x00, 0x01]).is_err());
+    }
+}
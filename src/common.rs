@@ -5,23 +5,41 @@
 * DATE: 12/30/23
 ********************************************************************************/
 /*******************************************************************************/
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::Color,
+    style::{Color, Modifier, Style},
+    text::Span,
     Frame,
 };
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::generator::Generator;
 
 /******************************************************************************/
 /*******************************************************************************
 * Public Interface
 *******************************************************************************/
 /******************************************************************************/
-pub type SerialFlag = Arc<Mutex<bool>>;
-pub type SerialBuffer = Arc<Mutex<Vec<u8>>>;
-pub type SerialError = Arc<Mutex<Option<String>>>;
-pub type SerialParams = Arc<Mutex<PortParameters>>;
+pub type CommandSender = Sender<SerialCommand>;
+pub type CommandReceiver = Receiver<SerialCommand>;
+pub type DataSender = Sender<Vec<u8>>;
+pub type DataReceiver = Receiver<Vec<u8>>;
+pub type EventSender = UnboundedSender<SerialEvent>;
+pub type EventReceiver = UnboundedReceiver<SerialEvent>;
+pub type SharedStats = Arc<Mutex<SerialStats>>;
+pub type SharedRx = Arc<Mutex<RxBuffer>>;
+// Whether each port probed could be opened, as the probes finish
+pub type SharedProbes = Arc<Mutex<Vec<(String, Result<(), SerialErrorKind>)>>>;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub enum Screen {
@@ -32,6 +50,17 @@ pub enum Screen {
     DeviceList,
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ConnectionStatus {
+    #[default]
+    Closed,
+    Connecting,
+    Connected,
+    Disconnected,
+    Reconnecting,
+    Failed(SerialErrorKind),
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub enum State {
     #[default]
@@ -39,20 +68,83 @@ pub enum State {
     Pausing,
     Stopping,
     Error(String),
-    Switching(Screen, Option<PortParameters>),
+    // Boxed, since the parameters dwarf every other state
+    Switching(Screen, Option<Box<PortParameters>>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Message {
+    Tick,
     Quit,
     Enter,
     Pause,
     Resume,
     Rx(Vec<u8>),
+    BridgeRx(Vec<u8>),
+    // What was sent at the time, read back from a replay
+    Sent(Vec<u8>),
+    Gap(Duration),
     Backspace,
+    Delete,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
     Input(char),
+    Paste(String),
+    PassThrough(KeyEvent),
+    TogglePassThrough,
+    ToggleCapture,
+    Export,
+    SendFile,
+    SendByte,
+    Xmodem,
+    RunScript,
+    MeasureLatency,
+    Generate,
+    PauseReplay,
+    CycleReplaySpeed,
+    StepReplay,
+    Repeat,
+    Trigger,
+    ToggleResponses,
+    Search,
+    Cancel,
+    Connection(ConnectionStatus),
+    Lines(LineStatus),
+    Break,
+    ToggleDtr,
+    ToggleRts,
+    ScrollUp,
+    ScrollDown,
+    ScrollTop,
+    ScrollBottom,
+    CycleMode,
+    ToggleMnemonics,
+    ToggleEcho,
+    ToggleGraph,
+    ToggleAtHistory,
+    LongerRows,
+    ShorterRows,
+    CycleLineEnding,
+    HistoryPrev,
+    HistoryNext,
+    Configure,
+    Flush,
+    Clear,
+    Reset,
+    Reconfigured(PortParameters),
+    Stats(SerialStats),
     NextElement,
     PreviousElement,
+    Complete,
+    RefreshDevices,
+    QuickConnect,
+    ToggleHidden,
+    ToggleNativeHidden,
+    ProbeDevices,
+    Copy,
+    Tested(Result<usize, SerialErrorKind>),
     Switching(Screen, Option<PortParameters>),
 }
 
@@ -62,6 +154,63 @@ pub enum NolpEvent {
     Error,
     Render,
     User(KeyEvent),
+    Paste(String),
+    Serial(SerialEvent),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerialCommand {
+    Open(PortParameters),
+    Close,
+    Shutdown,
+    Configure(PortParameters),
+    SetDtr(bool),
+    SetRts(bool),
+    Break(Duration),
+    Flush,
+    // Sends the pattern whenever nothing else is waiting, until stopped
+    Generate(Generator),
+    StopGenerating,
+    Replay(ReplayControl),
+    // Opens the port for a moment, counting what arrives, without a session
+    Test(PortParameters),
+}
+
+// How a replay is played back, which only a replay takes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayControl {
+    Pause(bool),
+    Speed(u32),
+    // Plays the next record while paused
+    Step,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerialEvent {
+    RxReady,
+    Status(ConnectionStatus),
+    Lines(LineStatus),
+    Error(SerialErrorKind),
+    Failed(SerialErrorKind),
+    Reconfigured(PortParameters),
+    Flushed,
+    Reset,
+    // The bytes received while the port was tested, or why it did not open
+    Tested(Result<usize, SerialErrorKind>),
+}
+
+// The failures of the serial layer, so that the terminal can react to (and
+// explain) each of them differently
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerialErrorKind {
+    OpenFailed { reason: String },
+    ReadFailed,
+    WriteFailed,
+    WriteTimedOut,
+    Disconnected,
+    PermissionDenied,
+    Busy,
+    Other(String),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -71,12 +220,129 @@ pub enum Parity {
     None,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlowControl {
+    None,
+    Hardware,
+    Software,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LineEnding {
+    None,
+    Cr,
+    Lf,
+    CrLf,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CaptureFormat {
+    Text,
+    Log,
+}
+
+// Whether the silences in the received data are marked, and if so whether
+// the markers are kept in the exports as well
+#[derive(Clone, Debug, PartialEq)]
+pub enum GapMarkers {
+    Hidden,
+    Shown,
+    Exported,
+}
+
+// Whether the NMEA sentences received in Ascii are checked, and if so whether
+// the latest fix is shown as well
+#[derive(Clone, Debug, PartialEq)]
+pub enum NmeaDecode {
+    Off,
+    Checked,
+    Fix,
+}
+
+// The framing the packets are wrapped in, when the data is read and sent as
+// packets rather than as a stream of bytes
+#[derive(Clone, Debug, PartialEq)]
+pub enum Framing {
+    Off,
+    Slip,
+    Cobs,
+}
+
+// How Ascii is laid out, as lines of text (optionally colored by its ANSI
+// sequences) or as the raw grid of bytes
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsciiView {
+    Text,
+    Ansi,
+    Raw,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Mode {
     Hex,
     Octal,
     Ascii,
     Decimal,
+    HexDump,
+}
+
+// The port that received the data, the bridge port is only open when
+// sniffing between two devices
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Origin {
+    #[default]
+    Primary,
+    Bridge,
+    // Only ever read back from a replay
+    Sent,
+}
+
+// An edit of a line of text at its cursor, as in the terminal input and the
+// fields of the menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edit {
+    Insert(char),
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+// Each line is None when the driver does not support reading it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineStatus {
+    pub cts: Option<bool>,
+    pub dsr: Option<bool>,
+    pub cd: Option<bool>,
+    pub ri: Option<bool>,
+}
+
+// Received data waiting for the terminal, the oldest bytes are dropped once
+// the capacity is reached so memory stays bounded if the UI falls behind
+#[derive(Clone, Debug, PartialEq)]
+pub struct RxBuffer {
+    data: VecDeque<u8>,
+    // Each run with the silence before it, when longer than the gap
+    origins: VecDeque<(Origin, usize, Option<Duration>)>,
+    capacity: usize,
+    dropped: u64,
+    gap: Option<Duration>,
+    received: Option<Instant>,
+}
+
+// Counters for the current connection, reset whenever a port is opened
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SerialStats {
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    pub write_errors: u64,
+    pub read_errors: u64,
+    // Only set while connected, the time of earlier connections is kept
+    // separately so that the uptime pauses while the device is unplugged
+    pub started: Option<Instant>,
+    pub uptime: Duration,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -87,6 +353,48 @@ pub struct PortParameters {
     pub stop_bits: Option<u8>,
     pub parity: Option<Parity>,
     pub mode: Option<Mode>,
+    pub flow_control: Option<FlowControl>,
+    pub dtr_on_open: Option<bool>,
+    pub rs485: bool,
+    pub rs485_discard_echo: bool,
+    pub timeout_ms: Option<u32>,
+    pub read_only: bool,
+    pub bridge: Option<String>,
+    pub reset_on_connect: bool,
+    pub char_delay_ms: Option<u32>,
+    pub line_delay_ms: Option<u32>,
+    pub scrollback: Option<usize>,
+    pub line_ending: Option<LineEnding>,
+    pub input_limit: Option<usize>,
+    pub paste_lines: bool,
+    pub ascii_view: Option<AsciiView>,
+    pub capture_path: Option<String>,
+    pub capture_format: Option<CaptureFormat>,
+    pub trigger_bell: bool,
+    pub response_rules: Option<String>,
+    pub gap_markers: Option<GapMarkers>,
+    pub gap_ms: Option<u32>,
+    pub row_length: Option<usize>,
+    pub nmea: Option<NmeaDecode>,
+    pub framing: Option<Framing>,
+    pub at_commands: bool,
+    pub at_timeout_ms: Option<u32>,
+    pub hex_line_ms: Option<u32>,
+    pub hex_prompt: Option<u8>,
+    // Whether what is sent is shown with the data, off for a device that
+    // echoes it back
+    pub echo: bool,
+}
+
+// A chord and the message it sends, on the screens it is bound on (on every
+// screen when none are given), with what help says it does
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub screens: Vec<Screen>,
+    pub message: Message,
+    pub description: String,
 }
 
 pub trait Tea {
@@ -105,17 +413,76 @@ pub trait Nolp {
 *******************************************************************************/
 /******************************************************************************/
 // Styles
+pub const ACTIVE_COLOR: Color = Color::LightGreen;
 pub const INVALID_COLOR: Color = Color::LightRed;
+pub const WARNING_COLOR: Color = Color::LightYellow;
 pub const SELECTED_COLOR: Color = Color::LightBlue;
 pub const PLACEHOLDER_COLOR: Color = Color::DarkGray;
+pub const TRIGGER_COLOR: Color = Color::LightMagenta;
+
+// Limits
+pub const RX_CAPACITY: usize = 4 * 1024 * 1024;
+
+// Devices
+pub const LOOPBACK_PORT: &str = "loopback:";
+pub const TCP_PREFIX: &str = "tcp://";
+pub const REPLAY_PREFIX: &str = "replay:";
 
 // Keyboard input
 pub const HELP_CHAR: char = 'h';
+pub const BREAK_CHAR: char = 'b';
 pub const QUIT_CHAR: char = 'q';
 pub const MENU_CHAR: char = 'n';
 pub const PAUSE_CHAR: char = 'p';
 pub const RESUME_CHAR: char = 'r';
 pub const DEVICE_LIST_CHAR: char = 'l';
+pub const DTR_CHAR: char = 'd';
+pub const RTS_CHAR: char = 't';
+pub const CONFIGURE_CHAR: char = 'o';
+pub const FLUSH_CHAR: char = 'f';
+pub const MODE_CHAR: char = 'e';
+pub const LINE_ENDING_CHAR: char = 'a';
+pub const PASSTHROUGH_CHAR: char = 'x';
+pub const CAPTURE_CHAR: char = 'w';
+pub const EXPORT_CHAR: char = 's';
+pub const SEND_FILE_CHAR: char = 'u';
+pub const XMODEM_CHAR: char = 'y';
+pub const REPEAT_CHAR: char = 'g';
+pub const TRIGGER_CHAR: char = 'z';
+pub const RESPONSES_CHAR: char = 'c';
+pub const SEARCH_CHAR: char = '/';
+pub const MNEMONICS_CHAR: char = 'v';
+pub const CLEAR_CHAR: char = 'k';
+pub const LONGER_ROWS_CHAR: char = '+';
+pub const SHORTER_ROWS_CHAR: char = '-';
+pub const SEND_BYTE_CHAR: char = '\\';
+pub const GRAPH_CHAR: char = 'j';
+// Typed as ctrl+space, which terminals send as NUL
+pub const AT_HISTORY_CHAR: char = ' ';
+// Typed as ctrl+^ on most layouts, which terminals send as ctrl+6
+pub const SCRIPT_CHAR: char = '6';
+// Most terminals send ctrl+5 as the control code of ctrl+]
+pub const LATENCY_CHAR: char = '5';
+// Every control chord is taken, so the generator is on a function key
+pub const GENERATOR_KEY: u8 = 2;
+pub const REPLAY_PAUSE_KEY: u8 = 3;
+pub const REPLAY_SPEED_KEY: u8 = 4;
+pub const REPLAY_STEP_KEY: u8 = 5;
+pub const ECHO_KEY: u8 = 6;
+// Only in the menu (and the device list), where there is no replay to step
+pub const REFRESH_KEY: u8 = 5;
+// Only in the device list, where there is no echo to toggle
+pub const SHOW_HIDDEN_KEY: u8 = 6;
+pub const HIDE_NATIVE_KEY: u8 = 7;
+pub const PROBE_KEY: u8 = 8;
+// Only in the menu and the device list
+pub const COPY_KEY: u8 = 9;
+// Only in the device list, where there are no responses to turn off
+pub const QUICK_CONNECT_CHAR: char = 'c';
+// Followed by QUIT_CHAR, leaves pass-through mode, where a Unix terminal
+// sends ctrl+] as 0x1D, which is read as ctrl+5
+pub const PASSTHROUGH_ESCAPE_CHAR: char = ']';
+pub const PASSTHROUGH_ESCAPE_ALIAS: char = '5';
 pub const NEXT_ELEMENT_CHAR: char = ']';
 pub const PREVIOUS_ELEMENT_CHAR: char = '[';
 
@@ -133,6 +500,35 @@ impl Default for PortParameters {
             stop_bits: None,
             parity: None,
             mode: None,
+            flow_control: None,
+            dtr_on_open: None,
+            rs485: false,
+            rs485_discard_echo: false,
+            timeout_ms: None,
+            read_only: false,
+            bridge: None,
+            reset_on_connect: false,
+            char_delay_ms: None,
+            line_delay_ms: None,
+            scrollback: None,
+            line_ending: None,
+            input_limit: None,
+            paste_lines: false,
+            ascii_view: None,
+            capture_path: None,
+            capture_format: None,
+            trigger_bell: false,
+            response_rules: None,
+            gap_markers: None,
+            gap_ms: None,
+            row_length: None,
+            nmea: None,
+            framing: None,
+            at_commands: false,
+            at_timeout_ms: None,
+            hex_line_ms: None,
+            hex_prompt: None,
+            echo: true,
         }
     }
 }
@@ -144,23 +540,293 @@ impl PortParameters {
     }
 }
 
-impl Parity {
-    pub fn to_string(self) -> String {
+impl Default for RxBuffer {
+    fn default() -> RxBuffer {
+        RxBuffer::new(RX_CAPACITY)
+    }
+}
+
+impl RxBuffer {
+    pub fn new(capacity: usize) -> RxBuffer {
+        RxBuffer {
+            data: VecDeque::new(),
+            origins: VecDeque::new(),
+            capacity,
+            dropped: 0,
+            gap: None,
+            received: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.origins.clear();
+        self.received = None;
+    }
+
+    pub fn drain(&mut self) -> Vec<(Origin, Vec<u8>, Option<Duration>)> {
+        // Consecutive bytes from the same port are kept together, unless a
+        // gap came between them
+        let mut runs = Vec::new();
+        for (origin, length, gap) in self.origins.drain(..) {
+            runs.push((origin, self.data.drain(..length).collect(), gap));
+        }
+        return runs;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.data.len();
+    }
+
+    pub fn push(&mut self, origin: Origin, bytes: &[u8]) -> bool {
+        // Returns whether the buffer was empty, the reader only needs to be
+        // notified once until it drains the buffer
+        let notify = self.len() == 0;
+        if bytes.len() == 0 {
+            return false;
+        }
+        // Timed as it is read, so a stalled UI does not stretch the gaps
+        let now = Instant::now();
+        let gap = match (self.gap, self.received) {
+            (Some(threshold), Some(received)) if now - received > threshold => Some(now - received),
+            _ => None,
+        };
+        self.received = Some(now);
+        match self.origins.back_mut() {
+            Some((o, length, _)) if *o == origin && gap.is_none() => *length += bytes.len(),
+            _ => self.origins.push_back((origin, bytes.len(), gap)),
+        }
+        self.data.extend(bytes);
+
+        if self.data.len() > self.capacity {
+            let excess = self.data.len() - self.capacity;
+            self.data.drain(..excess);
+            self.dropped += excess as u64;
+
+            let mut remaining = excess;
+            while remaining > 0 {
+                let (_, length, _) = self.origins.front_mut().unwrap();
+                if *length > remaining {
+                    *length -= remaining;
+                    remaining = 0;
+                } else {
+                    remaining -= *length;
+                    self.origins.pop_front();
+                }
+            }
+        }
+        return notify;
+    }
+
+    pub fn set_gap(&mut self, gap: Option<Duration>) {
+        self.gap = gap;
+    }
+
+    pub fn take_dropped(&mut self) -> u64 {
+        let dropped = self.dropped;
+        self.dropped = 0;
+        return dropped;
+    }
+}
+
+impl Binding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers, screens: &[Screen], message: Message, description: &str) -> Binding {
+        Binding {
+            code,
+            modifiers,
+            screens: screens.to_vec(),
+            message,
+            description: String::from(description),
+        }
+    }
+
+    pub fn is_bound(&self, screen: &Screen) -> bool {
+        return self.screens.len() == 0 || self.screens.contains(screen);
+    }
+
+    pub fn is_chord(&self, key: &KeyEvent) -> bool {
+        return self.code == key.code && self.modifiers == key.modifiers;
+    }
+
+    pub fn get_label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label += name;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label += "space",
+            KeyCode::Char(c) => label.push(c),
+            KeyCode::F(n) => label += &format!("F{}", n),
+            KeyCode::Delete => label += "del",
+            code => label += &format!("{:?}", code).to_lowercase(),
+        }
+        return label;
+    }
+}
+
+impl SerialStats {
+    pub fn get_uptime(&self) -> Duration {
+        let current = match self.started {
+            Some(s) => s.elapsed(),
+            None => Duration::default(),
+        };
+        return self.uptime + current;
+    }
+}
+
+impl SerialErrorKind {
+    pub fn get_guidance(&self) -> Option<&'static str> {
+        match self {
+            SerialErrorKind::OpenFailed { .. } => {
+                return Some("Check the port name and that the device is connected")
+            }
+            SerialErrorKind::Disconnected => {
+                return Some("Check the cable and that the device is powered")
+            }
+            SerialErrorKind::PermissionDenied => {
+                if cfg!(target_os = "linux") {
+                    return Some("Add your user to the dialout group and log in again");
+                }
+                return Some("Check that your user is allowed to access the port");
+            }
+            SerialErrorKind::Busy => {
+                return Some("Another application has the port open, close it and retry")
+            }
+            _ => return None,
+        }
+    }
+}
+
+impl fmt::Display for SerialErrorKind {
+    // Padded to be displayed in a block title
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Parity::Even => String::from("Even"),
-            Parity::Odd => String::from("Odd"),
-            Parity::None => String::from("None"),
+            SerialErrorKind::OpenFailed { reason } if reason.len() > 0 => {
+                write!(f, " Failed to open port: {} ", reason)
+            }
+            SerialErrorKind::OpenFailed { .. } => write!(f, " Failed to open port "),
+            SerialErrorKind::ReadFailed => write!(f, " Read failed "),
+            SerialErrorKind::WriteFailed => write!(f, " Write failed "),
+            SerialErrorKind::WriteTimedOut => write!(f, " Write timed out "),
+            SerialErrorKind::Disconnected => write!(f, " Device disconnected "),
+            SerialErrorKind::PermissionDenied => write!(f, " Permission denied "),
+            SerialErrorKind::Busy => write!(f, " Port is busy "),
+            SerialErrorKind::Other(message) => write!(f, " {} ", message.trim()),
         }
     }
 }
 
-impl Mode {
-    pub fn to_string(self) -> String {
+impl From<SerialErrorKind> for String {
+    fn from(error: SerialErrorKind) -> String {
+        return error.to_string();
+    }
+}
+
+impl fmt::Display for Parity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Mode::Ascii => String::from("Ascii"),
-            Mode::Decimal => String::from("Decimal"),
-            Mode::Hex => String::from("Hex"),
-            Mode::Octal => String::from("Octal"),
+            Parity::Even => write!(f, "Even"),
+            Parity::Odd => write!(f, "Odd"),
+            Parity::None => write!(f, "None"),
+        }
+    }
+}
+
+impl fmt::Display for FlowControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlowControl::None => write!(f, "None"),
+            FlowControl::Hardware => write!(f, "Hardware"),
+            FlowControl::Software => write!(f, "Software"),
+        }
+    }
+}
+
+impl LineEnding {
+    pub fn get_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Cr => b"\r",
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineEnding::None => write!(f, "None"),
+            LineEnding::Cr => write!(f, "CR"),
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::CrLf => write!(f, "CRLF"),
+        }
+    }
+}
+
+impl fmt::Display for AsciiView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsciiView::Text => write!(f, "Text"),
+            AsciiView::Ansi => write!(f, "ANSI"),
+            AsciiView::Raw => write!(f, "Raw"),
+        }
+    }
+}
+
+impl fmt::Display for Framing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Framing::Off => write!(f, "None"),
+            Framing::Slip => write!(f, "SLIP"),
+            Framing::Cobs => write!(f, "COBS"),
+        }
+    }
+}
+
+impl fmt::Display for NmeaDecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NmeaDecode::Off => write!(f, "No"),
+            NmeaDecode::Checked => write!(f, "Yes"),
+            NmeaDecode::Fix => write!(f, "Fix"),
+        }
+    }
+}
+
+impl fmt::Display for GapMarkers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GapMarkers::Hidden => write!(f, "No"),
+            GapMarkers::Shown => write!(f, "Yes"),
+            GapMarkers::Exported => write!(f, "Export"),
+        }
+    }
+}
+
+impl fmt::Display for CaptureFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureFormat::Text => write!(f, "Text"),
+            CaptureFormat::Log => write!(f, "Log"),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Ascii => write!(f, "Ascii"),
+            Mode::Decimal => write!(f, "Decimal"),
+            Mode::Hex => write!(f, "Hex"),
+            Mode::Octal => write!(f, "Octal"),
+            Mode::HexDump => write!(f, "HexDump"),
         }
     }
 }
@@ -193,24 +859,191 @@ pub fn get_center_bounds(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     return h_center[1];
 }
 
-pub fn serial_buffer_default() -> SerialBuffer {
-    let mutex = Mutex::new(Vec::new());
-    return Arc::new(mutex);
+pub fn edit_text(text: &mut String, cursor: &mut usize, edit: Edit) {
+    // The cursor counts characters, and is kept within the text
+    let length = text.chars().count();
+    *cursor = (*cursor).min(length);
+    match edit {
+        Edit::Insert(c) => {
+            text.insert(get_byte_index(text, *cursor), c);
+            *cursor += 1;
+        }
+        Edit::Backspace if *cursor > 0 => {
+            *cursor -= 1;
+            text.remove(get_byte_index(text, *cursor));
+        }
+        Edit::Delete if *cursor < length => {
+            text.remove(get_byte_index(text, *cursor));
+        }
+        Edit::Left => *cursor = cursor.saturating_sub(1),
+        Edit::Right => *cursor = (*cursor + 1).min(length),
+        Edit::Home => *cursor = 0,
+        Edit::End => *cursor = length,
+        Edit::Backspace | Edit::Delete => {}
+    }
+}
+
+pub fn get_byte_index(input: &str, cursor: usize) -> usize {
+    // The cursor counts characters, which may be wider than a byte
+    match input.char_indices().nth(cursor) {
+        Some((index, _)) => return index,
+        None => return input.len(),
+    }
+}
+
+pub fn get_cursor_spans<'a>(input: &str, cursor: usize, width: usize, style: Style) -> Vec<Span<'a>> {
+    // Only the region around the cursor is shown when the text is wider
+    // than the box, the cursor is inverted (a blank past the end)
+    let chars: Vec<char> = input.chars().collect();
+    let cursor = cursor.min(chars.len());
+    let start = (cursor + 1).saturating_sub(width.max(1));
+    let end = (start + width.max(1)).min(chars.len());
+    let before: String = chars[start..cursor].iter().collect();
+    let under = chars.get(cursor).copied().unwrap_or(' ');
+    let after: String = chars[(cursor + 1).min(end)..end].iter().collect();
+    return vec![
+        Span::styled(before, style),
+        Span::styled(under.to_string(), style.add_modifier(Modifier::REVERSED)),
+        Span::styled(after, style),
+    ];
 }
 
-pub fn serial_error_default() -> SerialError {
-    let mutex = Mutex::new(None);
-    return Arc::new(mutex);
+pub fn get_default_keymap() -> Vec<Binding> {
+    // In the order help lists them, where a chord some terminals send in
+    // place of another is bound to the same message
+    let ctrl = |c: char, message: Message, description: &str| {
+        Binding::new(KeyCode::Char(c), KeyModifiers::CONTROL, &[], message, description)
+    };
+    let key = |code: KeyCode, screens: &[Screen], message: Message, description: &str| {
+        Binding::new(code, KeyModifiers::NONE, screens, message, description)
+    };
+    let alt = |c: char, message: Message, description: &str| {
+        Binding::new(KeyCode::Char(c), KeyModifiers::ALT, &[], message, description)
+    };
+    let terminal = [Screen::Terminal];
+    let ports = [Screen::Menu, Screen::DeviceList];
+    let device_list = [Screen::DeviceList];
+    return vec![
+        ctrl(MENU_CHAR, Message::Switching(Screen::Menu, None), "Displays menu"),
+        ctrl(DEVICE_LIST_CHAR, Message::Switching(Screen::DeviceList, None), "Displays device list"),
+        ctrl(HELP_CHAR, Message::Switching(Screen::Help, None), "Displays help"),
+        ctrl(QUIT_CHAR, Message::Quit, "Quits application"),
+        ctrl(PAUSE_CHAR, Message::Pause, "Pauses input/output"),
+        ctrl(RESUME_CHAR, Message::Resume, "Resumes input/output"),
+        ctrl(BREAK_CHAR, Message::Break, "Sends break signal"),
+        ctrl(DTR_CHAR, Message::ToggleDtr, "Toggles DTR line"),
+        ctrl(RTS_CHAR, Message::ToggleRts, "Toggles RTS line"),
+        ctrl(CONFIGURE_CHAR, Message::Configure, "Reconfigures port"),
+        ctrl(FLUSH_CHAR, Message::Flush, "Flushes buffers"),
+        ctrl(MODE_CHAR, Message::CycleMode, "Cycles display mode"),
+        // Terminals send no control code for + and -, but do prefix them with
+        // an escape for alt (where the + is typed with shift on most layouts)
+        alt(SHORTER_ROWS_CHAR, Message::ShorterRows, "Fewer bytes per row"),
+        alt(LONGER_ROWS_CHAR, Message::LongerRows, "More bytes per row"),
+        alt('=', Message::LongerRows, "More bytes per row"),
+        ctrl(LINE_ENDING_CHAR, Message::CycleLineEnding, "Cycles line ending"),
+        ctrl(PASSTHROUGH_CHAR, Message::TogglePassThrough, "Sends keys as typed"),
+        ctrl(CAPTURE_CHAR, Message::ToggleCapture, "Starts/stops capture"),
+        ctrl(EXPORT_CHAR, Message::Export, "Exports the data"),
+        ctrl(SEND_FILE_CHAR, Message::SendFile, "Sends a file"),
+        ctrl(SEND_BYTE_CHAR, Message::SendByte, "Sends a single byte"),
+        // As for ctrl+/, ctrl+\ is often sent as ctrl+4
+        ctrl('4', Message::SendByte, "Sends a single byte"),
+        ctrl(XMODEM_CHAR, Message::Xmodem, "Transfers over XMODEM"),
+        ctrl(SCRIPT_CHAR, Message::RunScript, "Runs a script"),
+        ctrl(LATENCY_CHAR, Message::MeasureLatency, "Measures the latency"),
+        key(KeyCode::F(GENERATOR_KEY), &terminal, Message::Generate, "Sends a test pattern"),
+        key(KeyCode::F(REPLAY_PAUSE_KEY), &terminal, Message::PauseReplay, "Pauses the replay"),
+        key(KeyCode::F(REPLAY_SPEED_KEY), &terminal, Message::CycleReplaySpeed, "Speeds up the replay"),
+        key(KeyCode::F(REPLAY_STEP_KEY), &terminal, Message::StepReplay, "Steps the replay"),
+        key(KeyCode::F(ECHO_KEY), &terminal, Message::ToggleEcho, "Local echo on/off"),
+        ctrl(REPEAT_CHAR, Message::Repeat, "Repeats the input"),
+        ctrl(TRIGGER_CHAR, Message::Trigger, "Adds a trigger"),
+        ctrl(RESPONSES_CHAR, Message::ToggleResponses, "Toggles auto-response"),
+        ctrl(SEARCH_CHAR, Message::Search, "Searches the data"),
+        // Most terminals send ctrl+/ as ctrl+7, the same control code
+        ctrl('7', Message::Search, "Searches the data"),
+        ctrl(MNEMONICS_CHAR, Message::ToggleMnemonics, "Names control bytes"),
+        ctrl(GRAPH_CHAR, Message::ToggleGraph, "Graphs the numbers"),
+        ctrl(AT_HISTORY_CHAR, Message::ToggleAtHistory, "Shows AT history"),
+        ctrl(CLEAR_CHAR, Message::Clear, "Clears the display"),
+        Binding::new(KeyCode::Home, KeyModifiers::CONTROL, &[], Message::ScrollTop, "Oldest data"),
+        Binding::new(KeyCode::End, KeyModifiers::CONTROL, &[], Message::ScrollBottom, "Latest data"),
+        key(KeyCode::F(REFRESH_KEY), &ports, Message::RefreshDevices, "Refreshes the ports"),
+        // Typed letters filter the device list, so connecting is a chord
+        Binding::new(
+            KeyCode::Char(QUICK_CONNECT_CHAR),
+            KeyModifiers::CONTROL,
+            &device_list,
+            Message::QuickConnect,
+            "Connects a device",
+        ),
+        key(KeyCode::Delete, &device_list, Message::Delete, "Hides/shows a port"),
+        key(KeyCode::F(SHOW_HIDDEN_KEY), &device_list, Message::ToggleHidden, "Shows hidden ports"),
+        key(KeyCode::F(HIDE_NATIVE_KEY), &device_list, Message::ToggleNativeHidden, "Hides built-in ports"),
+        key(KeyCode::F(PROBE_KEY), &device_list, Message::ProbeDevices, "Probes busy ports (twice)"),
+        key(KeyCode::F(COPY_KEY), &ports, Message::Copy, "Copies the port"),
+        key(KeyCode::F(COPY_KEY), &[Screen::Help], Message::Copy, "Copies diagnostics"),
+    ];
+}
+
+pub fn get_binding<'a>(keymap: &'a [Binding], screen: &Screen, key: &KeyEvent) -> Option<&'a Binding> {
+    // A chord bound on the screen itself comes before one bound everywhere
+    let bound = keymap.iter().filter(|binding| binding.is_chord(key) && binding.is_bound(screen));
+    let mut everywhere = None;
+    for binding in bound {
+        if binding.screens.len() > 0 {
+            return Some(binding);
+        }
+        everywhere = everywhere.or(Some(binding));
+    }
+    return everywhere;
 }
 
-pub fn serial_flag_default() -> SerialFlag {
-    return Arc::new(Mutex::new(false));
+pub fn is_conflicted(keymap: &[Binding], index: usize) -> bool {
+    // Two messages on one chord, where one bound on a screen over one bound
+    // everywhere is an override rather than a conflict
+    let binding = &keymap[index];
+    return keymap.iter().enumerate().any(|(i, other)| {
+        let overlaps = match (binding.screens.len(), other.screens.len()) {
+            (0, 0) => true,
+            (0, _) | (_, 0) => false,
+            _ => binding.screens.iter().any(|screen| other.screens.contains(screen)),
+        };
+        let chord = other.code == binding.code && other.modifiers == binding.modifiers;
+        i != index && chord && overlaps && other.message != binding.message
+    });
 }
 
-pub fn serial_params_default() -> SerialParams {
-    let parameters = PortParameters::default();
-    let mutex = Mutex::new(parameters);
-    return Arc::new(mutex);
+pub fn get_escaped_bytes(input: &str) -> Result<Vec<u8>, String> {
+    // Control characters are typed as escapes (e.g., `AT\r\n`), and `\\` is
+    // a literal backslash
+    let mut bytes = Vec::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut encoded = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                if digits.len() != 2 || digits.chars().all(|d| d.is_ascii_hexdigit()) == false {
+                    return Err(format!(" Invalid escape '\\x{}' ", digits));
+                }
+                bytes.push(u8::from_str_radix(&digits, 16).unwrap());
+            }
+            Some(other) => return Err(format!(" Invalid escape '\\{}' ", other)),
+            None => return Err(String::from(" Incomplete escape '\\' ")),
+        }
+    }
+    return Ok(bytes);
 }
 
 /******************************************************************************/
@@ -231,6 +1064,69 @@ mod tests {
         assert_eq!(expected_bounds, actual_bounds);
     }
 
+    #[test]
+    fn test_edit_text() {
+        let mut text = String::new();
+        let mut cursor = 0;
+        let edit = |e: Edit, text: &mut String, cursor: &mut usize| edit_text(text, cursor, e);
+
+        // Nothing happens at either end of an empty text
+        for e in [Edit::Backspace, Edit::Delete, Edit::Left, Edit::Right] {
+            edit(e, &mut text, &mut cursor);
+        }
+        assert_eq!((text.as_str(), cursor), ("", 0));
+
+        for c in "ATé".chars() {
+            edit(Edit::Insert(c), &mut text, &mut cursor);
+        }
+        edit(Edit::Home, &mut text, &mut cursor);
+        edit(Edit::Insert('x'), &mut text, &mut cursor);
+        edit(Edit::Delete, &mut text, &mut cursor);
+        assert_eq!((text.as_str(), cursor), ("xTé", 1));
+        edit(Edit::End, &mut text, &mut cursor);
+        edit(Edit::Left, &mut text, &mut cursor);
+        edit(Edit::Backspace, &mut text, &mut cursor);
+        assert_eq!((text.as_str(), cursor), ("xé", 1));
+
+        // A cursor past the text (e.g., the text was replaced) is brought back
+        cursor = 10;
+        edit(Edit::Backspace, &mut text, &mut cursor);
+        assert_eq!((text.as_str(), cursor), ("x", 1));
+    }
+
+    #[test]
+    fn test_get_cursor_spans() {
+        let style = Style::default();
+        let text = |spans: Vec<Span>| -> Vec<String> {
+            spans.iter().map(|span| span.content.to_string()).collect()
+        };
+
+        // The window follows the cursor once it passes the width
+        assert_eq!(text(get_cursor_spans("0123456789", 10, 4, style)), vec!["789", " ", ""]);
+        assert_eq!(text(get_cursor_spans("0123456789", 5, 4, style)), vec!["234", "5", ""]);
+        assert_eq!(text(get_cursor_spans("0123456789", 1, 4, style)), vec!["0", "1", "23"]);
+        assert_eq!(text(get_cursor_spans("0123456789", 0, 20, style)), vec!["", "0", "123456789"]);
+        assert_eq!(text(get_cursor_spans("0123456789", 0, 0, style)), vec!["", "0", ""]);
+        assert_eq!(text(get_cursor_spans("01", 5, 4, style)), vec!["01", " ", ""]);
+    }
+
+    #[test]
+    fn test_get_escaped_bytes() {
+        let expected = vec![0x41, 0x54, 0x0D, 0x0A];
+        assert_eq!(get_escaped_bytes("AT\\r\\n"), Ok(expected));
+        assert_eq!(get_escaped_bytes("\\t\\0\\x7F\\xff"), Ok(vec![0x09, 0x00, 0x7F, 0xFF]));
+        assert_eq!(get_escaped_bytes("C:\\\\n"), Ok(b"C:\\n".to_vec()));
+        assert_eq!(get_escaped_bytes("é"), Ok(vec![0xC3, 0xA9]));
+
+        let error = String::from(" Invalid escape '\\q' ");
+        assert_eq!(get_escaped_bytes("a\\q"), Err(error));
+        let error = String::from(" Invalid escape '\\x4' ");
+        assert_eq!(get_escaped_bytes("\\x4"), Err(error));
+        assert!(get_escaped_bytes("\\xZZ").is_err());
+        assert!(get_escaped_bytes("\\x+1").is_err());
+        assert!(get_escaped_bytes("abc\\").is_err());
+    }
+
     #[test]
     fn test_mode_to_string() {
         let mut mode = Mode::Ascii;
@@ -241,6 +1137,8 @@ mod tests {
         assert_eq!(mode.to_string(), "Hex");
         mode = Mode::Octal;
         assert_eq!(mode.to_string(), "Octal");
+        mode = Mode::HexDump;
+        assert_eq!(mode.to_string(), "HexDump");
     }
 
     #[test]
@@ -260,4 +1158,159 @@ mod tests {
         parity = Parity::Odd;
         assert_eq!(parity.to_string(), "Odd");
     }    
+
+    #[test]
+    fn test_rx_buffer_overflow() {
+        let mut buffer = RxBuffer::new(4096);
+        let mut pushed = 0_u64;
+        let mut drained = 0_u64;
+
+        // Writes outpace reads by a factor of five
+        for i in 0..100 {
+            buffer.push(Origin::Primary, &[i as u8; 1000]);
+            pushed += 1000;
+            assert!(buffer.len() <= 4096);
+            if i % 5 == 0 {
+                for (_, data, _) in buffer.drain() {
+                    drained += data.len() as u64;
+                }
+            }
+        }
+
+        let remaining = buffer.len() as u64;
+        assert_eq!(buffer.take_dropped(), pushed - drained - remaining);
+        assert_eq!(buffer.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_rx_buffer_notify() {
+        let mut buffer = RxBuffer::new(8);
+        assert!(buffer.push(Origin::Primary, &[1, 2]));
+        assert!(!buffer.push(Origin::Primary, &[3]));
+        assert_eq!(buffer.drain(), vec![(Origin::Primary, vec![1, 2, 3], None)]);
+        assert!(buffer.push(Origin::Primary, &[4]));
+
+        // The newest bytes are kept when the capacity is exceeded
+        buffer.push(Origin::Primary, &[5, 6, 7, 8, 9, 10, 11, 12, 13]);
+        assert_eq!(
+            buffer.drain(),
+            vec![(Origin::Primary, vec![6, 7, 8, 9, 10, 11, 12, 13], None)]
+        );
+        assert_eq!(buffer.take_dropped(), 2);
+    }
+
+    #[test]
+    fn test_rx_buffer_origins() {
+        let mut buffer = RxBuffer::new(6);
+        buffer.push(Origin::Primary, &[1, 2]);
+        buffer.push(Origin::Bridge, &[3, 4]);
+        buffer.push(Origin::Bridge, &[5]);
+        buffer.push(Origin::Primary, &[6, 7, 8]);
+
+        // Dropping the oldest bytes also drops their origin
+        assert_eq!(
+            buffer.drain(),
+            vec![
+                (Origin::Bridge, vec![3, 4, 5], None),
+                (Origin::Primary, vec![6, 7, 8], None)
+            ]
+        );
+        assert_eq!(buffer.take_dropped(), 2);
+    }
+
+    #[test]
+    fn test_rx_buffer_gaps() {
+        let mut buffer = RxBuffer::new(64);
+        buffer.set_gap(Some(Duration::from_millis(20)));
+        buffer.push(Origin::Primary, &[1]);
+        buffer.push(Origin::Primary, &[2]);
+        std::thread::sleep(Duration::from_millis(30));
+        buffer.push(Origin::Primary, &[3]);
+
+        // The silence before a run is timed from the previous read
+        let runs = buffer.drain();
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].1.clone(), runs[0].2), (vec![1, 2], None));
+        assert_eq!(runs[1].1, vec![3]);
+        assert!(runs[1].2.unwrap() >= Duration::from_millis(30));
+
+        // The first read after a clear has nothing to be timed from
+        buffer.clear();
+        std::thread::sleep(Duration::from_millis(30));
+        buffer.push(Origin::Primary, &[4]);
+        assert_eq!(buffer.drain(), vec![(Origin::Primary, vec![4], None)]);
+    }
+
+    #[test]
+    fn test_serial_error_kind() {
+        let reason = String::from("No such file or directory");
+        let error = SerialErrorKind::OpenFailed { reason };
+        assert_eq!(error.to_string(), " Failed to open port: No such file or directory ");
+        assert!(error.get_guidance().is_some());
+
+        let reason = String::new();
+        let error = SerialErrorKind::OpenFailed { reason };
+        assert_eq!(String::from(error), " Failed to open port ");
+
+        let error = SerialErrorKind::Other(String::from("Break failed"));
+        assert_eq!(String::from(error.clone()), " Break failed ");
+        assert_eq!(error.get_guidance(), None);
+        assert!(SerialErrorKind::PermissionDenied.get_guidance().is_some());
+    }
+
+    #[test]
+    fn test_flow_control_to_string() {
+        let mut flow_control = FlowControl::None;
+        assert_eq!(flow_control.to_string(), "None");
+        flow_control = FlowControl::Hardware;
+        assert_eq!(flow_control.to_string(), "Hardware");
+        flow_control = FlowControl::Software;
+        assert_eq!(flow_control.to_string(), "Software");
+    }
+
+    #[test]
+    fn test_line_ending() {
+        assert_eq!(LineEnding::None.get_bytes(), b"");
+        assert_eq!(LineEnding::Cr.get_bytes(), b"\r");
+        assert_eq!(LineEnding::Lf.get_bytes(), b"\n");
+        assert_eq!(LineEnding::CrLf.get_bytes(), b"\r\n");
+        assert_eq!(LineEnding::CrLf.to_string(), "CRLF");
+    }
+
+    #[test]
+    fn test_get_binding() {
+        let keymap = get_default_keymap();
+        let key = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+        let message = |screen: Screen, key: KeyEvent| get_binding(&keymap, &screen, &key).map(|b| b.message.clone());
+
+        // A chord bound on a screen overrides the one bound everywhere
+        let quick = key(KeyCode::Char(QUICK_CONNECT_CHAR), KeyModifiers::CONTROL);
+        assert_eq!(message(Screen::DeviceList, quick), Some(Message::QuickConnect));
+        assert_eq!(message(Screen::Terminal, quick), Some(Message::ToggleResponses));
+        assert_eq!(message(Screen::Menu, key(KeyCode::Delete, KeyModifiers::NONE)), None);
+        assert_eq!(message(Screen::Terminal, key(KeyCode::F(5), KeyModifiers::NONE)), Some(Message::StepReplay));
+        assert_eq!(message(Screen::Menu, key(KeyCode::F(5), KeyModifiers::SHIFT)), None);
+
+        // Sent as the control code of ctrl+_, ctrl+- is searching
+        assert_eq!(message(Screen::Terminal, key(KeyCode::Char('-'), KeyModifiers::ALT)), Some(Message::ShorterRows));
+        assert_eq!(message(Screen::Terminal, key(KeyCode::Char('7'), KeyModifiers::CONTROL)), Some(Message::Search));
+
+        // None of the defaults conflict, unlike a chord bound twice
+        assert!((0..keymap.len()).all(|index| is_conflicted(&keymap, index) == false));
+        let mut keymap = keymap;
+        keymap.push(Binding::new(KeyCode::F(9), KeyModifiers::NONE, &[Screen::Menu], Message::Clear, "Clears"));
+        let conflicts: Vec<String> = (0..keymap.len())
+            .filter(|index| is_conflicted(&keymap, *index))
+            .map(|index| keymap[index].get_label())
+            .collect();
+        assert_eq!(conflicts, vec!["F9", "F9"]);
+        let delete = keymap.iter().find(|b| b.message == Message::Delete).unwrap();
+        assert_eq!(delete.get_label(), "del");
+        let space = keymap.iter().find(|b| b.message == Message::ToggleAtHistory).unwrap();
+        assert_eq!(space.get_label(), "ctrl+space");
+        let end = keymap.iter().find(|b| b.message == Message::ScrollBottom).unwrap();
+        assert_eq!(end.get_label(), "ctrl+end");
+        let longer = keymap.iter().find(|b| b.message == Message::LongerRows).unwrap();
+        assert_eq!(longer.get_label(), "alt++");
+    }
 }
@@ -0,0 +1,315 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the configure 'popup', which allows the user to change
+* the baud rate and framing of the open serial port.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::common::*;
+use crate::menu::{get_input_text, MenuInput};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigureModel {
+    state: State,
+    selected: usize,
+    inputs: Vec<MenuInput>,
+    parameters: PortParameters,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const POPUP_WIDTH: u16 = 36;
+const TITLE_WIDTH: usize = 12;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl ConfigureModel {
+    pub fn new(parameters: PortParameters) -> ConfigureModel {
+        let mut inputs = vec![
+            MenuInput::default()
+                .limit(10)
+                .title(String::from("Baudrate"))
+                .placeholder(String::from("9600")),
+
+            MenuInput::default()
+                .limit(1)
+                .title(String::from("Data bits"))
+                .placeholder(String::from("8")),
+
+            MenuInput::default()
+                .limit(1)
+                .title(String::from("Stop bits"))
+                .placeholder(String::from("1")),
+
+            MenuInput::default()
+                .limit(4)
+                .title(String::from("Parity"))
+                .placeholder(String::from("Even")),
+
+            MenuInput::default()
+                .limit(8)
+                .title(String::from("Flow ctrl"))
+                .placeholder(String::from("None")),
+        ];
+
+        // The inputs start from the settings of the open port
+        if let Some(b) = parameters.baud_rate {
+            inputs[0].value = b.to_string();
+        }
+        if let Some(d) = parameters.data_bits {
+            inputs[1].value = d.to_string();
+        }
+        if let Some(s) = parameters.stop_bits {
+            inputs[2].value = s.to_string();
+        }
+        if let Some(p) = parameters.parity.clone() {
+            inputs[3].value = p.to_string();
+        }
+        if let Some(f) = parameters.flow_control.clone() {
+            inputs[4].value = f.to_string();
+        }
+
+        ConfigureModel {
+            state: State::Running,
+            selected: 0,
+            parameters,
+            inputs,
+        }
+    }
+}
+
+impl Nolp for ConfigureModel {
+    fn get_state(&self) -> State {
+        return self.state.clone();
+    }
+
+    fn set_state(&mut self, s: State) {
+        self.state = s;
+    }
+}
+
+impl Tea for ConfigureModel {
+    fn update(&mut self, msg: Message) -> State {
+        match msg {
+            Message::PreviousElement => {
+                if self.selected == 0 {
+                    self.selected = self.inputs.len() - 1;
+                } else {
+                    self.selected -= 1;
+                }
+            }
+            Message::NextElement => {
+                if self.selected == self.inputs.len() - 1 {
+                    self.selected = 0;
+                } else {
+                    self.selected += 1;
+                }
+            }
+            Message::Input(input) => {
+                let element = &self.inputs[self.selected];
+                if element.value.len() < element.limit && validate_input(self.selected, input) {
+                    self.inputs[self.selected].value.push(input);
+                }
+            }
+            Message::Backspace => {
+                self.inputs[self.selected].value.pop();
+            }
+            Message::Enter => {
+                if let Some(parameters) = get_port_parameters(self) {
+                    self.state = State::Switching(Screen::Terminal, Some(Box::new(parameters)));
+                }
+            }
+            _ => {}
+        }
+        return self.get_state();
+    }
+
+    fn view(&mut self, frame: &mut Frame) {
+        let bounds = get_bounds(frame.size(), self.inputs.len() as u16 + 4);
+        let block = Block::default()
+            .title(" Configure ")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+
+        let mut lines = get_input_lines(self);
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("Enter to apply | ctrl+{} to cancel", CONFIGURE_CHAR),
+            Style::default().fg(crate::PLACEHOLDER_COLOR),
+        ));
+
+        let popup = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(block);
+        frame.render_widget(Clear, bounds);
+        frame.render_widget(popup, bounds);
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_bounds(area: Rect, height: u16) -> Rect {
+    let width = POPUP_WIDTH.min(area.width);
+    let height = height.min(area.height);
+    return Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+}
+
+fn get_input_lines<'a>(model: &ConfigureModel) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    let width = usize::from(POPUP_WIDTH) - TITLE_WIDTH - 4;
+    for (i, input) in model.inputs.iter().enumerate() {
+        let mut title_style = Style::default();
+        if input.invalid {
+            title_style = title_style.fg(crate::INVALID_COLOR);
+        } else if i == model.selected {
+            title_style = title_style.fg(crate::SELECTED_COLOR);
+        }
+
+        let (text, style) = get_input_text(input);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{: <w$}", input.title, w = TITLE_WIDTH), title_style),
+            Span::styled(format!("{: >w$}", text, w = width), style),
+        ]));
+    }
+    return lines;
+}
+
+fn get_port_parameters(model: &mut ConfigureModel) -> Option<PortParameters> {
+    let baud_rate = model.inputs[0].value.parse::<u32>().ok().filter(|b| *b > 0);
+    let data_bits = model.inputs[1].value.parse::<u8>().ok();
+    let stop_bits = model.inputs[2].value.parse::<u8>().ok();
+    let parity = match model.inputs[3].value.to_lowercase().as_str() {
+        "even" => Some(Parity::Even),
+        "odd" => Some(Parity::Odd),
+        "none" => Some(Parity::None),
+        _ => None,
+    };
+    let flow_control = match model.inputs[4].value.to_lowercase().as_str() {
+        "" | "none" => Some(FlowControl::None),
+        "hardware" => Some(FlowControl::Hardware),
+        "software" => Some(FlowControl::Software),
+        _ => None,
+    };
+
+    model.inputs[0].invalid = baud_rate.is_none();
+    model.inputs[1].invalid = data_bits.is_none();
+    model.inputs[2].invalid = stop_bits.is_none();
+    model.inputs[3].invalid = parity.is_none();
+    model.inputs[4].invalid = flow_control.is_none();
+    if model.inputs.iter().any(|input| input.invalid) {
+        return None;
+    }
+
+    let mut parameters = model.parameters.clone();
+    parameters.baud_rate = baud_rate;
+    parameters.data_bits = data_bits;
+    parameters.stop_bits = stop_bits;
+    parameters.parity = parity;
+    parameters.flow_control = flow_control;
+    return Some(parameters);
+}
+
+fn validate_input(selected: usize, input: char) -> bool {
+    match selected {
+        0 => match input.to_digit(10) {
+            Some(_) => return true,
+            None => return false,
+        },
+        1 => match input.to_digit(10) {
+            Some(v) => return (5..=8).contains(&v),
+            None => return false,
+        },
+        2 => match input.to_digit(10) {
+            Some(v) => return (1..=2).contains(&v),
+            None => return false,
+        },
+        _ => return true,
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_parameters() -> PortParameters {
+        let mut parameters = PortParameters::default().name(String::from("test/port"));
+        parameters.baud_rate = Some(9600);
+        parameters.data_bits = Some(8);
+        parameters.stop_bits = Some(1);
+        parameters.parity = Some(Parity::None);
+        parameters.mode = Some(Mode::Hex);
+        return parameters;
+    }
+
+    #[test]
+    fn test_update_apply() {
+        let mut test_model = ConfigureModel::new(test_parameters());
+        assert_eq!(test_model.inputs[0].value, "9600");
+        assert_eq!(test_model.inputs[3].value, "None");
+
+        for _ in 0..4 {
+            test_model.update(Message::Backspace);
+        }
+        for c in "115200".chars() {
+            test_model.update(Message::Input(c));
+        }
+
+        let mut expected = test_parameters();
+        expected.baud_rate = Some(115200);
+        expected.flow_control = Some(FlowControl::None);
+        assert_eq!(
+            test_model.update(Message::Enter),
+            State::Switching(Screen::Terminal, Some(Box::new(expected)))
+        );
+    }
+
+    #[test]
+    fn test_update_invalid() {
+        let mut test_model = ConfigureModel::new(test_parameters());
+
+        test_model.update(Message::PreviousElement);
+        test_model.update(Message::Input('x'));
+        assert_eq!(test_model.selected, 4);
+        assert_eq!(test_model.update(Message::Enter), State::Running);
+        assert!(test_model.inputs[4].invalid);
+
+        test_model.update(Message::NextElement);
+        test_model.update(Message::NextElement);
+        test_model.update(Message::Input('9'));
+        assert_eq!(test_model.inputs[1].value, "8");
+    }
+}
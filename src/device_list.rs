@@ -1,7 +1,10 @@
 /*******************************************************************************/
 /********************************************************************************
 * DESCRIPTION: Defines the device-list 'view', which allows the user to see
-* a list of available devices and/or choose one.
+* a list of available devices (with what they report of themselves) and/or
+* choose one, under the recent connections (which connect straight away). A
+* device can be connected to straight away too, as it last was (or with the
+* defaults of the menu).
 * AUTHOR: jb
 * DATE: 12/30/23
 ********************************************************************************/
@@ -9,28 +12,74 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
     Frame,
 };
-use std::rc::Rc;
+use std::{
+    cmp::Ordering,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use crate::common::*;
-use crate::serial::get_available_devices;
+use crate::menu::get_default_parameters;
+use crate::serial::{get_device_info, probe_devices, DeviceInfo, DeviceKind};
+use crate::terminal::get_port_summary;
 
 /******************************************************************************/
 /*******************************************************************************
 * Public Interface
 *******************************************************************************/
 /******************************************************************************/
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct DeviceListModel {
     state: State,
     bounds: Rect,
     offset: usize,
     selected: usize,
-    devices: Vec<String>,
+    devices: Vec<DeviceInfo>,
+    recent: Vec<RecentConnection>,
     scroll: ScrollbarState,
+    // Why the devices could not be listed, shown in their place
+    error: Option<String>,
+    // When the devices were last listed, which is not done on every frame
+    refreshed: Option<Instant>,
+    // The devices plugged in (or unplugged, which are still listed) lately,
+    // by name
+    changes: Vec<(String, DeviceChange)>,
+    // What is typed narrows the list down to what contains it
+    filter: String,
+    // The port of the session kept open behind the list, and how it stands
+    session: Option<(DeviceInfo, ConnectionStatus)>,
+    hidden: HiddenDevices,
+    // Whether what is hidden is shown anyway (dimmed), to be shown again
+    show_hidden: bool,
+    // Whether what is hidden changed, and is yet to be kept
+    hidden_changed: bool,
+    // Whether the ports could be opened, as far as the last probe found out
+    probes: Option<SharedProbes>,
+    // Whether a probe was asked for, and is yet to be confirmed
+    confirming: bool,
+    // The path to copy, until it is handed to the clipboard
+    copy: Option<String>,
+}
+
+// The ports hidden from the list, by name, and whether the ports built into
+// the machine are hidden as a whole
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HiddenDevices {
+    pub ports: Vec<String>,
+    pub native: bool,
+}
+
+// A connection started before, where the time is the timestamp it was
+// started at (as in the capture)
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecentConnection {
+    pub parameters: PortParameters,
+    pub time: String,
 }
 
 /******************************************************************************/
@@ -44,6 +93,22 @@ enum SelectElement {
     Next,
 }
 
+// The groups the devices are listed under, in this order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum DeviceGroup {
+    Usb,
+    Bluetooth,
+    Native,
+    Other,
+}
+
+// When a device was found plugged in, or found unplugged
+#[derive(Clone, Debug, PartialEq)]
+enum DeviceChange {
+    Added(Instant),
+    Removed(Instant),
+}
+
 /******************************************************************************/
 /*******************************************************************************
 * Local Constants
@@ -51,6 +116,11 @@ enum SelectElement {
 /******************************************************************************/
 const CONTENT_LENGTH: usize = 20;
 const MARGIN_TOP: usize = 2;
+// Often enough to notice an adapter plugged in, rarely enough that listing
+// them (which is slow on some platforms) does not hold up the frames
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+// How long a device plugged in is marked new, and one unplugged is kept
+const CHANGE_DURATION: Duration = Duration::from_secs(6);
 
 /******************************************************************************/
 /*******************************************************************************
@@ -63,6 +133,18 @@ impl Default for DeviceListModel {
             offset: 0,
             selected: 0,
             devices: Vec::new(),
+            recent: Vec::new(),
+            error: None,
+            refreshed: None,
+            changes: Vec::new(),
+            filter: String::new(),
+            session: None,
+            hidden: HiddenDevices::default(),
+            show_hidden: false,
+            hidden_changed: false,
+            probes: None,
+            confirming: false,
+            copy: None,
             state: State::Running,
             bounds: Rect::default(),
             scroll: ScrollbarState::default()
@@ -71,6 +153,48 @@ impl Default for DeviceListModel {
     }
 }
 
+impl DeviceListModel {
+    pub fn new(
+        recent: Vec<RecentConnection>,
+        session: Option<(String, ConnectionStatus)>,
+        hidden: HiddenDevices,
+    ) -> DeviceListModel {
+        let session = session.map(|(name, status)| (DeviceInfo { name, ..Default::default() }, status));
+        let mut model = DeviceListModel {
+            recent,
+            session,
+            hidden,
+            ..Default::default()
+        };
+        refresh_devices(&mut model);
+        return model;
+    }
+
+    pub fn take_copy(&mut self) -> Option<String> {
+        return self.copy.take();
+    }
+
+    // What is hidden, once it changed, for it to be kept between runs
+    pub fn take_hidden(&mut self) -> Option<HiddenDevices> {
+        if self.hidden_changed == false {
+            return None;
+        }
+        self.hidden_changed = false;
+        return Some(self.hidden.clone());
+    }
+}
+
+impl fmt::Display for DeviceGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviceGroup::Usb => write!(f, "USB"),
+            DeviceGroup::Bluetooth => write!(f, "Bluetooth"),
+            DeviceGroup::Native => write!(f, "PCI/native"),
+            DeviceGroup::Other => write!(f, "Other"),
+        }
+    }
+}
+
 impl Nolp for DeviceListModel {
     fn get_state(&self) -> State {
         return self.state.clone();
@@ -83,6 +207,10 @@ impl Nolp for DeviceListModel {
 
 impl Tea for DeviceListModel {
     fn update(&mut self, msg: Message) -> State {
+        // Anything else typed (but the frames going by) drops the probe
+        if matches!(msg, Message::ProbeDevices | Message::Tick) == false {
+            self.confirming = false;
+        }
         match msg {
             Message::PreviousElement => {
                 select_element(self, SelectElement::Previous);
@@ -93,6 +221,64 @@ impl Tea for DeviceListModel {
             Message::Enter => {
                 switch_screen(self);
             }
+            Message::QuickConnect => {
+                quick_connect(self);
+            }
+            Message::Connection(status) => {
+                if let Some((_, current)) = self.session.as_mut() {
+                    *current = status;
+                }
+            }
+            Message::RefreshDevices => {
+                refresh_devices(self);
+            }
+            Message::ProbeDevices if self.confirming => {
+                self.confirming = false;
+                probe(self);
+            }
+            // Opening a port toggles DTR (and RTS) on most platforms, which
+            // resets some boards, so the probe is only done once confirmed
+            Message::ProbeDevices => {
+                self.confirming = true;
+            }
+            Message::Copy => {
+                self.copy = Some(get_selected_name(self).unwrap_or_default());
+            }
+            Message::Delete => {
+                hide_device(self);
+            }
+            Message::ToggleHidden => {
+                self.show_hidden = self.show_hidden == false;
+                update_hidden(self);
+            }
+            Message::ToggleNativeHidden => {
+                self.hidden.native = self.hidden.native == false;
+                self.hidden_changed = true;
+                update_hidden(self);
+            }
+            Message::Tick if self.refreshed.is_none_or(|r| r.elapsed() >= REFRESH_INTERVAL) => {
+                refresh_devices(self);
+            }
+            Message::Input(input) => {
+                self.filter.push(input);
+                update_filter(self);
+            }
+            Message::Backspace => {
+                self.filter.pop();
+                update_filter(self);
+            }
+            // The filter is cleared first, then back to the session (or to
+            // the menu without a port chosen)
+            Message::Cancel if self.filter.len() > 0 => {
+                self.filter.clear();
+                update_filter(self);
+            }
+            Message::Cancel if self.session.is_some() => {
+                self.state = State::Switching(Screen::Terminal, None);
+            }
+            Message::Cancel => {
+                self.state = State::Switching(Screen::Menu, None);
+            }
             _ => {}
         }
         return self.get_state();
@@ -103,9 +289,7 @@ impl Tea for DeviceListModel {
         self.bounds = bounds;
 
         render_title(frame, layout[0]);
-
-        self.devices = get_available_devices().expect("Failed to determine available devices");
-
+        render_filter(frame, layout[1], self);
         render_device_list(frame, layout[2], self);
         render_scrollbar(frame, layout[2], self);
     }
@@ -129,8 +313,223 @@ fn get_layout(fsize: Rect) -> (Rect, Rc<[Rect]>) {
     return (bounds, layout);
 }
 
+fn get_length(model: &DeviceListModel) -> usize {
+    return get_shown_recent(model).len() + get_shown_devices(model).len();
+}
+
+fn get_shown_devices(model: &DeviceListModel) -> Vec<&DeviceInfo> {
+    // By the name or anything the device reports, in any case, where the
+    // port of the session is listed even once it is no longer found (or
+    // hidden), sorted by group then by name
+    let filter = model.filter.to_lowercase();
+    let missing = model.session.as_ref().map(|(device, _)| device).filter(|device| {
+        let pseudo = [TCP_PREFIX, REPLAY_PREFIX].iter().any(|p| device.name.starts_with(p));
+        pseudo == false && model.devices.iter().any(|d| d.name == device.name) == false
+    });
+    let shown = model.devices.iter().chain(missing).filter(|device| {
+        let text = format!("{} {}", device.name, get_device_detail(device));
+        let hidden = is_hidden(model, device) && model.show_hidden == false;
+        text.to_lowercase().contains(&filter) && (hidden == false || is_session(model, &device.name))
+    });
+    let mut shown: Vec<&DeviceInfo> = shown.collect();
+    shown.sort_by(|a, b| {
+        let order = get_group(a).cmp(&get_group(b));
+        return order.then_with(|| compare_names(&a.name, &b.name));
+    });
+    return shown;
+}
+
+fn get_group(device: &DeviceInfo) -> DeviceGroup {
+    // A port the platform says nothing of is, as a rule, one built into the
+    // machine (e.g., ttyS0 or COM1)
+    match device.kind {
+        DeviceKind::Usb => return DeviceGroup::Usb,
+        DeviceKind::Bluetooth => return DeviceGroup::Bluetooth,
+        DeviceKind::Pci => return DeviceGroup::Native,
+        DeviceKind::Loopback => return DeviceGroup::Other,
+        DeviceKind::Unknown => {
+            let name = device.name.rsplit('/').next().unwrap_or_default();
+            match name.starts_with("ttyS") || name.starts_with("COM") {
+                true => return DeviceGroup::Native,
+                false => return DeviceGroup::Other,
+            }
+        }
+    }
+}
+
+fn compare_names(a: &str, b: &str) -> Ordering {
+    // The numbers in the names are compared as numbers, so that ttyUSB2
+    // comes before ttyUSB10
+    let order = get_name_key(a).cmp(&get_name_key(b));
+    return order.then_with(|| a.cmp(b));
+}
+
+fn get_name_key(name: &str) -> Vec<(String, Option<u64>)> {
+    // As in `ttyUSB10` to `[("ttyUSB", Some(10))]`
+    let mut key = Vec::new();
+    let mut text = String::new();
+    let mut digits = String::new();
+    for c in name.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if digits.len() > 0 {
+            key.push((text.clone(), digits.parse().ok()));
+            text.clear();
+            digits.clear();
+        }
+        text.push(c);
+    }
+    key.push((text, digits.parse().ok()));
+    return key;
+}
+
+fn is_hidden(model: &DeviceListModel, device: &DeviceInfo) -> bool {
+    let native = model.hidden.native && get_group(device) == DeviceGroup::Native;
+    return native || model.hidden.ports.contains(&device.name);
+}
+
+fn is_group_start(shown: &[&DeviceInfo], index: usize) -> bool {
+    return index == 0 || get_group(shown[index - 1]) != get_group(shown[index]);
+}
+
+fn get_shown_recent(model: &DeviceListModel) -> Vec<&RecentConnection> {
+    let filter = model.filter.to_lowercase();
+    let shown = model.recent.iter().filter(|recent| {
+        let text = get_port_summary(&recent.parameters);
+        text.to_lowercase().contains(&filter)
+    });
+    return shown.collect();
+}
+
+fn get_device_detail(device: &DeviceInfo) -> String {
+    // As in `USB 0403:6001  FTDI  FT232R USB UART  SN A50285BI`, or the
+    // kind of the port where that is all there is
+    match device.kind {
+        DeviceKind::Usb => {
+            let mut detail = vec![String::from("USB")];
+            if let Some((vid, pid)) = device.vid_pid {
+                detail[0] += &format!(" {:04X}:{:04X}", vid, pid);
+            }
+            detail.extend(device.manufacturer.clone());
+            detail.extend(device.product.clone());
+            detail.extend(device.serial_number.as_ref().map(|s| format!("SN {}", s)));
+            return detail.join("  ");
+        }
+        DeviceKind::Pci => return String::from("PCI"),
+        DeviceKind::Bluetooth => return String::from("Bluetooth"),
+        DeviceKind::Loopback => return String::from("Loopback"),
+        DeviceKind::Unknown => return String::from("Unknown"),
+    }
+}
+
+fn get_quick_parameters(model: &DeviceListModel, name: &str) -> Option<PortParameters> {
+    // The last connection to the port, or the defaults (i.e., 9600 8N1)
+    let recent = model.recent.iter().find(|r| r.parameters.name.as_deref() == Some(name));
+    if let Some(recent) = recent {
+        return Some(recent.parameters.clone());
+    }
+    return Some(get_default_parameters(name));
+}
+
+fn is_session(model: &DeviceListModel, name: &str) -> bool {
+    return model.session.as_ref().is_some_and(|(device, _)| device.name == name);
+}
+
+fn is_session_connected(model: &DeviceListModel) -> bool {
+    // Unplugged, the port is not connected whatever the session last heard
+    let (device, status) = match &model.session {
+        Some(session) => session,
+        None => return false,
+    };
+    return *status == ConnectionStatus::Connected && is_available(model, &device.name);
+}
+
+fn get_selected_line(model: &DeviceListModel) -> usize {
+    // Past the header of the recent connections, and past the blank line
+    // and the header of the devices after them, where a device takes two
+    // and the header of each group one
+    let recent = get_shown_recent(model).len();
+    if model.selected < recent {
+        return model.selected + 1;
+    }
+    let start = if recent > 0 { recent + 3 } else { 0 };
+    let shown = get_shown_devices(model);
+    let index = model.selected - recent;
+    let headers = (0..=index.min(shown.len().saturating_sub(1))).filter(|i| is_group_start(&shown, *i)).count();
+    return start + index * 2 + headers;
+}
+
+fn get_offset(model: &DeviceListModel) -> usize {
+    // The first device of a group is scrolled to along with its header
+    let line = get_selected_line(model);
+    let index = model.selected.checked_sub(get_shown_recent(model).len());
+    let shown = get_shown_devices(model);
+    match index {
+        Some(index) if index < shown.len() && is_group_start(&shown, index) => return line - 1,
+        _ => return line,
+    }
+}
+
+fn get_change<'a>(changes: &'a [(String, DeviceChange)], name: &str) -> Option<&'a DeviceChange> {
+    return changes.iter().find(|(n, _)| n == name).map(|(_, change)| change);
+}
+
+fn get_merged(
+    shown: &[DeviceInfo],
+    changes: &[(String, DeviceChange)],
+    listed: Vec<DeviceInfo>,
+    now: Instant,
+) -> (Vec<DeviceInfo>, Vec<(String, DeviceChange)>) {
+    // What was not shown (or shown as unplugged) is new, and stays new for a
+    // while, where what was shown but is no longer listed is kept where it
+    // was for as long, so that the rest do not jump around
+    let recent = |since: &Instant| now.duration_since(*since) < CHANGE_DURATION;
+    let mut merged = listed;
+    let mut merged_changes = Vec::new();
+    for device in &merged {
+        let change = get_change(changes, &device.name);
+        let was_shown = shown.iter().any(|d| d.name == device.name);
+        match change {
+            Some(DeviceChange::Added(since)) if recent(since) => {
+                merged_changes.push((device.name.clone(), DeviceChange::Added(*since)));
+            }
+            Some(DeviceChange::Removed(_)) => {
+                merged_changes.push((device.name.clone(), DeviceChange::Added(now)));
+            }
+            _ if was_shown == false => {
+                merged_changes.push((device.name.clone(), DeviceChange::Added(now)));
+            }
+            _ => {}
+        }
+    }
+    for (index, device) in shown.iter().enumerate() {
+        if merged.iter().any(|d| d.name == device.name) {
+            continue;
+        }
+        let since = match get_change(changes, &device.name) {
+            Some(DeviceChange::Removed(since)) => *since,
+            _ => now,
+        };
+        if recent(&since) {
+            merged.insert(index.min(merged.len()), device.clone());
+            merged_changes.push((device.name.clone(), DeviceChange::Removed(since)));
+        }
+    }
+    return (merged, merged_changes);
+}
+
+fn is_available(model: &DeviceListModel, name: &str) -> bool {
+    // A network port or a replay is never listed, but is not missing
+    let pseudo = [TCP_PREFIX, REPLAY_PREFIX].iter().any(|p| name.starts_with(p));
+    let removed = matches!(get_change(&model.changes, name), Some(DeviceChange::Removed(_)));
+    return pseudo || (model.devices.iter().any(|d| d.name == name) && removed == false);
+}
+
 fn select_element(model: &mut DeviceListModel, direction: SelectElement) {
-    if model.devices.len() == 0 {
+    let length = get_length(model);
+    if length == 0 {
         return;
     }
 
@@ -138,10 +537,10 @@ fn select_element(model: &mut DeviceListModel, direction: SelectElement) {
         SelectElement::Previous => (
             model.selected == 0,
             model.selected as i32 - 1,
-            model.devices.len() - 1,
+            length - 1,
         ),
         SelectElement::Next => (
-            model.selected == model.devices.len() - 1,
+            model.selected == length - 1,
             model.selected as i32 + 1,
             0,
         ),
@@ -152,30 +551,238 @@ fn select_element(model: &mut DeviceListModel, direction: SelectElement) {
     } else {
         model.selected = nominal as usize;
     }
+    follow_selection(model);
+}
 
+fn follow_selection(model: &mut DeviceListModel) {
     if model.bounds.height <= CONTENT_LENGTH as u16 {
-        model.offset = model.selected;
+        model.offset = get_offset(model);
         model.scroll = model.scroll.position(model.offset);
     }
 }
 
-fn render_device_list(frame: &mut Frame, area: Rect, model: &mut DeviceListModel) {
-    let mut text: Vec<Line> = Vec::new();
+fn refresh_devices(model: &mut DeviceListModel) {
+    let devices = get_device_info().map_err(|e| format!("Failed to list the devices: {}", e));
+    let now = Instant::now();
+    update_devices(model, devices, now);
+    model.refreshed = Some(now);
+}
 
-    if model.devices.len() > 0 {
-        let style = Style::default().fg(crate::SELECTED_COLOR);
-        for (index, name) in model.devices.iter().enumerate() {
-            if index == model.selected {
-                text.push(Line::styled(name.to_string(), style));
-            } else {
-                text.push(Line::from(name.to_string()));
-            }
+fn update_filter(model: &mut DeviceListModel) {
+    // The list changes under the selection, which starts over from the top
+    model.selected = 0;
+    model.offset = 0;
+    model.scroll = model.scroll.position(0);
+    follow_selection(model);
+}
+
+fn get_selected_name(model: &DeviceListModel) -> Option<String> {
+    // The port of a recent connection, or the path of a device
+    let recent = get_shown_recent(model);
+    if let Some(recent) = recent.get(model.selected) {
+        return recent.parameters.name.clone();
+    }
+    let devices = get_shown_devices(model);
+    return devices.get(model.selected - recent.len()).map(|device| device.name.clone());
+}
+
+fn probe(model: &mut DeviceListModel) {
+    // Only what can be told apart by opening it, and never the port of the
+    // session (connected or not), where the results of the last probe go with it
+    let names = get_shown_devices(model).into_iter().filter(|device| {
+        let removed = matches!(get_change(&model.changes, &device.name), Some(DeviceChange::Removed(_)));
+        device.kind != DeviceKind::Loopback && removed == false
+    });
+    let names: Vec<String> = names.map(|device| device.name.clone()).collect();
+    let session = model.session.as_ref().map(|(device, _)| device.name.as_str());
+    model.probes = Some(probe_devices(names, session));
+}
+
+fn get_probe(model: &DeviceListModel, name: &str) -> Option<Result<(), SerialErrorKind>> {
+    let probes = model.probes.as_ref()?.lock().ok()?;
+    return probes.iter().find(|(n, _)| n == name).map(|(_, result)| result.clone());
+}
+
+fn hide_device(model: &mut DeviceListModel) {
+    // The device selected is hidden, or shown again if it was
+    let recent = get_shown_recent(model).len();
+    let device = model.selected.checked_sub(recent).and_then(|i| get_shown_devices(model).get(i).map(|d| d.name.clone()));
+    let name = match device {
+        Some(name) => name,
+        None => return,
+    };
+    match model.hidden.ports.iter().position(|port| *port == name) {
+        Some(index) => {
+            model.hidden.ports.remove(index);
         }
-    } else {
+        None => model.hidden.ports.push(name),
+    }
+    model.hidden_changed = true;
+    update_hidden(model);
+}
+
+fn update_hidden(model: &mut DeviceListModel) {
+    // The selection stays where it was, or on the last of what is left
+    model.selected = model.selected.min(get_length(model).saturating_sub(1));
+    follow_selection(model);
+}
+
+fn update_devices(model: &mut DeviceListModel, devices: Result<Vec<DeviceInfo>, String>, now: Instant) {
+    // The device selected stays selected wherever it moved to (or the
+    // selection stays where it was, if it went)
+    let recent = get_shown_recent(model).len();
+    let shown = get_shown_devices(model);
+    let selected = model.selected.checked_sub(recent).and_then(|i| shown.get(i)).map(|d| d.name.clone());
+    match devices {
+        // Nothing is new when the list is first shown
+        Ok(devices) if model.refreshed.is_none() => {
+            model.devices = devices;
+            model.error = None;
+        }
+        Ok(devices) => {
+            (model.devices, model.changes) = get_merged(&model.devices, &model.changes, devices, now);
+            model.error = None;
+        }
+        Err(e) => {
+            model.devices = Vec::new();
+            model.changes = Vec::new();
+            model.error = Some(e);
+        }
+    }
+    if let Some(name) = selected {
+        if let Some(i) = get_shown_devices(model).iter().position(|d| d.name == name) {
+            model.selected = recent + i;
+        }
+    }
+    model.selected = model.selected.min(get_length(model).saturating_sub(1));
+    follow_selection(model);
+}
+
+fn get_recent_lines<'a>(model: &DeviceListModel) -> Vec<Line<'a>> {
+    // As in `/dev/ttyUSB0 115200 8N1 [Hex]  2026-10-17 12:04`, dimmed for a
+    // port that is not plugged in (yet), which can still be chosen
+    let header = Style::default().add_modifier(Modifier::BOLD);
+    let mut text = vec![Line::styled("Recent", header)];
+    for (index, recent) in get_shown_recent(model).into_iter().enumerate() {
+        let name = recent.parameters.name.clone().unwrap_or_default();
+        let time = recent.time.get(..16).unwrap_or_default().replace('T', " ");
+        let mut style = Style::default();
+        if index == model.selected {
+            style = style.fg(crate::SELECTED_COLOR);
+        }
+        if is_available(model, &name) == false {
+            style = style.add_modifier(Modifier::DIM);
+        }
+        let entry = format!("{}  {}", get_port_summary(&recent.parameters), time);
+        text.push(Line::styled(entry, style));
+    }
+    text.push(Line::from(""));
+    text.push(Line::styled("Devices", header));
+    return text;
+}
+
+fn get_device_lines<'a>(model: &DeviceListModel) -> Vec<Line<'a>> {
+    // A device plugged in lately is tagged as new, and one unplugged is
+    // dimmed until it goes
+    let mut text = Vec::new();
+    let shown = get_shown_devices(model);
+    if shown.len() == 0 {
         let style = Style::default().fg(crate::INVALID_COLOR);
-        text.push(Line::styled("No devices available", style));
+        let empty = match (&model.error, model.devices.len()) {
+            (Some(error), _) => error.clone(),
+            (None, 0) => String::from("No devices available"),
+            (None, _) if model.filter.len() > 0 => String::from("No matching devices"),
+            (None, _) => format!("Every device is hidden (F{} shows them)", SHOW_HIDDEN_KEY),
+        };
+        text.push(Line::styled(empty, style));
+        return text;
+    }
+    let recent = get_shown_recent(model).len();
+    let tag_style = Style::default().fg(crate::SELECTED_COLOR).add_modifier(Modifier::BOLD);
+    let group_style = Style::default().fg(crate::PLACEHOLDER_COLOR).add_modifier(Modifier::BOLD);
+    for (index, device) in shown.iter().enumerate() {
+        if is_group_start(&shown, index) {
+            let mut header = get_group(device).to_string();
+            if model.hidden.native && get_group(device) == DeviceGroup::Native {
+                header += " (hidden)";
+            }
+            text.push(Line::styled(header, group_style));
+        }
+        let mut style = Style::default();
+        let detail_style = Style::default().add_modifier(Modifier::DIM);
+        if index + recent == model.selected {
+            style = style.fg(crate::SELECTED_COLOR);
+        }
+        let session = is_session(model, &device.name);
+        if session {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        let mut name = vec![Span::styled(device.name.clone(), style)];
+        match get_change(&model.changes, &device.name) {
+            Some(DeviceChange::Added(_)) => {
+                name.push(Span::styled(" NEW", tag_style));
+            }
+            Some(DeviceChange::Removed(_)) => {
+                name[0].style = style.add_modifier(Modifier::DIM);
+                name.push(Span::styled(" (removed)", detail_style));
+            }
+            None => {}
+        }
+        // Unmarked until its probe is done
+        match get_probe(model, &device.name) {
+            Some(Ok(())) => name.push(Span::styled(" ● available", Style::default().fg(crate::ACTIVE_COLOR))),
+            Some(Err(SerialErrorKind::Busy)) => {
+                name.push(Span::styled(" ● busy", Style::default().fg(crate::INVALID_COLOR)));
+            }
+            Some(Err(_)) => name.push(Span::styled(" ● unavailable", Style::default().fg(crate::WARNING_COLOR))),
+            None => {}
+        }
+        if is_hidden(model, device) && session == false {
+            name[0].style = name[0].style.add_modifier(Modifier::DIM);
+            name.push(Span::styled(" (hidden)", detail_style));
+        }
+        // The session is returned to rather than connected to again
+        if session && is_session_connected(model) {
+            name.push(Span::styled(" ● connected", Style::default().fg(crate::ACTIVE_COLOR)));
+            name.push(Span::styled("  Enter returns to it", detail_style));
+        } else if session {
+            name.push(Span::styled(" ● disconnected", Style::default().fg(crate::INVALID_COLOR)));
+            name.push(Span::styled("  Enter returns to it", detail_style));
+        } else if let Some(mut parameters) = get_quick_parameters(model, &device.name) {
+            // What connecting straight away would connect with
+            parameters.name = None;
+            let hint = format!("  {}", get_port_summary(&parameters).trim());
+            name.push(Span::styled(hint, detail_style));
+        }
+        text.push(Line::from(name));
+        text.push(Line::styled(get_device_detail(device), detail_style));
+    }
+    return text;
+}
+
+fn quick_connect(model: &mut DeviceListModel) {
+    let recent = get_shown_recent(model).len();
+    if model.selected < recent {
+        switch_screen(model);
+        return;
+    }
+    let device = get_shown_devices(model).get(model.selected - recent).map(|d| d.name.clone());
+    if device.as_deref().is_some_and(|name| is_session(model, name)) {
+        model.state = State::Switching(Screen::Terminal, None);
+    } else if let Some(parameters) = device.and_then(|name| get_quick_parameters(model, &name)) {
+        model.state = State::Switching(Screen::Terminal, Some(Box::new(parameters)));
+    }
+}
+
+fn render_device_list(frame: &mut Frame, area: Rect, model: &mut DeviceListModel) {
+    let mut text: Vec<Line> = Vec::new();
+
+    if get_shown_recent(model).len() > 0 {
+        text.append(&mut get_recent_lines(model));
     }
 
+    text.append(&mut get_device_lines(model));
+
     let list = Paragraph::new(text)
         .scroll((model.offset as u16, 0))
         .alignment(Alignment::Center);
@@ -184,7 +791,7 @@ fn render_device_list(frame: &mut Frame, area: Rect, model: &mut DeviceListModel
 }
 
 fn render_scrollbar(frame: &mut Frame, area: Rect, model: &mut DeviceListModel) {
-    if usize::from(area.height) <= CONTENT_LENGTH && model.devices.len() > 0 {
+    if usize::from(area.height) <= CONTENT_LENGTH && get_length(model) > 0 {
         let scrollbar = Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .track_symbol(None)
@@ -194,6 +801,21 @@ fn render_scrollbar(frame: &mut Frame, area: Rect, model: &mut DeviceListModel)
     }
 }
 
+fn render_filter(frame: &mut Frame, area: Rect, model: &DeviceListModel) {
+    // As the cursor of the menu, with a hint until something is typed
+    let warning = format!("Probing resets boards on DTR, F{} again to probe", PROBE_KEY);
+    let line = match model.filter.len() {
+        _ if model.confirming => Line::styled(warning, Style::default().fg(crate::WARNING_COLOR)),
+        0 => Line::styled("Type to filter", Style::default().fg(crate::PLACEHOLDER_COLOR)),
+        _ => Line::from(vec![
+            Span::from(format!("Filter: {}", model.filter)),
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+        ]),
+    };
+    let filter = Paragraph::new(line).alignment(Alignment::Center);
+    frame.render_widget(filter, Rect { height: 1, ..area });
+}
+
 fn render_title(frame: &mut Frame, area: Rect) {
     let title = Block::default()
         .title("Device List")
@@ -203,11 +825,24 @@ fn render_title(frame: &mut Frame, area: Rect) {
 }
 
 fn switch_screen(model: &mut DeviceListModel) {
-    if model.devices.len() > 0 {
-        let port_name = model.devices[model.selected].clone();
+    // A recent connection is started as it was, skipping the menu, where the
+    // port of the session goes back to the session as it is
+    let recent = get_shown_recent(model);
+    let devices = get_shown_devices(model);
+    let name = match recent.get(model.selected) {
+        Some(recent) => recent.parameters.name.clone(),
+        None => devices.get(model.selected - recent.len()).map(|d| d.name.clone()),
+    };
+    if name.is_some_and(|name| is_session(model, &name)) {
+        model.state = State::Switching(Screen::Terminal, None);
+    } else if let Some(recent) = recent.get(model.selected) {
+        let parameters = Box::new(recent.parameters.clone());
+        model.state = State::Switching(Screen::Terminal, Some(parameters));
+    } else if devices.len() > 0 {
+        let port_name = devices[model.selected - recent.len()].name.clone();
         model.state = State::Switching(
             Screen::Menu,
-            Some(PortParameters::default().name(port_name)),
+            Some(Box::new(PortParameters::default().name(port_name))),
         );
     } else {
         model.state = State::Switching(Screen::Menu, None);
@@ -223,6 +858,13 @@ fn switch_screen(model: &mut DeviceListModel) {
 mod tests {
     use super::*;
 
+    fn get_device(name: &str) -> DeviceInfo {
+        return DeviceInfo {
+            name: String::from(name),
+            ..Default::default()
+        };
+    }
+
     #[test]
     fn test_default_model() {
         let test_model = DeviceListModel::default();
@@ -238,8 +880,7 @@ mod tests {
 
     #[test]
     fn test_get_state() {
-        let mut test_model = DeviceListModel::default();
-        test_model.state = State::Running;
+        let test_model = DeviceListModel { state: State::Running, ..Default::default() };
         assert_eq!(test_model.get_state(), State::Running);
     }
 
@@ -276,10 +917,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_update_cancel() {
+        let mut test_model = DeviceListModel { devices: vec![get_device("test-device")], ..Default::default() };
+
+        // Esc returns without the selected device
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Menu, None));
+    }
+
     #[test]
     fn test_select_element() {
-        let mut test_model = DeviceListModel::default();
-        test_model.bounds = Rect::new(0, 0, 80, (CONTENT_LENGTH - 1) as u16);
+        let mut test_model = DeviceListModel {
+            bounds: Rect::new(0, 0, 80, (CONTENT_LENGTH - 1) as u16),
+            ..Default::default()
+        };
         
         select_element(&mut test_model, SelectElement::Next);
         assert_eq!(test_model.selected, 0);
@@ -289,7 +941,7 @@ mod tests {
 
         let mut test_devices = Vec::new();
         for i in 0..(CONTENT_LENGTH + 1) {
-           test_devices.push(format!("test-device/{}", i));
+           test_devices.push(get_device(&format!("test-device/{}", i)));
         }
         test_model.devices = test_devices;
 
@@ -299,8 +951,10 @@ mod tests {
         select_element(&mut test_model, SelectElement::Previous);
         assert_eq!(test_model.selected, 0);
 
+        // Each device takes two lines, the second for what it reports, under
+        // the header of its group
         select_element(&mut test_model, SelectElement::Next);
-        assert_eq!(test_model.offset, 1);
+        assert_eq!(test_model.offset, 3);
 
         test_model.selected = 0;
         select_element(&mut test_model, SelectElement::Previous);
@@ -318,12 +972,467 @@ mod tests {
         switch_screen(&mut test_model);
         assert_eq!(test_model.state, State::Switching(Screen::Menu, None));
 
-        test_model.devices = vec![String::from("test-device")];
+        test_model.devices = vec![get_device("test-device")];
         switch_screen(&mut test_model);
         let expected = State::Switching(
             Screen::Menu,
-            Some(PortParameters::default().name(String::from("test-device")))
+            Some(Box::new(PortParameters::default().name(String::from("test-device"))))
         );
         assert_eq!(test_model.state, expected);
     }
+
+    #[test]
+    fn test_recent() {
+        let recent = |name: &str| {
+            let mut parameters = PortParameters::default().name(String::from(name));
+            parameters.baud_rate = Some(115200);
+            let time = String::from("2026-10-17T12:04:05.000Z");
+            return RecentConnection { parameters, time };
+        };
+        let mut test_model = DeviceListModel::new(vec![recent("/dev/ttyUSB1"), recent("tcp://host:4000")], None, HiddenDevices::default());
+        test_model.devices = vec![get_device("/dev/ttyUSB0")];
+        test_model.bounds = Rect::new(0, 0, 80, 10);
+
+        // Listed first, under their header, where a port that is not
+        // plugged in is dimmed
+        let lines = get_recent_lines(&test_model);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1].spans[0].content, "/dev/ttyUSB1 115200 8N1  2026-10-17 12:04");
+        assert_eq!(lines[1].spans[0].style.fg, Some(crate::SELECTED_COLOR));
+        assert!(lines[1].spans[0].style.add_modifier.contains(Modifier::DIM));
+        assert!(lines[2].spans[0].style.add_modifier.contains(Modifier::DIM) == false);
+
+        // The devices follow, past the headers
+        select_element(&mut test_model, SelectElement::Next);
+        select_element(&mut test_model, SelectElement::Next);
+        assert_eq!((test_model.selected, test_model.offset), (2, 5));
+        test_model.update(Message::Enter);
+        let expected = PortParameters::default().name(String::from("/dev/ttyUSB0"));
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Menu, Some(Box::new(expected))));
+
+        // A recent connection is started straight away, even if missing
+        select_element(&mut test_model, SelectElement::Next);
+        assert_eq!((test_model.selected, test_model.offset), (0, 1));
+        test_model.update(Message::Enter);
+        let expected = recent("/dev/ttyUSB1").parameters;
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Terminal, Some(Box::new(expected))));
+    }
+
+    #[test]
+    fn test_get_device_detail() {
+        let mut device = DeviceInfo {
+            name: String::from("/dev/ttyUSB0"),
+            kind: DeviceKind::Usb,
+            vid_pid: Some((0x0403, 0x6001)),
+            manufacturer: Some(String::from("FTDI")),
+            product: Some(String::from("FT232R USB UART")),
+            serial_number: Some(String::from("A50285BI")),
+        };
+        assert_eq!(get_device_detail(&device), "USB 0403:6001  FTDI  FT232R USB UART  SN A50285BI");
+
+        // Only what a device reports is shown
+        device.manufacturer = None;
+        device.serial_number = None;
+        assert_eq!(get_device_detail(&device), "USB 0403:6001  FT232R USB UART");
+        device.kind = DeviceKind::Bluetooth;
+        assert_eq!(get_device_detail(&device), "Bluetooth");
+        assert_eq!(get_device_detail(&get_device("/dev/ttyS0")), "Unknown");
+    }
+
+    #[test]
+    fn test_update_devices() {
+        let mut test_model = DeviceListModel {
+            devices: vec![get_device("/dev/ttyUSB0"), get_device("/dev/ttyUSB1")],
+            selected: 1,
+            ..Default::default()
+        };
+
+        // The device selected stays selected as others come and go
+        let devices = vec![get_device("/dev/ttyACM0"), get_device("/dev/ttyUSB0"), get_device("/dev/ttyUSB1")];
+        update_devices(&mut test_model, Ok(devices.clone()), Instant::now());
+        assert_eq!(test_model.selected, 2);
+        update_devices(&mut test_model, Ok(devices), Instant::now());
+        assert_eq!(test_model.selected, 2);
+
+        // Or the selection stays in place (within the list) once it goes
+        update_devices(&mut test_model, Ok(vec![get_device("/dev/ttyACM0")]), Instant::now());
+        assert_eq!(test_model.selected, 0);
+
+        // A failure to list them is shown in their place, not a panic
+        update_devices(&mut test_model, Err(String::from("Failed to list the devices: udev")), Instant::now());
+        assert_eq!((test_model.devices.len(), test_model.selected), (0, 0));
+        assert_eq!(test_model.error, Some(String::from("Failed to list the devices: udev")));
+        update_devices(&mut test_model, Ok(vec![get_device("/dev/ttyACM0")]), Instant::now());
+        assert_eq!(test_model.error, None);
+    }
+
+    #[test]
+    fn test_update_refresh() {
+        // Listed when opened, then again once the interval has passed
+        let mut test_model = DeviceListModel::new(Vec::new(), None, HiddenDevices::default());
+        let refreshed = test_model.refreshed.unwrap();
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.refreshed, Some(refreshed));
+        test_model.refreshed = Some(refreshed - REFRESH_INTERVAL);
+        test_model.update(Message::Tick);
+        assert!(test_model.refreshed.unwrap() > refreshed - REFRESH_INTERVAL);
+        assert!(test_model.devices.iter().any(|d| d.name == LOOPBACK_PORT));
+    }
+
+    #[test]
+    fn test_get_merged() {
+        let start = Instant::now();
+        let at = |s: u64| start + Duration::from_secs(s);
+        let devices = |names: &[&str]| names.iter().map(|n| get_device(n)).collect::<Vec<DeviceInfo>>();
+        let names = |devices: &[DeviceInfo]| devices.iter().map(|d| d.name.clone()).collect::<Vec<String>>();
+
+        // A device plugged in is new, one unplugged is kept in its place
+        let shown = devices(&["COM1", "COM3", "COM4"]);
+        let (merged, changes) = get_merged(&shown, &[], devices(&["COM1", "COM4", "COM7"]), at(0));
+        assert_eq!(names(&merged), vec!["COM1", "COM3", "COM4", "COM7"]);
+        assert_eq!(get_change(&changes, "COM3"), Some(&DeviceChange::Removed(at(0))));
+        assert_eq!(get_change(&changes, "COM7"), Some(&DeviceChange::Added(at(0))));
+        assert_eq!(get_change(&changes, "COM1"), None);
+
+        // Both for a while, after which the unplugged one goes
+        let (merged, changes) = get_merged(&merged, &changes, devices(&["COM1", "COM4", "COM7"]), at(4));
+        assert_eq!((merged.len(), changes.len()), (4, 2));
+        let (merged, changes) = get_merged(&merged, &changes, devices(&["COM1", "COM4", "COM7"]), at(6));
+        assert_eq!(names(&merged), vec!["COM1", "COM4", "COM7"]);
+        assert_eq!(changes, vec![]);
+
+        // Plugged back in before it goes, it is new again
+        let (merged, changes) = get_merged(&merged, &changes, devices(&["COM1", "COM7"]), at(8));
+        let (merged, changes) = get_merged(&merged, &changes, devices(&["COM1", "COM4", "COM7"]), at(10));
+        assert_eq!(names(&merged), vec!["COM1", "COM4", "COM7"]);
+        assert_eq!(changes, vec![(String::from("COM4"), DeviceChange::Added(at(10)))]);
+    }
+
+    #[test]
+    fn test_update_devices_removed() {
+        let start = Instant::now();
+        let mut test_model = DeviceListModel::default();
+        update_devices(&mut test_model, Ok(vec![get_device("COM1"), get_device("COM3")]), start);
+        test_model.refreshed = Some(start);
+        test_model.selected = 1;
+
+        // Nothing is new at first, then the device selected is unplugged,
+        // which stays selected (and dimmed) until it goes
+        assert_eq!(test_model.changes, vec![]);
+        update_devices(&mut test_model, Ok(vec![get_device("COM1")]), start);
+        assert_eq!(test_model.selected, 1);
+        let lines = get_device_lines(&test_model);
+        assert_eq!(lines[3].spans[1].content, " (removed)");
+        assert!(lines[3].spans[0].style.add_modifier.contains(Modifier::DIM));
+        assert_eq!(lines[3].spans[0].style.fg, Some(crate::SELECTED_COLOR));
+        assert!(is_available(&test_model, "COM3") == false);
+
+        update_devices(&mut test_model, Ok(vec![get_device("COM1"), get_device("COM9")]), start + CHANGE_DURATION);
+        assert_eq!(test_model.selected, 1);
+        assert_eq!(test_model.devices[1].name, "COM9");
+        assert_eq!(get_device_lines(&test_model)[3].spans[1].content, " NEW");
+    }
+
+    #[test]
+    fn test_update_filter() {
+        let mut parameters = PortParameters::default().name(String::from("/dev/ttyUSB1"));
+        parameters.baud_rate = Some(115200);
+        let time = String::from("2026-10-17T12:04:05.000Z");
+        let mut test_model = DeviceListModel::new(vec![RecentConnection { parameters, time }], None, HiddenDevices::default());
+        let ftdi = DeviceInfo {
+            name: String::from("/dev/ttyUSB1"),
+            kind: DeviceKind::Usb,
+            manufacturer: Some(String::from("FTDI")),
+            ..Default::default()
+        };
+        test_model.devices = vec![get_device("/dev/ttyS0"), ftdi, get_device("/dev/ttyS1")];
+
+        // By what a device reports too, in any case, where the selection
+        // and Enter only see what is left
+        for c in "ftdi".chars() {
+            test_model.update(Message::Input(c));
+        }
+        assert_eq!(get_length(&test_model), 1);
+        test_model.update(Message::NextElement);
+        test_model.update(Message::Enter);
+        let expected = PortParameters::default().name(String::from("/dev/ttyUSB1"));
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Menu, Some(Box::new(expected))));
+
+        // The recent connections are filtered as well
+        test_model.set_state(State::Running);
+        test_model.filter = String::from("ttyusb");
+        assert_eq!((get_shown_recent(&test_model).len(), get_shown_devices(&test_model).len()), (1, 1));
+        test_model.filter = String::from("ttyS");
+        assert_eq!(get_length(&test_model), 2);
+        assert_eq!(get_recent_lines(&test_model).len(), 3);
+        test_model.update(Message::Input('9'));
+        assert_eq!(get_device_lines(&test_model)[0].spans[0].content, "No matching devices");
+        test_model.update(Message::Backspace);
+        assert_eq!((test_model.filter.as_str(), get_length(&test_model)), ("ttyS", 2));
+
+        // Esc clears the filter before it goes back
+        test_model.update(Message::Cancel);
+        assert_eq!((test_model.filter.as_str(), test_model.get_state()), ("", State::Running));
+        assert_eq!(get_length(&test_model), 4);
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Menu, None));
+    }
+
+    #[test]
+    fn test_update_quick_connect() {
+        let mut parameters = PortParameters::default().name(String::from("/dev/ttyUSB1"));
+        parameters.baud_rate = Some(115200);
+        parameters.mode = Some(Mode::Hex);
+        let time = String::from("2026-10-17T12:04:05.000Z");
+        let mut test_model = DeviceListModel::new(vec![RecentConnection { parameters: parameters.clone(), time }], None, HiddenDevices::default());
+        test_model.devices = vec![get_device("/dev/ttyUSB0"), get_device("/dev/ttyUSB1")];
+
+        // Each device hints at what it would be connected with
+        let lines = get_device_lines(&test_model);
+        assert_eq!(lines[1].spans[1].content, "  9600 8N1 [Ascii]");
+        assert_eq!(lines[3].spans[1].content, "  115200 8N1 [Hex]");
+
+        // Which is the last connection to it, or the defaults
+        test_model.selected = 2;
+        test_model.update(Message::QuickConnect);
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Terminal, Some(Box::new(parameters))));
+        test_model.set_state(State::Running);
+        test_model.selected = 1;
+        test_model.update(Message::QuickConnect);
+        let parameters = match test_model.get_state() {
+            State::Switching(Screen::Terminal, Some(parameters)) => *parameters,
+            state => panic!("unexpected state {:?}", state),
+        };
+        assert_eq!(parameters.name, Some(String::from("/dev/ttyUSB0")));
+        assert_eq!((parameters.baud_rate, parameters.data_bits, parameters.stop_bits), (Some(9600), Some(8), Some(1)));
+        assert_eq!((parameters.parity, parameters.mode), (Some(Parity::None), Some(Mode::Ascii)));
+
+        // Nothing happens without a device
+        let mut test_model = DeviceListModel::default();
+        test_model.update(Message::QuickConnect);
+        assert_eq!(test_model.get_state(), State::Running);
+    }
+
+    #[test]
+    fn test_update_session() {
+        let parameters = PortParameters::default().name(String::from("/dev/ttyUSB1"));
+        let time = String::from("2026-10-17T12:04:05.000Z");
+        let session = Some((String::from("/dev/ttyUSB1"), ConnectionStatus::Connected));
+        let mut test_model = DeviceListModel::new(vec![RecentConnection { parameters, time }], session, HiddenDevices::default());
+        test_model.devices = vec![get_device("/dev/ttyUSB0"), get_device("/dev/ttyUSB1")];
+        test_model.changes = Vec::new();
+
+        // The port of the session is marked, and returned to as it is
+        let lines = get_device_lines(&test_model);
+        assert!(lines[3].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(lines[3].spans[1].content, " ● connected");
+        assert_eq!(lines[3].spans[2].content, "  Enter returns to it");
+        for (selected, msg) in [(0, Message::Enter), (2, Message::Enter), (2, Message::QuickConnect), (1, Message::Cancel)] {
+            test_model.selected = selected;
+            test_model.update(msg);
+            assert_eq!(test_model.get_state(), State::Switching(Screen::Terminal, None));
+            test_model.set_state(State::Running);
+        }
+        test_model.update(Message::Connection(ConnectionStatus::Reconnecting));
+        assert_eq!(get_device_lines(&test_model)[3].spans[1].content, " ● disconnected");
+
+        // Unplugged, it is still listed (last), as disconnected
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+        test_model.devices = vec![get_device("/dev/ttyUSB0")];
+        let lines = get_device_lines(&test_model);
+        assert_eq!(lines[3].spans[0].content, "/dev/ttyUSB1");
+        assert_eq!(lines[3].spans[1].content, " ● disconnected");
+        assert_eq!(lines[4].spans[0].content, "Unknown");
+
+        // Which a network port never is
+        test_model.session = Some((get_device("tcp://host:4000"), ConnectionStatus::Connected));
+        assert_eq!(get_shown_devices(&test_model).len(), 1);
+    }
+
+    #[test]
+    fn test_get_shown_devices() {
+        let device = |name: &str, kind: DeviceKind| DeviceInfo {
+            name: String::from(name),
+            kind,
+            ..Default::default()
+        };
+        let test_model = DeviceListModel {
+            devices: vec![
+            device("/dev/pts/3", DeviceKind::Unknown),
+            device("/dev/ttyS1", DeviceKind::Unknown),
+            device("/dev/ttyUSB10", DeviceKind::Usb),
+            device("/dev/rfcomm0", DeviceKind::Bluetooth),
+            device("/dev/ttyS0", DeviceKind::Pci),
+            device("/dev/ttyUSB2", DeviceKind::Usb),
+        ],
+            ..Default::default()
+        };
+
+        // Grouped by type, then sorted with the numbers as numbers
+        let shown: Vec<&str> = get_shown_devices(&test_model).iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            shown,
+            vec!["/dev/ttyUSB2", "/dev/ttyUSB10", "/dev/rfcomm0", "/dev/ttyS0", "/dev/ttyS1", "/dev/pts/3"]
+        );
+        let headers: Vec<String> = get_device_lines(&test_model)
+            .iter()
+            .filter(|line| line.spans.len() == 1 && line.spans[0].style.fg == Some(crate::PLACEHOLDER_COLOR))
+            .map(|line| line.spans[0].content.to_string())
+            .collect();
+        assert_eq!(headers, vec!["USB", "Bluetooth", "PCI/native", "Other"]);
+        assert_eq!(compare_names("COM9", "COM10"), Ordering::Less);
+        assert_eq!(compare_names("tty", "tty0"), Ordering::Less);
+        assert_eq!(compare_names("ttyUSB01", "ttyUSB1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_select_element_groups() {
+        let usb = |name: &str| DeviceInfo {
+            name: String::from(name),
+            kind: DeviceKind::Usb,
+            ..Default::default()
+        };
+        let mut test_model = DeviceListModel {
+            bounds: Rect::new(0, 0, 80, (CONTENT_LENGTH - 1) as u16),
+            devices: vec![get_device("/dev/ttyS0"), usb("/dev/ttyUSB1"), usb("/dev/ttyUSB0")],
+            ..Default::default()
+        };
+
+        // The headers are stepped over, where the first device of a group
+        // is scrolled to along with its header
+        let mut offsets = Vec::new();
+        for _ in 0..3 {
+            let lines = get_device_lines(&test_model);
+            let line = &lines[get_selected_line(&test_model)];
+            let name = &get_shown_devices(&test_model)[test_model.selected].name;
+            assert_eq!(line.spans[0].content, name.as_str());
+            assert_eq!(line.spans[0].style.fg, Some(crate::SELECTED_COLOR));
+            select_element(&mut test_model, SelectElement::Next);
+            offsets.push(test_model.offset);
+        }
+        assert_eq!(offsets, vec![3, 5, 0]);
+
+        // As they are past the recent connections
+        let parameters = PortParameters::default().name(String::from("/dev/ttyUSB9"));
+        let time = String::from("2026-10-17T12:04:05.000Z");
+        test_model.recent = vec![RecentConnection { parameters, time }];
+        test_model.selected = 3;
+        let mut lines = get_recent_lines(&test_model);
+        lines.append(&mut get_device_lines(&test_model));
+        assert_eq!(lines[get_selected_line(&test_model)].spans[0].content, "/dev/ttyS0");
+        assert_eq!(get_offset(&test_model), get_selected_line(&test_model) - 1);
+        assert_eq!(lines[get_offset(&test_model)].spans[0].content, "PCI/native");
+    }
+
+    #[test]
+    fn test_update_hidden() {
+        let mut test_model = DeviceListModel::default();
+        let usb = DeviceInfo {
+            name: String::from("/dev/ttyUSB0"),
+            kind: DeviceKind::Usb,
+            ..Default::default()
+        };
+        test_model.devices = vec![get_device("/dev/ttyS1"), usb, get_device("/dev/ttyS0")];
+
+        // A device is hidden, and kept so once
+        test_model.update(Message::Delete);
+        assert_eq!(get_length(&test_model), 2);
+        let hidden = test_model.take_hidden().unwrap();
+        assert_eq!(hidden.ports, vec![String::from("/dev/ttyUSB0")]);
+        assert_eq!(test_model.take_hidden(), None);
+
+        // As is the whole group, which leaves nothing to select
+        test_model.selected = 1;
+        test_model.update(Message::ToggleNativeHidden);
+        assert_eq!((get_length(&test_model), test_model.selected), (0, 0));
+        assert_eq!(get_device_lines(&test_model)[0].spans[0].content, "Every device is hidden (F6 shows them)");
+        test_model.update(Message::NextElement);
+        test_model.update(Message::Delete);
+        assert!(test_model.take_hidden().unwrap().native);
+
+        // Shown anyway, what is hidden is dimmed, and shown again as it was
+        test_model.update(Message::ToggleHidden);
+        assert_eq!(get_length(&test_model), 3);
+        let lines = get_device_lines(&test_model);
+        assert_eq!(lines[1].spans[1].content, " (hidden)");
+        assert_eq!(lines[3].spans[0].content, "PCI/native (hidden)");
+        test_model.update(Message::Delete);
+        test_model.update(Message::ToggleHidden);
+        assert_eq!(get_shown_devices(&test_model)[0].name, "/dev/ttyUSB0");
+        assert_eq!(test_model.take_hidden().unwrap(), HiddenDevices { ports: vec![], native: true });
+    }
+
+    #[test]
+    fn test_update_probe() {
+        let session = Some((String::from("/dev/nolp-session"), ConnectionStatus::Connected));
+        let mut test_model = DeviceListModel::new(Vec::new(), session, HiddenDevices::default());
+        let loopback = DeviceInfo {
+            name: String::from("loopback:"),
+            kind: DeviceKind::Loopback,
+            ..Default::default()
+        };
+        test_model.devices = vec![get_device("/dev/nolp-missing"), get_device("/dev/nolp-session"), loopback];
+        test_model.changes = Vec::new();
+
+        // Nothing is probed until it is confirmed
+        test_model.update(Message::ProbeDevices);
+        test_model.update(Message::Tick);
+        assert!(test_model.probes.is_none());
+        test_model.update(Message::NextElement);
+        test_model.update(Message::ProbeDevices);
+        assert!(test_model.probes.is_none());
+
+        // Only the ports that are not the session (nor the loopback) are
+        // probed, in the background
+        test_model.update(Message::ProbeDevices);
+        let probes = test_model.probes.clone().unwrap();
+        let start = Instant::now();
+        while probes.lock().unwrap().len() == 0 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let probed: Vec<String> = probes.lock().unwrap().iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(probed, vec![String::from("/dev/nolp-missing")]);
+
+        // Each is marked once its probe is done
+        let results = vec![
+            (String::from("/dev/ttyS0"), Ok(())),
+            (String::from("/dev/ttyS1"), Err(SerialErrorKind::Busy)),
+            (String::from("/dev/ttyS2"), Err(SerialErrorKind::PermissionDenied)),
+        ];
+        let test_model = DeviceListModel {
+            devices: ["/dev/ttyS0", "/dev/ttyS1", "/dev/ttyS2", "/dev/ttyS3"].map(get_device).to_vec(),
+            probes: Some(SharedProbes::new(std::sync::Mutex::new(results))),
+            ..Default::default()
+        };
+        let lines = get_device_lines(&test_model);
+        assert_eq!(lines[1].spans[1].content, " ● available");
+        assert_eq!(lines[1].spans[1].style.fg, Some(crate::ACTIVE_COLOR));
+        assert_eq!(lines[3].spans[1].content, " ● busy");
+        assert_eq!(lines[5].spans[1].content, " ● unavailable");
+        assert!(lines[7].spans[1].content.contains('●') == false);
+    }
+
+    #[test]
+    fn test_update_copy() {
+        let parameters = PortParameters::default().name(String::from("/dev/ttyUSB9"));
+        let time = String::from("2026-10-17T12:04:05.000Z");
+        let mut test_model = DeviceListModel {
+            recent: vec![RecentConnection { parameters, time }],
+            devices: vec![get_device("/dev/serial/by-id/usb-FTDI-if00")],
+            ..Default::default()
+        };
+
+        // The port of what is selected, whether recent or a device
+        test_model.update(Message::Copy);
+        assert_eq!(test_model.take_copy(), Some(String::from("/dev/ttyUSB9")));
+        assert_eq!(test_model.take_copy(), None);
+        test_model.update(Message::NextElement);
+        test_model.update(Message::Copy);
+        assert_eq!(test_model.take_copy(), Some(String::from("/dev/serial/by-id/usb-FTDI-if00")));
+
+        // And nothing, where there is nothing to select
+        let mut test_model = DeviceListModel::default();
+        test_model.update(Message::Copy);
+        assert_eq!(test_model.take_copy(), Some(String::new()));
+    }
 }
@@ -0,0 +1,195 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the diagnostics asked for with a bug report, which are
+* the version built, the platform and terminal it runs in, and how the session
+* stands (its settings, connection, last error, and counters), gathered in one
+* block that Help shows and copies.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::Result;
+use std::{env, fs, time::SystemTime};
+
+use crate::capture::get_default_path;
+use crate::common::*;
+use crate::terminal::{get_port_summary, TerminalModel};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diagnostics {
+    pub version: String,
+    pub platform: String,
+    pub terminal: String,
+    // Only known while there is a session
+    pub parameters: Option<PortParameters>,
+    pub connection: Option<ConnectionStatus>,
+    pub error: Option<SerialErrorKind>,
+    pub stats: Option<SerialStats>,
+    // The entries held for display, and the bytes dropped to make room
+    pub buffered: Option<(usize, u64)>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const TITLE: &str = "nolp diagnostics";
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Diagnostics {
+    pub fn get_rows(&self) -> Vec<(String, String)> {
+        let none = || String::from("None");
+        let mut rows = vec![
+            (String::from("Version"), self.version.clone()),
+            (String::from("Platform"), self.platform.clone()),
+            (String::from("Terminal"), self.terminal.clone()),
+        ];
+        let parameters = match &self.parameters {
+            Some(parameters) => parameters,
+            None => {
+                rows.push((String::from("Session"), none()));
+                return rows;
+            }
+        };
+        let summary = get_port_summary(parameters);
+        let name = parameters.name.clone().unwrap_or_default();
+        let settings = summary.strip_prefix(&name).unwrap_or(&summary).trim();
+        rows.push((String::from("Port"), name));
+        rows.push((String::from("Settings"), String::from(settings)));
+        let connection = self.connection.as_ref().map(get_connection_text).unwrap_or_else(none);
+        rows.push((String::from("Connection"), connection));
+        let error = self.error.as_ref().map(|e| e.to_string().trim().to_string()).unwrap_or_else(none);
+        rows.push((String::from("Last error"), error));
+        if let Some(stats) = &self.stats {
+            rows.push((String::from("Sent"), format!("{} bytes", stats.tx_bytes)));
+            rows.push((String::from("Received"), format!("{} bytes", stats.rx_bytes)));
+            rows.push((
+                String::from("Errors"),
+                format!("{} write, {} read", stats.write_errors, stats.read_errors),
+            ));
+            rows.push((String::from("Uptime"), format!("{} s", stats.get_uptime().as_secs())));
+        }
+        if let Some((entries, dropped)) = self.buffered {
+            rows.push((String::from("Buffer"), format!("{} entries, {} bytes dropped", entries, dropped)));
+        }
+        return rows;
+    }
+
+    // As pasted into an issue
+    pub fn get_text(&self) -> String {
+        let mut text = format!("{}\n", TITLE);
+        for (label, value) in self.get_rows() {
+            text += &format!("{: <12}{}\n", label, value);
+        }
+        return text;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_diagnostics(terminal: Option<&TerminalModel>) -> Diagnostics {
+    let mut diagnostics = Diagnostics {
+        version: get_version(),
+        platform: format!("{} {}", env::consts::OS, env::consts::ARCH),
+        terminal: get_terminal(),
+        ..Default::default()
+    };
+    if let Some(model) = terminal {
+        diagnostics.parameters = Some(model.parameters.clone());
+        diagnostics.connection = Some(model.get_connection());
+        diagnostics.error = model.get_error();
+        diagnostics.stats = Some(model.get_stats());
+        diagnostics.buffered = Some(model.get_buffered());
+    }
+    return diagnostics;
+}
+
+pub fn get_version() -> String {
+    // The commit is handed over by the build script, where there was one
+    let commit = option_env!("NOLP_GIT_HASH").unwrap_or("unknown commit");
+    return format!("{} ({})", env!("CARGO_PKG_VERSION"), commit);
+}
+
+pub fn write_diagnostics(diagnostics: &Diagnostics) -> Result<String> {
+    // Next to the captures, named after the port as they are
+    let parameters = diagnostics.parameters.clone().unwrap_or_default();
+    let path = get_default_path(&parameters, SystemTime::now(), "diag.txt");
+    fs::write(&path, diagnostics.get_text())?;
+    return Ok(path);
+}
+
+fn get_terminal() -> String {
+    // Whatever the terminal says of itself, which is not always much
+    let term = env::var("TERM").unwrap_or_else(|_| String::from("unknown"));
+    let program = match (env::var("TERM_PROGRAM"), env::var("TERM_PROGRAM_VERSION")) {
+        (Ok(program), Ok(version)) => format!(" ({} {})", program, version),
+        (Ok(program), Err(_)) => format!(" ({})", program),
+        _ => String::new(),
+    };
+    let size = match crossterm::terminal::size() {
+        Ok((columns, rows)) => format!(" {}x{}", columns, rows),
+        Err(_) => String::new(),
+    };
+    return format!("{}{}{}", term, program, size);
+}
+
+fn get_connection_text(connection: &ConnectionStatus) -> String {
+    match connection {
+        ConnectionStatus::Closed => return String::from("Closed"),
+        ConnectionStatus::Connecting => return String::from("Connecting"),
+        ConnectionStatus::Connected => return String::from("Connected"),
+        ConnectionStatus::Disconnected => return String::from("Disconnected"),
+        ConnectionStatus::Reconnecting => return String::from("Reconnecting"),
+        ConnectionStatus::Failed(e) => return format!("Failed ({})", e.to_string().trim()),
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_diagnostics() {
+        // Without a session, only what the build and the platform say
+        let diagnostics = get_diagnostics(None);
+        assert!(diagnostics.version.starts_with(env!("CARGO_PKG_VERSION")));
+        assert!(diagnostics.platform.starts_with(env::consts::OS));
+        let rows = diagnostics.get_rows();
+        assert_eq!(rows.last(), Some(&(String::from("Session"), String::from("None"))));
+
+        let mut parameters = PortParameters::default().name(String::from("/dev/ttyUSB0"));
+        parameters.baud_rate = Some(115200);
+        parameters.mode = Some(Mode::Ascii);
+        let mut model = TerminalModel::new(parameters);
+        model.update(Message::Connection(ConnectionStatus::Connected));
+        model.update(Message::Rx(b"ok\r\n".to_vec()));
+        model.set_error(SerialErrorKind::ReadFailed);
+        let text = get_diagnostics(Some(&model)).get_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "nolp diagnostics");
+        assert!(lines.contains(&"Port        /dev/ttyUSB0"));
+        assert!(lines.contains(&"Settings    115200 8N1 [Ascii]"));
+        assert!(lines.contains(&"Connection  Connected"));
+        assert!(lines.contains(&"Last error  Read failed"));
+        assert!(lines.contains(&"Errors      0 write, 0 read"));
+        assert!(lines.iter().any(|line| line.starts_with("Buffer      ") && line.ends_with(", 0 bytes dropped")));
+    }
+}
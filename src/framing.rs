@@ -0,0 +1,164 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the packets read from framed data (SLIP or COBS). The
+* data is split on the delimiter of the framing as it arrives, so that a frame
+* split across reads is still decoded whole.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use crate::common::Framing;
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Packet {
+    Decoded(Vec<u8>),
+    // The bytes of a frame that could not be decoded, as they were received
+    Garbage(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deframer {
+    framing: Framing,
+    // What arrived since the last delimiter
+    frame: Vec<u8>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// The longest frame kept until its delimiter, a longer run is taken to be
+// data that is not framed and is passed on as garbage
+const FRAME_LIMIT: usize = 4096;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Deframer {
+    pub fn new(framing: Framing) -> Deframer {
+        Deframer {
+            framing,
+            frame: Vec::new(),
+        }
+    }
+
+    pub fn get_framing(&self) -> &Framing {
+        return &self.framing;
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Vec<Packet> {
+        // Back-to-back delimiters leave nothing between them, which is
+        // skipped rather than read as an empty packet
+        let delimiter = get_delimiter(&self.framing);
+        let mut packets = Vec::new();
+        for value in data.iter().copied() {
+            if delimiter != Some(value) {
+                self.frame.push(value);
+                if self.frame.len() >= FRAME_LIMIT {
+                    packets.push(Packet::Garbage(std::mem::take(&mut self.frame)));
+                }
+                continue;
+            }
+            let frame = std::mem::take(&mut self.frame);
+            if frame.len() == 0 {
+                continue;
+            }
+            let decoded = match self.framing {
+                Framing::Slip => crate::slip::decode(&frame),
+                Framing::Cobs => crate::cobs::decode(&frame),
+                Framing::Off => Ok(frame.clone()),
+            };
+            packets.push(match decoded {
+                Ok(packet) => Packet::Decoded(packet),
+                Err(_) => Packet::Garbage(frame),
+            });
+        }
+        return packets;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn encode(framing: &Framing, packet: &[u8]) -> Vec<u8> {
+    match framing {
+        Framing::Slip => return crate::slip::encode(packet),
+        Framing::Cobs => return crate::cobs::encode(packet),
+        Framing::Off => return packet.to_vec(),
+    }
+}
+
+fn get_delimiter(framing: &Framing) -> Option<u8> {
+    match framing {
+        Framing::Slip => return Some(crate::slip::END),
+        Framing::Cobs => return Some(crate::cobs::DELIMITER),
+        Framing::Off => return None,
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_slip() {
+        let mut deframer = Deframer::new(Framing::Slip);
+        let mut data = crate::slip::encode(&[0x01, 0xC0, 0x02]);
+        data.extend(crate::slip::encode(b"ok"));
+
+        // A frame split across reads is decoded once its END arrives
+        assert_eq!(deframer.update(&data[..4]), vec![]);
+        assert_eq!(
+            deframer.update(&data[4..]),
+            vec![Packet::Decoded(vec![0x01, 0xC0, 0x02]), Packet::Decoded(b"ok".to_vec())]
+        );
+
+        // Noise reads as a packet when it decodes, a bad escape does not
+        let packets = deframer.update(&[b'z', 0xC0, 0x01, 0xDB, 0x02, 0xC0, 0xC0, 0x03, 0xC0]);
+        assert_eq!(
+            packets,
+            vec![
+                Packet::Decoded(b"z".to_vec()),
+                Packet::Garbage(vec![0x01, 0xDB, 0x02]),
+                Packet::Decoded(vec![0x03]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_cobs() {
+        let mut deframer = Deframer::new(Framing::Cobs);
+        let mut data = encode(&Framing::Cobs, &[0x11, 0x00, 0x22]);
+        data.extend(encode(&Framing::Cobs, &[]));
+        data.extend([0x05, 0x11, 0x00]);
+        let packets = deframer.update(&data);
+        assert_eq!(
+            packets,
+            vec![
+                Packet::Decoded(vec![0x11, 0x00, 0x22]),
+                Packet::Decoded(vec![]),
+                Packet::Garbage(vec![0x05, 0x11]),
+            ]
+        );
+
+        // Data that is never delimited is passed on, rather than held
+        let packets = deframer.update(&vec![0x01; FRAME_LIMIT + 1]);
+        assert_eq!(packets, vec![Packet::Garbage(vec![0x01; FRAME_LIMIT])]);
+        assert_eq!(deframer.frame.len(), 1);
+    }
+}
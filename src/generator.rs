@@ -0,0 +1,332 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the patterns sent to test a link (incrementing bytes,
+* alternating 0x55/0xAA, PRBS-15, or a text) and the check of what comes back
+* when the link is looped. Each pattern repeats on a period, which the check
+* locks onto from the first bytes received, so that it needs no start marker.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::{fmt, time::Instant};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    Increment,
+    Alternate,
+    Prbs,
+    Text(Vec<u8>),
+}
+
+// The pattern as it is sent, held to the given bytes a second (or sent as
+// fast as the port takes it)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generator {
+    period: Vec<u8>,
+    offset: usize,
+    rate: Option<u64>,
+    started: Option<Instant>,
+    generated: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verifier {
+    period: Vec<u8>,
+    // The position in the period of the next byte, once locked on
+    offset: Option<usize>,
+    // What arrived while not locked on, no longer than needed to lock
+    window: Vec<u8>,
+    // The mismatches in a row, past a limit the bytes are taken as lost
+    run: usize,
+    checked: u64,
+    errors: u64,
+    bit_errors: u64,
+    resyncs: u64,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// The most handed to the port at a time, so a rate stays smooth
+const CHUNK: u64 = 256;
+// The bytes matched before the check locks on, enough to tell any position
+// of the PRBS (15 bits) apart
+const LOCK_LENGTH: usize = 4;
+const LOSS_LIMIT: usize = 8;
+const PRBS_MASK: u16 = 0x7FFF;
+const PRBS_PERIOD: usize = 0x7FFF;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Increment => write!(f, "Incrementing"),
+            Pattern::Alternate => write!(f, "0x55/0xAA"),
+            Pattern::Prbs => write!(f, "PRBS-15"),
+            Pattern::Text(_) => write!(f, "Text"),
+        }
+    }
+}
+
+impl Generator {
+    pub fn new(pattern: &Pattern, rate: Option<u64>) -> Generator {
+        Generator {
+            period: get_period(pattern),
+            offset: 0,
+            rate,
+            started: None,
+            generated: 0,
+        }
+    }
+
+    pub fn take(&mut self, now: Instant) -> Vec<u8> {
+        // The rate is kept from the first chunk on, so that a stall of the
+        // port is made up for rather than lost
+        let started = *self.started.get_or_insert(now);
+        let allowed = match self.rate {
+            Some(rate) => {
+                let due = (now.duration_since(started).as_secs_f64() * rate as f64) as u64;
+                due.saturating_sub(self.generated).min(CHUNK)
+            }
+            None => CHUNK,
+        };
+        let mut chunk = Vec::with_capacity(allowed as usize);
+        for _ in 0..allowed {
+            chunk.push(self.period[self.offset]);
+            self.offset = (self.offset + 1) % self.period.len();
+        }
+        self.generated += allowed;
+        return chunk;
+    }
+}
+
+impl Verifier {
+    pub fn new(pattern: &Pattern) -> Verifier {
+        Verifier {
+            period: get_period(pattern),
+            offset: None,
+            window: Vec::new(),
+            run: 0,
+            checked: 0,
+            errors: 0,
+            bit_errors: 0,
+            resyncs: 0,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        return self.offset.is_some();
+    }
+
+    pub fn get_checked(&self) -> u64 {
+        return self.checked;
+    }
+
+    pub fn get_errors(&self) -> u64 {
+        return self.errors;
+    }
+
+    pub fn get_bit_errors(&self) -> u64 {
+        return self.bit_errors;
+    }
+
+    pub fn get_resyncs(&self) -> u64 {
+        return self.resyncs;
+    }
+
+    pub fn get_ber(&self) -> Option<f64> {
+        if self.checked == 0 {
+            return None;
+        }
+        return Some(self.bit_errors as f64 / (self.checked * 8) as f64);
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for value in data.iter().copied() {
+            let offset = match self.offset {
+                Some(offset) => offset,
+                None => {
+                    self.lock(value);
+                    continue;
+                }
+            };
+            let expected = self.period[offset];
+            self.offset = Some((offset + 1) % self.period.len());
+            self.checked += 1;
+            if value == expected {
+                self.run = 0;
+                continue;
+            }
+            self.errors += 1;
+            self.bit_errors += u64::from((value ^ expected).count_ones());
+            // A byte flipped here and there is an error, a run of them is
+            // the pattern having slipped (e.g., a byte was dropped)
+            self.run += 1;
+            if self.run >= LOSS_LIMIT {
+                self.offset = None;
+                self.run = 0;
+                self.resyncs += 1;
+            }
+        }
+    }
+
+    fn lock(&mut self, value: u8) {
+        // The window is slid on until it is found somewhere in the period
+        self.window.push(value);
+        if self.window.len() < LOCK_LENGTH.min(self.period.len()) {
+            return;
+        }
+        let length = self.period.len();
+        let found = (0..length).find(|start| {
+            let mut matched = self.window.iter().enumerate();
+            matched.all(|(i, value)| self.period[(start + i) % length] == *value)
+        });
+        match found {
+            Some(start) => {
+                self.offset = Some((start + self.window.len()) % length);
+                self.window.clear();
+            }
+            None => {
+                self.window.remove(0);
+            }
+        }
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_period(pattern: &Pattern) -> Vec<u8> {
+    match pattern {
+        Pattern::Increment => return (0..=255).collect(),
+        Pattern::Alternate => return vec![0x55, 0xAA],
+        Pattern::Prbs => return get_prbs(),
+        Pattern::Text(text) => return text.clone(),
+    }
+}
+
+fn get_prbs() -> Vec<u8> {
+    // x^15 + x^14 + 1 (as in ITU-T O.150), packed most significant bit
+    // first, where the 32767 bits repeat over as many bytes
+    let mut state = PRBS_MASK;
+    let mut period = Vec::with_capacity(PRBS_PERIOD);
+    for _ in 0..PRBS_PERIOD {
+        let mut value = 0;
+        for _ in 0..8 {
+            let bit = ((state >> 14) ^ (state >> 13)) & 1;
+            state = ((state << 1) | bit) & PRBS_MASK;
+            value = (value << 1) | bit as u8;
+        }
+        period.push(value);
+    }
+    return period;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn get_sent(pattern: &Pattern, length: usize) -> Vec<u8> {
+        let mut generator = Generator::new(pattern, None);
+        let mut sent = Vec::new();
+        while sent.len() < length {
+            sent.extend(generator.take(Instant::now()));
+        }
+        sent.truncate(length);
+        return sent;
+    }
+
+    #[test]
+    fn test_get_period() {
+        assert_eq!(get_period(&Pattern::Increment).len(), 256);
+        assert_eq!(get_period(&Pattern::Text(b"abc".to_vec())), b"abc".to_vec());
+
+        // The PRBS does not repeat within its period
+        let prbs = get_prbs();
+        assert_eq!(prbs.len(), 32767);
+        assert_eq!(&prbs[..2], &[0x00, 0x02]);
+        let mut windows: Vec<&[u8]> = prbs.windows(LOCK_LENGTH).collect();
+        windows.sort();
+        windows.dedup();
+        assert_eq!(windows.len(), prbs.len() - LOCK_LENGTH + 1);
+    }
+
+    #[test]
+    fn test_take() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut generator = Generator::new(&Pattern::Alternate, Some(1000));
+
+        // Held to the rate, and made up for after a stall
+        assert_eq!(generator.take(at(0)), vec![]);
+        assert_eq!(generator.take(at(3)), vec![0x55, 0xAA, 0x55]);
+        assert_eq!(generator.take(at(5)), vec![0xAA, 0x55]);
+        assert_eq!(generator.take(at(5)), vec![]);
+        assert_eq!(generator.take(at(1000)).len(), CHUNK as usize);
+
+        let mut generator = Generator::new(&Pattern::Increment, None);
+        assert_eq!(generator.take(at(0)), (0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_update() {
+        // Locked onto mid-stream, split across reads, without errors
+        for pattern in [Pattern::Increment, Pattern::Alternate, Pattern::Prbs, Pattern::Text(b"hello".to_vec())] {
+            let sent = get_sent(&pattern, 5000);
+            let mut verifier = Verifier::new(&pattern);
+            for chunk in sent[1234..].chunks(7) {
+                verifier.update(chunk);
+            }
+            assert!(verifier.is_locked());
+            let lock = LOCK_LENGTH.min(get_period(&pattern).len());
+            assert_eq!(verifier.get_checked(), (5000 - 1234 - lock) as u64);
+            assert_eq!((verifier.get_errors(), verifier.get_resyncs()), (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_update_errors() {
+        let mut received = get_sent(&Pattern::Prbs, 10_000);
+        let mut verifier = Verifier::new(&Pattern::Prbs);
+
+        // Each bit flipped is counted, without losing the lock
+        received[100] ^= 0x01;
+        received[2000] ^= 0x81;
+        received[9000] ^= 0xFF;
+        verifier.update(&received);
+        assert_eq!(verifier.get_checked(), 10_000 - LOCK_LENGTH as u64);
+        assert_eq!((verifier.get_errors(), verifier.get_bit_errors()), (3, 11));
+        assert_eq!(verifier.get_resyncs(), 0);
+        let ber = verifier.get_ber().unwrap();
+        assert!((ber - 11.0 / ((10_000 - LOCK_LENGTH) * 8) as f64).abs() < 1e-12);
+
+        // A dropped byte slips the pattern, which is locked onto again
+        let mut received = get_sent(&Pattern::Prbs, 2000);
+        received.remove(1000);
+        let mut verifier = Verifier::new(&Pattern::Prbs);
+        verifier.update(&received);
+        assert_eq!(verifier.get_resyncs(), 1);
+        assert!(verifier.is_locked());
+        assert!(verifier.get_errors() >= LOSS_LIMIT as u64);
+        assert_eq!(Verifier::new(&Pattern::Prbs).get_ber(), None);
+    }
+}
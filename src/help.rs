@@ -16,6 +16,8 @@ use ratatui::{
 use std::rc::Rc;
 
 use crate::common::*;
+use crate::diagnostics::Diagnostics;
+use crate::terminal::get_port_summary;
 /******************************************************************************/
 /*******************************************************************************
 * Public Interface
@@ -26,9 +28,16 @@ pub struct HelpModel {
     state: State,
     bounds: Rect,
     offset: usize,
+    // The lines of the content and how many of them fit, as last rendered
+    length: usize,
+    height: usize,
     caller: Screen,
     scroll: ScrollbarState,
+    // The diagnostics to copy, until they are handed to the clipboard
+    copy: Option<String>,
     pub parameters: Option<PortParameters>,
+    pub keymap: Vec<Binding>,
+    pub diagnostics: Diagnostics,
 }
 
 /******************************************************************************/
@@ -36,7 +45,6 @@ pub struct HelpModel {
 * Local Constants
 *******************************************************************************/
 /******************************************************************************/
-const CONTENT_LENGTH: usize = 18;
 const MARGIN_TOP: usize = 2;
 
 /******************************************************************************/
@@ -48,21 +56,31 @@ impl Default for HelpModel {
     fn default() -> HelpModel {
         HelpModel {
             offset: 0,
+            length: 0,
+            height: 0,
             parameters: None,
+            copy: None,
+            keymap: get_default_keymap(),
+            diagnostics: Diagnostics::default(),
             caller: Screen::Menu,
             state: State::Running,
             bounds: Rect::default(),
-            scroll: ScrollbarState::default().content_length(CONTENT_LENGTH),
+            scroll: ScrollbarState::default(),
         }
     }
 }
 
 impl HelpModel {
     pub fn new(caller: Screen, parameters: Option<PortParameters>) -> HelpModel {
-        let mut model = HelpModel::default();
-        model.parameters = parameters;
-        model.caller = caller;
-        return model;
+        return HelpModel {
+            parameters,
+            caller,
+            ..Default::default()
+        };
+    }
+
+    pub fn take_copy(&mut self) -> Option<String> {
+        return self.copy.take();
     }
 }
 
@@ -80,22 +98,19 @@ impl Tea for HelpModel {
     fn update(&mut self, msg: Message) -> State {
         match msg {
             Message::PreviousElement => {
-                if self.offset == 0 {
-                    self.offset = CONTENT_LENGTH;
-                } else {
-                    self.offset -= 1;
-                }
+                self.offset = self.offset.saturating_sub(1);
                 self.scroll = self.scroll.position(self.offset);
             }
             Message::NextElement => {
-                if self.offset >= CONTENT_LENGTH {
-                    self.offset = 0;
-                } else {
-                    self.offset += 1;
-                }
+                let max = get_max_offset(self.length, self.height);
+                self.offset = (self.offset + 1).min(max);
                 self.scroll = self.scroll.position(self.offset);
             }
-            Message::Enter => {
+            Message::Copy => {
+                self.copy = Some(self.diagnostics.get_text());
+            }
+            // Either goes back to where help was opened from
+            Message::Enter | Message::Cancel => {
                 switch_screen(self);
             }
             _ => {}
@@ -158,50 +173,349 @@ fn get_input_info<'a>(width: usize) -> Vec<Line<'a>> {
         ),
     ]));
 
+    info.push(Line::from(vec![
+        Span::from(format!("{: <7}", "")),
+        Span::styled(format!("{: >w$}", "or HexDump", w = width), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Flow ctrl"),
+        Span::styled(
+            format!("{: >w$}", "None|Hardware|Software", w = width),
+            style,
+        ),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("DTR on open"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from(format!("{: <9}", "RS485")),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Discard echo"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 3), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Timeout ms"),
+        Span::styled(format!("{: >w$}", "1 - 999999", w = width - 1), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from(format!("{: <9}", "Mode RO")),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Bridge port"),
+        Span::styled(format!("{: >w$}", "Second port name", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from(format!("{: <9}", "Reset")),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Char delay ms"),
+        Span::styled(format!("{: >w$}", "0 - 9999", w = width - 4), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Line delay ms"),
+        Span::styled(format!("{: >w$}", "0 - 9999", w = width - 4), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Scrollback"),
+        Span::styled(format!("{: >w$}", "1 - 9999999", w = width - 1), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Line ending"),
+        Span::styled(format!("{: >w$}", "None|CR|LF|CRLF", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Input limit"),
+        Span::styled(format!("{: >w$}", "1 - 99999", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Paste lines"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Ascii view"),
+        Span::styled(format!("{: >w$}", "Text|ANSI|Raw", w = width - 1), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Capture path"),
+        Span::styled(format!("{: >w$}", "File name", w = width - 3), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Capture format"),
+        Span::styled(format!("{: >w$}", "Text|Log", w = width - 5), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Trigger bell"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 3), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Response rules"),
+        Span::styled(format!("{: >w$}", "File name", w = width - 5), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Gap markers"),
+        Span::styled(format!("{: >w$}", "No|Yes|Export", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Gap ms"),
+        Span::styled(format!("{: >w$}", "1 - 9999", w = width + 3), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Row length"),
+        Span::styled(format!("{: >w$}", "Auto|4|8|16|32", w = width - 1), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("NMEA"),
+        Span::styled(format!("{: >w$}", "No|Yes|Fix", w = width + 5), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Framing"),
+        Span::styled(format!("{: >w$}", "None|SLIP|COBS", w = width + 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("AT commands"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 2), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("AT timeout ms"),
+        Span::styled(format!("{: >w$}", "1 - 999999", w = width - 4), style),
+    ]));
+
+    info.push(Line::from(vec![
+        Span::from("Local echo"),
+        Span::styled(format!("{: >w$}", "Yes|No", w = width - 1), style),
+    ]));
+
+    info.push(Line::from(""));
+
     return info;
 }
 
-fn get_keymap<'a>(width: usize) -> Vec<Line<'a>> {
+fn get_connection_info<'a>(parameters: &PortParameters, width: usize) -> Vec<Line<'a>> {
+    // As the menu input, where what does not fit is left to run over
+    let mut info: Vec<Line> = Vec::new();
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let mut settings = parameters.clone();
+    settings.name = None;
+    let rows = [
+        ("Port", parameters.name.clone().unwrap_or_default()),
+        ("Settings", get_port_summary(&settings).trim().to_string()),
+        ("Flow ctrl", parameters.flow_control.clone().map(|f| f.to_string()).unwrap_or(String::from("None"))),
+        ("Line ending", parameters.line_ending.clone().map(|l| l.to_string()).unwrap_or(String::from("None"))),
+        ("Local echo", String::from(if parameters.echo { "Yes" } else { "No" })),
+    ];
+
+    info.push(Line::from("Connection"));
+    info.push(Line::from(""));
+
+    for (label, value) in rows {
+        let w = (width + 9).saturating_sub(label.len().max(9));
+        info.push(Line::from(vec![
+            Span::from(format!("{: <9}", label)),
+            Span::styled(format!("{: >w$}", value, w = w), style),
+        ]));
+    }
+
+    info.push(Line::from(""));
+
+    return info;
+}
+
+fn get_diagnostics_info<'a>(diagnostics: &Diagnostics, bindings: &[Binding], width: usize) -> Vec<Line<'a>> {
+    // Last, as it is only wanted for a bug report, with the keys to copy it
+    let mut info: Vec<Line> = Vec::new();
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+
+    info.push(Line::from("Diagnostics"));
+    info.push(Line::from(""));
+
+    for (label, value) in diagnostics.get_rows() {
+        let w = (width + 9).saturating_sub(label.len().max(9));
+        info.push(Line::from(vec![
+            Span::from(format!("{: <9}", label)),
+            Span::styled(format!("{: >w$}", value, w = w), style),
+        ]));
+    }
+
+    info.push(Line::from(""));
+    let help = |binding: &Binding| binding.screens.contains(&Screen::Help);
+    info.append(&mut get_bindings(bindings, help, width + 3));
+
+    return info;
+}
+
+fn get_keymap<'a>(bindings: &[Binding], width: usize) -> Vec<Line<'a>> {
     let mut keymap: Vec<Line> = Vec::new();
     let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
 
     keymap.push(Line::from("Keymap (Views)"));
     keymap.push(Line::from(""));
 
+    keymap.append(&mut get_bindings(bindings, is_view, width));
+
+    keymap.push(Line::from(""));
+    keymap.push(Line::from("Keymap (Movement)"));
+    keymap.push(Line::from(""));
+
     keymap.push(Line::from(vec![
-        Span::from(format!("ctrl+{}", MENU_CHAR)),
-        Span::styled(format!("{: >w$}", "Displays menu", w = width), style),
+        Span::from(PREVIOUS_ELEMENT_CHAR.to_string()),
+        Span::styled(format!("{: >w$}", "Previous/scroll up", w = width + 5), style),
     ]));
 
     keymap.push(Line::from(vec![
-        Span::from(format!("ctrl+{}", DEVICE_LIST_CHAR)),
-        Span::styled(format!("{: >w$}", "Displays device list", w = width), style),
+        Span::from(NEXT_ELEMENT_CHAR.to_string()),
+        Span::styled(format!("{: >w$}", "Next/scroll down", w = width + 5), style),
     ]));
 
     keymap.push(Line::from(vec![
-        Span::from(format!("ctrl+{}", QUIT_CHAR)),
-        Span::styled(format!("{: >w$}", "Quits application", w = width), style),
+        Span::from("shift+tab/tab"),
+        Span::styled(format!("{: >w$}", "Previous/next", w = width - 7), style),
+    ]));
+
+    keymap.push(Line::from(vec![
+        Span::from("esc"),
+        Span::styled(format!("{: >w$}", "Cancels/goes back", w = width + 3), style),
     ]));
 
     keymap.push(Line::from(""));
-    keymap.push(Line::from("Keymap (Movement)"));
+
+    return keymap;
+}
+
+fn get_terminal_keymap<'a>(bindings: &[Binding], width: usize) -> Vec<Line<'a>> {
+    let mut keymap: Vec<Line> = Vec::new();
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+
+    keymap.push(Line::from("Keymap (Terminal)"));
+    keymap.push(Line::from(""));
+
+    let terminal = |binding: &Binding| binding.is_bound(&Screen::Terminal) && is_view(binding) == false;
+    keymap.append(&mut get_bindings(bindings, terminal, width));
+
+    keymap.push(Line::from(vec![
+        Span::from(format!("ctrl+{} {}", PASSTHROUGH_ESCAPE_CHAR, QUIT_CHAR)),
+        Span::styled(format!("{: >w$}", "Leaves pass-through", w = width - 2), style),
+    ]));
+
+    keymap.push(Line::from(vec![
+        Span::from("up/down"),
+        Span::styled(format!("{: >w$}", "Recalls sent input", w = width - 1), style),
+    ]));
+
+    keymap.push(Line::from(vec![
+        Span::from("pgup/pgdn"),
+        Span::styled(format!("{: >w$}", "Scrolls the data", w = width - 3), style),
+    ]));
+
+    keymap.push(Line::from(""));
+
+    return keymap;
+}
+
+fn get_menu_keymap<'a>(bindings: &[Binding], width: usize) -> Vec<Line<'a>> {
+    let mut keymap: Vec<Line> = Vec::new();
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+
+    keymap.push(Line::from("Keymap (Menu)"));
     keymap.push(Line::from(""));
 
     keymap.push(Line::from(vec![
-        Span::from(format!("ctrl+{}", PREVIOUS_ELEMENT_CHAR)),
-        Span::styled(format!("{: >w$}", "Previous/scroll up", w = width), style),
+        Span::from("left/right"),
+        Span::styled(format!("{: >w$}", "Cursor/cycles a choice", w = width - 4), style),
     ]));
 
     keymap.push(Line::from(vec![
-        Span::from(format!("ctrl+{}", NEXT_ELEMENT_CHAR)),
-        Span::styled(format!("{: >w$}", "Next/scroll down", w = width), style),
+        Span::from("tab"),
+        Span::styled(format!("{: >w$}", "Completes the port", w = width + 3), style),
     ]));
 
+    let menu = |binding: &Binding| binding.screens.contains(&Screen::Menu);
+    keymap.append(&mut get_bindings(bindings, menu, width));
+
     keymap.push(Line::from(""));
 
     return keymap;
 }
 
+fn get_device_list_keymap<'a>(bindings: &[Binding], width: usize) -> Vec<Line<'a>> {
+    let mut keymap: Vec<Line> = Vec::new();
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+
+    keymap.push(Line::from("Keymap (Device List)"));
+    keymap.push(Line::from(""));
+
+    keymap.push(Line::from(vec![
+        Span::from("a-z"),
+        Span::styled(format!("{: >w$}", "Filters the list", w = width + 3), style),
+    ]));
+
+    let device_list = |binding: &Binding| binding.screens.contains(&Screen::DeviceList);
+    keymap.append(&mut get_bindings(bindings, device_list, width));
+
+    keymap.push(Line::from(""));
+
+    return keymap;
+}
+
+fn get_bindings<'a>(bindings: &[Binding], shown: impl Fn(&Binding) -> bool, width: usize) -> Vec<Line<'a>> {
+    // Listed from the keymap in effect, so that a chord rebound shows as it
+    // is, where a chord sent in place of another is listed once (unless it
+    // conflicts, as any chord bound to two messages stands out)
+    let mut lines: Vec<Line> = Vec::new();
+    for (index, binding) in bindings.iter().enumerate() {
+        let conflicted = is_conflicted(bindings, index);
+        let listed = bindings[..index].iter().any(|b| b.message == binding.message && b.screens == binding.screens);
+        if shown(binding) == false || (listed && conflicted == false) {
+            continue;
+        }
+        let (label_style, style) = match conflicted {
+            true => (Style::default().fg(crate::INVALID_COLOR), Style::default().fg(crate::INVALID_COLOR)),
+            false => (Style::default(), Style::default().fg(crate::PLACEHOLDER_COLOR)),
+        };
+        let label = binding.get_label();
+        let w = (width + 6).saturating_sub(label.len());
+        lines.push(Line::from(vec![
+            Span::styled(label, label_style),
+            Span::styled(format!("{: >w$}", binding.description, w = w), style),
+        ]));
+    }
+    return lines;
+}
+
+fn is_view(binding: &Binding) -> bool {
+    return matches!(binding.message, Message::Quit | Message::Switching(_, _));
+}
+
 fn get_layout(fsize: Rect) -> (Rect, Rc<[Rect]>) {
     let bounds = get_center_bounds(50, 50, fsize);
     let layout = Layout::default()
@@ -215,12 +529,59 @@ fn get_layout(fsize: Rect) -> (Rect, Rc<[Rect]>) {
     return (bounds, layout);
 }
 
+fn get_content<'a>(model: &HelpModel, width: usize) -> Vec<Line<'a>> {
+    // What the screen help was opened from comes first, then the keys that
+    // work everywhere, then the rest of the screens
+    let mut text = get_screen_help(&model.caller, model.parameters.as_ref(), &model.keymap, width);
+    text.append(&mut get_keymap(&model.keymap, width));
+    for screen in [Screen::Terminal, Screen::Menu, Screen::DeviceList] {
+        if screen != model.caller {
+            text.append(&mut get_screen_help(&screen, None, &model.keymap, width));
+        }
+    }
+    text.append(&mut get_diagnostics_info(&model.diagnostics, &model.keymap, width - 3));
+    return text;
+}
+
+fn get_screen_help<'a>(
+    screen: &Screen,
+    parameters: Option<&PortParameters>,
+    keymap: &[Binding],
+    width: usize,
+) -> Vec<Line<'a>> {
+    // The connection is only known to the terminal that has one
+    match screen {
+        Screen::Terminal => {
+            let mut text = parameters.map(|p| get_connection_info(p, width - 3)).unwrap_or_default();
+            text.append(&mut get_terminal_keymap(keymap, width));
+            return text;
+        }
+        Screen::Menu => {
+            let mut text = get_input_info(width - 3);
+            text.append(&mut get_menu_keymap(keymap, width));
+            return text;
+        }
+        Screen::DeviceList => return get_device_list_keymap(keymap, width),
+        Screen::Help => return Vec::new(),
+    }
+}
+
+fn get_max_offset(length: usize, height: usize) -> usize {
+    // The last line stops at the bottom, rather than scrolling on into blank
+    // space (or wrapping back to the top)
+    return length.saturating_sub(height);
+}
+
 fn render_help(frame: &mut Frame, area: Rect, model: &mut HelpModel) {
-    let mut text: Vec<Line> = Vec::new();
-    let width = 24;
+    let text = get_content(model, 24);
 
-    text.append(&mut get_keymap(width));
-    text.append(&mut get_input_info(width - 3));
+    // The content changes with the caller, and the window can be resized
+    // under it, so the bounds are taken again on every frame
+    model.length = text.len();
+    model.height = usize::from(area.height);
+    let max = get_max_offset(model.length, model.height);
+    model.offset = model.offset.min(max);
+    model.scroll = model.scroll.content_length(max + 1).position(model.offset);
 
     let help = Paragraph::new(text)
         .scroll((model.offset as u16, 0))
@@ -230,7 +591,7 @@ fn render_help(frame: &mut Frame, area: Rect, model: &mut HelpModel) {
 }
 
 fn render_scrollbar(frame: &mut Frame, area: Rect, model: &mut HelpModel) {
-    if usize::from(area.height) <= CONTENT_LENGTH {
+    if model.length > model.height {
         let scrollbar = Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .track_symbol(None)
@@ -249,7 +610,7 @@ fn render_title(frame: &mut Frame, area: Rect) {
 }
 
 fn switch_screen(model: &mut HelpModel) {
-    model.state = State::Switching(model.caller.to_owned(), model.parameters.to_owned());
+    model.state = State::Switching(model.caller.to_owned(), model.parameters.to_owned().map(Box::new));
 }
 
 /******************************************************************************/
@@ -260,14 +621,16 @@ fn switch_screen(model: &mut HelpModel) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
 
     #[test]
     fn test_switch_screen() {
-        let mut test_model = HelpModel::default();
-
-        // Ensure that switching screen works when
-        // parameters are None.
-        test_model.caller = Screen::Menu;
+        let mut test_model = HelpModel {
+            // Ensure that switching screen works when
+            // parameters are None.
+            caller: Screen::Menu,
+            ..Default::default()
+        };
         switch_screen(&mut test_model);
         assert_eq!(
             test_model.get_state(), 
@@ -284,7 +647,153 @@ mod tests {
         switch_screen(&mut test_model);
         assert_eq!(
             test_model.get_state(), 
-            State::Switching(Screen::Menu, Some(test_parameters))
+            State::Switching(Screen::Menu, Some(Box::new(test_parameters)))
         );
     }
+
+    #[test]
+    fn test_update_cancel() {
+        let mut test_model = HelpModel {
+            caller: Screen::Terminal,
+            parameters: Some(PortParameters::default()),
+            ..Default::default()
+        };
+
+        // Esc goes back as enter does
+        test_model.update(Message::Cancel);
+        assert_eq!(
+            test_model.get_state(),
+            State::Switching(Screen::Terminal, Some(Box::new(PortParameters::default())))
+        );
+    }
+
+    #[test]
+    fn test_get_content() {
+        let mut parameters = PortParameters::default().name(String::from("/dev/ttyUSB0"));
+        parameters.baud_rate = Some(115200);
+        parameters.mode = Some(Mode::Hex);
+        let headers = |caller: Screen, parameters: Option<PortParameters>| {
+            let model = HelpModel::new(caller, parameters);
+            let text = get_content(&model, 24);
+            let titles = text.iter().filter(|line| line.spans.len() == 1 && line.spans[0].content.len() > 0);
+            return titles.map(|line| line.spans[0].content.to_string()).collect::<Vec<String>>();
+        };
+
+        // What the screen help was opened from comes first, then the keys
+        // that work everywhere, then the rest
+        let terminal = headers(Screen::Terminal, Some(parameters.clone()));
+        assert_eq!(
+            terminal,
+            vec![
+                "Connection",
+                "Keymap (Terminal)",
+                "Keymap (Views)",
+                "Keymap (Movement)",
+                "Menu Input - Expected Value",
+                "Keymap (Menu)",
+                "Keymap (Device List)",
+                "Diagnostics"
+            ]
+        );
+        let menu = headers(Screen::Menu, None);
+        assert_eq!(&menu[..4], ["Menu Input - Expected Value", "Keymap (Menu)", "Keymap (Views)", "Keymap (Movement)"]);
+        assert_eq!(&menu[4..], ["Keymap (Terminal)", "Keymap (Device List)", "Diagnostics"]);
+        let device_list = headers(Screen::DeviceList, None);
+        assert_eq!(&device_list[..2], ["Keymap (Device List)", "Keymap (Views)"]);
+        assert_eq!(device_list.len(), 7);
+
+        // The connection of the terminal is shown as it is
+        let info = get_screen_help(&Screen::Terminal, Some(&parameters), &get_default_keymap(), 24);
+        let text: Vec<String> = info.iter().map(|line| line.spans.iter().map(|s| s.content.to_string()).collect()).collect();
+        assert!(text[2].starts_with("Port") && text[2].ends_with("/dev/ttyUSB0"));
+        assert!(text[3].ends_with("115200 8N1 [Hex]"));
+        assert_eq!(text[2].len(), text[6].len());
+    }
+
+    #[test]
+    fn test_scroll_bounds() {
+        // Nothing to scroll when everything fits
+        assert_eq!(get_max_offset(20, 40), 0);
+        assert_eq!(get_max_offset(40, 40), 0);
+        assert_eq!(get_max_offset(100, 40), 60);
+
+        let mut test_model = HelpModel::new(Screen::Menu, None);
+        test_model.length = 10;
+        test_model.height = 7;
+        test_model.update(Message::PreviousElement);
+        assert_eq!(test_model.offset, 0);
+        for _ in 0..5 {
+            test_model.update(Message::NextElement);
+        }
+        assert_eq!(test_model.offset, 3);
+
+        // A taller window leaves nothing to scroll
+        test_model.height = 40;
+        test_model.update(Message::NextElement);
+        assert_eq!(test_model.offset, 0);
+
+        // The bounds are taken from what is rendered, and a window resized
+        // under the offset pulls it back
+        let mut test_model = HelpModel::new(Screen::DeviceList, None);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 40)).unwrap();
+        terminal.draw(|frame| test_model.view(frame)).unwrap();
+        let length = get_content(&test_model, 24).len();
+        assert_eq!((test_model.length, test_model.height), (length, 17));
+        for _ in 0..length {
+            test_model.update(Message::NextElement);
+        }
+        assert_eq!(test_model.offset, length - 17);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 400)).unwrap();
+        terminal.draw(|frame| test_model.view(frame)).unwrap();
+        assert_eq!(test_model.offset, 0);
+    }
+
+    #[test]
+    fn test_get_bindings() {
+        let mut model = HelpModel::new(Screen::Menu, None);
+        let text = |model: &HelpModel| -> Vec<(String, Style)> {
+            let content = get_content(model, 24);
+            let rows = content.iter().filter(|line| line.spans.len() == 2);
+            return rows.map(|line| (line.spans.iter().map(|s| s.content.to_string()).collect(), line.spans[1].style)).collect();
+        };
+        let find = |rows: &[(String, Style)], description: &str| rows.iter().find(|(row, _)| row.ends_with(description)).cloned();
+
+        // A chord sent in place of another is listed once, and the rows
+        // line up whatever the chord
+        let rows = text(&model);
+        assert_eq!(rows.iter().filter(|(row, _)| row.ends_with("Sends a single byte")).count(), 1);
+        assert_eq!(find(&rows, "Displays help").unwrap().0.len(), 30);
+        assert_eq!(find(&rows, "Hides built-in ports").unwrap().0.len(), 30);
+
+        // A rebound chord is listed as it is, and a chord bound twice stands
+        // out wherever it is listed
+        let clear = model.keymap.iter_mut().find(|b| b.message == Message::Clear).unwrap();
+        clear.code = KeyCode::F(12);
+        clear.modifiers = KeyModifiers::NONE;
+        model.keymap.push(Binding::new(KeyCode::F(REFRESH_KEY), KeyModifiers::NONE, &[Screen::Menu], Message::Copy, "Copies"));
+        let rows = text(&model);
+        assert!(find(&rows, "Clears the display").unwrap().0.starts_with("F12 "));
+        let (row, style) = find(&rows, "Refreshes the ports").unwrap();
+        assert!(row.starts_with("F5 "));
+        assert_eq!(style.fg, Some(crate::INVALID_COLOR));
+        assert_eq!(find(&rows, "Copies").unwrap().1.fg, Some(crate::INVALID_COLOR));
+        assert_eq!(find(&rows, "Copies the port").unwrap().1.fg, Some(crate::PLACEHOLDER_COLOR));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut test_model = HelpModel::new(Screen::Menu, None);
+        test_model.diagnostics = crate::diagnostics::get_diagnostics(None);
+
+        // Shown last, with the key that copies it, and copied whole
+        let content = get_content(&test_model, 24);
+        let text: Vec<String> = content.iter().map(|line| line.spans.iter().map(|s| s.content.to_string()).collect()).collect();
+        let start = text.iter().position(|line| line == "Diagnostics").unwrap();
+        assert!(text[start + 2].starts_with("Version") && text[start + 2].len() == 30);
+        assert_eq!(text.last().unwrap(), &format!("F{}{: >28}", COPY_KEY, "Copies diagnostics"));
+        test_model.update(Message::Copy);
+        assert_eq!(test_model.take_copy(), Some(test_model.diagnostics.get_text()));
+        assert_eq!(test_model.take_copy(), None);
+        assert_eq!(test_model.get_state(), State::Running);
+    }
 }
@@ -0,0 +1,326 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the Intel HEX files sent to simple bootloaders, read a
+* record a line and checked before anything is sent, then sent a line at a
+* time. The next line waits for the delay after the one before it, or for the
+* prompt the bootloader answers each line with (or for both).
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::time::{Duration, Instant};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordKind {
+    Data,
+    EndOfFile,
+    // The upper 16 bits of the addresses of the data records that follow
+    ExtendedLinearAddress,
+    // Where the program starts, which is sent on like the rest
+    StartLinearAddress,
+}
+
+// A record with the line it was read from, counted from 1, where the address
+// of a data record is the full 32-bit one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub number: usize,
+    pub line: String,
+    pub kind: RecordKind,
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pacing {
+    pub delay: Option<Duration>,
+    pub prompt: Option<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HexState {
+    Running,
+    Done,
+    // The prompt did not arrive after the line of this number
+    Failed(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HexSender {
+    records: Vec<Record>,
+    // The index of the next record to send
+    index: usize,
+    pacing: Pacing,
+    // When the last line was sent, and whether its prompt has arrived
+    sent: Option<Instant>,
+    prompted: bool,
+    state: HexState,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const LINE_END: &[u8] = b"\r\n";
+// Long enough for a bootloader to erase a page before it answers
+pub const PROMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl HexSender {
+    pub fn new(records: Vec<Record>, pacing: Pacing) -> HexSender {
+        HexSender {
+            records,
+            index: 0,
+            pacing,
+            sent: None,
+            prompted: false,
+            state: HexState::Running,
+        }
+    }
+
+    pub fn get_state(&self) -> &HexState {
+        return &self.state;
+    }
+
+    pub fn get_index(&self) -> usize {
+        return self.index;
+    }
+
+    pub fn get_length(&self) -> usize {
+        return self.records.len();
+    }
+
+    // The address reached by the data sent so far
+    pub fn get_address(&self) -> Option<u32> {
+        let sent = self.records[..self.index].iter().rev();
+        return sent.filter(|record| record.kind == RecordKind::Data).map(|record| record.address).next();
+    }
+
+    pub fn get_range(&self) -> Option<(u32, u32)> {
+        return get_range(&self.records);
+    }
+
+    pub fn update(&mut self, input: &[u8]) {
+        // Only what arrives after a line is sent answers it
+        if let Some(prompt) = self.pacing.prompt {
+            if self.sent.is_some() && input.contains(&prompt) {
+                self.prompted = true;
+            }
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Vec<u8> {
+        if self.state != HexState::Running {
+            return Vec::new();
+        }
+        // The line before has to be answered (and the delay passed) before
+        // the next is sent, the end of file record included
+        if let Some(sent) = self.sent {
+            let waited = now.duration_since(sent);
+            if self.pacing.prompt.is_some() && self.prompted == false {
+                if waited >= PROMPT_TIMEOUT {
+                    self.state = HexState::Failed(self.records[self.index - 1].number);
+                }
+                return Vec::new();
+            }
+            if self.pacing.delay.is_some_and(|delay| waited < delay) {
+                return Vec::new();
+            }
+        }
+        let record = match self.records.get(self.index) {
+            Some(record) => record,
+            None => {
+                self.state = HexState::Done;
+                return Vec::new();
+            }
+        };
+        let mut output = record.line.as_bytes().to_vec();
+        output.extend_from_slice(LINE_END);
+        self.index += 1;
+        self.sent = Some(now);
+        self.prompted = false;
+        return output;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_records(text: &str) -> Result<Vec<Record>, String> {
+    // Blank lines are skipped, anything after the end of file is an error
+    // rather than left unsent without notice
+    let mut records: Vec<Record> = Vec::new();
+    let mut base = 0;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.len() == 0 {
+            continue;
+        }
+        let error = |e: &str| format!(" Line {}: {} ", number, e);
+        if records.last().is_some_and(|record| record.kind == RecordKind::EndOfFile) {
+            return Err(error("record after the end of file"));
+        }
+        let digits = line.strip_prefix(':').ok_or(error("expected ':' to start the record"))?;
+        let bytes = get_bytes(digits).ok_or(error("invalid hex digits"))?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(error("length does not match the byte count"));
+        }
+        let sum = bytes.iter().fold(0u8, |sum, value| sum.wrapping_add(*value));
+        if sum != 0 {
+            let expected = bytes[bytes.len() - 1].wrapping_sub(sum);
+            return Err(error(&format!("bad checksum (expected {:02X})", expected)));
+        }
+        let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u32;
+        let data = bytes[4..bytes.len() - 1].to_vec();
+        let (kind, address) = match (bytes[3], data.len()) {
+            (0x00, _) => (RecordKind::Data, base + offset),
+            (0x01, 0) => (RecordKind::EndOfFile, offset),
+            (0x04, 2) => {
+                base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16;
+                (RecordKind::ExtendedLinearAddress, offset)
+            }
+            (0x05, 4) => (RecordKind::StartLinearAddress, offset),
+            (0x01 | 0x04 | 0x05, _) => return Err(error(&format!("bad length for record type {:02X}", bytes[3]))),
+            (kind, _) => return Err(error(&format!("unexpected record type {:02X}", kind))),
+        };
+        records.push(Record {
+            number,
+            line: String::from(line),
+            kind,
+            address,
+            data,
+        });
+    }
+    if records.last().is_none_or(|record| record.kind != RecordKind::EndOfFile) {
+        return Err(String::from(" The file has no end of file record "));
+    }
+    return Ok(records);
+}
+
+// The lowest and highest address written by the data records
+pub fn get_range(records: &[Record]) -> Option<(u32, u32)> {
+    let data = records.iter().filter(|record| record.kind == RecordKind::Data && record.data.len() > 0);
+    let first = data.clone().map(|record| record.address).min()?;
+    let last = data.map(|record| record.address + record.data.len() as u32 - 1).max()?;
+    return Some((first, last));
+}
+
+pub fn is_hex_path(path: &str) -> bool {
+    let path = path.to_lowercase();
+    return path.ends_with(".hex") || path.ends_with(".ihx");
+}
+
+fn get_bytes(digits: &str) -> Option<Vec<u8>> {
+    if digits.len().is_multiple_of(2) == false || digits.is_ascii() == false {
+        return None;
+    }
+    let pairs = (0..digits.len()).step_by(2);
+    return pairs.map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok()).collect();
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = ":020000040800F2\n\
+        :10000000000102030405060708090A0B0C0D0E0F78\n\
+        :04001000DEADBEEFB4\n\
+        \n\
+        :0400000508000101ED\n\
+        :00000001FF\n";
+
+    #[test]
+    fn test_get_records() {
+        let records = get_records(FILE).unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0].kind, RecordKind::ExtendedLinearAddress);
+
+        // The data is placed past the extended linear address
+        assert_eq!(records[1].kind, RecordKind::Data);
+        assert_eq!(records[1].address, 0x0800_0000);
+        assert_eq!(records[1].data, (0..16).collect::<Vec<u8>>());
+        assert_eq!((records[2].address, records[2].number), (0x0800_0010, 3));
+        assert_eq!(records[3].kind, RecordKind::StartLinearAddress);
+        assert_eq!(records[4].line, ":00000001FF");
+        assert_eq!(get_range(&records), Some((0x0800_0000, 0x0800_0013)));
+
+        // Without one, the addresses are 16-bit
+        let records = get_records(":0300300002337A1E\n:00000001FF").unwrap();
+        assert_eq!(get_range(&records), Some((0x0030, 0x0032)));
+        assert_eq!(get_range(&records[1..]), None);
+    }
+
+    #[test]
+    fn test_get_records_errors() {
+        let error = |text: &str| get_records(text).unwrap_err();
+        assert_eq!(error(":04001000DEADBEEFB5\n:00000001FF"), " Line 1: bad checksum (expected B4) ");
+        assert_eq!(error(":00000001FF\n:00000001FF"), " Line 2: record after the end of file ");
+        assert_eq!(error("\n04001000DEADBEEFB4"), " Line 2: expected ':' to start the record ");
+        assert_eq!(error(":0400100GDEADBEEFB4"), " Line 1: invalid hex digits ");
+        assert_eq!(error(":05001000DEADBEEFB3"), " Line 1: length does not match the byte count ");
+        assert_eq!(error(":020000021000EC"), " Line 1: unexpected record type 02 ");
+        assert_eq!(error(":0100000100FE"), " Line 1: bad length for record type 01 ");
+        assert_eq!(error(":04001000DEADBEEFB4"), " The file has no end of file record ");
+        assert_eq!(error(""), " The file has no end of file record ");
+    }
+
+    #[test]
+    fn test_tick() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let pacing = Pacing {
+            delay: Some(Duration::from_millis(10)),
+            prompt: Some(b'>'),
+        };
+        let mut sender = HexSender::new(get_records(FILE).unwrap(), pacing);
+
+        // Each line waits for the prompt after the one before, then the delay
+        assert_eq!(sender.tick(at(0)), b":020000040800F2\r\n".to_vec());
+        sender.update(b"ok");
+        assert_eq!(sender.tick(at(20)), vec![]);
+        sender.update(b"\r\n>");
+        assert_eq!(sender.tick(at(20)).len(), 45);
+        sender.update(b">");
+        assert_eq!(sender.tick(at(25)), vec![]);
+        assert_eq!(sender.tick(at(30)), b":04001000DEADBEEFB4\r\n".to_vec());
+        assert_eq!(sender.get_address(), Some(0x0800_0010));
+        assert_eq!((sender.get_index(), sender.get_length()), (3, 5));
+
+        // A line left unanswered fails the transfer
+        assert_eq!(sender.tick(at(30) + PROMPT_TIMEOUT), vec![]);
+        assert_eq!(*sender.get_state(), HexState::Failed(3));
+
+        // Unpaced, a line is sent on every tick
+        let mut sender = HexSender::new(get_records(FILE).unwrap(), Pacing::default());
+        for _ in 0..5 {
+            assert!(sender.tick(at(0)).len() > 0);
+        }
+        assert_eq!(sender.tick(at(0)), vec![]);
+        assert_eq!(*sender.get_state(), HexState::Done);
+    }
+
+    #[test]
+    fn test_is_hex_path() {
+        assert!(is_hex_path("firmware.hex"));
+        assert!(is_hex_path("build/FIRMWARE.IHX"));
+        assert!(is_hex_path("firmware.bin") == false);
+    }
+}
@@ -0,0 +1,222 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the measurement of the round trip of a link, a probe
+* sent at a time and timed until its echo arrives (or counted as lost once it
+* times out). The echo is matched as the data arrives, so that a probe split
+* across reads is still found.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::time::{Duration, Instant};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Latency {
+    probe: Vec<u8>,
+    count: usize,
+    timeout: Duration,
+    sent: usize,
+    // When the probe waiting for its echo was sent
+    pending: Option<Instant>,
+    // What arrived while the probe waits, no longer than the probe
+    received: Vec<u8>,
+    times: Vec<Duration>,
+    lost: usize,
+}
+
+// The round trips measured so far, where the jitter is the mean difference
+// between each and the one before it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    pub sent: usize,
+    pub received: usize,
+    pub lost: usize,
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+    pub jitter: Duration,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Latency {
+    pub fn new(probe: Vec<u8>, count: usize, timeout: Duration) -> Latency {
+        Latency {
+            probe,
+            count,
+            timeout,
+            sent: 0,
+            pending: None,
+            received: Vec::new(),
+            times: Vec::new(),
+            lost: 0,
+        }
+    }
+
+    pub fn get_count(&self) -> usize {
+        return self.count;
+    }
+
+    pub fn get_last(&self) -> Option<Duration> {
+        return self.times.last().copied();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        return self.sent >= self.count && self.pending.is_none();
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Vec<u8> {
+        // The next probe waits for the echo of the last, so that each echo
+        // is timed against its own probe
+        if let Some(sent) = self.pending {
+            if now.duration_since(sent) < self.timeout {
+                return Vec::new();
+            }
+            self.lost += 1;
+            self.pending = None;
+        }
+        if self.sent >= self.count {
+            return Vec::new();
+        }
+        self.sent += 1;
+        self.pending = Some(now);
+        self.received.clear();
+        return self.probe.clone();
+    }
+
+    pub fn update(&mut self, data: &[u8], now: Instant) -> Vec<u8> {
+        let sent = match self.pending {
+            Some(sent) => sent,
+            None => return self.tick(now),
+        };
+        self.received.extend_from_slice(data);
+        if self.received.windows(self.probe.len()).any(|window| window == self.probe) {
+            self.times.push(now.duration_since(sent));
+            self.pending = None;
+        } else {
+            // Only the end could still be the start of the echo
+            let keep = (self.probe.len() - 1).min(self.received.len());
+            self.received.drain(..self.received.len() - keep);
+        }
+        return self.tick(now);
+    }
+
+    pub fn get_stats(&self) -> LatencyStats {
+        let mut stats = LatencyStats {
+            sent: self.sent,
+            received: self.times.len(),
+            lost: self.lost,
+            ..Default::default()
+        };
+        if self.times.len() == 0 {
+            return stats;
+        }
+        stats.min = *self.times.iter().min().unwrap();
+        stats.max = *self.times.iter().max().unwrap();
+        stats.avg = self.times.iter().sum::<Duration>() / self.times.len() as u32;
+        if self.times.len() > 1 {
+            let differences = self.times.windows(2).map(|pair| pair[0].abs_diff(pair[1]));
+            stats.jitter = differences.sum::<Duration>() / (self.times.len() - 1) as u32;
+        }
+        return stats;
+    }
+}
+
+impl LatencyStats {
+    pub fn get_summary(&self) -> String {
+        // As in `10 sent, 0 lost, min/avg/max 4.1/5.0/7.2 ms, jitter 0.8 ms`
+        let mut summary = format!("{} sent, {} lost", self.sent, self.lost);
+        if self.received > 0 {
+            summary += &format!(
+                ", min/avg/max {}/{}/{} ms, jitter {} ms",
+                get_ms_text(self.min),
+                get_ms_text(self.avg),
+                get_ms_text(self.max),
+                get_ms_text(self.jitter)
+            );
+        }
+        return summary;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_ms_text(duration: Duration) -> String {
+    return format!("{:.1}", duration.as_secs_f64() * 1000.0);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback::LoopbackPort;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_loopback() {
+        // Each probe is echoed after the delay, read a few bytes at a time
+        let mut port = LoopbackPort::new(Duration::from_millis(20), Duration::from_millis(2));
+        let mut latency = Latency::new(b"nolp-probe".to_vec(), 3, Duration::from_secs(1));
+        let mut output = latency.tick(Instant::now());
+        while latency.is_finished() == false {
+            port.write_all(&output).unwrap();
+            let mut buffer = [0; 3];
+            let input = match port.read(&mut buffer) {
+                Ok(n) => buffer[..n].to_vec(),
+                Err(_) => Vec::new(),
+            };
+            output = latency.update(&input, Instant::now());
+        }
+        let stats = latency.get_stats();
+        assert_eq!((stats.sent, stats.received, stats.lost), (3, 3, 0));
+        assert!(stats.min >= Duration::from_millis(20));
+        assert!(stats.min <= stats.avg && stats.avg <= stats.max);
+        assert!(stats.max < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_update() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut latency = Latency::new(b"ping".to_vec(), 4, Duration::from_millis(100));
+
+        // The echo is found across reads, among other data
+        assert_eq!(latency.tick(at(0)), b"ping".to_vec());
+        assert_eq!(latency.tick(at(5)), vec![]);
+        assert_eq!(latency.update(b"> pi", at(8)), vec![]);
+        assert_eq!(latency.update(b"ng\r\n", at(10)), b"ping".to_vec());
+        assert_eq!(latency.update(b"ping", at(24)), b"ping".to_vec());
+
+        // A probe without an echo is lost once it times out
+        assert_eq!(latency.tick(at(100)), vec![]);
+        assert_eq!(latency.tick(at(124)), b"ping".to_vec());
+        assert_eq!(latency.update(b"ping", at(140)), vec![]);
+        assert!(latency.is_finished());
+        assert_eq!(latency.get_last(), Some(Duration::from_millis(16)));
+
+        let stats = latency.get_stats();
+        assert_eq!((stats.sent, stats.received, stats.lost), (4, 3, 1));
+        assert_eq!((stats.min, stats.max), (Duration::from_millis(10), Duration::from_millis(16)));
+        assert_eq!(stats.avg, Duration::from_millis(40) / 3);
+        assert_eq!(stats.jitter, Duration::from_millis(3));
+        assert_eq!(stats.get_summary(), "4 sent, 1 lost, min/avg/max 10.0/13.3/16.0 ms, jitter 3.0 ms");
+
+        let latency = Latency::new(b"ping".to_vec(), 2, Duration::from_millis(100));
+        assert_eq!(latency.get_stats().get_summary(), "0 sent, 0 lost");
+    }
+}
@@ -0,0 +1,285 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the loopback 'port', an in-process echo that can be used
+* in place of a serial device (e.g., for demonstrations or tests).
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::Result;
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::common::*;
+use crate::serial::{get_timeout, NolpPort};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Debug)]
+pub struct LoopbackPort {
+    delay: Duration,
+    timeout: Duration,
+    pending: VecDeque<(Instant, Vec<u8>)>,
+    dtr: bool,
+    rts: bool,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const DEFAULT_DELAY_MS: u64 = 10;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl LoopbackPort {
+    pub fn new(delay: Duration, timeout: Duration) -> LoopbackPort {
+        LoopbackPort {
+            delay,
+            timeout,
+            pending: VecDeque::new(),
+            dtr: true,
+            rts: true,
+        }
+    }
+}
+
+impl Read for LoopbackPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Waits like a serial port would, either for the next echo to be due
+        // or for the timeout to pass
+        let now = Instant::now();
+        let due = match self.pending.front() {
+            Some((due, _)) => *due,
+            None => now + self.timeout,
+        };
+        if due > now {
+            thread::sleep(self.timeout.min(due - now));
+        }
+
+        let ready = match self.pending.front() {
+            Some((due, _)) => *due <= Instant::now(),
+            None => false,
+        };
+        if ready == false || buf.len() == 0 {
+            return Err(io::Error::from(ErrorKind::TimedOut));
+        }
+
+        let (_, data) = self.pending.front_mut().unwrap();
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        data.drain(..n);
+        if data.len() == 0 {
+            self.pending.pop_front();
+        }
+        return Ok(n);
+    }
+}
+
+impl Write for LoopbackPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > 0 {
+            self.pending.push_back((Instant::now() + self.delay, buf.to_vec()));
+        }
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+impl NolpPort for LoopbackPort {
+    fn bytes_to_read(&self) -> Result<u32> {
+        let now = Instant::now();
+        let ready = self
+            .pending
+            .iter()
+            .take_while(|(due, _)| *due <= now)
+            .map(|(_, data)| data.len())
+            .sum::<usize>();
+        return Ok(u32::try_from(ready).unwrap_or(u32::MAX));
+    }
+
+    fn clear_all(&mut self) -> Result<()> {
+        self.pending.clear();
+        return Ok(());
+    }
+
+    fn clear_input(&mut self) -> Result<()> {
+        self.pending.clear();
+        return Ok(());
+    }
+
+    fn configure(&mut self, _parameters: &PortParameters) -> Result<()> {
+        return Ok(());
+    }
+
+    fn read_lines(&mut self) -> LineStatus {
+        // Wired like a loopback plug, the outputs are looped to the inputs
+        return LineStatus {
+            cts: Some(self.rts),
+            dsr: Some(self.dtr),
+            cd: Some(self.dtr),
+            ri: Some(false),
+        };
+    }
+
+    fn set_break(&mut self, _enabled: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn set_dtr(&mut self, level: bool) -> Result<()> {
+        self.dtr = level;
+        return Ok(());
+    }
+
+    fn set_rts(&mut self, level: bool) -> Result<()> {
+        self.rts = level;
+        return Ok(());
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn is_loopback(parameters: &PortParameters) -> bool {
+    match &parameters.name {
+        Some(name) => return name.starts_with(LOOPBACK_PORT),
+        None => return false,
+    }
+}
+
+pub fn open_loopback(parameters: &PortParameters) -> Result<LoopbackPort> {
+    let timeout = Duration::from_millis(u64::from(get_timeout(parameters)));
+    return Ok(LoopbackPort::new(get_delay(parameters)?, timeout));
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_delay(parameters: &PortParameters) -> Result<Duration> {
+    // The echo delay follows the port name (e.g., `loopback:250`)
+    let name = parameters.name.clone().unwrap_or_default();
+    let delay = name.trim_start_matches(LOOPBACK_PORT);
+    if delay.len() == 0 {
+        return Ok(Duration::from_millis(DEFAULT_DELAY_MS));
+    }
+    return Ok(Duration::from_millis(delay.parse::<u64>()?));
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::{get_stats, read_write_port};
+    use std::sync::mpsc;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn loopback_parameters(name: &str) -> PortParameters {
+        let mut parameters = PortParameters::default().name(String::from(name));
+        parameters.baud_rate = Some(9600);
+        parameters.data_bits = Some(8);
+        parameters.stop_bits = Some(1);
+        parameters.parity = Some(Parity::None);
+        parameters.mode = Some(Mode::Ascii);
+        parameters.timeout_ms = Some(5);
+        return parameters;
+    }
+
+    #[test]
+    fn test_get_delay() {
+        let parameters = loopback_parameters(LOOPBACK_PORT);
+        assert!(is_loopback(&parameters));
+        assert_eq!(get_delay(&parameters).unwrap(), Duration::from_millis(DEFAULT_DELAY_MS));
+
+        let parameters = loopback_parameters("loopback:250");
+        assert_eq!(get_delay(&parameters).unwrap(), Duration::from_millis(250));
+
+        let parameters = loopback_parameters("loopback:x");
+        assert!(open_loopback(&parameters).is_err());
+        assert!(!is_loopback(&loopback_parameters("/dev/ttyUSB0")));
+    }
+
+    #[test]
+    fn test_echo() {
+        let mut port = LoopbackPort::new(Duration::from_millis(0), Duration::from_millis(1));
+        let mut buffer = [0; 4];
+        assert_eq!(port.read(&mut buffer).unwrap_err().kind(), ErrorKind::TimedOut);
+
+        port.write_all(b"hello").unwrap();
+        assert_eq!(port.bytes_to_read().unwrap(), 5);
+        assert_eq!(port.read(&mut buffer).unwrap(), 4);
+        assert_eq!(&buffer, b"hell");
+        assert_eq!(port.read(&mut buffer).unwrap(), 1);
+        assert_eq!(buffer[0], b'o');
+
+        port.set_rts(false).unwrap();
+        assert_eq!(port.read_lines().cts, Some(false));
+        assert_eq!(port.read_lines().dsr, Some(true));
+    }
+
+    #[test]
+    fn test_read_write_port_loopback() {
+        let (commands, command_receiver) = mpsc::channel();
+        let (tx, data_receiver) = mpsc::channel();
+        let (event_sender, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let rx = SharedRx::default();
+
+        let (t_stats, t_rx) = (stats.clone(), rx.clone());
+        let handle = thread::spawn(move || {
+            return read_write_port(
+                loopback_parameters(LOOPBACK_PORT),
+                open_loopback,
+                &command_receiver,
+                &data_receiver,
+                &event_sender,
+                &t_stats,
+                &t_rx,
+            );
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = Vec::new();
+        while received.len() < 5 && Instant::now() < deadline {
+            match events.try_recv() {
+                Ok(SerialEvent::Status(ConnectionStatus::Connected)) => {
+                    tx.send(b"hello".to_vec()).unwrap();
+                }
+                Ok(SerialEvent::RxReady) => {
+                    for (_, mut data, _) in rx.lock().unwrap().drain() {
+                        received.append(&mut data);
+                    }
+                }
+                _ => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        assert_eq!(received, b"hello".to_vec());
+
+        commands.send(SerialCommand::Close).unwrap();
+        assert!(!handle.join().unwrap());
+        let stats = get_stats(&stats);
+        assert_eq!(stats.tx_bytes, 5);
+        assert_eq!(stats.rx_bytes, 5);
+    }
+}
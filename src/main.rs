@@ -5,31 +5,41 @@
 * DATE: 12/30/23
 ********************************************************************************/
 /*******************************************************************************/
+// The project spells these out on purpose, so the lints that flag them are off:
+// `needless_return`, every function ends in an explicit `return`
+// `len_zero`, emptiness is checked as `len() > 0` or `len() == 0`
+// `bool_comparison`, negation is written `== false` rather than `!`
+#![allow(clippy::bool_comparison, clippy::len_zero, clippy::needless_return)]
 use anyhow::{anyhow, Result};
 use crossterm::{
     cursor,
-    event::{self, Event, EventStream, KeyCode, KeyEvent, KeyEventKind},
+    event::{
+        DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode, KeyEvent,
+        KeyEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::{FutureExt, StreamExt};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Style,
     terminal::Terminal,
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph},
     Frame,
 };
-use serial::read_write_port;
 use std::{
-    io::{stdout, Stdout},
+    env,
+    io::{stdout, Stdout, Write},
     panic,
+    path::PathBuf,
+    process,
     rc::Rc,
-    sync::Arc,
+    sync::{mpsc, Arc},
     thread,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::{
     self, select,
@@ -38,20 +48,73 @@ use tokio::{
     time::interval,
 };
 
+mod at;
+mod capture;
+mod clipboard;
+mod cobs;
 mod common;
+mod configure;
 mod device_list;
+mod diagnostics;
+mod framing;
+mod generator;
 mod help;
+mod ihex;
+mod latency;
+mod loopback;
 mod menu;
+mod nmea;
+mod replay;
+mod script;
 mod serial;
+mod series;
+mod settings;
+mod slip;
+mod tcp;
 mod terminal;
+mod trigger;
+mod xmodem;
 
+use crate::capture::{get_timestamp, start_capture, Capture, CaptureSource};
 use crate::common::*;
-use crate::device_list::DeviceListModel;
+use crate::clipboard::copy_text;
+use crate::device_list::{DeviceListModel, HiddenDevices, RecentConnection};
+use crate::diagnostics::{get_diagnostics, write_diagnostics, Diagnostics};
 use crate::help::HelpModel;
-use crate::menu::MenuModel;
+use crate::loopback::{is_loopback, open_loopback};
+use crate::menu::{parse_values, MenuModel};
+use crate::replay::{is_replay, open_replay};
 use crate::serial::*;
+use crate::settings::{
+    get_hidden_path, get_recent_path, get_settings_path, read_entries, read_values, write_entries, write_hidden,
+    write_values,
+};
+use crate::tcp::{is_tcp, open_tcp};
 use crate::terminal::TerminalModel;
 
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const NO_PERSIST_ARG: &str = "--no-persist";
+const HELP_ARG: &str = "--help";
+// The flags that fill in the menu, with the key of the field each fills
+const PORT_ARGS: [(&str, &str); 6] = [
+    ("--port", "port"),
+    ("--baud", "baudrate"),
+    ("--data-bits", "data_bits"),
+    ("--stop-bits", "stop_bits"),
+    ("--parity", "parity"),
+    ("--mode", "mode"),
+];
+const USAGE: &str = "Usage: nolp [--no-persist] [--port PORT] [--baud RATE] [--data-bits 5-8]
+            [--stop-bits 1|2] [--parity none|even|odd]
+            [--mode ascii|hex|decimal|octal|hexdump]
+
+Given a port, connects to it straight away, otherwise the flags given fill in
+the menu (the rest taking its defaults).";
+const RECENT_LIMIT: usize = 10;
+// Below this, the screens cannot be laid out and a placeholder is drawn
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
 type NolpBackend = CrosstermBackend<Stdout>;
 type NolpTerminal = Terminal<NolpBackend>;
 
@@ -60,13 +123,37 @@ type NolpTerminal = Terminal<NolpBackend>;
 * Internal Interface
 *******************************************************************************/
 /******************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, PartialEq)]
+struct Arguments {
+    no_persist: bool,
+    // As filled into the menu, by the key of the field
+    values: Vec<(String, String)>,
+}
+
+// Where the command line starts, past the menu once it names a port (boxed,
+// as State boxes the parameters)
+#[derive(Debug, PartialEq)]
+enum Start {
+    Menu(Box<MenuModel>),
+    Terminal(Box<PortParameters>),
+}
+
+#[derive(Debug)]
 struct Scene {
     screen: Screen,
     help: Option<HelpModel>,
     menu: Option<MenuModel>,
     terminal: Option<TerminalModel>,
     device_list: Option<DeviceListModel>,
+    notice: Option<String>,
+    capture: Option<Capture>,
+    // Where the last connection is remembered (and the recent ones next to
+    // it), unless it is not to be
+    settings: Option<PathBuf>,
+    // Whether the menu opens the port before switching to the terminal
+    probe: bool,
+    // The chords in effect, which help lists as they are
+    keymap: Vec<Binding>,
 }
 
 #[derive(Debug)]
@@ -90,6 +177,11 @@ impl Default for Scene {
             help: None,
             terminal: None,
             device_list: None,
+            notice: None,
+            capture: None,
+            settings: None,
+            probe: false,
+            keymap: get_default_keymap(),
             screen: Screen::default(),
             menu: Some(MenuModel::default()),
         }
@@ -118,11 +210,17 @@ impl EventListener {
     }
 
     fn handle_event(tx: &UnboundedSender<NolpEvent>, event: Event) {
-        if let Event::Key(k) = event {
-            if k.kind == KeyEventKind::Press {
+        match event {
+            Event::Key(k) if k.kind == KeyEventKind::Press => {
                 tx.send(NolpEvent::User(k))
                     .expect("Failed to send user event");
             }
+            // Arrives whole, so none of its characters are taken as keys
+            Event::Paste(text) => {
+                tx.send(NolpEvent::Paste(text))
+                    .expect("Failed to send paste event");
+            }
+            _ => {}
         }
     }
 
@@ -171,9 +269,34 @@ impl EventListener {
 * Utility Functions
 *******************************************************************************/
 /******************************************************************************/
-fn get_frame_border<'a>() -> Block<'a> {
+fn acknowledge_error(model: &mut impl Nolp, state: &mut State, msg: &Message) -> bool {
+    // An error remains displayed until the user acknowledges it with enter,
+    // which is consumed rather than forwarded to the model
+    if let State::Error(_) = model.get_state() {
+        if *msg == Message::Enter {
+            model.set_state(State::Running);
+            *state = State::Running;
+            return true;
+        }
+    }
+    return false;
+}
+
+fn get_frame_border<'a>(summary: Option<String>, width: u16) -> Block<'a> {
+    // The summary is cut short to fit between the corners, with NOLP kept
+    let title = match summary {
+        Some(summary) => {
+            let room = usize::from(width).saturating_sub(11);
+            let title = match summary.chars().count() > room {
+                true => summary.chars().take(room.saturating_sub(1)).collect::<String>() + "…",
+                false => summary,
+            };
+            format!(" NOLP — {} ", title)
+        }
+        None => String::from(" NOLP "),
+    };
     Block::default()
-        .title(" NOLP ")
+        .title(title)
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
@@ -186,57 +309,235 @@ fn get_layout(frame: &mut Frame) -> Rc<[Rect]> {
         .split(frame.size())
 }
 
-fn get_info<'a>(model: &mut impl Nolp) -> Paragraph<'a> {
+fn get_too_small<'a>(size: Rect) -> Paragraph<'a> {
+    // Centered on what little room there is, until the window is resized
+    let style = Style::default().fg(crate::WARNING_COLOR);
+    let mut text = vec![Line::from(""); usize::from(size.height.saturating_sub(2) / 2)];
+    text.push(Line::styled("Window too small", style));
+    text.push(Line::styled(
+        format!("{}x{}, needs {}x{}", size.width, size.height, MIN_WIDTH, MIN_HEIGHT),
+        Style::default().fg(crate::PLACEHOLDER_COLOR),
+    ));
+    return Paragraph::new(text).alignment(Alignment::Center);
+}
+
+fn get_info<'a>(model: &mut impl Nolp, notice: &Option<String>, recording: bool) -> Paragraph<'a> {
     let mut style = Style::default().fg(crate::PLACEHOLDER_COLOR);
     let mut message = format!(" Help (ctrl+{}) | Quit (ctrl+{}) ", HELP_CHAR, QUIT_CHAR);
 
+    if let Some(n) = notice {
+        style = style.fg(crate::WARNING_COLOR);
+        message = n.clone();
+    }
+
     if let State::Error(m) = model.get_state() {
         style = style.fg(crate::INVALID_COLOR);
         message = m;
     }
 
-    let commands = Line::styled(message, style);
+    // Shown alongside anything else, for as long as the capture runs
+    let mut spans = Vec::new();
+    if recording {
+        spans.push(Span::styled(" ● REC |", Style::default().fg(crate::INVALID_COLOR)));
+    }
+    spans.push(Span::styled(message, style));
+    let commands = Line::from(spans);
     let help = Paragraph::new(commands).alignment(Alignment::Center);
     return help;
 }
 
 fn get_message(scene: &mut Scene, key: KeyEvent) -> Option<Message> {
-    if key.modifiers == event::KeyModifiers::CONTROL {
-        match key.code {
-            KeyCode::Char(QUIT_CHAR) => {
-                return Some(Message::Quit);
-            }
-            KeyCode::Char(DEVICE_LIST_CHAR) => {
-                return Some(Message::Switching(Screen::DeviceList, None));
-            }
-            KeyCode::Char(MENU_CHAR) => {
-                let parameters = get_parameters(scene);
-                return Some(Message::Switching(Screen::Menu, parameters));
-            }
-            KeyCode::Char(HELP_CHAR) => {
+    // Every key belongs to the device, including the shortcuts, until the
+    // escape chord is typed
+    if scene.screen == Screen::Terminal && scene.terminal.as_ref().unwrap().is_passthrough() {
+        return Some(Message::PassThrough(key));
+    }
+
+    // The chords are looked up in the keymap, where the screens opened
+    // with the parameters of the session are handed them
+    let message = get_binding(&scene.keymap, &scene.screen, &key).map(|binding| binding.message.clone());
+    if let Some(message) = message {
+        return match message {
+            Message::Switching(screen @ (Screen::Menu | Screen::Help), _) => {
                 let parameters = get_parameters(scene);
-                return Some(Message::Switching(Screen::Help, parameters));
-            }
-            KeyCode::Char(RESUME_CHAR) => {
-                return Some(Message::Resume);
-            }
-            KeyCode::Char(PAUSE_CHAR) => {
-                return Some(Message::Pause);
+                Some(Message::Switching(screen, parameters))
             }
+            message => Some(message),
+        };
+    }
+
+    // The arrows recall the input history and move the cursor, which only
+    // the terminal has
+    if scene.screen == Screen::Terminal {
+        match key.code {
+            KeyCode::Up => return Some(Message::HistoryPrev),
+            KeyCode::Down => return Some(Message::HistoryNext),
+            KeyCode::Left => return Some(Message::CursorLeft),
+            KeyCode::Right => return Some(Message::CursorRight),
+            KeyCode::Home => return Some(Message::CursorHome),
+            KeyCode::End => return Some(Message::CursorEnd),
+            KeyCode::Delete => return Some(Message::Delete),
             _ => {}
         }
     }
 
+    // In the menu, the sideways arrows move the cursor of a field (or cycle
+    // its choices, or accept the port suggested), and tab completes the port
+    if scene.screen == Screen::Menu {
+        match key.code {
+            KeyCode::Left => return Some(Message::CursorLeft),
+            KeyCode::Right => return Some(Message::CursorRight),
+            KeyCode::Home => return Some(Message::CursorHome),
+            KeyCode::End => return Some(Message::CursorEnd),
+            KeyCode::Delete => return Some(Message::Delete),
+            KeyCode::Tab => return Some(Message::Complete),
+            _ => {}
+        }
+    }
+
+    // The brackets only move between the elements of a screen without any
+    // text to type them into, where the arrows and tab do the same
+    let typed = scene.screen == Screen::Menu || scene.screen == Screen::Terminal;
     return match key.code {
-        KeyCode::Char(PREVIOUS_ELEMENT_CHAR) => Some(Message::PreviousElement),
-        KeyCode::Char(NEXT_ELEMENT_CHAR) => Some(Message::NextElement),
+        KeyCode::Char(PREVIOUS_ELEMENT_CHAR) if typed == false => Some(Message::PreviousElement),
+        KeyCode::Char(NEXT_ELEMENT_CHAR) if typed == false => Some(Message::NextElement),
+        KeyCode::Up | KeyCode::BackTab => Some(Message::PreviousElement),
+        KeyCode::Down | KeyCode::Tab => Some(Message::NextElement),
         KeyCode::Char(input) => Some(Message::Input(input)),
         KeyCode::Backspace => Some(Message::Backspace),
         KeyCode::Enter => Some(Message::Enter),
+        KeyCode::Esc => Some(Message::Cancel),
+        KeyCode::PageUp => Some(Message::ScrollUp),
+        KeyCode::PageDown => Some(Message::ScrollDown),
         _ => None,
     };
 }
 
+fn get_arguments(args: &[String]) -> Result<Option<Arguments>, String> {
+    // None when only the usage is asked for, where a flag takes its value
+    // as the next argument or after `=`
+    let mut arguments = Arguments::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == HELP_ARG || arg == "-h" {
+            return Ok(None);
+        }
+        if arg == NO_PERSIST_ARG {
+            arguments.no_persist = true;
+            continue;
+        }
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(String::from(value))),
+            None => (arg.as_str(), None),
+        };
+        let key = match PORT_ARGS.iter().find(|(f, _)| *f == flag) {
+            Some((_, key)) => key,
+            None => return Err(format!("unknown argument '{}'", arg)),
+        };
+        let value = match value.or_else(|| args.next().cloned()) {
+            Some(value) if value.len() > 0 => value,
+            _ => return Err(format!("{} needs a value", flag)),
+        };
+        if arguments.values.iter().any(|(k, _)| k == key) {
+            return Err(format!("{} is given more than once", flag));
+        }
+        arguments.values.push((String::from(*key), value));
+    }
+    return Ok(Some(arguments));
+}
+
+fn get_start(values: &[(String, String)]) -> Result<Option<Start>, String> {
+    // Checked as the menu checks them, the port included, so that nothing
+    // is left to find out once the screen is taken over
+    if values.len() == 0 {
+        return Ok(None);
+    }
+    let mut menu = MenuModel::default();
+    menu.probe = true;
+    for (key, value) in values {
+        menu.set_value(key, value).map_err(|e| String::from(e.trim()))?;
+    }
+    if values.iter().any(|(key, _)| key == "port") == false {
+        menu.check_values().map_err(|e| String::from(e.trim()))?;
+        return Ok(Some(Start::Menu(Box::new(menu))));
+    }
+    let parameters = menu.check_parameters().map_err(|e| String::from(e.trim()))?;
+    return Ok(Some(Start::Terminal(Box::new(parameters))));
+}
+
+fn get_menu(scene: &Scene, parameters: Option<PortParameters>) -> MenuModel {
+    let mut model = match parameters {
+        Some(p) => MenuModel::new(p),
+        // Opened fresh, the menu starts from the last connection
+        None => match scene.settings.as_ref().and_then(|path| read_values(path)) {
+            Some(values) => MenuModel::restore(&values),
+            None => MenuModel::default(),
+        },
+    };
+    model.probe = scene.probe;
+    // Only the menu shown lists the ports, a menu built to check or convert
+    // the values does not enumerate them
+    model.update(Message::RefreshDevices);
+    return model;
+}
+
+fn get_recent(scene: &Scene) -> Vec<RecentConnection> {
+    // An entry the menu no longer takes is dropped, rather than the list
+    let path = scene.settings.as_ref().map(|path| get_recent_path(path));
+    let entries = path.and_then(|path| read_entries(&path)).unwrap_or_default();
+    let mut recent = Vec::new();
+    for values in entries {
+        let time = values.iter().find(|(key, _)| key == "time").map(|(_, time)| time.clone());
+        if let Some(parameters) = parse_values(&values) {
+            let time = time.unwrap_or_default();
+            recent.push(RecentConnection { parameters, time });
+        }
+    }
+    return recent;
+}
+
+fn get_hidden(scene: &Scene) -> HiddenDevices {
+    // Anything but a port or the native group is dropped
+    let path = scene.settings.as_ref().map(|path| get_hidden_path(path));
+    let values = path.and_then(|path| read_values(&path)).unwrap_or_default();
+    let mut hidden = HiddenDevices::default();
+    for (key, value) in values {
+        match (key.as_str(), value.as_str()) {
+            ("port", _) => hidden.ports.push(value),
+            ("group", "native") => hidden.native = true,
+            _ => {}
+        }
+    }
+    return hidden;
+}
+
+fn save_hidden(scene: &Scene, hidden: &HiddenDevices) {
+    // As for the recent connections, not being able to is not an error
+    if let Some(path) = &scene.settings {
+        let mut values: Vec<(String, String)> = hidden.ports.iter().map(|port| (String::from("port"), port.clone())).collect();
+        if hidden.native {
+            values.push((String::from("group"), String::from("native")));
+        }
+        let _ = write_hidden(&get_hidden_path(path), &values);
+    }
+}
+
+fn start_with(scene: &mut Scene, start: Option<Start>, commands: &CommandSender) {
+    // A connection from the command line is remembered as one started from
+    // the menu would be
+    match start {
+        Some(Start::Menu(mut menu)) => {
+            menu.probe = scene.probe;
+            scene.menu = Some(*menu);
+        }
+        Some(Start::Terminal(parameters)) => {
+            remember(scene, &parameters);
+            switch_screen(Screen::Terminal, scene, Some(*parameters), commands);
+        }
+        None => {}
+    }
+}
+
 fn get_parameters(scene: &mut Scene) -> Option<PortParameters> {
     match scene.screen {
         Screen::Help => scene.help.as_mut().unwrap().parameters.clone(),
@@ -245,28 +546,94 @@ fn get_parameters(scene: &mut Scene) -> Option<PortParameters> {
     }
 }
 
+fn handle_serial(
+    scene: &mut Scene,
+    state: &mut State,
+    event: SerialEvent,
+    rx: &SharedRx,
+) {
+    // Serial events only concern the terminal (but for a test, which is the
+    // menu's), anything arriving after it was left belongs to a session that
+    // has already been closed
+    let tested = matches!(event, SerialEvent::Tested(_));
+    if scene.terminal.is_none() && tested == false {
+        return;
+    }
+
+    match event {
+        SerialEvent::Tested(result) => {
+            if let Some(model) = scene.menu.as_mut() {
+                model.update(Message::Tested(result));
+            }
+        }
+        SerialEvent::RxReady => receive(scene, state, rx),
+        SerialEvent::Status(status) => {
+            // The device list shows how the session behind it stands
+            if let Some(model) = scene.device_list.as_mut() {
+                model.update(Message::Connection(status.clone()));
+            }
+            scene.terminal.as_mut().unwrap().update(Message::Connection(status));
+        }
+        SerialEvent::Lines(lines) => {
+            scene.terminal.as_mut().unwrap().update(Message::Lines(lines));
+        }
+        SerialEvent::Reconfigured(parameters) => {
+            scene.terminal.as_mut().unwrap().update(Message::Reconfigured(parameters));
+        }
+        SerialEvent::Reset => {
+            scene.terminal.as_mut().unwrap().update(Message::Reset);
+        }
+        SerialEvent::Flushed => {
+            show_notice(scene, String::from(" Buffers flushed "));
+        }
+        SerialEvent::Error(e) => {
+            *state = State::Error(e.clone().into());
+            scene.terminal.as_mut().unwrap().set_error(e);
+        }
+        SerialEvent::Failed(e) => {
+            // The terminal displays the reason and returns to the menu once
+            // it is acknowledged, a port that failed to open has no session
+            let status = ConnectionStatus::Failed(e);
+            scene.terminal.as_mut().unwrap().update(Message::Connection(status));
+        }
+    }
+}
+
 fn init_terminal() -> Result<NolpTerminal> {
     enable_raw_mode()?;
     execute!(stdout(), EnterAlternateScreen)?;
     execute!(stdout(), cursor::Hide)?;
+    execute!(stdout(), EnableBracketedPaste)?;
     let backend = NolpBackend::new(stdout());
     let terminal = NolpTerminal::new(backend)?;
     Ok(terminal)
 }
 
 fn reset_terminal() -> Result<()> {
+    execute!(stdout(), DisableBracketedPaste)?;
     execute!(stdout(), LeaveAlternateScreen)?;
     execute!(stdout(), cursor::Show)?;
     disable_raw_mode()?;
     Ok(())
 }
 
-fn render_screen(terminal: &mut NolpTerminal, model: &mut (impl Tea + Nolp)) {
+fn render_screen(
+    terminal: &mut Terminal<impl Backend>,
+    model: &mut (impl Tea + Nolp),
+    notice: &Option<String>,
+    recording: bool,
+    summary: Option<String>,
+) {
     terminal
         .draw(|frame| {
+            let size = frame.size();
+            if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+                frame.render_widget(get_too_small(size), size);
+                return;
+            }
             let layout = get_layout(frame);
-            let frame_border = get_frame_border();
-            let info = get_info(model);
+            let frame_border = get_frame_border(summary, size.width);
+            let info = get_info(model, notice, recording);
 
             frame.render_widget(frame_border, frame.size());
             model.view(frame);
@@ -275,43 +642,110 @@ fn render_screen(terminal: &mut NolpTerminal, model: &mut (impl Tea + Nolp)) {
         .expect("Failed to render frame");
 }
 
-fn render(terminal: &mut NolpTerminal, scene: &mut Scene, state: &mut State, error: &SerialError) {
+fn receive(scene: &mut Scene, state: &mut State, rx: &SharedRx) {
+    // Also while Help is shown over the terminal, so nothing is missed
+    if scene.terminal.is_none() {
+        return;
+    }
+
+    let (runs, dropped) = match rx.lock() {
+        Ok(mut r_mutex) => (r_mutex.drain(), r_mutex.take_dropped()),
+        Err(_) => return,
+    };
+    for (origin, data, gap) in runs {
+        if let Some(gap) = gap {
+            update_terminal(scene, state, Message::Gap(gap));
+        }
+        if let Some(capture) = &scene.capture {
+            let source = match origin {
+                Origin::Primary => CaptureSource::Rx,
+                Origin::Bridge => CaptureSource::Bridge,
+                Origin::Sent => CaptureSource::Tx,
+            };
+            capture.record(source, &data);
+        }
+        let msg = match origin {
+            Origin::Primary => Message::Rx(data),
+            Origin::Bridge => Message::BridgeRx(data),
+            Origin::Sent => Message::Sent(data),
+        };
+        update_terminal(scene, state, msg);
+    }
+    if dropped > 0 {
+        show_notice(scene, format!(" Receive buffer full, dropped {} bytes ", dropped));
+    }
+}
+
+fn render(terminal: &mut NolpTerminal, scene: &mut Scene) {
     match scene.screen {
         Screen::Menu => {
             let model = scene.menu.as_mut().unwrap();
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
-            }
-            render_screen(terminal, model);
+            render_screen(terminal, model, &scene.notice, scene.capture.is_some(), None);
         }
         Screen::DeviceList => {
             let model = scene.device_list.as_mut().unwrap();
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
-            }
-            render_screen(terminal, model);
+            render_screen(terminal, model, &scene.notice, scene.capture.is_some(), None);
         }
         Screen::Help => {
             let model = scene.help.as_mut().unwrap();
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
-            }
-            render_screen(terminal, model);
+            render_screen(terminal, model, &scene.notice, scene.capture.is_some(), None);
         }
         Screen::Terminal => {
             let model = scene.terminal.as_mut().unwrap();
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
-            }
-            render_screen(terminal, model);
+            let summary = model.get_summary();
+            render_screen(terminal, model, &scene.notice, scene.capture.is_some(), summary);
         }
     };
 }
 
+fn remember(scene: &Scene, parameters: &PortParameters) {
+    // Not being able to remember the connection is not worth an error
+    if let Some(path) = &scene.settings {
+        let values = MenuModel::new(parameters.clone()).get_values();
+        let _ = write_values(path, &values);
+
+        // A port is listed once, at the latest connection to it
+        let recent = get_recent_path(path);
+        let mut entries = read_entries(&recent).unwrap_or_default();
+        let port = (String::from("port"), parameters.name.clone().unwrap_or_default());
+        entries.retain(|entry| entry.contains(&port) == false);
+        let mut entry = values;
+        entry.push((String::from("time"), get_timestamp(SystemTime::now())));
+        entries.insert(0, entry);
+        entries.truncate(RECENT_LIMIT);
+        let _ = write_entries(&recent, &entries);
+    }
+}
+
+fn copy(text: &str) -> String {
+    // Shown once, where a copy that could not be done says why
+    if text.len() == 0 {
+        return String::from(" Nothing to copy ");
+    }
+    match copy_text(text) {
+        Ok(_) => return String::from(" Copied "),
+        Err(e) => return format!(" Failed to copy: {} ", e),
+    }
+}
+
+fn copy_diagnostics(diagnostics: &Diagnostics) -> String {
+    // Without a clipboard, a file does as well for pasting into an issue
+    if copy_text(&diagnostics.get_text()).is_ok() {
+        return String::from(" Copied ");
+    }
+    match write_diagnostics(diagnostics) {
+        Ok(path) => return format!(" Written to {} ", path),
+        Err(e) => return format!(" Failed to copy or write: {} ", e),
+    }
+}
+
+fn ring_bell() {
+    // Rung by the terminal the application is running in, if it has one
+    let mut out = stdout();
+    let _ = out.write_all(b"\x07");
+    let _ = out.flush();
+}
+
 fn set_panic_hook() {
     let hook = panic::take_hook();
     panic::set_hook(Box::new(move |info| {
@@ -320,10 +754,8 @@ fn set_panic_hook() {
         if cfg!(debug_assertions) {
             hook(info);
         } else {
-            let err_msg = info.payload().downcast_ref::<&str>();
-            match err_msg {
-                Some(msg) => eprintln!("{:?}", msg),
-                None => {}
+            if let Some(msg) = info.payload().downcast_ref::<&str>() {
+                eprintln!("{:?}", msg);
             }
         }
     }));
@@ -332,26 +764,28 @@ fn set_panic_hook() {
 fn switch_screen(
     new: Screen,
     scene: &mut Scene,
-    flag: &SerialFlag,
     port_params: Option<PortParameters>,
-    serial_params: &SerialParams,
+    commands: &CommandSender,
 ) {
-    if scene.screen == Screen::Terminal {
-        if !close_connection(flag) {
+    // The session is kept while Help or the device list is shown over it,
+    // and is only closed once another screen is opened (or another port is
+    // connected to from the list)
+    let keep = scene.terminal.is_some()
+        && match (&scene.screen, &new) {
+            (_, Screen::Help | Screen::DeviceList) | (Screen::Help, Screen::Terminal) => true,
+            (Screen::DeviceList, Screen::Terminal) => port_params.is_none(),
+            _ => false,
+        };
+    if scene.terminal.is_some() && keep == false {
+        if commands.send(SerialCommand::Close).is_err() {
             panic!("Failed to close connection");
         }
+        stop_capture(scene);
+        scene.terminal = None;
     }
     match new {
         Screen::Menu => {
-            let model: MenuModel;
-            match port_params {
-                Some(p) => {
-                    model = MenuModel::new(p);
-                }
-                None => {
-                    model = MenuModel::default();
-                }
-            }
+            let model = get_menu(scene, port_params);
             scene.help = None;
             scene.terminal = None;
             scene.device_list = None;
@@ -360,226 +794,340 @@ fn switch_screen(
         Screen::DeviceList => {
             scene.menu = None;
             scene.help = None;
-            scene.terminal = None;
-            scene.device_list = Some(DeviceListModel::default());
+            let session = scene.terminal.as_ref().map(|t| {
+                (t.parameters.name.clone().unwrap_or_default(), t.get_connection())
+            });
+            scene.device_list = Some(DeviceListModel::new(get_recent(scene), session, get_hidden(scene)));
         }
         Screen::Help => {
             scene.menu = None;
-            scene.terminal = None;
             scene.device_list = None;
-            scene.help = Some(HelpModel::new(scene.screen.clone(), port_params));
+            let mut model = HelpModel::new(scene.screen.clone(), port_params);
+            model.keymap = scene.keymap.clone();
+            model.diagnostics = get_diagnostics(scene.terminal.as_ref());
+            scene.help = Some(model);
         }
         Screen::Terminal => {
             scene.help = None;
             scene.menu = None;
             scene.device_list = None;
-            let params = port_params.expect("Failed to provide port parameters");
-            if open_connection(flag, serial_params, params.clone()) {
-                scene.terminal = Some(TerminalModel::new(params));
-            } 
+            if keep == false {
+                let params = port_params.expect("Failed to provide port parameters");
+                let mut model = TerminalModel::new(params.clone());
+                if commands.send(SerialCommand::Open(params)).is_err() {
+                    model.set_state(State::Error(String::from(" Failed to open port ")));
+                }
+                scene.terminal = Some(model);
+            }
         }
     }
 
     scene.screen = new;
 }
 
-fn update(
-    scene: &mut Scene,
-    state: &mut State,
-    msg: Message,
-    flag: &SerialFlag,
-    error: &SerialError,
-    params: &SerialParams,
-) {
+fn update(scene: &mut Scene, state: &mut State, msg: Message, commands: &CommandSender) {
     match scene.screen {
         Screen::Menu => {
             let model = scene.menu.as_mut().unwrap();
-            *state = model.update(msg);
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
+            if !acknowledge_error(model, state, &msg) {
+                *state = model.update(msg);
+            }
+            if let Some(parameters) = model.take_test() {
+                let _ = commands.send(SerialCommand::Test(parameters));
+            }
+            if let Some(text) = model.take_copy() {
+                show_notice(scene, copy(&text));
             }
         }
         Screen::DeviceList => {
             let model = scene.device_list.as_mut().unwrap();
-            *state = model.update(msg);
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
+            if !acknowledge_error(model, state, &msg) {
+                *state = model.update(msg);
+            }
+            let copied = model.take_copy();
+            if let Some(hidden) = model.take_hidden() {
+                save_hidden(scene, &hidden);
+            }
+            if let Some(text) = copied {
+                show_notice(scene, copy(&text));
             }
         }
         Screen::Help => {
             let model = scene.help.as_mut().unwrap();
-            *state = model.update(msg);
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
+            if !acknowledge_error(model, state, &msg) {
+                *state = model.update(msg);
             }
-        }
-        Screen::Terminal => {
-            let model = scene.terminal.as_mut().unwrap();
-            *state = model.update(msg);
-            if let Some(e) = get_error(error) {
-                *state = State::Error(e.clone());
-                model.set_state(State::Error(e));
+            if model.take_copy().is_some() {
+                let notice = copy_diagnostics(&model.diagnostics);
+                show_notice(scene, notice);
             }
         }
+        Screen::Terminal => update_terminal(scene, state, msg),
     };
 
     if let State::Switching(s, p) = state {
         let screen = s.clone();
-        let parameters = p.clone();
-        if scene.screen == Screen::Terminal {
-            if !close_connection(flag) {
-                panic!("Failed to close connection");
-            }
+        let parameters = p.clone().map(|p| *p);
+        if let (Screen::Menu | Screen::DeviceList, Screen::Terminal, Some(p)) = (&scene.screen, &screen, &parameters) {
+            remember(scene, p);
         }
-        switch_screen(screen, scene, flag, parameters, params);
+        switch_screen(screen, scene, parameters, commands);
         *state = State::Running;
     }
 }
 
-fn send_receive(
-    scene: &mut Scene,
-    state: &mut State,
-    flag: &SerialFlag,
-    rx: &SerialBuffer,
-    tx: &SerialBuffer,
-    error: &SerialError,
-    params: &SerialParams,
-) {
-    let message: Message;
-    let mut rx_lock = rx.try_lock();
-    if let Ok(ref mut mutex) = rx_lock {
-        if (**mutex).len() > 0 {
-            message = Message::Rx((**mutex).clone());
-            update(scene, state, message, flag, error, params);
-            (**mutex).clear();
-        }
-        drop(rx_lock);
+fn update_terminal(scene: &mut Scene, state: &mut State, msg: Message) {
+    // While Help (or the device list) is shown over it, the terminal keeps
+    // its state to itself
+    // until it is shown again
+    let model = scene.terminal.as_mut().unwrap();
+    if scene.screen != Screen::Terminal {
+        model.update(msg);
+    } else if !acknowledge_error(model, state, &msg) {
+        *state = model.update(msg);
+    }
+    let notice = model.take_notice();
+    if model.take_bell() {
+        ring_bell();
+    }
+    if let (Some(marker), Some(capture)) = (model.take_capture_marker(), &scene.capture) {
+        capture.mark(&marker);
     }
+    if let Some(notice) = notice {
+        show_notice(scene, notice);
+    }
+}
+
+fn send_output(scene: &mut Scene, tx: &DataSender, commands: &CommandSender) {
+    // Both channels are unbounded, so the pending output is always handed
+    // off and never left waiting for a later tick
     let terminal = scene.terminal.as_mut().unwrap();
-    let mut buffer = terminal.get_output_buffer();
+    for command in terminal.get_commands() {
+        let _ = commands.send(command);
+    }
+    terminal.clear_commands();
+
+    let buffer = terminal.get_output_buffer();
     if buffer.len() > 0 {
-        let mut tx_lock = tx.try_lock();
-        if let Ok(ref mut mutex) = tx_lock {
-            (**mutex).append(&mut buffer);
-            drop(tx_lock);
-            terminal.clear_output_buffer();
+        if let Some(capture) = &scene.capture {
+            capture.record(CaptureSource::Tx, &buffer);
+        }
+        let _ = tx.send(buffer);
+    }
+    terminal.clear_output_buffer();
+}
+
+fn show_notice(scene: &mut Scene, notice: String) {
+    // Shown once, the notice is cleared by the next key press
+    scene.notice = Some(notice);
+}
+
+fn stop_capture(scene: &mut Scene) {
+    if let Some(capture) = scene.capture.take() {
+        let notice = match capture.stop() {
+            Ok(path) => format!(" Capture saved to {} ", path),
+            Err(e) => format!(" Capture failed: {} ", e),
+        };
+        show_notice(scene, notice);
+    }
+}
+
+fn toggle_capture(scene: &mut Scene) {
+    if scene.screen != Screen::Terminal {
+        return;
+    }
+    if scene.capture.is_some() {
+        stop_capture(scene);
+        return;
+    }
+    let parameters = &scene.terminal.as_ref().unwrap().parameters;
+    scene.notice = match start_capture(parameters) {
+        Ok(capture) => {
+            let notice = format!(" Capturing to {} ", capture.get_path());
+            scene.capture = Some(capture);
+            Some(notice)
         }
+        Err(e) => Some(format!(" Capture failed: {} ", e)),
+    };
+}
+
+fn wait_for_thread(handle: &thread::JoinHandle<()>, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while !handle.is_finished() {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
     }
+    return true;
 }
+
 /******************************************************************************/
 /*******************************************************************************
 * Entry Point
 *******************************************************************************/
 /******************************************************************************/
 fn main() {
-    let flag = serial_flag_default();
-    let error = serial_error_default();
-    let rx_buffer = serial_buffer_default();
-    let tx_buffer = serial_buffer_default();
-    let parameters = serial_params_default();
-
-    let serial_flag = Arc::clone(&flag);
-    let serial_error = Arc::clone(&error);
-    let serial_rx = Arc::clone(&rx_buffer);
-    let serial_tx = Arc::clone(&tx_buffer);
-    let serial_params = Arc::clone(&parameters);
-    serial_main(
-        serial_flag,
-        serial_rx,
-        serial_tx,
-        serial_params,
-        serial_error,
-    );
-
-    let nolp_flag = Arc::clone(&flag);
-    let nolp_error = Arc::clone(&error);
-    let nolp_rx = Arc::clone(&rx_buffer);
-    let nolp_tx = Arc::clone(&tx_buffer);
-    let nolp_params = Arc::clone(&parameters);
-    nolp_main(nolp_flag, nolp_rx, nolp_tx, nolp_error, nolp_params);
-}
-
-fn serial_main(f: SerialFlag, rx: SerialBuffer, tx: SerialBuffer, p: SerialParams, e: SerialError) {
+    let (command_tx, command_rx) = mpsc::channel();
+    let (data_tx, data_rx) = mpsc::channel();
+    let (event_tx, event_rx) = unbounded_channel();
+    let stats = SharedStats::default();
+    let rx = SharedRx::default();
+
+    // Anything wrong is said before the screen is taken over
+    let args: Vec<String> = env::args().skip(1).collect();
+    let arguments = match get_arguments(&args) {
+        Ok(Some(arguments)) => arguments,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("nolp: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let start = match get_start(&arguments.values) {
+        Ok(start) => start,
+        Err(e) => {
+            eprintln!("nolp: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // Nothing is remembered on a shared machine
+    let settings = match arguments.no_persist {
+        true => None,
+        false => get_settings_path(),
+    };
+
+    let serial_stats = Arc::clone(&stats);
+    let serial_rx = Arc::clone(&rx);
+    let handle = serial_main(command_rx, data_rx, event_tx, serial_stats, serial_rx);
+
+    let mut scene = Scene {
+        settings,
+        probe: true,
+        ..Default::default()
+    };
+    scene.menu = Some(get_menu(&scene, None));
+    start_with(&mut scene, start, &command_tx);
+    nolp_main(command_tx, data_tx, event_rx, stats, rx, handle, scene);
+}
+
+fn serial_main(
+    commands: CommandReceiver,
+    tx: DataReceiver,
+    events: EventSender,
+    stats: SharedStats,
+    rx: SharedRx,
+) -> thread::JoinHandle<()> {
     thread::spawn(move || {
-        let mut spawned = false;
-        let mut handle = None;
-        loop {
-            let f_lock = f.try_lock();
-            if let Ok(ref f_mutex) = f_lock {
-                if (**f_mutex) == true {
-                    if !spawned {
-                        let p_lock = p.try_lock();
-                        if let Ok(ref p_mutex) = p_lock {
-                            let port = match get_port((**p_mutex).clone()) {
-                                Ok(pr) => pr,
-                                Err(_) => {
-                                    let mut e_lock = e.try_lock();
-                                    if let Ok(ref mut e_mutex) = e_lock {
-                                        **e_mutex = Some(String::from(" Create port failed "));
-                                        drop(e_lock);
-                                    }
-                                    return;
-                                }
-                            };
-                            handle = Some(read_write_port(port, &rx, &tx, &f, &e));
-                            spawned = true;
-                            drop(p_lock);
-                        }
+        // Nothing is done while closed, the thread sleeps until a request
+        while let Ok(command) = commands.recv() {
+            match command {
+                SerialCommand::Open(parameters) => {
+                    let shutdown = if is_loopback(&parameters) {
+                        read_write_port(parameters, open_loopback, &commands, &tx, &events, &stats, &rx)
+                    } else if is_tcp(&parameters) {
+                        read_write_port(parameters, open_tcp, &commands, &tx, &events, &stats, &rx)
+                    } else if is_replay(&parameters) {
+                        read_write_port(parameters, open_replay, &commands, &tx, &events, &stats, &rx)
+                    } else {
+                        read_write_port(parameters, open_serial, &commands, &tx, &events, &stats, &rx)
+                    };
+                    if shutdown {
+                        return;
                     }
-                    drop(f_lock);
-                } else {
-                    drop(f_lock);
-                    if spawned {
-                        handle.unwrap().join().unwrap();
-                        handle = None;
-                        spawned = false;
+                }
+                SerialCommand::Test(parameters) => {
+                    if is_loopback(&parameters) {
+                        test_port(&parameters, open_loopback, &events);
+                    } else if is_tcp(&parameters) {
+                        test_port(&parameters, open_tcp, &events);
+                    } else if is_replay(&parameters) {
+                        test_port(&parameters, open_replay, &events);
+                    } else {
+                        test_port(&parameters, open_serial, &events);
                     }
                 }
+                SerialCommand::Shutdown => return,
+                _ => {}
             }
-            thread::sleep(Duration::from_millis(104));
         }
-    });
+    })
 }
 
 #[tokio::main]
 async fn nolp_main(
-    flag: SerialFlag,
-    rx: SerialBuffer,
-    tx: SerialBuffer,
-    error: SerialError,
-    params: SerialParams,
+    commands: CommandSender,
+    tx: DataSender,
+    mut events: EventReceiver,
+    stats: SharedStats,
+    rx: SharedRx,
+    handle: thread::JoinHandle<()>,
+    mut scene: Scene,
 ) {
     set_panic_hook();
 
     let mut state = State::default();
-    let mut scene = Scene::default();
     let mut listener = EventListener::new();
     let mut terminal = init_terminal().expect("Failed to initialize terminal");
     while state != State::Stopping {
-        let event = listener.listen().await.unwrap();
+        let event = select! {
+            e = listener.listen() => e.unwrap(),
+            Some(e) = events.recv() => NolpEvent::Serial(e),
+        };
+        if let NolpEvent::User(_) | NolpEvent::Paste(_) = event {
+            scene.notice = None;
+        }
         match event {
-            NolpEvent::User(k) => match get_message(&mut scene, k) {
-                Some(m) => match m {
-                    Message::Quit => state = State::Stopping,
-                    Message::Switching(s, p) => switch_screen(s, &mut scene, &flag, p, &params),
-                    ms => update(&mut scene, &mut state, ms, &flag, &error, &params),
-                },
-                None => {}
-            },
+            NolpEvent::User(k) => {
+                if let Some(m) = get_message(&mut scene, k) {
+                    match m {
+                        Message::Quit => state = State::Stopping,
+                        Message::ToggleCapture => toggle_capture(&mut scene),
+                        Message::Switching(s, p) => switch_screen(s, &mut scene, p, &commands),
+                        ms => update(&mut scene, &mut state, ms, &commands),
+                    }
+                }
+            }
+            NolpEvent::Paste(text) => update(&mut scene, &mut state, Message::Paste(text), &commands),
+            NolpEvent::Serial(e) => handle_serial(&mut scene, &mut state, e, &rx),
             NolpEvent::Tick => {
-                if scene.screen == Screen::Terminal {
-                    send_receive(&mut scene, &mut state, &flag, &rx, &tx, &error, &params);
+                // The devices are listed again every so often, not per frame
+                if scene.screen == Screen::DeviceList {
+                    update(&mut scene, &mut state, Message::Tick, &commands);
+                }
+                if scene.terminal.is_some() {
+                    // Catches data left behind while no notification was due
+                    receive(&mut scene, &mut state, &rx);
+                    update_terminal(&mut scene, &mut state, Message::Tick);
+                    let current = Message::Stats(get_stats(&stats));
+                    scene.terminal.as_mut().unwrap().update(current);
+                }
+                // The counters of the session behind Help run on
+                if let Some(model) = scene.help.as_mut() {
+                    model.diagnostics = get_diagnostics(scene.terminal.as_ref());
                 }
             }
-            NolpEvent::Render => render(&mut terminal, &mut scene, &mut state, &error),
+            NolpEvent::Render => render(&mut terminal, &mut scene),
             _ => {}
         }
+
+        if scene.terminal.is_some() {
+            send_output(&mut scene, &tx, &commands);
+        }
     }
 
+    stop_capture(&mut scene);
+    let _ = commands.send(SerialCommand::Shutdown);
+    let stopped = wait_for_thread(&handle, SHUTDOWN_TIMEOUT);
+
     reset_terminal().expect("Failed to reset terminal");
+    if !stopped {
+        eprintln!("Warning: the serial connection did not close in time");
+    }
 }
 
 
@@ -592,10 +1140,12 @@ async fn nolp_main(
 mod tests {
     use super::*;
     use crossterm::event::KeyModifiers;
+    use ratatui::backend::TestBackend;
+    use std::sync::Mutex;
 
     #[test]
     fn test_get_message() {
-        let mut scene = Scene::default();
+        let mut scene = Scene { screen: Screen::DeviceList, ..Default::default() };
 
         let mut event = KeyEvent::new(KeyCode::Char(PREVIOUS_ELEMENT_CHAR), KeyModifiers::NONE);
         let mut msg = get_message(&mut scene, event);
@@ -604,14 +1154,57 @@ mod tests {
         event.code = KeyCode::Char(NEXT_ELEMENT_CHAR);
         msg = get_message(&mut scene, event);
         assert_eq!(msg, Some(Message::NextElement));
-        
-        event.code = KeyCode::Char(RESUME_CHAR);
+
+        event.code = KeyCode::F(REFRESH_KEY);
         msg = get_message(&mut scene, event);
-        assert_eq!(msg, Some(Message::Resume));
-        
-        event.code = KeyCode::Char(PAUSE_CHAR);
+        assert_eq!(msg, Some(Message::RefreshDevices));
+
+        event.code = KeyCode::F(SHOW_HIDDEN_KEY);
         msg = get_message(&mut scene, event);
-        assert_eq!(msg, Some(Message::Pause));
+        assert_eq!(msg, Some(Message::ToggleHidden));
+
+        event.code = KeyCode::F(HIDE_NATIVE_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleNativeHidden));
+
+        event.code = KeyCode::F(PROBE_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ProbeDevices));
+
+        event.code = KeyCode::F(COPY_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Copy));
+
+        event.code = KeyCode::Delete;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Delete));
+
+        // Where a letter filters the list, and the chord connects
+        event.code = KeyCode::Char(QUICK_CONNECT_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Input(QUICK_CONNECT_CHAR)));
+        event.modifiers = KeyModifiers::CONTROL;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::QuickConnect));
+        event.modifiers = KeyModifiers::NONE;
+
+        event.code = KeyCode::Tab;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::NextElement));
+
+        event.code = KeyCode::BackTab;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::PreviousElement));
+
+        // In the menu, the brackets are typed and the arrows move instead
+        scene.screen = Screen::Menu;
+        event.code = KeyCode::Char(NEXT_ELEMENT_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Input(NEXT_ELEMENT_CHAR)));
+
+        event.code = KeyCode::Down;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::NextElement));
         
         event.code = KeyCode::Backspace;
         msg = get_message(&mut scene, event);
@@ -624,6 +1217,91 @@ mod tests {
         event.code = KeyCode::Char('c');
         msg = get_message(&mut scene, event);
         assert_eq!(msg, Some(Message::Input('c')));
+
+        event.code = KeyCode::PageUp;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ScrollUp));
+
+        event.code = KeyCode::Up;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::PreviousElement));
+
+        event.code = KeyCode::Right;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CursorRight));
+
+        event.code = KeyCode::Home;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CursorHome));
+
+        event.code = KeyCode::Esc;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Cancel));
+
+        event.code = KeyCode::Delete;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Delete));
+
+        event.code = KeyCode::Tab;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Complete));
+
+        event.code = KeyCode::F(REFRESH_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::RefreshDevices));
+
+        event.code = KeyCode::F(COPY_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Copy));
+
+        event.code = KeyCode::Up;
+
+        scene = terminal_scene(PortParameters::default());
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::HistoryPrev));
+
+        event.code = KeyCode::Down;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::HistoryNext));
+
+        event.code = KeyCode::F(GENERATOR_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Generate));
+
+        event.code = KeyCode::F(REPLAY_PAUSE_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::PauseReplay));
+
+        event.code = KeyCode::F(REPLAY_SPEED_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CycleReplaySpeed));
+
+        event.code = KeyCode::F(REPLAY_STEP_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::StepReplay));
+
+        event.code = KeyCode::F(ECHO_KEY);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleEcho));
+
+        event.code = KeyCode::End;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CursorEnd));
+
+        event.modifiers = KeyModifiers::CONTROL;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ScrollBottom));
+
+        // The shortcuts are forwarded to the device in pass-through mode
+        event.code = KeyCode::Char(PASSTHROUGH_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::TogglePassThrough));
+        let model = scene.terminal.as_mut().unwrap();
+        model.update(Message::Connection(ConnectionStatus::Connected));
+        model.update(Message::TogglePassThrough);
+        event.code = KeyCode::Char(QUIT_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::PassThrough(event)));
     }
 
     #[test]
@@ -645,5 +1323,742 @@ mod tests {
         event.code = KeyCode::Char(MENU_CHAR);
         msg = get_message(&mut scene, event); 
         assert_eq!(msg, Some(Message::Switching(Screen::Menu, None)));
+
+        event.code = KeyCode::Char(RESUME_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Resume));
+        
+        event.code = KeyCode::Char(PAUSE_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Pause));
+
+        event.code = KeyCode::Char(BREAK_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Break));
+
+        event.code = KeyCode::Char(DTR_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleDtr));
+
+        event.code = KeyCode::Char(RTS_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleRts));
+
+        event.code = KeyCode::Char('7');
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Search));
+
+        event.code = KeyCode::Char(MNEMONICS_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleMnemonics));
+
+        event.code = KeyCode::Char(CLEAR_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Clear));
+
+        event.code = KeyCode::Char(GRAPH_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleGraph));
+
+        event.code = KeyCode::Char(AT_HISTORY_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ToggleAtHistory));
+
+        event.code = KeyCode::Char(SCRIPT_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::RunScript));
+
+        event.code = KeyCode::Char(LATENCY_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::MeasureLatency));
+
+        event.code = KeyCode::Char('4');
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::SendByte));
+
+        event.code = KeyCode::Char('=');
+        event.modifiers = KeyModifiers::ALT;
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::LongerRows));
+
+        event.code = KeyCode::Char(SHORTER_ROWS_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::ShorterRows));
+        event.modifiers = KeyModifiers::CONTROL;
+
+        event.code = KeyCode::Char(CONFIGURE_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Configure));
+
+        event.code = KeyCode::Char(FLUSH_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Flush));
+
+        event.code = KeyCode::Char(MODE_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CycleMode));
+
+        event.code = KeyCode::Char(LINE_ENDING_CHAR);
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::CycleLineEnding));
+
+        // A chord rebound in the keymap takes the message with it, and help
+        // is handed the keymap as it is
+        let binding = scene.keymap.iter_mut().find(|b| b.message == Message::CycleLineEnding).unwrap();
+        binding.code = KeyCode::F(12);
+        binding.modifiers = KeyModifiers::NONE;
+        msg = get_message(&mut scene, KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE));
+        assert_eq!(msg, Some(Message::CycleLineEnding));
+        msg = get_message(&mut scene, event);
+        assert_eq!(msg, Some(Message::Input(LINE_ENDING_CHAR)));
+        let (commands, _receiver) = mpsc::channel();
+        switch_screen(Screen::Help, &mut scene, None, &commands);
+        assert_eq!(scene.help.as_ref().unwrap().keymap, scene.keymap);
+    }
+
+    fn terminal_scene(parameters: PortParameters) -> Scene {
+        let scene = Scene {
+            screen: Screen::Terminal,
+            menu: None,
+            terminal: Some(TerminalModel::new(parameters)),
+            ..Default::default()
+        };
+        return scene;
+    }
+
+    #[test]
+    fn test_switch_screen_help() {
+        let parameters = PortParameters {
+            name: Some(String::from("/dev/ttyUSB0")),
+            mode: Some(Mode::Ascii),
+            ..Default::default()
+        };
+        let mut scene = terminal_scene(parameters.clone());
+        let mut state = State::Running;
+        let (commands, receiver) = mpsc::channel();
+        let rx = SharedRx::default();
+        handle_serial(&mut scene, &mut state, SerialEvent::Status(ConnectionStatus::Connected), &rx);
+
+        // The session carries on behind Help, receiving as it did
+        switch_screen(Screen::Help, &mut scene, Some(parameters.clone()), &commands);
+        assert!(scene.terminal.is_some());
+        rx.lock().unwrap().push(Origin::Primary, b"while away\r\n");
+        handle_serial(&mut scene, &mut state, SerialEvent::RxReady, &rx);
+        assert_eq!(rx.lock().unwrap().len(), 0);
+
+        // Its diagnostics are shown there, for the key that copies them
+        let diagnostics = &scene.help.as_ref().unwrap().diagnostics;
+        assert_eq!(diagnostics.parameters, Some(parameters.clone()));
+        assert_eq!(diagnostics.connection, Some(ConnectionStatus::Connected));
+        let msg = get_message(&mut scene, KeyEvent::new(KeyCode::F(COPY_KEY), KeyModifiers::NONE));
+        assert_eq!(msg, Some(Message::Copy));
+
+        // And is shown as it was left, without opening the port again
+        update(&mut scene, &mut state, Message::Enter, &commands);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert!(receiver.try_recv().is_err());
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        render_screen(&mut terminal, scene.terminal.as_mut().unwrap(), &None, false, None);
+        let text: String = terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("while away"));
+
+        // Leaving Help for another screen closes it
+        switch_screen(Screen::Help, &mut scene, Some(parameters), &commands);
+        switch_screen(Screen::Menu, &mut scene, None, &commands);
+        assert_eq!(receiver.try_recv(), Ok(SerialCommand::Close));
+        assert!(scene.terminal.is_none());
+    }
+
+    #[test]
+    fn test_copy() {
+        // Nothing is copied without a port, which is said
+        let mut scene = Scene::default();
+        let mut state = State::Running;
+        let (commands, _receiver) = mpsc::channel();
+        switch_screen(Screen::DeviceList, &mut scene, None, &commands);
+        scene.device_list = Some(DeviceListModel::default());
+        update(&mut scene, &mut state, Message::Copy, &commands);
+        assert_eq!(scene.notice, Some(String::from(" Nothing to copy ")));
+    }
+
+    #[test]
+    fn test_hidden() {
+        let mut state = State::Running;
+        let (commands, _receiver) = mpsc::channel();
+        let path = env::temp_dir().join(format!("nolp-hidden-{}", std::process::id())).join("last.toml");
+        let mut scene = Scene {
+            settings: Some(path.clone()),
+            ..Default::default()
+        };
+        assert_eq!(get_hidden(&scene), HiddenDevices::default());
+
+        // What is hidden from the list is kept as soon as it changes
+        switch_screen(Screen::DeviceList, &mut scene, None, &commands);
+        update(&mut scene, &mut state, Message::ToggleNativeHidden, &commands);
+        assert!(get_hidden(&scene).native);
+        let hidden = HiddenDevices {
+            ports: vec![String::from("/dev/ttyS0"), String::from("COM\"quoted\"")],
+            native: false,
+        };
+        save_hidden(&scene, &hidden);
+        assert_eq!(get_hidden(&scene), hidden);
+
+        // And is not, where nothing is remembered
+        scene.settings = None;
+        save_hidden(&scene, &HiddenDevices::default());
+        scene.settings = Some(path.clone());
+        assert_eq!(get_hidden(&scene), hidden);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_switch_screen_device_list() {
+        let parameters = PortParameters { name: Some(String::from("/dev/ttyUSB0")), ..Default::default() };
+        let mut scene = terminal_scene(parameters.clone());
+        let mut state = State::Running;
+        let (commands, receiver) = mpsc::channel();
+        let rx = SharedRx::default();
+        handle_serial(&mut scene, &mut state, SerialEvent::Status(ConnectionStatus::Connected), &rx);
+
+        // The session carries on behind the list, which hears how it stands
+        switch_screen(Screen::DeviceList, &mut scene, None, &commands);
+        assert!(scene.terminal.is_some());
+        handle_serial(&mut scene, &mut state, SerialEvent::Status(ConnectionStatus::Reconnecting), &rx);
+        assert_eq!(scene.terminal.as_ref().unwrap().get_connection(), ConnectionStatus::Reconnecting);
+
+        // And is returned to without opening the port again
+        update(&mut scene, &mut state, Message::Cancel, &commands);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert!(scene.terminal.is_some());
+        assert!(receiver.try_recv().is_err());
+
+        // Going on to the menu from the list closes it
+        switch_screen(Screen::DeviceList, &mut scene, None, &commands);
+        switch_screen(Screen::Menu, &mut scene, None, &commands);
+        assert_eq!(receiver.try_recv(), Ok(SerialCommand::Close));
+        assert!(scene.terminal.is_none());
+    }
+
+    #[test]
+    fn test_handle_serial_error() {
+        let mut scene = Scene::default();
+        let mut state = State::Running;
+        let rx = SharedRx::default();
+
+        let event = SerialEvent::Error(SerialErrorKind::ReadFailed);
+        handle_serial(&mut scene, &mut state, event.clone(), &rx);
+        assert_eq!(state, State::Running);
+
+        scene = terminal_scene(PortParameters::default());
+        handle_serial(&mut scene, &mut state, event, &rx);
+        let expected = State::Error(String::from(" Read failed "));
+        assert_eq!(state, expected);
+        assert_eq!(scene.terminal.as_ref().unwrap().get_state(), expected);
+    }
+
+    #[test]
+    fn test_receive_dropped() {
+        let mut scene = Scene::default();
+        let mut state = State::Running;
+        let rx = SharedRx::new(Mutex::new(RxBuffer::new(4)));
+
+        rx.lock().unwrap().push(Origin::Primary, &[1, 2, 3, 4, 5, 6]);
+        receive(&mut scene, &mut state, &rx);
+        assert_eq!(rx.lock().unwrap().len(), 4);
+        assert_eq!(scene.notice, None);
+
+        scene = terminal_scene(PortParameters::default());
+        receive(&mut scene, &mut state, &rx);
+        assert_eq!(rx.lock().unwrap().len(), 0);
+        let notice = String::from(" Receive buffer full, dropped 2 bytes ");
+        assert_eq!(scene.notice, Some(notice));
+
+        // The warning is only raised once per overflow
+        scene.notice = None;
+        receive(&mut scene, &mut state, &rx);
+        assert_eq!(scene.notice, None);
+    }
+
+    #[test]
+    fn test_acknowledge_error() {
+        let mut model = TerminalModel::default();
+        let mut state = State::Running;
+
+        assert!(!acknowledge_error(&mut model, &mut state, &Message::Enter));
+
+        model.set_state(State::Error(String::from(" Failed to open port ")));
+        assert!(!acknowledge_error(&mut model, &mut state, &Message::Input('c')));
+        assert!(acknowledge_error(&mut model, &mut state, &Message::Enter));
+        assert_eq!(model.get_state(), State::Running);
+        assert_eq!(state, State::Running);
+    }
+
+    #[test]
+    fn test_wait_for_thread() {
+        let handle = thread::spawn(|| {});
+        assert!(wait_for_thread(&handle, Duration::from_secs(1)));
+
+        let handle = thread::spawn(|| thread::sleep(Duration::from_millis(200)));
+        assert!(!wait_for_thread(&handle, Duration::from_millis(10)));
+    }
+
+    fn wait_for_event(events: &mut EventReceiver) -> Option<SerialEvent> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if let Ok(event) = events.try_recv() {
+                return Some(event);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        return None;
+    }
+
+    #[test]
+    fn test_serial_main_failed_open() {
+        let (commands, command_rx) = mpsc::channel();
+        let (_tx, data_rx) = mpsc::channel();
+        let (event_tx, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let handle = serial_main(command_rx, data_rx, event_tx, stats, SharedRx::default());
+
+        let parameters = PortParameters {
+            name: Some(String::from("test/missing-port")),
+            baud_rate: Some(9600),
+            data_bits: Some(8),
+            stop_bits: Some(1),
+            parity: Some(Parity::None),
+            mode: Some(Mode::Ascii),
+            ..Default::default()
+        };
+        let failed = |event| matches!(event, Some(SerialEvent::Failed(_)));
+
+        commands.send(SerialCommand::Open(parameters.clone())).unwrap();
+        assert!(failed(wait_for_event(&mut events)));
+
+        // The thread must still be alive to act on a second request
+        commands.send(SerialCommand::Open(parameters)).unwrap();
+        assert!(failed(wait_for_event(&mut events)));
+
+        commands.send(SerialCommand::Shutdown).unwrap();
+        assert!(wait_for_thread(&handle, SHUTDOWN_TIMEOUT));
+    }
+
+    #[test]
+    fn test_serial_main_test() {
+        let (commands, command_rx) = mpsc::channel();
+        let (_tx, data_rx) = mpsc::channel();
+        let (event_tx, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let handle = serial_main(command_rx, data_rx, event_tx, stats, SharedRx::default());
+
+        // Nothing arrives on a loopback that nothing is sent to
+        let mut parameters = PortParameters::default().name(String::from(LOOPBACK_PORT));
+        parameters.timeout_ms = Some(5);
+        commands.send(SerialCommand::Test(parameters.clone())).unwrap();
+        assert_eq!(wait_for_event(&mut events), Some(SerialEvent::Tested(Ok(0))));
+
+        // A port that does not open is reported, and the thread carries on
+        parameters.name = Some(String::from("test/missing-port"));
+        parameters.baud_rate = Some(9600);
+        parameters.data_bits = Some(8);
+        parameters.stop_bits = Some(1);
+        parameters.parity = Some(Parity::None);
+        parameters.flow_control = Some(FlowControl::None);
+        commands.send(SerialCommand::Test(parameters)).unwrap();
+        let failed = matches!(wait_for_event(&mut events), Some(SerialEvent::Tested(Err(_))));
+        assert!(failed);
+
+        commands.send(SerialCommand::Shutdown).unwrap();
+        assert!(wait_for_thread(&handle, SHUTDOWN_TIMEOUT));
+    }
+
+    #[test]
+    fn test_serial_main_open_latency() {
+        let (commands, command_rx) = mpsc::channel();
+        let (_tx, data_rx) = mpsc::channel();
+        let (event_tx, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let handle = serial_main(command_rx, data_rx, event_tx, stats, SharedRx::default());
+
+        // The thread is left idle first, a request must wake it immediately,
+        // well within the 500ms it used to poll at
+        thread::sleep(Duration::from_millis(50));
+        let mut parameters = PortParameters::default().name(String::from(LOOPBACK_PORT));
+        parameters.mode = Some(Mode::Ascii);
+        parameters.timeout_ms = Some(5);
+        let start = Instant::now();
+        commands.send(SerialCommand::Open(parameters)).unwrap();
+        let connected = SerialEvent::Status(ConnectionStatus::Connected);
+        while events.try_recv() != Ok(connected.clone()) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::yield_now();
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        commands.send(SerialCommand::Shutdown).unwrap();
+        assert!(wait_for_thread(&handle, SHUTDOWN_TIMEOUT));
+    }
+
+    #[test]
+    fn test_serial_main_stale_data() {
+        let (commands, command_rx) = mpsc::channel();
+        let (tx, data_rx) = mpsc::channel();
+        let (event_tx, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let rx = SharedRx::default();
+        let handle = serial_main(command_rx, data_rx, event_tx, stats.clone(), rx.clone());
+
+        // Left behind by a previous session that was never drained
+        rx.lock().unwrap().push(Origin::Primary, b"stale");
+        tx.send(b"stale".to_vec()).unwrap();
+
+        let mut parameters = PortParameters::default().name(String::from(LOOPBACK_PORT));
+        parameters.mode = Some(Mode::Ascii);
+        parameters.timeout_ms = Some(5);
+        commands.send(SerialCommand::Open(parameters.clone())).unwrap();
+        let connected = SerialEvent::Status(ConnectionStatus::Connected);
+        assert_eq!(wait_for_event(&mut events), Some(connected));
+        assert_eq!(rx.lock().unwrap().len(), 0);
+
+        let mut scene = terminal_scene(parameters);
+        let mut state = State::Running;
+        handle_serial(&mut scene, &mut state, SerialEvent::RxReady, &rx);
+        assert_eq!(scene.notice, None);
+        assert_eq!(get_stats(&stats).tx_bytes, 0);
+
+        commands.send(SerialCommand::Shutdown).unwrap();
+        assert!(wait_for_thread(&handle, SHUTDOWN_TIMEOUT));
+        assert_eq!(get_stats(&stats).rx_bytes, 0);
+    }
+
+    #[test]
+    fn test_handle_serial_failed() {
+        let mut state = State::Running;
+        let (commands, receiver) = mpsc::channel();
+        let rx = SharedRx::default();
+
+        let parameters = PortParameters::default().name(String::from("test/port"));
+        let mut scene = terminal_scene(parameters);
+
+        let reason = String::from("No such file or directory");
+        let event = SerialEvent::Failed(SerialErrorKind::OpenFailed { reason });
+        handle_serial(&mut scene, &mut state, event, &rx);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert_eq!(state, State::Running);
+
+        // Acknowledging the failure returns to the menu with the parameters
+        update(&mut scene, &mut state, Message::Enter, &commands);
+        assert_eq!(scene.screen, Screen::Menu);
+        assert_eq!(scene.menu.as_ref().unwrap().inputs[0].value, "test/port");
+        assert_eq!(state, State::Running);
+        assert_eq!(receiver.try_recv(), Ok(SerialCommand::Close));
+    }
+
+    #[test]
+    fn test_typed_brackets() {
+        let mut state = State::Running;
+        let (commands, _receiver) = mpsc::channel();
+        let mut scene = Scene::default();
+
+        // Every printable character reaches the field, the brackets included
+        for c in "/dev/tty.usbserial-p[1]r".chars() {
+            let msg = get_message(&mut scene, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            update(&mut scene, &mut state, msg.unwrap(), &commands);
+        }
+        assert_eq!(scene.menu.as_ref().unwrap().inputs[0].value, "/dev/tty.usbserial-p[1]r");
+
+        let mut scene = terminal_scene(PortParameters::default());
+        let msg = get_message(&mut scene, KeyEvent::new(KeyCode::Char('['), KeyModifiers::NONE));
+        assert_eq!(msg, Some(Message::Input('[')));
+        let msg = get_message(&mut scene, KeyEvent::new(KeyCode::Char('p'), KeyModifiers::SHIFT));
+        assert_eq!(msg, Some(Message::Input('p')));
+    }
+
+    #[test]
+    fn test_remember() {
+        let mut state = State::Running;
+        let (commands, receiver) = mpsc::channel();
+        let path = env::temp_dir().join(format!("nolp-remember-{}", std::process::id())).join("last.toml");
+        let mut scene = Scene {
+            settings: Some(path.clone()),
+            ..Default::default()
+        };
+
+        // Starting from the menu remembers the connection, which the menu
+        // is opened with next time
+        scene.menu.as_mut().unwrap().inputs[0].value = String::from("loopback:");
+        update(&mut scene, &mut state, Message::Enter, &commands);
+        update(&mut scene, &mut state, Message::Enter, &commands);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert!(matches!(receiver.try_recv(), Ok(SerialCommand::Open(_))));
+        assert_eq!(get_menu(&scene, None).inputs[0].value, "loopback:");
+        assert_eq!(get_menu(&scene, None).inputs[1].value, "9600");
+
+        // A corrupt file is ignored
+        std::fs::write(&path, "port = loopback:").unwrap();
+        assert_eq!(get_menu(&scene, None).inputs[0].value, "");
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_recent() {
+        let mut state = State::Running;
+        let (commands, receiver) = mpsc::channel();
+        let path = env::temp_dir().join(format!("nolp-recent-{}", std::process::id())).join("last.toml");
+        let mut scene = Scene {
+            settings: Some(path.clone()),
+            ..Default::default()
+        };
+        let mut start = |scene: &mut Scene, port: &str, baud_rate: &str| {
+            switch_screen(Screen::Menu, scene, None, &commands);
+            let menu = scene.menu.as_mut().unwrap();
+            menu.inputs[0].value = String::from(port);
+            menu.inputs[1].value = String::from(baud_rate);
+            update(scene, &mut state, Message::Enter, &commands);
+            update(scene, &mut state, Message::Enter, &commands);
+        };
+
+        // Each start is listed first, once per port, up to the limit
+        start(&mut scene, "loopback:", "9600");
+        start(&mut scene, "tcp://localhost:4000", "9600");
+        start(&mut scene, "loopback:", "115200");
+        let recent = get_recent(&scene);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].parameters.baud_rate, Some(115200));
+        assert_eq!(recent[1].parameters.name, Some(String::from("tcp://localhost:4000")));
+        assert_eq!(recent[0].time.len(), 24);
+        for i in 0..RECENT_LIMIT {
+            start(&mut scene, &format!("tcp://localhost:{}", 5000 + i), "9600");
+        }
+        assert_eq!(get_recent(&scene).len(), RECENT_LIMIT);
+
+        // Chosen from the device list, one is connected to straight away in
+        // place of the session
+        switch_screen(Screen::DeviceList, &mut scene, None, &commands);
+        while receiver.try_recv().is_ok() {}
+        update(&mut scene, &mut state, Message::NextElement, &commands);
+        update(&mut scene, &mut state, Message::Enter, &commands);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert_eq!(receiver.try_recv(), Ok(SerialCommand::Close));
+        match receiver.try_recv() {
+            Ok(SerialCommand::Open(parameters)) => assert_eq!(parameters.name, Some(String::from("tcp://localhost:5008"))),
+            command => panic!("unexpected command {:?}", command),
+        }
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_send_output() {
+        let mut scene = terminal_scene(PortParameters::default());
+        let (commands, command_rx) = mpsc::channel();
+        let (tx, data_rx) = mpsc::channel();
+
+        let terminal = scene.terminal.as_mut().unwrap();
+        terminal.update(Message::Connection(ConnectionStatus::Connected));
+        terminal.update(Message::ToggleDtr);
+        terminal.update(Message::ToggleRts);
+        send_output(&mut scene, &tx, &commands);
+
+        assert_eq!(command_rx.try_recv(), Ok(SerialCommand::SetDtr(false)));
+        assert_eq!(command_rx.try_recv(), Ok(SerialCommand::SetRts(false)));
+        assert!(scene.terminal.as_ref().unwrap().get_commands().is_empty());
+        assert!(data_rx.try_recv().is_err());
+        assert!(command_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_toggle_capture() {
+        let path = std::env::temp_dir().join(format!("nolp-capture-{}.txt", std::process::id()));
+        let parameters = PortParameters {
+            mode: Some(Mode::Ascii),
+            capture_path: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let mut scene = terminal_scene(parameters);
+        let mut state = State::Running;
+        let (commands, _command_rx) = mpsc::channel();
+        let (tx, _data_rx) = mpsc::channel();
+        let rx = SharedRx::new(Mutex::new(RxBuffer::new(64)));
+        let model = scene.terminal.as_mut().unwrap();
+        model.update(Message::Connection(ConnectionStatus::Connected));
+
+        // Only what passes while capturing is written
+        model.update(Message::Input('a'));
+        model.update(Message::Enter);
+        send_output(&mut scene, &tx, &commands);
+        toggle_capture(&mut scene);
+        assert!(scene.capture.is_some());
+
+        let model = scene.terminal.as_mut().unwrap();
+        model.update(Message::Input('b'));
+        model.update(Message::Enter);
+        send_output(&mut scene, &tx, &commands);
+        rx.lock().unwrap().push(Origin::Primary, b"ok\r\n");
+        receive(&mut scene, &mut state, &rx);
+
+        // Leaving the terminal stops the capture
+        switch_screen(Screen::Menu, &mut scene, None, &commands);
+        assert!(scene.capture.is_none());
+        let notice = format!(" Capture saved to {} ", path.to_string_lossy());
+        assert_eq!(scene.notice, Some(notice));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "b\nok\n");
+        std::fs::remove_file(&path).unwrap();
+
+        // Nothing is captured outside of the terminal
+        toggle_capture(&mut scene);
+        assert!(scene.capture.is_none());
+    }
+
+    #[test]
+    fn test_get_frame_border() {
+        let title = |summary: Option<&str>, width: u16| -> String {
+            let mut terminal = Terminal::new(TestBackend::new(width, 3)).unwrap();
+            let block = get_frame_border(summary.map(String::from), width);
+            terminal.draw(|frame| frame.render_widget(block, frame.size())).unwrap();
+            let buffer = terminal.backend().buffer();
+            return buffer.content[..usize::from(width)].iter().map(|cell| cell.symbol()).collect();
+        };
+        assert!(title(None, 40).contains(" NOLP "));
+        let summary = Some("/dev/ttyUSB0 115200 8N1 [Hex]");
+        assert!(title(summary, 60).contains(" NOLP — /dev/ttyUSB0 115200 8N1 [Hex] "));
+
+        // Cut short on a narrow window, keeping the ends of the border
+        let narrow = title(summary, 30);
+        assert!(narrow.contains(" NOLP — /dev/ttyUSB0 11520… "));
+        assert!(narrow.ends_with("╮"));
+    }
+
+    #[test]
+    fn test_render_screen_small() {
+        let parameters = PortParameters {
+            name: Some(String::from("/dev/ttyUSB0")),
+            mode: Some(Mode::Ascii),
+            ..Default::default()
+        };
+        let mut terminal_model = TerminalModel::new(parameters);
+        terminal_model.update(Message::Connection(ConnectionStatus::Connected));
+        terminal_model.update(Message::Rx(b"hello world\r\n".repeat(20)));
+        let text = |terminal: &Terminal<TestBackend>| -> String {
+            terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect()
+        };
+
+        // None of the screens is laid out in a window this small
+        let notice = Some(String::from(" Notice "));
+        for (width, height) in (1..=10).flat_map(|w| (1..=5).map(move |h| (w, h))) {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            render_screen(&mut terminal, &mut MenuModel::default(), &notice, true, None);
+            render_screen(&mut terminal, &mut DeviceListModel::default(), &notice, true, None);
+            render_screen(&mut terminal, &mut HelpModel::new(Screen::Menu, None), &notice, true, None);
+            render_screen(&mut terminal, &mut terminal_model, &notice, true, None);
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(MIN_WIDTH - 1, MIN_HEIGHT)).unwrap();
+        render_screen(&mut terminal, &mut terminal_model, &None, false, None);
+        assert!(text(&terminal).contains("Window too small"));
+        let mut terminal = Terminal::new(TestBackend::new(MIN_WIDTH, MIN_HEIGHT)).unwrap();
+        render_screen(&mut terminal, &mut terminal_model, &None, false, None);
+        assert!(text(&terminal).contains("hello world"));
+    }
+
+    #[test]
+    fn test_get_arguments() {
+        let args = |args: &[&str]| get_arguments(&args.iter().map(|a| a.to_string()).collect::<Vec<String>>());
+        let value = |key: &str, value: &str| (String::from(key), String::from(value));
+
+        let arguments = args(&["--port", "/dev/ttyUSB0", "--baud=115200", "--no-persist", "--parity", "none"]);
+        let expected = Arguments {
+            no_persist: true,
+            values: vec![value("port", "/dev/ttyUSB0"), value("baudrate", "115200"), value("parity", "none")],
+        };
+        assert_eq!(arguments, Ok(Some(expected)));
+        assert_eq!(args(&[]), Ok(Some(Arguments::default())));
+        assert_eq!(args(&["--mode", "hex", "--help"]), Ok(None));
+
+        // Anything else is said rather than ignored
+        assert_eq!(args(&["--speed", "9600"]), Err(String::from("unknown argument '--speed'")));
+        assert_eq!(args(&["--port"]), Err(String::from("--port needs a value")));
+        assert_eq!(args(&["--baud="]), Err(String::from("--baud needs a value")));
+        assert_eq!(args(&["--mode", "hex", "--mode=ascii"]), Err(String::from("--mode is given more than once")));
+    }
+
+    #[test]
+    fn test_usage_values() {
+        // Every value the usage lists is one the menu takes, where a range
+        // (e.g., 5-8) stands for each value in it
+        let mut checked = 0;
+        for (flag, key) in PORT_ARGS {
+            let listed = USAGE.split(&format!("[{} ", flag)).nth(1).and_then(|rest| rest.split(']').next());
+            let listed = listed.unwrap_or_else(|| panic!("{} is not in the usage", flag));
+            if listed.chars().any(|c| c.is_ascii_uppercase()) {
+                continue;
+            }
+            let values: Vec<String> = match listed.split_once('-') {
+                Some((low, high)) => {
+                    let range = low.parse::<u8>().unwrap()..=high.parse().unwrap();
+                    range.map(|v| v.to_string()).collect()
+                }
+                None => listed.split('|').map(String::from).collect(),
+            };
+            for value in values {
+                assert_eq!(MenuModel::default().set_value(key, &value), Ok(()), "--{} {}", flag, value);
+                checked += 1;
+            }
+        }
+        assert_eq!(checked, 14);
+    }
+
+    #[test]
+    fn test_get_start() {
+        let value = |key: &str, value: &str| (String::from(key), String::from(value));
+
+        // A port connects straight away, with the rest as the menu takes it
+        let values = [value("port", LOOPBACK_PORT), value("baudrate", "115200"), value("mode", "HEX")];
+        let parameters = match get_start(&values) {
+            Ok(Some(Start::Terminal(parameters))) => parameters,
+            start => panic!("{:?}", start),
+        };
+        assert_eq!(parameters.name, Some(String::from(LOOPBACK_PORT)));
+        assert_eq!((parameters.baud_rate, parameters.mode), (Some(115200), Some(Mode::Hex)));
+        assert_eq!(get_start(&[]), Ok(None));
+
+        // Without one, the menu is filled in with what was given
+        match get_start(&[value("data_bits", "7"), value("parity", "even")]) {
+            Ok(Some(Start::Menu(menu))) => {
+                assert_eq!(menu.inputs[2].value, "7");
+                assert_eq!(menu.inputs[4].value, "Even");
+                assert_eq!(menu.inputs[0].value, "");
+            }
+            start => panic!("{:?}", start),
+        }
+
+        // And anything the menu would not take is an error
+        let error = |values: &[(String, String)]| get_start(values).unwrap_err();
+        assert_eq!(error(&[value("data_bits", "9")]), "Data bits must be one of 5, 6, 7, 8");
+        assert_eq!(error(&[value("baudrate", "0")]), "Baudrate must be 1 - 4000000");
+        assert_eq!(error(&[value("baudrate", "fast")]), "Invalid baudrate 'fast'");
+        let missing = error(&[value("port", "/dev/nolp-missing")]);
+        assert_eq!(missing, "Failed to open port: No such file or directory");
+    }
+
+    #[test]
+    fn test_start_with() {
+        let (commands, receiver) = mpsc::channel();
+        let mut scene = Scene::default();
+        let mut parameters = PortParameters::default().name(String::from(LOOPBACK_PORT));
+        parameters.mode = Some(Mode::Ascii);
+
+        // Past the menu, the port is opened before the first key
+        start_with(&mut scene, Some(Start::Terminal(Box::new(parameters.clone()))), &commands);
+        assert_eq!(scene.screen, Screen::Terminal);
+        assert!(scene.menu.is_none());
+        assert_eq!(receiver.try_recv(), Ok(SerialCommand::Open(parameters)));
+
+        let mut scene = Scene::default();
+        let mut menu = MenuModel::default();
+        menu.set_value("port", "COM4").unwrap();
+        start_with(&mut scene, Some(Start::Menu(Box::new(menu))), &commands);
+        assert_eq!(scene.screen, Screen::Menu);
+        assert_eq!(scene.menu.as_ref().unwrap().inputs[0].value, "COM4");
+        assert!(receiver.try_recv().is_err());
     }
 }
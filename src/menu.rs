@@ -14,8 +14,11 @@ use ratatui::{
     widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
 };
 use std::rc::Rc;
+use std::str::FromStr;
 
 use crate::common::*;
+use crate::serial::{get_available_devices, probe_port};
+use crate::tcp::get_address;
 
 /******************************************************************************/
 /*******************************************************************************
@@ -29,13 +32,26 @@ struct MenuSpans<'a> {
     underline: Vec<Span<'a>>,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputKind {
+    Text,
+    // Only one of the options, cycled through rather than typed
+    Choice(Vec<String>),
+    // The options are cycled through, but any number can be typed
+    Preset(Vec<String>),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MenuInput {
-    limit: usize,
-    title: String,
-    invalid: bool,
+    pub limit: usize,
+    pub title: String,
+    pub invalid: bool,
     pub value: String,
+    pub kind: InputKind,
     placeholder: String,
+    // Where the next character is typed, None while at the end (as after
+    // the value is replaced)
+    cursor: Option<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -48,6 +64,20 @@ pub struct MenuModel {
     min_width: usize,
     min_height: usize,
     scroll: ScrollbarState,
+    // The ports to complete the port from, as of when the menu was opened
+    // (or refreshed)
+    devices: Vec<String>,
+    // What was typed before tab, and which of its matches is shown
+    completion: Option<(String, usize)>,
+    // Whether Start opens the port once before switching to the terminal,
+    // which is left off where no device is to be touched (e.g., tests)
+    pub probe: bool,
+    // The test asked for, until it is handed to the serial thread, and how
+    // it went (shown under the buttons)
+    test: Option<PortParameters>,
+    test_status: Option<TestStatus>,
+    // The port to copy, until it is handed to the clipboard
+    copy: Option<String>,
     pub inputs: Vec<MenuInput>,
 }
 
@@ -62,10 +92,10 @@ enum SelectElement {
     Next,
 }
 
-#[derive(Debug, PartialEq)]
-enum UpdateElement {
-    Sub,
-    Add(char),
+#[derive(Clone, Debug, PartialEq)]
+enum TestStatus {
+    Running,
+    Done(Result<usize, SerialErrorKind>),
 }
 
 /******************************************************************************/
@@ -73,10 +103,20 @@ enum UpdateElement {
 * Local Constants
 *******************************************************************************/
 /******************************************************************************/
-const CONTENT_LENGTH: usize = 20;
+const BAUD_RATES: [&str; 11] = [
+    "1200", "2400", "4800", "9600", "19200", "38400", "57600", "115200", "230400", "460800", "921600",
+];
 const INPUT_WIDTH: usize = 18;
+// Cancel, Test, and Start, which follow the inputs in that order
+const BUTTON_COUNT: usize = 3;
 const MARGIN_TOP: usize = 2;
 const GAP_WIDTH: usize = 10;
+// Beyond what common USB adapters reach
+const MAX_BAUD_RATE: u64 = 4_000_000;
+// What a blank field is taken as, as its placeholder shows
+const DEFAULT_BAUD_RATE: u32 = 9600;
+const DEFAULT_DATA_BITS: u8 = 8;
+const DEFAULT_STOP_BITS: u8 = 1;
 
 /******************************************************************************/
 /*******************************************************************************
@@ -90,74 +130,44 @@ impl Default for MenuInput {
             invalid: false,
             title: String::from(""),
             value: String::from(""),
+            kind: InputKind::Text,
             placeholder: String::from(""),
+            cursor: None,
         }
     }
 }
 
 impl MenuInput {
-    fn title(mut self, s: String) -> Self {
+    pub fn title(mut self, s: String) -> Self {
         self.title = s;
         return self;
     }
 
-    fn placeholder(mut self, s: String) -> Self {
+    pub fn placeholder(mut self, s: String) -> Self {
         self.placeholder = s;
         return self;
     }
 
-    fn limit(mut self, l: usize) -> Self {
+    pub fn limit(mut self, l: usize) -> Self {
         self.limit = l;
         return self;
     }
+
+    pub fn choices(mut self, options: &[&str]) -> Self {
+        self.kind = InputKind::Choice(options.iter().map(|o| String::from(*o)).collect());
+        return self;
+    }
+
+    pub fn presets(mut self, options: &[&str]) -> Self {
+        self.kind = InputKind::Preset(options.iter().map(|o| String::from(*o)).collect());
+        return self;
+    }
 }
 
 impl Default for MenuModel {
     fn default() -> MenuModel {
-        let mut inputs = Vec::new();
-        inputs.push(
-            MenuInput::default()
-                .title(String::from("Port"))
-                .placeholder(String::from("COM4")),
-        );
-
-        inputs.push(
-            MenuInput::default()
-                .limit(10)
-                .title(String::from("Baudrate"))
-                .placeholder(String::from("9600")),
-        );
-
-        inputs.push(
-            MenuInput::default()
-                .limit(1)
-                .title(String::from("Data bits"))
-                .placeholder(String::from("8")),
-        );
-
-        inputs.push(
-            MenuInput::default()
-                .limit(1)
-                .title(String::from("Stop bits"))
-                .placeholder(String::from("1")),
-        );
-
-        inputs.push(
-            MenuInput::default()
-                .limit(4)
-                .title(String::from("Parity"))
-                .placeholder(String::from("Even")),
-        );
-
-        inputs.push(
-            MenuInput::default()
-                .limit(7)
-                .title(String::from("Mode"))
-                .placeholder(String::from("Ascii")),
-        );
-
         MenuModel {
-            scroll: ScrollbarState::default().content_length(CONTENT_LENGTH),
+            scroll: ScrollbarState::default(),
             bounds: Rect::default(),
             state: State::Running,
             min_height: 0,
@@ -165,7 +175,13 @@ impl Default for MenuModel {
             split: true,
             selected: 0,
             offset: 0,
-            inputs,
+            devices: Vec::new(),
+            completion: None,
+            probe: false,
+            test: None,
+            test_status: None,
+            copy: None,
+            inputs: get_inputs(),
         }
     }
 }
@@ -194,8 +210,174 @@ impl MenuModel {
             Some(m) => m.to_string(),
             None => String::from(""),
         };
+        model.inputs[6].value = match parameters.flow_control {
+            Some(f) => f.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[7].value = match parameters.dtr_on_open {
+            Some(true) => String::from("Yes"),
+            Some(false) => String::from("No"),
+            None => String::from(""),
+        };
+        if parameters.rs485 {
+            model.inputs[8].value = String::from("Yes");
+        }
+        if parameters.rs485_discard_echo {
+            model.inputs[9].value = String::from("Yes");
+        }
+        model.inputs[10].value = match parameters.timeout_ms {
+            Some(t) => t.to_string(),
+            None => String::from(""),
+        };
+        if parameters.read_only {
+            model.inputs[11].value = String::from("Yes");
+        }
+        model.inputs[12].value = parameters.bridge.unwrap_or(String::from(""));
+        if parameters.reset_on_connect {
+            model.inputs[13].value = String::from("Yes");
+        }
+        model.inputs[14].value = match parameters.char_delay_ms {
+            Some(d) => d.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[15].value = match parameters.line_delay_ms {
+            Some(d) => d.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[16].value = match parameters.scrollback {
+            Some(s) => s.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[17].value = match parameters.line_ending {
+            Some(l) => l.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[18].value = match parameters.input_limit {
+            Some(l) => l.to_string(),
+            None => String::from(""),
+        };
+        if parameters.paste_lines {
+            model.inputs[19].value = String::from("Yes");
+        }
+        model.inputs[20].value = match parameters.ascii_view {
+            Some(v) => v.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[21].value = parameters.capture_path.unwrap_or(String::from(""));
+        model.inputs[22].value = match parameters.capture_format {
+            Some(f) => f.to_string(),
+            None => String::from(""),
+        };
+        if parameters.trigger_bell {
+            model.inputs[23].value = String::from("Yes");
+        }
+        model.inputs[24].value = parameters.response_rules.unwrap_or(String::from(""));
+        model.inputs[25].value = match parameters.gap_markers {
+            Some(g) => g.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[26].value = match parameters.gap_ms {
+            Some(g) => g.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[27].value = match parameters.row_length {
+            Some(l) if l > 0 => l.to_string(),
+            _ => String::from(""),
+        };
+        model.inputs[28].value = match parameters.nmea {
+            Some(n) => n.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[29].value = match parameters.framing {
+            Some(Framing::Off) | None => String::from(""),
+            Some(f) => f.to_string(),
+        };
+        if parameters.at_commands {
+            model.inputs[30].value = String::from("Yes");
+        }
+        model.inputs[31].value = match parameters.at_timeout_ms {
+            Some(t) => t.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[32].value = match parameters.hex_line_ms {
+            Some(d) => d.to_string(),
+            None => String::from(""),
+        };
+        model.inputs[33].value = match parameters.hex_prompt {
+            Some(p) => (p as char).to_string(),
+            None => String::from(""),
+        };
+        if parameters.echo == false {
+            model.inputs[34].value = String::from("No");
+        }
+        return model;
+    }
+
+    // The fields of a remembered connection, where a key no longer in the
+    // menu is skipped
+    pub fn restore(values: &[(String, String)]) -> MenuModel {
+        let mut model = MenuModel::default();
+        set_inputs(&mut model.inputs, values);
         return model;
     }
+
+    pub fn take_test(&mut self) -> Option<PortParameters> {
+        return self.test.take();
+    }
+
+    pub fn take_copy(&mut self) -> Option<String> {
+        return self.copy.take();
+    }
+
+    // What Start would connect with, without the checks of the port, or why
+    // the values would not do
+    pub fn check_values(&mut self) -> Result<PortParameters, String> {
+        return get_port_parameters(&mut self.inputs);
+    }
+
+    // What Start would connect with, or why it would not, where the menu is
+    // left in error as it would be
+    pub fn check_parameters(&mut self) -> Result<PortParameters, String> {
+        let error = |model: &MenuModel| match model.get_state() {
+            State::Error(e) => e,
+            _ => String::new(),
+        };
+        let parameters = get_checked_parameters(self).ok_or_else(|| error(self))?;
+        if let Some(e) = get_port_error(self, &parameters) {
+            self.inputs[0].invalid = true;
+            self.set_state(State::Error(e.clone()));
+            return Err(e);
+        }
+        return Ok(parameters);
+    }
+
+    // Fills in a field by its key (as in the settings) as if it was typed,
+    // where a choice is taken whatever its case
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let input = self.inputs.iter_mut().find(|i| get_key(&i.title) == key);
+        let input = input.ok_or(format!(" Unknown field '{}' ", key))?;
+        input.value = match &input.kind {
+            InputKind::Choice(options) => match options.iter().find(|o| o.eq_ignore_ascii_case(value)) {
+                Some(option) => option.clone(),
+                None => return Err(format!(" {} must be one of {} ", input.title, options.join(", "))),
+            },
+            _ => String::from(value),
+        };
+        if input.title == "Baudrate" {
+            if value.parse::<u64>().is_err() {
+                return Err(format!(" Invalid baudrate '{}' ", value));
+            }
+            if let Some(e) = get_baud_rate_error(value) {
+                return Err(e);
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn get_values(&self) -> Vec<(String, String)> {
+        let filled = self.inputs.iter().filter(|i| i.value.len() > 0);
+        return filled.map(|i| (get_key(&i.title), i.value.clone())).collect();
+    }
 }
 
 impl Nolp for MenuModel {
@@ -210,6 +392,11 @@ impl Nolp for MenuModel {
 
 impl Tea for MenuModel {
     fn update(&mut self, msg: Message) -> State {
+        // Tab keeps cycling the matches of what was typed, until anything
+        // else is done
+        if msg != Message::Complete {
+            self.completion = None;
+        }
         match msg {
             Message::PreviousElement => {
                 select_element(self, SelectElement::Previous);
@@ -217,13 +404,50 @@ impl Tea for MenuModel {
             Message::NextElement => {
                 select_element(self, SelectElement::Next);
             }
+            Message::CursorLeft => {
+                cycle_element(self, SelectElement::Previous);
+                update_element(self, Edit::Left);
+            }
+            Message::CursorRight => {
+                // The suggestion is only taken from the end of the port
+                if accept_suggestion(self) {
+                    return self.get_state();
+                }
+                cycle_element(self, SelectElement::Next);
+                update_element(self, Edit::Right);
+            }
+            Message::CursorHome => update_element(self, Edit::Home),
+            Message::CursorEnd => update_element(self, Edit::End),
+            Message::Complete => {
+                // Tab moves on once there is no port to complete
+                if complete_element(self) {
+                    return self.get_state();
+                }
+                select_element(self, SelectElement::Next);
+            }
+            Message::RefreshDevices => {
+                self.devices = get_available_devices().unwrap_or_default();
+            }
+            // Whichever field is selected, the port is what is worth copying
+            Message::Copy => {
+                self.copy = Some(String::from(self.inputs[0].value.trim()));
+            }
+            Message::Tested(result) if self.test_status == Some(TestStatus::Running) => {
+                self.test_status = Some(TestStatus::Done(result));
+            }
             Message::Input(input) => {
-                update_element(self, UpdateElement::Add(input));
+                update_element(self, Edit::Insert(input));
             }
-            Message::Backspace => {
-                update_element(self, UpdateElement::Sub);
+            Message::Paste(text) => {
+                // Each character is validated as if it was typed
+                for input in text.chars().filter(|c| c.is_control() == false) {
+                    update_element(self, Edit::Insert(input));
+                }
             }
+            Message::Backspace => update_element(self, Edit::Backspace),
+            Message::Delete => update_element(self, Edit::Delete),
             Message::Enter => update_state(self),
+            Message::Cancel => cancel_menu(self),
             Message::Quit => self.set_state(State::Stopping),
             _ => {}
         }
@@ -235,6 +459,8 @@ impl Tea for MenuModel {
         self.bounds = layout[2];
 
         update_split(self, bounds);
+        // Kept in view as the menu is resized (e.g., from split to not)
+        follow_selection(self);
 
         render_title(frame, layout[0]);
         render_menu(frame, layout[2], self);
@@ -247,26 +473,58 @@ impl Tea for MenuModel {
 * Utility Functions
 *******************************************************************************/
 /******************************************************************************/
+fn get_baud_rate_error(value: &str) -> Option<String> {
+    // Blank is left to the port (or not needed by it)
+    match value.parse::<u64>() {
+        Ok(b) if b == 0 || b > MAX_BAUD_RATE => {
+            return Some(format!(" Baudrate must be 1 - {} ", MAX_BAUD_RATE));
+        }
+        _ => return None,
+    }
+}
+
 fn get_button_elements<'a>(model: &mut MenuModel, selected_style: Style) -> Vec<Line<'a>> {
     let mut buttons = Vec::new();
 
     let mut cancel = Span::from("Cancel");
+    let mut test = Span::from("Test");
     let mut start = Span::from("Start");
 
     if model.selected == model.inputs.len() {
         cancel.patch_style(selected_style);
     } else if model.selected == model.inputs.len() + 1 {
+        test.patch_style(selected_style);
+    } else if model.selected == model.inputs.len() + 2 {
         start.patch_style(selected_style);
     }
 
     if model.split {
-        let gap_span = Span::from(format!("{}", " ".repeat(GAP_WIDTH)));
-        buttons.push(Line::from(vec![cancel, gap_span, start]));
+        let gap_span = Span::from(" ".repeat(GAP_WIDTH));
+        buttons.push(Line::from(vec![cancel, gap_span.clone(), test, gap_span, start]));
     } else {
         buttons.push(Line::from(cancel));
+        buttons.push(Line::from(test));
         buttons.push(Line::from(start));
     }
 
+    // How the last test went, until another is started
+    let port = model.inputs[0].value.clone();
+    match &model.test_status {
+        Some(TestStatus::Running) => {
+            let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+            buttons.push(Line::styled(format!("Testing {}...", port), style));
+        }
+        Some(TestStatus::Done(Ok(received))) => {
+            let plural = if *received == 1 { "" } else { "s" };
+            buttons.push(Line::from(format!("Opened OK, {} byte{} received", received, plural)));
+        }
+        Some(TestStatus::Done(Err(e))) => {
+            let style = Style::default().fg(crate::INVALID_COLOR);
+            buttons.push(Line::styled(e.to_string().trim().to_string(), style));
+        }
+        None => {}
+    }
+
     return buttons;
 }
 
@@ -278,13 +536,15 @@ fn get_input_elements<'a>(model: &'a mut MenuModel, selected_style: Style) -> Ve
 
     let mut i = 0_usize;
     while i < model.inputs.len() {
-        let increment: usize;
-        let mut spans = get_input_spans(&model.inputs[i], underline_fmt.clone());
+        let value = get_value_spans(model, i);
+        let mut spans = get_input_spans(&model.inputs[i], value, underline_fmt.clone());
 
-        match model.split {
+        let increment = match model.split && i + 1 < model.inputs.len() {
             true => {
+                let value = get_value_spans(model, i + 1);
                 update_spans_split(
                     &model.inputs[i + 1],
+                    value,
                     &mut spans,
                     &underline_fmt,
                     gap_fmt.clone(),
@@ -298,7 +558,7 @@ fn get_input_elements<'a>(model: &'a mut MenuModel, selected_style: Style) -> Ve
                     spans.underline[1].patch_style(selected_style);
                 }
 
-                increment = 2;
+                2
             }
             false => {
                 if i == model.selected {
@@ -306,9 +566,9 @@ fn get_input_elements<'a>(model: &'a mut MenuModel, selected_style: Style) -> Ve
                     spans.underline[0].patch_style(selected_style);
                 }
 
-                increment = 1;
+                1
             }
-        }
+        };
 
         elements.push(Line::from(spans.title));
         elements.push(Line::from(spans.input));
@@ -320,18 +580,136 @@ fn get_input_elements<'a>(model: &'a mut MenuModel, selected_style: Style) -> Ve
     return elements;
 }
 
-fn get_input_spans<'a>(input: &'a MenuInput, underline: String) -> MenuSpans<'a> {
-    let (text, style) = get_input_text(&input);
+fn get_checked_parameters(model: &mut MenuModel) -> Option<PortParameters> {
+    // The menu is left in error when a value does not hold
+    if validate_values(model) == false {
+        let error = match get_baud_rate_error(&model.inputs[1].value) {
+            Some(error) => error,
+            None => String::from(" Invalid input (ctrl+h) for help "),
+        };
+        model.set_state(State::Error(error));
+        return None;
+    }
+    match get_port_parameters(&mut model.inputs) {
+        Ok(parameters) => return Some(parameters),
+        Err(e) => {
+            model.set_state(State::Error(e));
+            return None;
+        }
+    }
+}
+
+fn get_content_length(model: &MenuModel) -> usize {
+    // Each input is three lines (title, value, underline), two to a row when
+    // split, where the last is alone with an odd count. The buttons are a
+    // line of their own when split, and one each otherwise, followed by the
+    // result of a test
+    let tested = usize::from(model.test_status.is_some());
+    match model.split {
+        true => return model.inputs.len().div_ceil(2) * 3 + 1 + tested,
+        false => return model.inputs.len() * 3 + BUTTON_COUNT + tested,
+    }
+}
+
+fn get_element_line(model: &MenuModel, i: usize) -> usize {
+    // The first line of an input, or the line of a button
+    let count = model.inputs.len();
+    match (model.split, i < count) {
+        (true, true) => return (i / 2) * 3,
+        (true, false) => return count.div_ceil(2) * 3,
+        (false, true) => return i * 3,
+        (false, false) => return count * 3 + (i - count),
+    }
+}
+
+fn get_matches(model: &MenuModel, prefix: &str) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let matches = model.devices.iter().filter(|d| d.to_lowercase().starts_with(&prefix));
+    return matches.cloned().collect();
+}
+
+fn get_value_spans<'a>(model: &MenuModel, i: usize) -> Vec<Span<'a>> {
+    // The rest of the first port matching what was typed is suggested, and
+    // a port matching none is only warned of (e.g., a pseudo-port)
+    let input = &model.inputs[i];
+    let (text, mut style) = get_input_text(input);
+    let suggestion = match i == 0 {
+        true => get_suggestion(model).and_then(|d| d.get(input.value.len()..).map(String::from)),
+        false => None,
+    };
+    let suggestion = suggestion.unwrap_or_default();
+    if i == 0 {
+        let known = model.devices.iter().any(|d| d.eq_ignore_ascii_case(&input.value));
+        let pseudo = [TCP_PREFIX, REPLAY_PREFIX].iter().any(|p| input.value.starts_with(p));
+        if input.value.len() > 0 && known == false && pseudo == false && suggestion.is_empty() {
+            style = style.fg(crate::WARNING_COLOR);
+        }
+    }
+    if i != model.selected || input.kind != InputKind::Text {
+        return vec![Span::styled(format!("{: <w$}", text, w = INPUT_WIDTH), style)];
+    }
+
+    // The selected field shows its cursor, which at the end sits on what
+    // would be taken (the suggestion, or the placeholder of a blank field)
+    let cursor = get_cursor(input);
+    let mut spans = get_cursor_spans(&input.value, cursor, INPUT_WIDTH, style);
+    let ghost = match input.value.len() > 0 {
+        true => suggestion,
+        false => input.placeholder.clone(),
+    };
+    let mut ghost = ghost.chars();
+    if let (true, Some(c)) = (cursor == input.value.chars().count(), ghost.next()) {
+        let dim = Style::default().fg(crate::PLACEHOLDER_COLOR);
+        let width = INPUT_WIDTH.saturating_sub(spans[0].width() + 1);
+        spans[1] = Span::styled(c.to_string(), dim.add_modifier(Modifier::REVERSED));
+        spans[2] = Span::styled(ghost.take(width).collect::<String>(), dim);
+    }
+    let width: usize = spans.iter().map(|s| s.width()).sum();
+    spans.push(Span::from(" ".repeat(INPUT_WIDTH.saturating_sub(width))));
+    return spans;
+}
+
+fn get_cursor(input: &MenuInput) -> usize {
+    let length = input.value.chars().count();
+    return input.cursor.unwrap_or(length).min(length);
+}
+
+fn get_port_error(model: &mut MenuModel, parameters: &PortParameters) -> Option<String> {
+    // A pseudo-port is left to the terminal, which reports it failing to
+    // open, as is a listed device, which would be reset twice by DTR where
+    // it was opened here too, so only a name not listed is opened to find
+    // out why (e.g., mistyped, or a path without the permission to open it),
+    // as the operating system tells it
+    let name = parameters.name.clone().unwrap_or_default();
+    let pseudo = [TCP_PREFIX, REPLAY_PREFIX, LOOPBACK_PORT].iter().any(|p| name.starts_with(p));
+    // A test still running has the port, and the terminal opens it after
+    let testing = model.test_status == Some(TestStatus::Running);
+    if model.probe == false || pseudo || testing {
+        return None;
+    }
+    model.devices = get_available_devices().unwrap_or_default();
+    if model.devices.contains(&name) {
+        return None;
+    }
+    return probe_port(parameters).err().map(|e| e.to_string());
+}
+
+fn get_suggestion(model: &MenuModel) -> Option<String> {
+    let value = &model.inputs[0].value;
+    if value.is_empty() || model.selected != 0 {
+        return None;
+    }
+    return get_matches(model, value).into_iter().find(|d| d.len() > value.len());
+}
+
+fn get_input_spans<'a>(input: &'a MenuInput, value: Vec<Span<'a>>, underline: String) -> MenuSpans<'a> {
     let mut span = MenuSpans {
         title: vec![Span::from(format!(
             "{: <w$}",
             input.title.to_string(),
             w = INPUT_WIDTH
         ))],
-        input: vec![Span::styled(
-            format!("{: <w$}", text, w = INPUT_WIDTH),
-            style,
-        )],
+        input: value,
         underline: vec![Span::from(underline)],
     };
 
@@ -344,7 +722,34 @@ fn get_input_spans<'a>(input: &'a MenuInput, underline: String) -> MenuSpans<'a>
     return span;
 }
 
-fn get_input_text(input: &MenuInput) -> (String, Style) {
+pub fn get_input_text(input: &MenuInput) -> (String, Style) {
+    // A choice is shown between the arrows that cycle it
+    if let InputKind::Choice(_) = input.kind {
+        return match input.value.len() > 0 {
+            true => (format!("◂ {} ▸", input.value), Style::default()),
+            false => (
+                format!("◂ {} ▸", input.placeholder),
+                Style::default().fg(crate::PLACEHOLDER_COLOR),
+            ),
+        };
+    }
+
+    // As is a preset, apart from a typed value
+    if let InputKind::Preset(options) = &input.kind {
+        let text = match input.value.len() > 0 {
+            true => &input.value,
+            false => &input.placeholder,
+        };
+        let style = match input.value.len() > 0 {
+            true => Style::default(),
+            false => Style::default().fg(crate::PLACEHOLDER_COLOR),
+        };
+        return match options.contains(text) {
+            true => (format!("◂ {} ▸", text), style),
+            false => (format!("{} (custom)", text), style),
+        };
+    }
+
     if input.value.len() > 0 {
         let text = &input.value.to_string();
         if input.value.len() >= INPUT_WIDTH {
@@ -362,6 +767,12 @@ fn get_input_text(input: &MenuInput) -> (String, Style) {
     }
 }
 
+fn get_key(title: &str) -> String {
+    // As in `data_bits` for Data bits
+    let title = title.to_lowercase();
+    return title.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+}
+
 fn get_layout(fsize: Rect) -> (Rect, Rc<[Rect]>) {
     let bounds = get_center_bounds(50, 50, fsize);
     let layout = Layout::default()
@@ -376,32 +787,406 @@ fn get_layout(fsize: Rect) -> (Rect, Rc<[Rect]>) {
     return (bounds, layout);
 }
 
-fn get_port_parameters(model: &MenuModel) -> PortParameters {
-    let baud_rate = model.inputs[1].value.parse::<u32>().unwrap();
-    let data_bits = model.inputs[2].value.parse::<u8>().unwrap();
-    let stop_bits = model.inputs[3].value.parse::<u8>().unwrap();
-    let parity = match model.inputs[4].value.to_lowercase().as_str() {
+// The values of a saved connection as Start would take them, without a menu
+// (i.e., nor its list of the ports) to show them in
+pub fn parse_values(values: &[(String, String)]) -> Option<PortParameters> {
+    let mut inputs = get_inputs();
+    set_inputs(&mut inputs, values);
+    return get_port_parameters(&mut inputs).ok();
+}
+
+// What a menu left blank but for the port would connect with (i.e., 9600
+// 8N1), without building one
+pub fn get_default_parameters(name: &str) -> PortParameters {
+    return PortParameters {
+        name: Some(String::from(name)),
+        baud_rate: Some(DEFAULT_BAUD_RATE),
+        data_bits: Some(DEFAULT_DATA_BITS),
+        stop_bits: Some(DEFAULT_STOP_BITS),
+        parity: Some(Parity::None),
+        mode: Some(Mode::Ascii),
+        flow_control: Some(FlowControl::None),
+        line_ending: Some(LineEnding::None),
+        ascii_view: Some(AsciiView::Text),
+        capture_format: Some(CaptureFormat::Text),
+        gap_markers: Some(GapMarkers::Hidden),
+        nmea: Some(NmeaDecode::Off),
+        framing: Some(Framing::Off),
+        ..Default::default()
+    };
+}
+
+fn get_port_parameters(inputs: &mut [MenuInput]) -> Result<PortParameters, String> {
+    // Not one of the choices, as the serial library cannot set it, but named
+    // rather than taken for a typo
+    if inputs[3].value == "1.5" {
+        inputs[3].invalid = true;
+        return Err(String::from(" 1.5 stop bits are not supported "));
+    }
+
+    // A choice could only hold anything else if it was opened with it
+    for i in 0..inputs.len() {
+        let input = &inputs[i];
+        if let InputKind::Choice(options) = &input.kind {
+            if input.value.len() > 0 && options.contains(&input.value) == false {
+                return Err(get_input_error(inputs, i));
+            }
+        }
+    }
+
+    // The framing left blank takes the defaults (which a network port or a
+    // replay ignores)
+    let baud_rate = get_number(inputs, 1)?.unwrap_or(DEFAULT_BAUD_RATE);
+    let data_bits = get_number(inputs, 2)?.unwrap_or(DEFAULT_DATA_BITS);
+    let stop_bits = get_number(inputs, 3)?.unwrap_or(DEFAULT_STOP_BITS);
+    let parity = match inputs[4].value.to_lowercase().as_str() {
         "even" => Parity::Even,
         "odd" => Parity::Odd,
-        "none" => Parity::None,
-        _ => unreachable!(),
+        _ => Parity::None,
     };
-    let mode = match model.inputs[5].value.to_lowercase().as_str() {
-        "ascii" => Mode::Ascii,
+    let mode = match inputs[5].value.to_lowercase().as_str() {
+        "" | "ascii" => Mode::Ascii,
         "hex" => Mode::Hex,
         "decimal" => Mode::Decimal,
         "octal" => Mode::Octal,
-        _ => unreachable!(),
+        "hexdump" => Mode::HexDump,
+        _ => return Err(get_input_error(inputs, 5)),
     };
-
-    return PortParameters {
-        name: Some(model.inputs[0].value.clone()),
+    // Flow control is optional, a blank value preserves the previous behavior
+    let flow_control = match inputs[6].value.to_lowercase().as_str() {
+        "hardware" => FlowControl::Hardware,
+        "software" => FlowControl::Software,
+        _ => FlowControl::None,
+    };
+    // A blank value leaves DTR to the operating system (i.e., asserted)
+    let dtr_on_open = match inputs[7].value.to_lowercase().as_str() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    };
+    let rs485 = inputs[8].value.to_lowercase() == "yes";
+    let rs485_discard_echo = inputs[9].value.to_lowercase() == "yes";
+    // A blank timeout uses the default of the serial interface
+    let timeout_ms = get_number::<u32>(inputs, 10)?;
+    let read_only = inputs[11].value.to_lowercase() == "yes";
+    // Filling in a second port sniffs the traffic between the two devices
+    let bridge = match inputs[12].value.is_empty() {
+        true => None,
+        false => Some(inputs[12].value.clone()),
+    };
+    // Pulses DTR once connected, to capture the target from its boot
+    let reset_on_connect = inputs[13].value.to_lowercase() == "yes";
+    // Blank delays (or zero) write the data as fast as the port allows
+    let char_delay_ms = get_number::<u32>(inputs, 14)?;
+    let line_delay_ms = get_number::<u32>(inputs, 15)?;
+    // A blank scrollback uses the default of the terminal
+    let scrollback = get_number::<usize>(inputs, 16)?;
+    // Nothing is appended to the input when blank
+    let line_ending = match inputs[17].value.to_lowercase().as_str() {
+        "cr" => LineEnding::Cr,
+        "lf" => LineEnding::Lf,
+        "crlf" => LineEnding::CrLf,
+        _ => LineEnding::None,
+    };
+    // A blank limit uses the default of the terminal
+    let input_limit = get_number::<usize>(inputs, 18)?;
+    // Otherwise the newlines of a paste are dropped, joining its lines
+    let paste_lines = inputs[19].value.to_lowercase() == "yes";
+    // Ascii is shown as lines of text unless set otherwise
+    let ascii_view = match inputs[20].value.to_lowercase().as_str() {
+        "ansi" => AsciiView::Ansi,
+        "raw" => AsciiView::Raw,
+        _ => AsciiView::Text,
+    };
+    // A blank path is named after the port and the time it is started
+    let capture_path = match inputs[21].value.is_empty() {
+        true => None,
+        false => Some(inputs[21].value.clone()),
+    };
+    let capture_format = match inputs[22].value.to_lowercase().as_str() {
+        "log" => CaptureFormat::Log,
+        _ => CaptureFormat::Text,
+    };
+    let trigger_bell = inputs[23].value.to_lowercase() == "yes";
+    // Nothing is answered without a file of rules
+    let response_rules = match inputs[24].value.is_empty() {
+        true => None,
+        false => Some(inputs[24].value.clone()),
+    };
+    // The silences are only marked on the display, unless set otherwise
+    let gap_markers = match inputs[25].value.to_lowercase().as_str() {
+        "yes" => GapMarkers::Shown,
+        "export" => GapMarkers::Exported,
+        _ => GapMarkers::Hidden,
+    };
+    // A blank gap uses the default of the serial interface
+    let gap_ms = get_number::<u32>(inputs, 26)?;
+    // The bytes are packed to the width when blank (or zero)
+    let row_length = match inputs[27].value.to_lowercase().as_str() {
+        "auto" => None,
+        _ => get_number::<usize>(inputs, 27)?.filter(|l| *l > 0),
+    };
+    // The received sentences are shown as they are, unless set otherwise
+    let nmea = match inputs[28].value.to_lowercase().as_str() {
+        "yes" => NmeaDecode::Checked,
+        "fix" => NmeaDecode::Fix,
+        _ => NmeaDecode::Off,
+    };
+    // The data is a stream of bytes, unless it is framed
+    let framing = match inputs[29].value.to_lowercase().as_str() {
+        "slip" => Framing::Slip,
+        "cobs" => Framing::Cobs,
+        _ => Framing::Off,
+    };
+    let at_commands = inputs[30].value.to_lowercase() == "yes";
+    // A blank timeout uses the default of the terminal
+    let at_timeout_ms = get_number::<u32>(inputs, 31)?;
+    // The lines of a HEX file are sent without waiting when both are blank
+    let hex_line_ms = get_number::<u32>(inputs, 32)?;
+    let hex_prompt = inputs[33].value.bytes().next();
+    // Shown unless the device echoes it (blank is as before)
+    let echo = inputs[34].value.to_lowercase() != "no";
+
+    return Ok(PortParameters {
+        name: Some(inputs[0].value.clone()),
         baud_rate: Some(baud_rate),
         data_bits: Some(data_bits),
         stop_bits: Some(stop_bits),
         parity: Some(parity),
         mode: Some(mode),
-    };
+        flow_control: Some(flow_control),
+        dtr_on_open,
+        rs485,
+        rs485_discard_echo,
+        timeout_ms,
+        read_only,
+        bridge,
+        reset_on_connect,
+        char_delay_ms,
+        line_delay_ms,
+        scrollback,
+        line_ending: Some(line_ending),
+        input_limit,
+        paste_lines,
+        ascii_view: Some(ascii_view),
+        capture_path,
+        capture_format: Some(capture_format),
+        trigger_bell,
+        response_rules,
+        gap_markers: Some(gap_markers),
+        gap_ms,
+        row_length,
+        nmea: Some(nmea),
+        framing: Some(framing),
+        at_commands,
+        at_timeout_ms,
+        hex_line_ms,
+        hex_prompt,
+        echo,
+    });
+}
+
+fn get_inputs() -> Vec<MenuInput> {
+    return vec![
+        MenuInput::default()
+            .title(String::from("Port"))
+            .placeholder(String::from("COM4")),
+
+        MenuInput::default()
+            .limit(7)
+            .presets(&BAUD_RATES)
+            .title(String::from("Baudrate"))
+            .placeholder(String::from("9600")),
+
+        MenuInput::default()
+            .choices(&["5", "6", "7", "8"])
+            .title(String::from("Data bits"))
+            .placeholder(String::from("8")),
+
+        MenuInput::default()
+            .choices(&["1", "2"])
+            .title(String::from("Stop bits"))
+            .placeholder(String::from("1")),
+
+        MenuInput::default()
+            .choices(&["None", "Even", "Odd"])
+            .title(String::from("Parity"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .choices(&["Ascii", "Hex", "Decimal", "Octal", "HexDump"])
+            .title(String::from("Mode"))
+            .placeholder(String::from("Ascii")),
+
+        MenuInput::default()
+            .limit(8)
+            .title(String::from("Flow ctrl"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("DTR on open"))
+            .placeholder(String::from("Yes")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("RS485"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Discard echo"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(6)
+            .title(String::from("Timeout ms"))
+            .placeholder(String::from("100")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Mode RO"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .title(String::from("Bridge port"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Reset"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Char delay ms"))
+            .placeholder(String::from("0")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Line delay ms"))
+            .placeholder(String::from("0")),
+
+        MenuInput::default()
+            .limit(7)
+            .title(String::from("Scrollback"))
+            .placeholder(String::from("100000")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Line ending"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(5)
+            .title(String::from("Input limit"))
+            .placeholder(String::from("512")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Paste lines"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Ascii view"))
+            .placeholder(String::from("Text")),
+
+        MenuInput::default()
+            .title(String::from("Capture path"))
+            .placeholder(String::from("Default")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Capture format"))
+            .placeholder(String::from("Text")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Trigger bell"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .title(String::from("Response rules"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(6)
+            .title(String::from("Gap markers"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Gap ms"))
+            .placeholder(String::from("50")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Row length"))
+            .placeholder(String::from("Auto")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("NMEA"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(4)
+            .title(String::from("Framing"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("AT commands"))
+            .placeholder(String::from("No")),
+
+        MenuInput::default()
+            .limit(6)
+            .title(String::from("AT timeout ms"))
+            .placeholder(String::from("5000")),
+
+        MenuInput::default()
+            .limit(6)
+            .title(String::from("HEX line ms"))
+            .placeholder(String::from("0")),
+
+        MenuInput::default()
+            .limit(1)
+            .title(String::from("HEX prompt"))
+            .placeholder(String::from("None")),
+
+        MenuInput::default()
+            .limit(3)
+            .title(String::from("Local echo"))
+            .placeholder(String::from("Yes")),
+    ];
+}
+
+fn set_inputs(inputs: &mut [MenuInput], values: &[(String, String)]) {
+    for (key, value) in values {
+        let input = inputs.iter_mut().find(|i| get_key(&i.title) == *key);
+        if let Some(input) = input {
+            input.value = value.clone();
+        }
+    }
+}
+
+fn get_input_error(inputs: &mut [MenuInput], i: usize) -> String {
+    let input = &mut inputs[i];
+    input.invalid = true;
+    return format!(" Invalid {} '{}' ", input.title.to_lowercase(), input.value);
+}
+
+fn get_number<T: FromStr>(inputs: &mut [MenuInput], i: usize) -> Result<Option<T>, String> {
+    // Blank is left to the default, anything else has to be a number that
+    // fits (e.g., a baudrate past u32)
+    if inputs[i].value.is_empty() {
+        return Ok(None);
+    }
+    match inputs[i].value.parse::<T>() {
+        Ok(n) => return Ok(Some(n)),
+        Err(_) => return Err(get_input_error(inputs, i)),
+    }
 }
 
 fn render_menu(frame: &mut Frame, area: Rect, model: &mut MenuModel) {
@@ -421,7 +1206,7 @@ fn render_menu(frame: &mut Frame, area: Rect, model: &mut MenuModel) {
 }
 
 fn render_scrollbar(frame: &mut Frame, area: Rect, model: &mut MenuModel) {
-    if usize::from(area.height) <= model.min_height {
+    if usize::from(area.height) < model.min_height {
         let scrollbar = Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .track_symbol(None)
@@ -446,10 +1231,10 @@ fn select_element(model: &mut MenuModel, direction: SelectElement) {
         SelectElement::Previous => (
             model.selected == 0,
             model.selected as i32 - 1,
-            model.inputs.len() + 1,
+            model.inputs.len() + BUTTON_COUNT - 1,
         ),
         SelectElement::Next => (
-            model.selected == model.inputs.len() + 1,
+            model.selected == model.inputs.len() + BUTTON_COUNT - 1,
             model.selected as i32 + 1,
             0,
         ),
@@ -461,90 +1246,207 @@ fn select_element(model: &mut MenuModel, direction: SelectElement) {
         model.selected = nominal as usize;
     }
 
-    let min_height = if model.split {
-        (CONTENT_LENGTH / 2) + 1
+    follow_selection(model);
+}
+
+fn follow_selection(model: &mut MenuModel) {
+    // Scrolled only while the menu does not fit, back to the top once it does
+    if usize::from(model.bounds.height) < get_content_length(model) {
+        update_scroll(model);
     } else {
-        CONTENT_LENGTH
+        model.offset = 0;
+        model.scroll = model.scroll.position(0);
+    }
+}
+
+fn cycle_element(model: &mut MenuModel, direction: SelectElement) {
+    let input = match model.inputs.get_mut(model.selected) {
+        Some(input) => input,
+        None => return,
+    };
+    let options = match &input.kind {
+        InputKind::Choice(options) | InputKind::Preset(options) => options,
+        InputKind::Text => return,
     };
 
-    if model.bounds.height <= min_height as u16 {
-        update_scroll(model);
+    // A blank choice starts from the placeholder, which is what it shows,
+    // and a typed number from the preset either side of it
+    let index = options.iter().position(|o| *o == input.value);
+    let typed = input.value.parse::<u64>().ok();
+    let value = match (index, direction) {
+        (None, _) if input.value.is_empty() && options.contains(&input.placeholder) => input.placeholder.clone(),
+        (None, SelectElement::Previous) if typed.is_some() => {
+            let lower = options.iter().rev().find(|o| o.parse::<u64>().ok() < typed);
+            lower.unwrap_or(&options[options.len() - 1]).clone()
+        }
+        (None, SelectElement::Next) if typed.is_some() => {
+            let higher = options.iter().find(|o| o.parse::<u64>().ok() > typed);
+            higher.unwrap_or(&options[0]).clone()
+        }
+        (None, _) => options[0].clone(),
+        (Some(i), SelectElement::Previous) => options[(i + options.len() - 1) % options.len()].clone(),
+        (Some(i), SelectElement::Next) => options[(i + 1) % options.len()].clone(),
+    };
+    input.value = value;
+}
+
+fn accept_suggestion(model: &mut MenuModel) -> bool {
+    let input = &model.inputs[0];
+    if input.cursor.is_some_and(|c| c < input.value.chars().count()) {
+        return false;
     }
+    match get_suggestion(model) {
+        Some(device) => {
+            model.inputs[0].value = device;
+            model.inputs[0].cursor = None;
+            return true;
+        }
+        None => return false,
+    }
+}
+
+fn complete_element(model: &mut MenuModel) -> bool {
+    if model.selected != 0 {
+        return false;
+    }
+    let (prefix, index) = match &model.completion {
+        Some((prefix, index)) => (prefix.clone(), index + 1),
+        None => (model.inputs[0].value.clone(), 0),
+    };
+    let matches = get_matches(model, &prefix);
+    if matches.len() == 0 {
+        return false;
+    }
+    model.inputs[0].value = matches[index % matches.len()].clone();
+    model.inputs[0].cursor = None;
+    model.completion = Some((prefix, index % matches.len()));
+    return true;
+}
+
+fn cancel_menu(model: &mut MenuModel) {
+    // As the Cancel button, which is selected first so that a stray esc
+    // does not quit
+    let cancel_btn = model.inputs.len();
+    if model.selected == cancel_btn {
+        model.set_state(State::Stopping);
+    } else {
+        model.selected = cancel_btn;
+        follow_selection(model);
+    }
+}
+
+fn choose_element(model: &mut MenuModel, typed: char) {
+    let input = &mut model.inputs[model.selected];
+    let options = match &input.kind {
+        InputKind::Choice(options) => options,
+        _ => return,
+    };
+
+    // Typing the first letter again moves on to the next option it starts
+    // (e.g., h for Hex then HexDump)
+    let typed = typed.to_ascii_lowercase();
+    let matching: Vec<&String> = options
+        .iter()
+        .filter(|o| o.to_lowercase().starts_with(typed))
+        .collect();
+    if matching.len() == 0 {
+        return;
+    }
+    let value = match matching.iter().position(|o| **o == input.value) {
+        Some(i) => matching[(i + 1) % matching.len()].clone(),
+        None => matching[0].clone(),
+    };
+    input.value = value;
 }
 
 fn update_split(model: &mut MenuModel, area: Rect) {
-    model.split = true;
     // Width * 2 to account for side-by-side inputs
     model.min_width = (INPUT_WIDTH * 2) + GAP_WIDTH;
-    // Input count / 2 to account for split
-    // Multiplied by 3 to account for input line count
-    // Add 1 to account for buttons
-    model.min_height = (model.inputs.len() / 2) * 3;
-
-    if usize::from(area.width) < model.min_width {
-        model.min_height = model.inputs.len() * 3 + 1;
-        model.split = false;
-    }
+    model.split = usize::from(area.width) >= model.min_width;
+    model.min_height = get_content_length(model);
+    model.scroll = model.scroll.content_length(model.min_height);
 }
 
-fn update_element(model: &mut MenuModel, update: UpdateElement) {
-    match update {
-        UpdateElement::Add(input) => {
-            let is_valid = validate_input(model, input);
-            let element = &model.inputs[model.selected];
-            let within_limit = element.value.len() < element.limit;
-            if within_limit && is_valid {
-                model.inputs[model.selected].value.push(input);
-            }
+fn update_element(model: &mut MenuModel, edit: Edit) {
+    let kind = match model.inputs.get(model.selected) {
+        Some(input) => input.kind.clone(),
+        None => return,
+    };
+
+    // A choice is cycled (or chosen by its first letter), and cleared back
+    // to its placeholder rather than typed
+    if let InputKind::Choice(_) = kind {
+        match edit {
+            Edit::Insert(' ') => cycle_element(model, SelectElement::Next),
+            Edit::Insert(input) => choose_element(model, input),
+            Edit::Backspace => model.inputs[model.selected].value.clear(),
+            _ => {}
         }
-        UpdateElement::Sub => {
-            if model.inputs[model.selected].value.len() > 0 {
-                model.inputs[model.selected].value.pop();
-            }
+        return;
+    }
+
+    // A preset is only typed at its end, the arrows cycle it instead
+    if kind != InputKind::Text && [Edit::Left, Edit::Right, Edit::Home].contains(&edit) {
+        return;
+    }
+    if let Edit::Insert(input) = edit {
+        let is_valid = validate_input(model, input);
+        let element = &model.inputs[model.selected];
+        let within_limit = element.value.chars().count() < element.limit;
+        if within_limit == false || is_valid == false {
+            return;
         }
     }
+    let input = &mut model.inputs[model.selected];
+    let mut cursor = get_cursor(input);
+    edit_text(&mut input.value, &mut cursor, edit);
+    input.cursor = match cursor < input.value.chars().count() {
+        true => Some(cursor),
+        false => None,
+    };
 }
 
 fn update_scroll(model: &mut MenuModel) {
-    match model.split {
-        true => {
-            let adjustment = if model.selected % 2 != 0 {
-                model.selected - 1
-            } else {
-                model.selected
-            };
-            model.offset = (adjustment / 2) * 3;
-            model.scroll = model.scroll.position(model.offset);
-        }
-        false => {
-            model.offset = if model.selected != 7 {
-                model.selected * 3
-            } else {
-                model.selected * 3 - 2
-            };
-            model.scroll = model.scroll.position(model.offset);
-        }
-    }
+    // The selected element is scrolled to the top
+    model.offset = get_element_line(model, model.selected);
+    model.scroll = model.scroll.content_length(get_content_length(model)).position(model.offset);
 }
 
 fn update_state(model: &mut MenuModel) {
     let cancel_btn = model.inputs.len();
-    let start_btn = cancel_btn + 1;
-    if model.selected == cancel_btn {
+    let test_btn = cancel_btn + 1;
+    let start_btn = cancel_btn + 2;
+    if model.selected == 0 {
+        // Every other field has a default, so the port is all Start needs
+        model.selected = start_btn;
+        follow_selection(model);
+    } else if model.selected < cancel_btn {
+        select_element(model, SelectElement::Next);
+    } else if model.selected == cancel_btn {
         model.set_state(State::Stopping);
+    } else if model.selected == test_btn {
+        // One test at a time, run by the serial thread
+        if model.test_status == Some(TestStatus::Running) {
+            return;
+        }
+        if let Some(parameters) = get_checked_parameters(model) {
+            model.test = Some(parameters);
+            model.test_status = Some(TestStatus::Running);
+        }
     } else if model.selected == start_btn {
-        if validate_values(model) {
-            let parameters = get_port_parameters(model);
-            model.set_state(State::Switching(Screen::Terminal, Some(parameters)));
-        } else {
-            model.set_state(State::Error(String::from(
-                " Invalid input (ctrl+h) for help ",
-            )));
+        if let Ok(parameters) = model.check_parameters() {
+            model.set_state(State::Switching(Screen::Terminal, Some(Box::new(parameters))));
         }
     }
 }
 
-fn update_spans_split(input: &MenuInput, spans: &mut MenuSpans, underline: &String, gap: String) {
+fn update_spans_split<'a>(
+    input: &MenuInput,
+    value: Vec<Span<'a>>,
+    spans: &mut MenuSpans<'a>,
+    underline: &String,
+    gap: String,
+) {
     spans.title.push(Span::from(format!(
         "{}{: <w$}",
         &gap,
@@ -552,11 +1454,8 @@ fn update_spans_split(input: &MenuInput, spans: &mut MenuSpans, underline: &Stri
         w = INPUT_WIDTH
     )));
 
-    let (text, style) = get_input_text(&input);
-    spans.input.push(Span::styled(
-        format!("{}{: <w$}", &gap, text, w = INPUT_WIDTH),
-        style,
-    ));
+    spans.input.push(Span::from(gap.clone()));
+    spans.input.extend(value);
 
     spans.underline.push(Span::from(format!(
         "{}{: <w$}",
@@ -578,12 +1477,8 @@ fn validate_input(model: &mut MenuModel, input: char) -> bool {
             Some(_) => return true,
             None => return false,
         },
-        2 => match input.to_digit(10) {
-            Some(v) => return (5..=8).contains(&v),
-            None => return false,
-        },
-        3 => match input.to_digit(10) {
-            Some(v) => return (1..=2).contains(&v),
+        10 | 14 | 15 | 16 | 18 | 26 => match input.to_digit(10) {
+            Some(_) => return true,
             None => return false,
         },
         _ => return true,
@@ -592,31 +1487,601 @@ fn validate_input(model: &mut MenuModel, input: char) -> bool {
 
 fn validate_values(model: &mut MenuModel) -> bool {
     let mut valid = true;
+    for input in model.inputs.iter_mut() {
+        input.invalid = false;
+    }
+
+    // Only the port is required, the framing left blank takes the defaults
+    let tcp = model.inputs[0].value.starts_with(TCP_PREFIX);
+    for i in 0..4 {
+        model.inputs[i].invalid = i == 0 && model.inputs[i].value.is_empty();
+    }
+    if model.inputs[0].invalid {
+        valid = false;
+    }
+
+    if tcp && get_address(&model.inputs[0].value).is_none() {
+        model.inputs[0].invalid = true;
+        valid = false;
+    }
+
+    if get_baud_rate_error(&model.inputs[1].value).is_some() {
+        model.inputs[1].invalid = true;
+        valid = false;
+    }
 
-    for i in 0..(model.inputs.len() - 2) {
-        if model.inputs[i].value.is_empty() {
-            model.inputs[i].invalid = true;
+    // A choice can only hold one of its options, or be left blank
+    model.inputs[4].invalid = false;
+    model.inputs[5].invalid = false;
+
+    match model.inputs[6].value.to_lowercase().as_str() {
+        "" | "none" | "hardware" | "software" => model.inputs[6].invalid = false,
+        _ => {
+            model.inputs[6].invalid = true;
             valid = false;
-        } else {
-            model.inputs[i].invalid = false;
         }
     }
 
-    match model.inputs[4].value.to_lowercase().as_str() {
-        "even" | "odd" | "none" => model.inputs[4].invalid = false,
+    match model.inputs[17].value.to_lowercase().as_str() {
+        "" | "none" | "cr" | "lf" | "crlf" => model.inputs[17].invalid = false,
         _ => {
-            model.inputs[4].invalid = true;
+            model.inputs[17].invalid = true;
             valid = false;
         }
     }
 
-    match model.inputs[5].value.to_lowercase().as_str() {
-        "ascii" | "decimal" | "hex" | "octal" => model.inputs[5].invalid = false,
+    match model.inputs[20].value.to_lowercase().as_str() {
+        "" | "text" | "ansi" | "raw" => model.inputs[20].invalid = false,
         _ => {
-            model.inputs[5].invalid = true;
+            model.inputs[20].invalid = true;
             valid = false;
         }
     }
 
+    match model.inputs[22].value.to_lowercase().as_str() {
+        "" | "text" | "log" => model.inputs[22].invalid = false,
+        _ => {
+            model.inputs[22].invalid = true;
+            valid = false;
+        }
+    }
+
+    match model.inputs[25].value.to_lowercase().as_str() {
+        "" | "no" | "yes" | "export" => model.inputs[25].invalid = false,
+        _ => {
+            model.inputs[25].invalid = true;
+            valid = false;
+        }
+    }
+
+    match model.inputs[27].value.to_lowercase().as_str() {
+        "" | "auto" | "0" | "4" | "8" | "16" | "32" => model.inputs[27].invalid = false,
+        _ => {
+            model.inputs[27].invalid = true;
+            valid = false;
+        }
+    }
+
+    match model.inputs[28].value.to_lowercase().as_str() {
+        "" | "no" | "yes" | "fix" => model.inputs[28].invalid = false,
+        _ => {
+            model.inputs[28].invalid = true;
+            valid = false;
+        }
+    }
+
+    match model.inputs[29].value.to_lowercase().as_str() {
+        "" | "none" | "slip" | "cobs" => model.inputs[29].invalid = false,
+        _ => {
+            model.inputs[29].invalid = true;
+            valid = false;
+        }
+    }
+
+    // The prompt is matched as a single byte of what arrives
+    if model.inputs[33].value.is_ascii() {
+        model.inputs[33].invalid = false;
+    } else {
+        model.inputs[33].invalid = true;
+        valid = false;
+    }
+
+    for i in [7, 8, 9, 11, 13, 19, 23, 30, 34] {
+        match model.inputs[i].value.to_lowercase().as_str() {
+            "" | "yes" | "no" => model.inputs[i].invalid = false,
+            _ => {
+                model.inputs[i].invalid = true;
+                valid = false;
+            }
+        }
+    }
+
+    // A zero timeout would turn every read into a busy poll
+    match model.inputs[10].value.parse::<u32>() {
+        Ok(0) => {
+            model.inputs[10].invalid = true;
+            valid = false;
+        }
+        _ => model.inputs[10].invalid = false,
+    }
+
+    // Nothing could be displayed without any scrollback, or typed without
+    // any input, every read would be marked without a gap, and every AT
+    // command would time out
+    for i in [16, 18, 26, 31] {
+        match model.inputs[i].value.parse::<usize>() {
+            Ok(0) => {
+                model.inputs[i].invalid = true;
+                valid = false;
+            }
+            _ => model.inputs[i].invalid = false,
+        }
+    }
+
     return valid;
 }
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_element() {
+        let mut model = MenuModel { selected: 4, ..Default::default() };
+
+        // A blank choice takes its placeholder first, then wraps around
+        model.update(Message::CursorRight);
+        assert_eq!(model.inputs[4].value, "None");
+        model.update(Message::CursorRight);
+        model.update(Message::Input(' '));
+        assert_eq!(model.inputs[4].value, "Odd");
+        model.update(Message::CursorRight);
+        assert_eq!(model.inputs[4].value, "None");
+        model.update(Message::CursorLeft);
+        assert_eq!(model.inputs[4].value, "Odd");
+        model.update(Message::Backspace);
+        assert_eq!(model.inputs[4].value, "");
+        assert_eq!(get_input_text(&model.inputs[4]).0, "◂ None ▸");
+
+        // A typed baudrate cycles to the presets either side of it
+        model.selected = 1;
+        model.inputs[1].value = String::from("250000");
+        assert_eq!(get_input_text(&model.inputs[1]).0, "250000 (custom)");
+        model.update(Message::CursorRight);
+        assert_eq!(model.inputs[1].value, "460800");
+        model.inputs[1].value = String::from("250000");
+        model.update(Message::CursorLeft);
+        assert_eq!(get_input_text(&model.inputs[1]).0, "◂ 230400 ▸");
+        model.update(Message::Input('0'));
+        assert_eq!(model.inputs[1].value, "2304000");
+
+        // A text field is left alone
+        model.selected = 17;
+        model.update(Message::CursorRight);
+        assert_eq!(model.inputs[17].value, "");
+        assert_eq!(model.get_state(), State::Running);
+    }
+
+    #[test]
+    fn test_choose_element() {
+        let mut model = MenuModel { selected: 5, ..Default::default() };
+
+        // The first letter picks the choice, again for the next it starts
+        model.update(Message::Input('h'));
+        assert_eq!(model.inputs[5].value, "Hex");
+        model.update(Message::Input('H'));
+        assert_eq!(model.inputs[5].value, "HexDump");
+        model.update(Message::Input('h'));
+        assert_eq!(model.inputs[5].value, "Hex");
+        model.update(Message::Input('x'));
+        assert_eq!(model.inputs[5].value, "Hex");
+
+        model.selected = 2;
+        model.update(Message::Paste(String::from("7")));
+        assert_eq!(model.inputs[2].value, "7");
+        model.update(Message::Input('9'));
+        assert_eq!(model.inputs[2].value, "7");
+    }
+
+    #[test]
+    fn test_baud_rate_error() {
+        let mut model = MenuModel::new(PortParameters {
+            name: Some(String::from("COM4")),
+            baud_rate: Some(5_000_000),
+            data_bits: Some(8),
+            stop_bits: Some(1),
+            parity: Some(Parity::None),
+            mode: Some(Mode::Ascii),
+            ..Default::default()
+        });
+        model.selected = model.inputs.len() + 2;
+
+        // The baudrate out of range is named rather than left to the help
+        model.update(Message::Enter);
+        assert_eq!(model.get_state(), State::Error(String::from(" Baudrate must be 1 - 4000000 ")));
+        assert!(model.inputs[1].invalid);
+        assert_eq!(get_baud_rate_error("0"), get_baud_rate_error("4000001"));
+        assert_eq!(get_baud_rate_error("4000000"), None);
+
+        model.inputs[1].value = String::from("921600");
+        model.update(Message::Enter);
+        assert!(matches!(model.get_state(), State::Switching(Screen::Terminal, Some(_))));
+    }
+
+    #[test]
+    fn test_complete_element() {
+        let mut model = MenuModel {
+            devices: vec![
+            String::from("/dev/ttyACM0"),
+            String::from("/dev/ttyUSB0"),
+            String::from("/dev/ttyUSB1"),
+            String::from(LOOPBACK_PORT),
+        ],
+            ..Default::default()
+        };
+
+        // Tab cycles the matches of what was typed
+        model.update(Message::Paste(String::from("/dev/ttyu")));
+        assert_eq!(get_suggestion(&model), Some(String::from("/dev/ttyUSB0")));
+        model.update(Message::Complete);
+        assert_eq!(model.inputs[0].value, "/dev/ttyUSB0");
+        model.update(Message::Complete);
+        assert_eq!(model.inputs[0].value, "/dev/ttyUSB1");
+        model.update(Message::Complete);
+        assert_eq!(model.inputs[0].value, "/dev/ttyUSB0");
+
+        // Anything else starts from what is in the field again
+        model.update(Message::Backspace);
+        model.update(Message::Complete);
+        assert_eq!(model.inputs[0].value, "/dev/ttyUSB0");
+
+        // Right accepts the suggestion, which is only of a longer port
+        model.inputs[0].value = String::from("/dev/ttyA");
+        model.update(Message::CursorRight);
+        assert_eq!(model.inputs[0].value, "/dev/ttyACM0");
+        assert_eq!(get_suggestion(&model), None);
+        model.selected = 1;
+        model.inputs[0].value = String::from("/dev/ttyA");
+        assert_eq!(get_suggestion(&model), None);
+    }
+
+    #[test]
+    fn test_get_value_spans() {
+        let mut model = MenuModel { devices: vec![String::from("/dev/ttyUSB0")], ..Default::default() };
+        let text = |model: &MenuModel, i: usize| {
+            let spans = get_value_spans(model, i);
+            spans.iter().map(|s| s.content.to_string()).collect::<Vec<_>>()
+        };
+
+        // The cursor sits on the suggestion, or on the placeholder
+        model.inputs[0].value = String::from("/dev/tty");
+        assert_eq!(text(&model, 0), vec!["/dev/tty", "U", "SB0", "      "]);
+        model.inputs[0].value.clear();
+        assert_eq!(text(&model, 0), vec!["", "C", "OM4", "              "]);
+        model.inputs[0].value = String::from("/dev/tty");
+        model.inputs[0].cursor = Some(5);
+        assert_eq!(text(&model, 0), vec!["/dev/", "t", "ty", "          "]);
+        assert_eq!(text(&model, 2), vec!["◂ 8 ▸             "]);
+
+        // An unknown port is warned of, unless it is a pseudo-port
+        model.inputs[0].cursor = None;
+        model.inputs[0].value = String::from("/dev/ttyS9");
+        assert_eq!(get_value_spans(&model, 0)[0].style.fg, Some(crate::WARNING_COLOR));
+        model.inputs[0].value = String::from("tcp://localhost:4000");
+        assert_eq!(get_value_spans(&model, 0)[0].style.fg, None);
+        model.inputs[0].value = String::from("/dev/ttyUSB0");
+        assert_eq!(get_value_spans(&model, 0)[0].style.fg, None);
+    }
+
+    #[test]
+    fn test_update_cursor() {
+        let mut model = MenuModel { devices: vec![String::from("/dev/ttyUSB0")], ..Default::default() };
+        for input in "/dev/ttyUSB".chars() {
+            model.update(Message::Input(input));
+        }
+
+        // Typed at the cursor, where right only takes the suggestion from
+        // the end
+        model.update(Message::CursorHome);
+        model.update(Message::Delete);
+        model.update(Message::CursorRight);
+        model.update(Message::CursorRight);
+        model.update(Message::CursorRight);
+        model.update(Message::Input('x'));
+        assert_eq!(model.inputs[0].value, "devx/ttyUSB");
+        assert_eq!(model.inputs[0].cursor, Some(4));
+        model.update(Message::CursorLeft);
+        model.update(Message::Backspace);
+        assert_eq!(model.inputs[0].value, "dex/ttyUSB");
+        model.update(Message::CursorEnd);
+        assert_eq!(model.inputs[0].cursor, None);
+
+        // Past the limit nothing is inserted, wherever the cursor is
+        model.selected = 10;
+        for input in "123456".chars() {
+            model.update(Message::Input(input));
+        }
+        model.update(Message::CursorHome);
+        model.update(Message::Input('9'));
+        assert_eq!((model.inputs[10].value.as_str(), model.inputs[10].cursor), ("123456", Some(0)));
+
+        // A preset is cycled by the arrows rather than edited
+        model.selected = 1;
+        model.update(Message::CursorRight);
+        model.update(Message::CursorHome);
+        model.update(Message::Backspace);
+        assert_eq!((model.inputs[1].value.as_str(), model.inputs[1].cursor), ("960", None));
+    }
+
+    #[test]
+    fn test_blank_defaults() {
+        let mut model = MenuModel::default();
+        model.selected = model.inputs.len() + 2;
+
+        // Only the port is required
+        model.update(Message::Enter);
+        assert!(matches!(model.get_state(), State::Error(_)));
+        assert!(model.inputs[0].invalid);
+
+        model.inputs[0].value = String::from("/dev/ttyUSB0");
+        model.update(Message::Enter);
+        let parameters = match model.get_state() {
+            State::Switching(Screen::Terminal, Some(parameters)) => *parameters,
+            state => panic!("unexpected state {:?}", state),
+        };
+        assert_eq!(parameters.name, Some(String::from("/dev/ttyUSB0")));
+        assert_eq!(parameters.baud_rate, Some(9600));
+        assert_eq!((parameters.data_bits, parameters.stop_bits), (Some(8), Some(1)));
+        assert_eq!(parameters.parity, Some(Parity::None));
+        assert_eq!(parameters.mode, Some(Mode::Ascii));
+        assert_eq!(parameters.flow_control, Some(FlowControl::None));
+        assert_eq!(parameters.line_ending, Some(LineEnding::None));
+        assert!(model.inputs.iter().all(|input| input.invalid == false));
+
+        // As the device list offers it without a menu
+        assert_eq!(parameters, get_default_parameters("/dev/ttyUSB0"));
+    }
+
+    #[test]
+    fn test_get_port_parameters() {
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from("/dev/ttyUSB0");
+        model.inputs[27].value = String::from("Auto");
+        assert_eq!(get_port_parameters(&mut model.inputs).unwrap().row_length, None);
+
+        // A number that does not fit is named, rather than unwrapped
+        model.inputs[1].value = String::from("99999999999");
+        let error = get_port_parameters(&mut model.inputs).unwrap_err();
+        assert_eq!(error, " Invalid baudrate '99999999999' ");
+        assert!(model.inputs[1].invalid);
+
+        model.inputs[1].value = String::new();
+        model.inputs[10].value = String::from("1O0");
+        assert_eq!(get_port_parameters(&mut model.inputs).unwrap_err(), " Invalid timeout ms '1O0' ");
+        model.inputs[10].value = String::new();
+        model.inputs[5].value = String::from("Binary");
+        assert_eq!(get_port_parameters(&mut model.inputs).unwrap_err(), " Invalid mode 'Binary' ");
+        model.inputs[5].value = String::new();
+        model.inputs[2].value = String::from("9");
+        assert_eq!(get_port_parameters(&mut model.inputs).unwrap_err(), " Invalid data bits '9' ");
+
+        // Which is surfaced by Start, where the mark is cleared once fixed
+        model.selected = model.inputs.len() + 2;
+        model.update(Message::Enter);
+        assert_eq!(model.get_state(), State::Error(String::from(" Invalid data bits '9' ")));
+        model.inputs[2].value = String::new();
+        model.update(Message::Enter);
+        assert!(model.inputs.iter().all(|input| input.invalid == false));
+        assert!(matches!(model.get_state(), State::Switching(..)));
+    }
+
+    #[test]
+    fn test_restore() {
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from("/dev/ttyUSB0");
+        model.inputs[1].value = String::from("115200");
+        model.inputs[6].value = String::from("Hardware");
+        let mut values = model.get_values();
+        assert_eq!(values[2], (String::from("flow_ctrl"), String::from("Hardware")));
+
+        // Restored with the port selected, so that Enter twice starts it
+        values.push((String::from("gone"), String::from("Yes")));
+        let mut model = MenuModel::restore(&values);
+        assert_eq!(model.inputs[1].value, "115200");
+        // Only the menu shown lists the ports, on opening or refreshing
+        assert_eq!(model.devices.len(), 0);
+        model.update(Message::Enter);
+        model.update(Message::Enter);
+        let parameters = match model.get_state() {
+            State::Switching(Screen::Terminal, Some(parameters)) => *parameters,
+            state => panic!("unexpected state {:?}", state),
+        };
+        assert_eq!(parameters.flow_control, Some(FlowControl::Hardware));
+
+        // Parsed alike without a menu, and refused alike
+        assert_eq!(parse_values(&values), Some(parameters));
+        values.push((String::from("mode"), String::from("Binary")));
+        assert_eq!(parse_values(&values), None);
+    }
+
+    #[test]
+    fn test_parity_round_trip() {
+        // Each parity is shown as the choice it was opened with, and back
+        for parity in [Parity::None, Parity::Even, Parity::Odd] {
+            let mut parameters = PortParameters::default().name(String::from("/dev/ttyS0"));
+            parameters.parity = Some(parity.clone());
+            let mut model = MenuModel::new(parameters);
+            assert_eq!(model.inputs[4].value, parity.clone().to_string());
+            assert_eq!(get_port_parameters(&mut model.inputs).unwrap().parity, Some(parity));
+        }
+
+        // Neither mark nor space is a choice, and 1.5 stop bits are named as
+        // unsupported
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from("/dev/ttyS0");
+        model.inputs[4].value = String::from("Mark");
+        assert!(get_port_parameters(&mut model.inputs).is_err());
+        model.inputs[4].value = String::new();
+        model.inputs[3].value = String::from("1.5");
+        assert_eq!(get_port_parameters(&mut model.inputs).unwrap_err(), " 1.5 stop bits are not supported ");
+        assert!(model.inputs[3].invalid);
+    }
+
+    #[test]
+    fn test_enter_element() {
+        let mut model = MenuModel { devices: vec![String::from("/dev/ttyUSB0")], ..Default::default() };
+
+        // Enter moves on from a field, and only the buttons act on it
+        model.selected = 4;
+        model.update(Message::Enter);
+        assert_eq!((model.selected, model.inputs[4].value.as_str()), (5, ""));
+        model.selected = model.inputs.len() - 1;
+        model.update(Message::Enter);
+        assert_eq!(model.selected, model.inputs.len());
+        assert_eq!(model.get_state(), State::Running);
+        model.update(Message::Enter);
+        assert_eq!(model.get_state(), State::Stopping);
+
+        // Tab completes the port while it can, then moves on
+        let mut model = MenuModel { devices: vec![String::from("/dev/ttyUSB0")], ..Default::default() };
+        model.update(Message::Paste(String::from("/dev/ttyS")));
+        model.update(Message::Complete);
+        assert_eq!((model.selected, model.inputs[0].value.as_str()), (1, "/dev/ttyS"));
+        model.update(Message::Complete);
+        assert_eq!(model.selected, 2);
+    }
+
+    #[test]
+    fn test_get_port_error() {
+        let mut model = MenuModel::default();
+        model.probe = true;
+        model.selected = model.inputs.len() + 2;
+
+        // A mistyped port stays in the menu, with why it did not open
+        model.inputs[0].value = String::from("/dev/nolp-missing");
+        model.update(Message::Enter);
+        let error = String::from(" Failed to open port: No such file or directory ");
+        assert_eq!(model.get_state(), State::Error(error));
+        assert!(model.inputs[0].invalid);
+
+        // Pseudo-ports are left to the terminal to open
+        for port in ["loopback:", "tcp://localhost:1", "replay:missing.log"] {
+            model.set_state(State::Running);
+            model.inputs[0].value = String::from(port);
+            model.update(Message::Enter);
+            assert!(matches!(model.get_state(), State::Switching(Screen::Terminal, _)));
+            assert!(model.inputs[0].invalid == false);
+        }
+    }
+
+    #[test]
+    fn test_cancel_menu() {
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from("loopback:");
+
+        // The first esc selects Cancel, the second quits
+        model.update(Message::Cancel);
+        assert_eq!((model.selected, model.get_state()), (model.inputs.len(), State::Running));
+        model.update(Message::Cancel);
+        assert_eq!(model.get_state(), State::Stopping);
+    }
+
+    #[test]
+    fn test_update_scroll() {
+        let style = Style::default();
+        for count in [5, 6, 7, 10] {
+            for (width, height) in [(60, 12), (100, 24), (160, 40), (240, 80)] {
+                let mut model = MenuModel::default();
+                model.inputs.truncate(count);
+                let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+                terminal.draw(|frame| model.view(frame)).unwrap();
+
+                // The lines drawn are those counted, with an odd input alone
+                // on the last row
+                let lines = get_input_elements(&mut model.clone(), style).len();
+                let buttons = get_button_elements(&mut model.clone(), style).len();
+                assert_eq!(lines + buttons, get_content_length(&model));
+
+                // Every element is scrolled into view in turn
+                for _ in 0..count + BUTTON_COUNT {
+                    model.update(Message::NextElement);
+                    terminal.draw(|frame| model.view(frame)).unwrap();
+                    let line = get_element_line(&model, model.selected);
+                    let visible = usize::from(model.bounds.height);
+                    assert!(line >= model.offset && line < model.offset + visible.max(1));
+                    if visible >= get_content_length(&model) {
+                        assert_eq!(model.offset, 0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_test() {
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from("loopback:");
+        model.selected = model.inputs.len() + 1;
+        let text = |model: &mut MenuModel| {
+            let buttons = get_button_elements(model, Style::default());
+            buttons.last().unwrap().spans.iter().map(|s| s.content.to_string()).collect::<String>()
+        };
+
+        // Handed over once, and not again until it is done
+        model.update(Message::Enter);
+        let parameters = model.take_test().unwrap();
+        assert_eq!(parameters.name, Some(String::from("loopback:")));
+        assert_eq!(model.take_test(), None);
+        model.update(Message::Enter);
+        assert_eq!(model.take_test(), None);
+        assert_eq!(text(&mut model), "Testing loopback:...");
+
+        // The result is shown without leaving the menu
+        model.update(Message::Tested(Ok(14)));
+        assert_eq!(model.get_state(), State::Running);
+        assert_eq!(text(&mut model), "Opened OK, 14 bytes received");
+        model.update(Message::Enter);
+        assert!(model.take_test().is_some());
+        model.update(Message::Tested(Err(SerialErrorKind::Busy)));
+        assert_eq!(text(&mut model), "Port is busy");
+
+        // A result nobody waits for is dropped
+        model.update(Message::Tested(Ok(1)));
+        assert_eq!(text(&mut model), "Port is busy");
+    }
+
+    #[test]
+    fn test_update_copy() {
+        let mut model = MenuModel::default();
+        model.inputs[0].value = String::from(" /dev/serial/by-id/usb-FTDI_FT232R-if00-port0 ");
+        model.selected = 3;
+
+        // The port is copied wherever the selection is, once
+        model.update(Message::Copy);
+        assert_eq!(model.take_copy(), Some(String::from("/dev/serial/by-id/usb-FTDI_FT232R-if00-port0")));
+        assert_eq!(model.take_copy(), None);
+    }
+
+    #[test]
+    fn test_set_value() {
+        let mut model = MenuModel::default();
+
+        // As if typed, where a choice takes the case of its option
+        assert_eq!(model.set_value("port", "/dev/ttyACM0"), Ok(()));
+        assert_eq!(model.set_value("mode", "hexdump"), Ok(()));
+        assert_eq!(model.set_value("flow_ctrl", "hardware"), Ok(()));
+        assert_eq!(model.inputs[5].value, "HexDump");
+        let parameters = model.check_values().unwrap();
+        assert_eq!(parameters.mode, Some(Mode::HexDump));
+        assert_eq!(parameters.flow_control, Some(FlowControl::Hardware));
+
+        assert_eq!(model.set_value("stop_bits", "3"), Err(String::from(" Stop bits must be one of 1, 2 ")));
+        assert_eq!(model.set_value("speed", "1"), Err(String::from(" Unknown field 'speed' ")));
+        assert_eq!(model.set_value("baudrate", "4000001"), Err(String::from(" Baudrate must be 1 - 4000000 ")));
+    }
+}
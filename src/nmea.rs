@@ -0,0 +1,322 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the NMEA 0183 sentences found in the received data, as
+* sent by GPS modules (e.g., `$GPRMC,...*6A`), checked against their XOR
+* checksum. The data is split into lines as it arrives, so that a sentence
+* split across reads is still read whole, and the latest RMC or GGA sentence
+* is kept as the current fix.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Check {
+    Valid,
+    Invalid,
+    // Ended before its checksum, as when cut off or still arriving
+    Truncated,
+}
+
+// The bytes of a line from its `$` up to the end of its checksum
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sentence {
+    pub start: usize,
+    pub end: usize,
+    pub check: Check,
+}
+
+// Each part is None until a sentence carries it, the position is in signed
+// degrees (north and east are positive)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fix {
+    pub time: Option<String>,
+    pub position: Option<(f64, f64)>,
+    pub quality: Option<String>,
+    pub satellites: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NmeaReader {
+    // What arrived since the last line break
+    line: Vec<u8>,
+    fix: Option<Fix>,
+    valid: u64,
+    invalid: u64,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// A sentence is at most 82 bytes, the rest of a longer line is dropped
+const LINE_LIMIT: usize = 256;
+const NO_FIX: &str = "No fix";
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl NmeaReader {
+    pub fn clear(&mut self) {
+        *self = NmeaReader::default();
+    }
+
+    pub fn get_fix(&self) -> Option<&Fix> {
+        return self.fix.as_ref();
+    }
+
+    pub fn get_valid(&self) -> u64 {
+        return self.valid;
+    }
+
+    pub fn get_invalid(&self) -> u64 {
+        return self.invalid;
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for value in data.iter().copied() {
+            if value != b'\r' && value != b'\n' {
+                if self.line.len() < LINE_LIMIT {
+                    self.line.push(value);
+                }
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            for sentence in get_sentences(&line) {
+                match sentence.check {
+                    Check::Valid => {
+                        self.valid += 1;
+                        // Without the `$` and the checksum
+                        let body = &line[sentence.start + 1..sentence.end - 3];
+                        update_fix(self.fix.get_or_insert_with(Fix::default), body);
+                    }
+                    Check::Invalid | Check::Truncated => self.invalid += 1,
+                }
+            }
+        }
+    }
+}
+
+impl Fix {
+    pub fn get_summary(&self) -> String {
+        // As in `12:35:19 | 48.11730 N, 11.51667 E | GPS, 8 sats`
+        let mut summary = vec![self.time.clone().unwrap_or(String::from("--:--:--"))];
+        summary.push(match self.position {
+            Some((latitude, longitude)) => format!(
+                "{:.5} {}, {:.5} {}",
+                latitude.abs(),
+                if latitude < 0.0 { "S" } else { "N" },
+                longitude.abs(),
+                if longitude < 0.0 { "W" } else { "E" }
+            ),
+            None => String::from("no position"),
+        });
+        let quality = self.quality.clone().unwrap_or(String::from(NO_FIX));
+        summary.push(match self.satellites {
+            Some(satellites) => format!("{}, {} sats", quality, satellites),
+            None => quality,
+        });
+        return summary.join(" | ");
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_sentences(line: &[u8]) -> Vec<Sentence> {
+    // Each `$` starts a sentence, which ends on the two digits after its `*`,
+    // or is truncated by the next `$` or the end of the line
+    let mut sentences = Vec::new();
+    let mut start = match line.iter().position(|value| *value == b'$') {
+        Some(start) => start,
+        None => return sentences,
+    };
+    loop {
+        let rest = &line[start + 1..];
+        let stop = rest.iter().position(|value| *value == b'$' || *value == b'*');
+        let sentence = match stop.map(|stop| (stop, rest[stop])) {
+            Some((stop, b'*')) => {
+                let digits = rest[stop + 1..].iter().take(2).take_while(|value| value.is_ascii_hexdigit());
+                let digits = digits.count();
+                let end = start + 1 + stop + 1 + digits;
+                let check = match digits {
+                    2 if get_checksum(&rest[..stop]) == get_hex(&rest[stop + 1..stop + 3]) => Check::Valid,
+                    0 | 1 if end == line.len() => Check::Truncated,
+                    _ => Check::Invalid,
+                };
+                Sentence { start, end, check }
+            }
+            Some((stop, _)) => Sentence { start, end: start + 1 + stop, check: Check::Truncated },
+            None => Sentence { start, end: line.len(), check: Check::Truncated },
+        };
+        let end = sentence.end;
+        sentences.push(sentence);
+        start = match line[end..].iter().position(|value| *value == b'$') {
+            Some(next) => end + next,
+            None => return sentences,
+        };
+    }
+}
+
+pub fn get_checksum(body: &[u8]) -> u8 {
+    return body.iter().fold(0, |checksum, value| checksum ^ value);
+}
+
+fn get_hex(digits: &[u8]) -> u8 {
+    let text = String::from_utf8_lossy(digits);
+    return u8::from_str_radix(&text, 16).unwrap_or_default();
+}
+
+fn get_coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    // Degrees and minutes run together, as in `4807.038` for 48° 07.038'
+    let value = value.parse::<f64>().ok()?;
+    let degrees = (value / 100.0).trunc();
+    let coordinate = degrees + (value - degrees * 100.0) / 60.0;
+    return match hemisphere {
+        "N" | "E" => Some(coordinate),
+        "S" | "W" => Some(-coordinate),
+        _ => None,
+    };
+}
+
+fn get_time(value: &str) -> Option<String> {
+    // As in `123519` or `123519.00`, the fraction is left out
+    let digits = value.get(..6).filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))?;
+    return Some(format!("{}:{}:{}", &digits[..2], &digits[2..4], &digits[4..]));
+}
+
+fn get_quality(value: &str) -> Option<String> {
+    let quality = match value {
+        "0" => NO_FIX,
+        "1" => "GPS",
+        "2" => "DGPS",
+        "3" => "PPS",
+        "4" => "RTK",
+        "5" => "Float RTK",
+        "6" => "Estimated",
+        "7" => "Manual",
+        "8" => "Simulated",
+        _ => return None,
+    };
+    return Some(String::from(quality));
+}
+
+fn update_fix(fix: &mut Fix, body: &[u8]) {
+    // Any talker is read (e.g., GP, GN, GL), only the type of the sentence
+    // after it matters
+    let body = String::from_utf8_lossy(body);
+    let fields: Vec<&str> = body.split(',').collect();
+    let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+    match fields[0].get(2..) {
+        Some("RMC") => {
+            fix.time = get_time(field(1)).or(fix.time.take());
+            if field(2) == "A" {
+                fix.position = get_coordinate(field(3), field(4)).zip(get_coordinate(field(5), field(6)));
+                // The quality of a GGA says more than that the fix is valid
+                if fix.quality.as_deref().unwrap_or(NO_FIX) == NO_FIX {
+                    fix.quality = Some(String::from("Fix"));
+                }
+            } else {
+                fix.position = None;
+                fix.quality = Some(String::from(NO_FIX));
+            }
+        }
+        Some("GGA") => {
+            fix.time = get_time(field(1)).or(fix.time.take());
+            fix.quality = get_quality(field(6));
+            fix.satellites = field(7).parse::<u8>().ok();
+            fix.position = match fix.quality.as_deref() {
+                Some(NO_FIX) | None => None,
+                Some(_) => get_coordinate(field(2), field(3)).zip(get_coordinate(field(4), field(5))),
+            };
+        }
+        _ => {}
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from a module warming up, with a sentence cut off by a reset
+    // and one that was corrupted on the line
+    const LOG: &[u8] = b"$GPGGA,,,,,,0,00,99.99,,,,,,*48\r\n\
+        $GPRMC,002154.000,V,,,,,,,,,,N*4F\r\n\
+        $GPGGA,002153.000,3342.6618,S,11751.3858,W,2,10,1.2,27.0,M,-34.2,M,,0000*40\r\n\
+        $GPRMC,123519,A,4807.0$GNGSA,A,3,04,05,,09,12,,,24,,,,,2.5,1.3,2.1*27\r\n\
+        $GPGGA,123520,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*4E\r\n\
+        $GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n";
+
+    #[test]
+    fn test_get_sentences() {
+        let line = b"$GPRMC,002154.000,V,,,,,,,,,,N*4F";
+        assert_eq!(get_checksum(&line[1..30]), 0x4F);
+        let sentences = get_sentences(line);
+        assert_eq!(sentences, vec![Sentence { start: 0, end: line.len(), check: Check::Valid }]);
+
+        // The checksum is read in either case, the text around is left out
+        let sentences = get_sentences(b"> $GPRMC,002154.000,V,,,,,,,,,,N*4f ok");
+        assert_eq!(sentences, vec![Sentence { start: 2, end: 35, check: Check::Valid }]);
+
+        let sentences = get_sentences(b"$GPRMC,002154.000,V,,,,,,,,,,N*40");
+        assert_eq!(sentences[0].check, Check::Invalid);
+        let sentences = get_sentences(b"$GPRMC,002154.000,V,,,,,,,,,,N*4G");
+        assert_eq!(sentences[0].check, Check::Invalid);
+        assert_eq!(sentences[0].end, 32);
+
+        // Cut off by the end of the line, or by the next sentence
+        let sentences = get_sentences(b"$GPRMC,002154.000,V,,,,,,,,,,N*4");
+        assert_eq!(sentences[0].check, Check::Truncated);
+        let sentences = get_sentences(b"$GPRMC,0021$GPRMC,002154.000,V,,,,,,,,,,N*4F");
+        assert_eq!(sentences[0], Sentence { start: 0, end: 11, check: Check::Truncated });
+        assert_eq!(sentences[1], Sentence { start: 11, end: 44, check: Check::Valid });
+
+        assert_eq!(get_sentences(b"no sentence here"), vec![]);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut reader = NmeaReader::default();
+
+        // The log split across reads, as it arrives from the port
+        for chunk in LOG[..70].chunks(7) {
+            reader.update(chunk);
+        }
+        assert_eq!(reader.get_valid(), 2);
+        assert_eq!(reader.get_fix().unwrap().get_summary(), "00:21:54 | no position | No fix, 0 sats");
+
+        reader.update(&LOG[70..]);
+        assert_eq!((reader.get_valid(), reader.get_invalid()), (5, 2));
+        let fix = reader.get_fix().unwrap();
+        assert_eq!(fix.get_summary(), "12:35:19 | 48.11730 N, 11.51667 E | DGPS, 10 sats");
+
+        // A fix in the southern and western hemispheres is signed
+        reader.clear();
+        reader.update(LOG.split(|value| *value == b'\n').nth(2).unwrap());
+        let (latitude, longitude) = reader.get_fix().unwrap().position.unwrap();
+        assert!((latitude + 33.71103).abs() < 0.00001);
+        assert!((longitude + 117.85643).abs() < 0.00001);
+        assert_eq!(reader.get_fix().unwrap().quality.as_deref(), Some("DGPS"));
+
+        // Nothing is read from a line that never breaks
+        reader.clear();
+        reader.update(&[b'$'; LINE_LIMIT * 2]);
+        assert_eq!(reader.line.len(), LINE_LIMIT);
+        assert_eq!(reader.get_fix(), None);
+    }
+}
@@ -0,0 +1,411 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the replay 'port', which plays a session captured in the
+* Log format back into the terminal (e.g., to review a field capture in each
+* display mode). The records are read back as they were timed, faster when
+* asked, and what was written to the replay is discarded.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::{anyhow, Result};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, ErrorKind, Read, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::capture::get_millis;
+use crate::common::*;
+use crate::serial::{get_timeout, NolpPort};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Debug)]
+pub struct ReplayPort {
+    records: VecDeque<ReplayRecord>,
+    timeout: Duration,
+    // How far into the capture the replay is, as of when it was updated
+    position: Duration,
+    updated: Instant,
+    speed: u32,
+    paused: bool,
+}
+
+// The data of a line of the capture, at its time since the first
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayRecord {
+    pub at: Duration,
+    pub origin: Origin,
+    pub data: Vec<u8>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl ReplayPort {
+    pub fn new(records: Vec<ReplayRecord>, timeout: Duration) -> ReplayPort {
+        ReplayPort {
+            records: VecDeque::from(records),
+            timeout,
+            position: Duration::ZERO,
+            updated: Instant::now(),
+            speed: 1,
+            paused: false,
+        }
+    }
+
+    fn get_position(&self, now: Instant) -> Duration {
+        if self.paused {
+            return self.position;
+        }
+        return self.position + now.duration_since(self.updated) * self.speed;
+    }
+
+    fn advance(&mut self) {
+        let now = Instant::now();
+        self.position = self.get_position(now);
+        self.updated = now;
+    }
+
+    fn is_due(&self, record: &ReplayRecord) -> bool {
+        return record.at <= self.position;
+    }
+}
+
+impl Read for ReplayPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Waits like a serial port would, either for the next record to be
+        // due or for the timeout to pass
+        self.advance();
+        let wait = match self.records.front() {
+            Some(record) if self.paused == false => record.at.saturating_sub(self.position) / self.speed,
+            Some(record) if self.is_due(record) => Duration::ZERO,
+            _ => self.timeout,
+        };
+        if wait > Duration::ZERO {
+            thread::sleep(self.timeout.min(wait));
+            self.advance();
+        }
+
+        // What was sent (or bridged) is left to be read back on its own
+        let ready = match self.records.front() {
+            Some(record) => record.origin == Origin::Primary && self.is_due(record),
+            None => false,
+        };
+        if ready == false || buf.len() == 0 {
+            return Err(io::Error::from(ErrorKind::TimedOut));
+        }
+
+        let record = self.records.front_mut().unwrap();
+        let n = record.data.len().min(buf.len());
+        buf[..n].copy_from_slice(&record.data[..n]);
+        record.data.drain(..n);
+        if record.data.len() == 0 {
+            self.records.pop_front();
+        }
+        return Ok(n);
+    }
+}
+
+// Nothing is sent anywhere, the replay only plays back what was captured
+impl Write for ReplayPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+impl NolpPort for ReplayPort {
+    fn bytes_to_read(&self) -> Result<u32> {
+        let position = self.get_position(Instant::now());
+        let ready = self
+            .records
+            .iter()
+            .take_while(|record| record.origin == Origin::Primary && record.at <= position)
+            .map(|record| record.data.len())
+            .sum::<usize>();
+        return Ok(u32::try_from(ready).unwrap_or(u32::MAX));
+    }
+
+    fn clear_all(&mut self) -> Result<()> {
+        return Ok(());
+    }
+
+    fn clear_input(&mut self) -> Result<()> {
+        return Ok(());
+    }
+
+    fn configure(&mut self, _parameters: &PortParameters) -> Result<()> {
+        return Ok(());
+    }
+
+    // The control lines were not captured
+    fn read_lines(&mut self) -> LineStatus {
+        return LineStatus::default();
+    }
+
+    fn set_break(&mut self, _enabled: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn set_dtr(&mut self, _level: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn set_rts(&mut self, _level: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn read_replayed(&mut self) -> Vec<(Origin, Vec<u8>)> {
+        // Only up to the next received data, so that the order is kept
+        self.advance();
+        let mut replayed = Vec::new();
+        while let Some(record) = self.records.front() {
+            if record.origin == Origin::Primary || self.is_due(record) == false {
+                break;
+            }
+            let record = self.records.pop_front().unwrap();
+            replayed.push((record.origin, record.data));
+        }
+        return replayed;
+    }
+
+    fn control_replay(&mut self, control: ReplayControl) {
+        self.advance();
+        match control {
+            ReplayControl::Pause(paused) => self.paused = paused,
+            ReplayControl::Speed(speed) => self.speed = speed.max(1),
+            // Skips the silence up to the next record
+            ReplayControl::Step => {
+                if let Some(record) = self.records.iter().find(|record| record.at > self.position) {
+                    self.position = record.at;
+                }
+            }
+        }
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn is_replay(parameters: &PortParameters) -> bool {
+    match &parameters.name {
+        Some(name) => return name.starts_with(REPLAY_PREFIX),
+        None => return false,
+    }
+}
+
+pub fn open_replay(parameters: &PortParameters) -> Result<ReplayPort> {
+    // The capture follows the port name (e.g., `replay:session.log`)
+    let name = parameters.name.clone().unwrap_or_default();
+    let path = name.trim_start_matches(REPLAY_PREFIX);
+    let text = fs::read_to_string(path).map_err(|e| anyhow!("{}: {}", path, e))?;
+    let records = get_records(&text).map_err(|e| anyhow!("{}: {}", path, e))?;
+    let timeout = Duration::from_millis(u64::from(get_timeout(parameters)));
+    return Ok(ReplayPort::new(records, timeout));
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_records(text: &str) -> Result<Vec<ReplayRecord>> {
+    // The markers were notes of the terminal at the time, so only the data
+    // is replayed
+    let mut records = Vec::new();
+    let mut start = None;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let mut fields = line.split_whitespace();
+        let timestamp = match fields.next() {
+            Some(timestamp) => timestamp,
+            None => continue,
+        };
+        let millis = get_millis(timestamp).ok_or(anyhow!("line {}: expected a Log capture", number))?;
+        let origin = match fields.next().unwrap_or_default() {
+            "RX" => Origin::Primary,
+            "TX" => Origin::Sent,
+            "BRIDGE" => Origin::Bridge,
+            "MARKER" => continue,
+            source => return Err(anyhow!("line {}: unknown direction '{}'", number, source)),
+        };
+        let data = fields
+            .map(|value| u8::from_str_radix(value, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| anyhow!("line {}: invalid byte", number))?;
+        let start = *start.get_or_insert(millis);
+        records.push(ReplayRecord {
+            at: Duration::from_millis(millis.saturating_sub(start)),
+            origin,
+            data,
+        });
+    }
+    if records.len() == 0 {
+        return Err(anyhow!("nothing to replay"));
+    }
+    return Ok(records);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::{get_stats, read_write_port};
+    use std::sync::mpsc;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    const CAPTURE: &str = "2026-10-17T12:04:05.000Z TX 41 54 0D\n\
+        2026-10-17T12:04:05.000Z MARKER <CLEARED>\n\
+        2026-10-17T12:04:05.040Z RX 4F 4B\n\
+        \n\
+        2026-10-17T12:04:05.040Z BRIDGE FF\n\
+        2026-10-17T12:04:05.080Z RX 0D 0A\n";
+
+    fn record(ms: u64, origin: Origin, data: &[u8]) -> ReplayRecord {
+        return ReplayRecord {
+            at: Duration::from_millis(ms),
+            origin,
+            data: data.to_vec(),
+        };
+    }
+
+    #[test]
+    fn test_get_records() {
+        let expected = vec![
+            record(0, Origin::Sent, b"AT\r"),
+            record(40, Origin::Primary, b"OK"),
+            record(40, Origin::Bridge, &[0xFF]),
+            record(80, Origin::Primary, b"\r\n"),
+        ];
+        assert_eq!(get_records(CAPTURE).unwrap(), expected);
+
+        // A capture in the Text format is not timed, so it is not replayed
+        let error = get_records("OK\r\n").unwrap_err().to_string();
+        assert_eq!(error, "line 1: expected a Log capture");
+        let error = get_records("2026-10-17T12:04:05.000Z RX 4G").unwrap_err().to_string();
+        assert_eq!(error, "line 1: invalid byte");
+        let error = get_records("2026-10-17T12:04:05.000Z XX 00").unwrap_err().to_string();
+        assert_eq!(error, "line 1: unknown direction 'XX'");
+        assert!(get_records("2026-10-17T12:04:05.000Z MARKER <CLEARED>").is_err());
+    }
+
+    #[test]
+    fn test_read() {
+        let records = vec![record(0, Origin::Sent, b"AT"), record(30, Origin::Primary, b"OK")];
+        let mut port = ReplayPort::new(records, Duration::from_millis(5));
+        let mut buffer = [0; 8];
+
+        // Sent data is read back on its own, before what was received after
+        assert_eq!(port.read(&mut buffer).unwrap_err().kind(), ErrorKind::TimedOut);
+        assert_eq!(port.read_replayed(), vec![(Origin::Sent, b"AT".to_vec())]);
+        assert_eq!(port.bytes_to_read().unwrap(), 0);
+
+        // While paused, nothing is due until stepped to
+        port.control_replay(ReplayControl::Pause(true));
+        thread::sleep(Duration::from_millis(40));
+        assert_eq!(port.read(&mut buffer).unwrap_err().kind(), ErrorKind::TimedOut);
+        port.control_replay(ReplayControl::Step);
+        assert_eq!(port.bytes_to_read().unwrap(), 2);
+        assert_eq!(port.read(&mut buffer).unwrap(), 2);
+        assert_eq!(&buffer[..2], b"OK");
+        assert_eq!(port.read_replayed(), vec![]);
+    }
+
+    #[test]
+    fn test_read_speed() {
+        // At 10x, a second of the capture takes a tenth of one
+        let records = vec![record(0, Origin::Primary, b"a"), record(1000, Origin::Primary, b"b")];
+        let mut port = ReplayPort::new(records, Duration::from_millis(50));
+        port.control_replay(ReplayControl::Speed(10));
+        let start = Instant::now();
+        let mut buffer = [0; 8];
+        let mut received = Vec::new();
+        while received.len() < 2 && start.elapsed() < Duration::from_secs(1) {
+            if let Ok(n) = port.read(&mut buffer) {
+                received.extend_from_slice(&buffer[..n]);
+            }
+        }
+        assert_eq!(received, b"ab");
+        assert!(start.elapsed() >= Duration::from_millis(90));
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_read_write_port_replay() {
+        let path = std::env::temp_dir().join(format!("nolp-replay-{}.log", std::process::id()));
+        fs::write(&path, CAPTURE).unwrap();
+        let mut parameters = PortParameters::default().name(format!("{}{}", REPLAY_PREFIX, path.display()));
+        parameters.timeout_ms = Some(5);
+        assert!(is_replay(&parameters));
+
+        let (commands, command_receiver) = mpsc::channel();
+        let (tx, data_receiver) = mpsc::channel();
+        let (event_sender, mut events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let rx = SharedRx::default();
+
+        let (t_stats, t_rx) = (stats.clone(), rx.clone());
+        let handle = thread::spawn(move || {
+            return read_write_port(
+                parameters,
+                open_replay,
+                &command_receiver,
+                &data_receiver,
+                &event_sender,
+                &t_stats,
+                &t_rx,
+            );
+        });
+
+        // Each origin is passed on as it was captured, in order
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = Vec::new();
+        while received.len() < 4 && Instant::now() < deadline {
+            match events.try_recv() {
+                Ok(SerialEvent::Status(ConnectionStatus::Connected)) => {
+                    tx.send(b"discarded".to_vec()).unwrap();
+                }
+                Ok(SerialEvent::RxReady) => {
+                    for (origin, data, _) in rx.lock().unwrap().drain() {
+                        received.push((origin, data));
+                    }
+                }
+                _ => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        let expected = vec![
+            (Origin::Sent, b"AT\r".to_vec()),
+            (Origin::Primary, b"OK".to_vec()),
+            (Origin::Bridge, vec![0xFF]),
+            (Origin::Primary, b"\r\n".to_vec()),
+        ];
+        assert_eq!(received, expected);
+
+        commands.send(SerialCommand::Close).unwrap();
+        assert!(!handle.join().unwrap());
+        let stats = get_stats(&stats);
+        assert_eq!(stats.rx_bytes, 5);
+        fs::remove_file(&path).unwrap();
+
+        let parameters = PortParameters::default().name(String::from("replay:/nonexistent.log"));
+        assert!(open_replay(&parameters).is_err());
+    }
+}
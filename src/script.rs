@@ -0,0 +1,294 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the scripts run against a connection, a directive a line
+* (`send AT\r\n`, `expect OK 2000`, `sleep 500`), each run once the one before
+* it is done. Like a transfer, the script is driven by what arrives and by the
+* passing time, and hands back what it sends.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::time::{Duration, Instant};
+
+use crate::common::get_escaped_bytes;
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Directive {
+    Send(Vec<u8>),
+    // The text to wait for, and for how long
+    Expect(Vec<u8>, Duration),
+    Sleep(Duration),
+}
+
+// A directive with the line it was read from, counted from 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub number: usize,
+    pub line: String,
+    pub directive: Directive,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptState {
+    Running,
+    Done,
+    // An expect timed out, which is left as the current step
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    steps: Vec<Step>,
+    index: usize,
+    // When the current step started
+    started: Instant,
+    // What arrived while the current expect waits, no longer than its text
+    received: Vec<u8>,
+    // The line of each expect run, and whether it matched
+    results: Vec<(usize, bool)>,
+    state: ScriptState,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Script {
+    pub fn new(steps: Vec<Step>, now: Instant) -> Script {
+        Script {
+            steps,
+            index: 0,
+            started: now,
+            received: Vec::new(),
+            results: Vec::new(),
+            state: ScriptState::Running,
+        }
+    }
+
+    pub fn get_state(&self) -> &ScriptState {
+        return &self.state;
+    }
+
+    pub fn get_current(&self) -> Option<&Step> {
+        return self.steps.get(self.index);
+    }
+
+    pub fn get_index(&self) -> usize {
+        return self.index;
+    }
+
+    pub fn get_length(&self) -> usize {
+        return self.steps.len();
+    }
+
+    pub fn get_results(&self) -> &[(usize, bool)] {
+        return &self.results;
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Vec<u8> {
+        // Runs on until a step has to wait, so that an expect is already
+        // waiting when the reply to the send before it arrives
+        let mut output = Vec::new();
+        while self.state == ScriptState::Running {
+            let step = match self.steps.get(self.index) {
+                Some(step) => step,
+                None => {
+                    self.state = ScriptState::Done;
+                    break;
+                }
+            };
+            let waited = now.duration_since(self.started);
+            match &step.directive {
+                Directive::Send(bytes) => output.extend_from_slice(bytes),
+                Directive::Sleep(duration) if waited < *duration => break,
+                Directive::Sleep(_) => {}
+                Directive::Expect(_, timeout) if waited < *timeout => break,
+                Directive::Expect(..) => {
+                    self.results.push((step.number, false));
+                    self.state = ScriptState::Failed;
+                    break;
+                }
+            }
+            self.next(now);
+        }
+        return output;
+    }
+
+    pub fn update(&mut self, input: &[u8], now: Instant) -> Vec<u8> {
+        // What arrives while no expect waits is not looked at
+        if let Some(Step { number, directive: Directive::Expect(text, _), .. }) = self.get_current() {
+            if self.state == ScriptState::Running {
+                let (number, text) = (*number, text.clone());
+                self.received.extend_from_slice(input);
+                if self.received.windows(text.len()).any(|window| window == text) {
+                    self.results.push((number, true));
+                    self.next(now);
+                } else {
+                    // Only the end could still be the start of a match
+                    let keep = text.len().saturating_sub(1).min(self.received.len());
+                    self.received.drain(..self.received.len() - keep);
+                }
+            }
+        }
+        return self.tick(now);
+    }
+
+    fn next(&mut self, now: Instant) {
+        self.index += 1;
+        self.started = now;
+        self.received.clear();
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_steps(text: &str) -> Result<Vec<Step>, String> {
+    // Blank lines and those starting with # are skipped, the text of a send
+    // or an expect is escaped as typed in the input
+    let mut steps = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().len() == 0 || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let line = line.trim_start();
+        let (name, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let error = |e: &str| format!(" Line {}: {} ", number, e.trim());
+        let directive = match name {
+            "send" if argument.len() > 0 => Directive::Send(get_escaped_bytes(argument).map_err(|e| error(&e))?),
+            "expect" => {
+                let (text, timeout) = argument.trim_end().rsplit_once(' ').ok_or(error("expected expect <text> <ms>"))?;
+                let timeout = get_duration(timeout).ok_or(error(&format!("invalid timeout '{}'", timeout)))?;
+                let text = get_escaped_bytes(text.trim_end()).map_err(|e| error(&e))?;
+                if text.len() == 0 {
+                    return Err(error("expected expect <text> <ms>"));
+                }
+                Directive::Expect(text, timeout)
+            }
+            "sleep" => Directive::Sleep(get_duration(argument.trim()).ok_or(error("expected sleep <ms>"))?),
+            "send" => return Err(error("expected send <text>")),
+            _ => return Err(error(&format!("unknown directive '{}'", name))),
+        };
+        steps.push(Step {
+            number,
+            line: String::from(line.trim_end()),
+            directive,
+        });
+    }
+    if steps.len() == 0 {
+        return Err(String::from(" The script has no directives "));
+    }
+    return Ok(steps);
+}
+
+fn get_duration(ms: &str) -> Option<Duration> {
+    return ms.parse::<u64>().ok().map(Duration::from_millis);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback::LoopbackPort;
+    use std::io::{Read, Write};
+
+    const SCRIPT: &str = "# Wakes the modem\n\
+        send AT\\r\\n\n\
+        expect AT\\r 500\n\
+        \n\
+        sleep 20\n\
+        send hello world\n\
+        expect lo wor 500\n\
+        expect never 50\n\
+        send unreached\n";
+
+    // Drives the script against the echo, as the terminal does with the
+    // port, until it is no longer running
+    fn run(script: &mut Script, port: &mut LoopbackPort) -> Vec<u8> {
+        let mut sent = Vec::new();
+        let mut output = script.tick(Instant::now());
+        while *script.get_state() == ScriptState::Running {
+            port.write_all(&output).unwrap();
+            sent.extend(output);
+            let mut buffer = [0; 64];
+            let input = match port.read(&mut buffer) {
+                Ok(n) => buffer[..n].to_vec(),
+                Err(_) => Vec::new(),
+            };
+            output = script.update(&input, Instant::now());
+        }
+        return sent;
+    }
+
+    #[test]
+    fn test_get_steps() {
+        let steps = get_steps(SCRIPT).unwrap();
+        assert_eq!(steps.len(), 7);
+        assert_eq!(steps[0].number, 2);
+        assert_eq!(steps[0].directive, Directive::Send(b"AT\r\n".to_vec()));
+        assert_eq!(steps[1].directive, Directive::Expect(b"AT\r".to_vec(), Duration::from_millis(500)));
+        assert_eq!(steps[2].directive, Directive::Sleep(Duration::from_millis(20)));
+        assert_eq!(steps[2].line, "sleep 20");
+
+        // The text of an expect may hold spaces, the timeout is the last word
+        assert_eq!(steps[4].directive, Directive::Expect(b"lo wor".to_vec(), Duration::from_millis(500)));
+
+        assert_eq!(get_steps("sleep 10\nwait 10"), Err(String::from(" Line 2: unknown directive 'wait' ")));
+        assert_eq!(get_steps("expect OK"), Err(String::from(" Line 1: expected expect <text> <ms> ")));
+        assert_eq!(get_steps("expect OK soon"), Err(String::from(" Line 1: invalid timeout 'soon' ")));
+        assert_eq!(get_steps("send \\q"), Err(String::from(" Line 1: Invalid escape '\\q' ")));
+        assert!(get_steps("sleep").is_err());
+        assert!(get_steps("send").is_err());
+        assert!(get_steps("# nothing to run\n\n").is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let mut port = LoopbackPort::new(Duration::from_millis(5), Duration::from_millis(5));
+        let mut script = Script::new(get_steps(SCRIPT).unwrap(), Instant::now());
+
+        // Every send before the expect that never arrives is made, and the
+        // script stops on the expect that timed out
+        let sent = run(&mut script, &mut port);
+        assert_eq!(sent, b"AT\r\nhello world".to_vec());
+        assert_eq!(*script.get_state(), ScriptState::Failed);
+        assert_eq!(script.get_current().unwrap().number, 8);
+        assert_eq!(script.get_results(), &[(3, true), (7, true), (8, false)]);
+
+        let mut script = Script::new(get_steps("send ok\nexpect ok 500").unwrap(), Instant::now());
+        assert_eq!(run(&mut script, &mut port), b"ok".to_vec());
+        assert_eq!(*script.get_state(), ScriptState::Done);
+        assert_eq!(script.get_current(), None);
+        assert_eq!((script.get_index(), script.get_length()), (2, 2));
+    }
+
+    #[test]
+    fn test_update() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut script = Script::new(get_steps("expect OK\\r\\n 100\nsleep 50\nsend AT").unwrap(), at(0));
+
+        // The text is matched across reads, and what follows waits on
+        assert_eq!(script.tick(at(0)), vec![]);
+        assert_eq!(script.update(b"+CSQ: 1\r\nO", at(10)), vec![]);
+        assert_eq!(script.update(b"K\r", at(20)), vec![]);
+        assert_eq!(script.update(b"\n", at(30)), vec![]);
+        assert_eq!(script.get_index(), 1);
+        assert_eq!(script.tick(at(60)), vec![]);
+        assert_eq!(script.tick(at(80)), b"AT".to_vec());
+        assert_eq!(*script.get_state(), ScriptState::Done);
+    }
+}
@@ -7,177 +7,1709 @@
 ********************************************************************************/
 /*******************************************************************************/
 use anyhow::Result;
-use serialport::{DataBits, Parity as SParity, SerialPortBuilder, StopBits};
-use std::{io::ErrorKind, sync::Arc, thread, time::Duration};
+use serialport::{
+    ClearBuffer, DataBits, FlowControl as SFlowControl, Parity as SParity, SerialPort, SerialPortBuilder,
+    SerialPortInfo, SerialPortType, StopBits,
+};
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    sync::{
+        mpsc::{self, RecvTimeoutError, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::common::*;
+use crate::generator::Generator;
+
 /******************************************************************************/
 /*******************************************************************************
-* Public Interface | Implementation
+* Public Interface
 *******************************************************************************/
 /******************************************************************************/
-pub fn close_connection(flag: &SerialFlag) -> bool {
-    let mut success = false;
-    let mut f_lock = flag.try_lock();
-    if let Ok(ref mut f_mutex) = f_lock {
-        **f_mutex = false;
-        success = true;
-        drop(f_lock);
+// The operations the session loop needs from a connection, so that it can run
+// against something other than a serial port (e.g., in tests)
+pub trait NolpPort: Read + Write {
+    fn bytes_to_read(&self) -> Result<u32>;
+    fn clear_all(&mut self) -> Result<()>;
+    fn clear_input(&mut self) -> Result<()>;
+    fn configure(&mut self, parameters: &PortParameters) -> Result<()>;
+    fn read_lines(&mut self) -> LineStatus;
+    fn set_break(&mut self, enabled: bool) -> Result<()>;
+    fn set_dtr(&mut self, level: bool) -> Result<()>;
+    fn set_rts(&mut self, level: bool) -> Result<()>;
+
+    // What a replay has besides the received data (i.e., what was sent and
+    // bridged), which no other connection has
+    fn read_replayed(&mut self) -> Vec<(Origin, Vec<u8>)> {
+        return Vec::new();
     }
-    return success;
+
+    fn control_replay(&mut self, _control: ReplayControl) {}
 }
 
-pub fn get_available_devices() -> Result<Vec<String>> {
-    let mut devices = Vec::new();
-    let ports = serialport::available_ports()?;
-    for port in ports {
-        devices.push(port.port_name);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum DeviceKind {
+    Usb,
+    Pci,
+    Bluetooth,
+    Loopback,
+    #[default]
+    Unknown,
+}
+
+// A device as it was listed, where all but the name are only known for a USB
+// device (and even then only what it reports)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub kind: DeviceKind,
+    pub vid_pid: Option<(u16, u16)>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// The port timeout paces the session loop, it bounds how long queued writes
+// and commands (e.g., close) wait on an idle line
+const DEFAULT_TIMEOUT_MS: u32 = 100;
+const READ_CHUNK: usize = 256;
+// While bridging, neither port is left blocking the other, so the loop
+// sleeps this long when both are idle
+const BRIDGE_POLL: Duration = Duration::from_millis(1);
+// A blocked write is abandoned after this long, regardless of the timeout
+const WRITE_RETRY_MS: u32 = 5000;
+const DISCONNECT_LIMIT: usize = 3;
+// DTR is held low this long to reset the target when connecting
+const RESET_PULSE: Duration = Duration::from_millis(100);
+const RECONNECT_BACKOFF: [u64; 3] = [1, 2, 5];
+// The silence marked in the received data, unless set otherwise
+const DEFAULT_GAP_MS: u32 = 50;
+// How long a probe waits on the port to open
+const PROBE_TIMEOUT: Duration = Duration::from_millis(100);
+// Any rate does to find out whether a port opens
+const PROBE_BAUD_RATE: u32 = 9600;
+// How many ports are opened at once when probing the devices
+const PROBE_WORKERS: usize = 4;
+// How long Start waits on a port that is not listed to open, as a device
+// node can hang in its open
+const PROBE_WAIT: Duration = Duration::from_millis(500);
+// How long a test from the menu listens before closing the port
+const TEST_DURATION: Duration = Duration::from_secs(1);
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl NolpPort for Box<dyn SerialPort> {
+    fn bytes_to_read(&self) -> Result<u32> {
+        return Ok(SerialPort::bytes_to_read(self.as_ref())?);
+    }
+
+    fn clear_all(&mut self) -> Result<()> {
+        self.clear(ClearBuffer::All)?;
+        return Ok(());
+    }
+
+    fn clear_input(&mut self) -> Result<()> {
+        self.clear(ClearBuffer::Input)?;
+        return Ok(());
+    }
+
+    fn configure(&mut self, parameters: &PortParameters) -> Result<()> {
+        self.set_baud_rate(parameters.baud_rate.unwrap())?;
+        self.set_data_bits(get_data_bits(parameters))?;
+        self.set_stop_bits(get_stop_bits(parameters))?;
+        self.set_parity(get_parity(parameters))?;
+        self.set_flow_control(get_flow_control(parameters))?;
+        return Ok(());
+    }
+
+    fn read_lines(&mut self) -> LineStatus {
+        return LineStatus {
+            cts: self.read_clear_to_send().ok(),
+            dsr: self.read_data_set_ready().ok(),
+            cd: self.read_carrier_detect().ok(),
+            ri: self.read_ring_indicator().ok(),
+        };
     }
+
+    fn set_break(&mut self, enabled: bool) -> Result<()> {
+        match enabled {
+            true => SerialPort::set_break(self.as_ref())?,
+            false => self.clear_break()?,
+        }
+        return Ok(());
+    }
+
+    fn set_dtr(&mut self, level: bool) -> Result<()> {
+        self.write_data_terminal_ready(level)?;
+        return Ok(());
+    }
+
+    fn set_rts(&mut self, level: bool) -> Result<()> {
+        self.write_request_to_send(level)?;
+        return Ok(());
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_available_devices() -> Result<Vec<String>> {
+    let devices = get_device_info()?;
+    return Ok(devices.into_iter().map(|device| device.name).collect());
+}
+
+pub fn get_device_info() -> Result<Vec<DeviceInfo>> {
+    let mut devices: Vec<DeviceInfo> = serialport::available_ports()?.into_iter().map(get_info).collect();
+    // Always available, so the application can be tried without a device
+    devices.push(DeviceInfo {
+        name: String::from(LOOPBACK_PORT),
+        kind: DeviceKind::Loopback,
+        ..Default::default()
+    });
     return Ok(devices);
 }
 
-pub fn get_error(error: &SerialError) -> Option<String> {
-    let mut e_lock = error.try_lock();
-    if let Ok(ref mut e_mutex) = e_lock {
-        if (**e_mutex).is_some() {
-            let msg = (**e_mutex).clone().unwrap();
-            (**e_mutex) = None;
-            return Some(msg);
+pub fn get_port(parameters: PortParameters) -> Result<SerialPortBuilder> {
+    let timeout = Duration::from_millis(u64::from(get_timeout(&parameters)));
+    let port = serialport::new(parameters.name.clone().unwrap(), parameters.baud_rate.unwrap())
+        .data_bits(get_data_bits(&parameters))
+        .stop_bits(get_stop_bits(&parameters))
+        .parity(get_parity(&parameters))
+        .flow_control(get_flow_control(&parameters))
+        .timeout(timeout);
+
+    return Ok(port);
+}
+
+pub fn get_stats(stats: &SharedStats) -> SerialStats {
+    match stats.lock() {
+        Ok(s) => return s.clone(),
+        Err(_) => return SerialStats::default(),
+    }
+}
+
+pub fn get_timeout(parameters: &PortParameters) -> u32 {
+    match parameters.timeout_ms {
+        Some(t) if t > 0 => t,
+        _ => DEFAULT_TIMEOUT_MS,
+    }
+}
+
+pub fn open_serial(parameters: &PortParameters) -> Result<Box<dyn SerialPort>> {
+    let port = get_port(parameters.clone())?;
+    return Ok(port.open()?);
+}
+
+pub fn probe_port(parameters: &PortParameters) -> Result<(), SerialErrorKind> {
+    // Opening twice would disturb what is connected to a port that is to be
+    // left as is (e.g., DTR resets some boards), so those are not probed
+    if parameters.read_only || parameters.dtr_on_open == Some(false) {
+        return Ok(());
+    }
+    let port = match get_port(parameters.clone()) {
+        Ok(port) => port.timeout(PROBE_TIMEOUT),
+        Err(_) => return Ok(()),
+    };
+    // Opened by a worker, closing it again before answering, so that a port
+    // that hangs holds up the caller no longer than the wait, and is given
+    // up on rather than opened by the session while the probe may hold it
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(port.open().map(|_| ()).map_err(|e| get_open_error(&anyhow::Error::from(e))));
+    });
+    match receiver.recv_timeout(PROBE_WAIT) {
+        Ok(result) => return result,
+        Err(_) => {
+            let reason = format!("no answer within {}ms", PROBE_WAIT.as_millis());
+            return Err(SerialErrorKind::OpenFailed { reason });
         }
-        drop(e_lock);
     }
-    return None;
 }
 
-pub fn get_port(parameters: PortParameters) -> Result<SerialPortBuilder> {
-    let timeout = Duration::from_secs(10);
-    let data_bits = match parameters.data_bits.unwrap() {
+pub fn probe_devices(names: Vec<String>, session: Option<&str>) -> SharedProbes {
+    // Never the port of the session (nor a pseudo-port), as opening it toggles
+    // DTR and RTS on most platforms, which resets some boards
+    let probed = |name: &String| {
+        let pseudo = [TCP_PREFIX, REPLAY_PREFIX, LOOPBACK_PORT].iter().any(|p| name.starts_with(p));
+        return pseudo == false && Some(name.as_str()) != session;
+    };
+    let queue: VecDeque<String> = names.into_iter().filter(probed).collect();
+    // A few workers take the ports in turn, so that a port slow to open (e.g.,
+    // over Bluetooth, which connects as it opens) only holds up its own worker,
+    // where each port is closed again as soon as it opened
+    let workers = PROBE_WORKERS.min(queue.len());
+    let queue = Arc::new(Mutex::new(queue));
+    let probes = SharedProbes::default();
+    for _ in 0..workers {
+        let queue = Arc::clone(&queue);
+        let probes = Arc::clone(&probes);
+        thread::spawn(move || loop {
+            let name = match queue.lock() {
+                Ok(mut queue) => queue.pop_front(),
+                Err(_) => None,
+            };
+            let name = match name {
+                Some(name) => name,
+                None => return,
+            };
+            let port = serialport::new(name.clone(), PROBE_BAUD_RATE).timeout(PROBE_TIMEOUT);
+            let result = port.open().map(|_| ()).map_err(|e| get_open_error(&anyhow::Error::from(e)));
+            if let Ok(mut probes) = probes.lock() {
+                probes.push((name, result));
+            }
+        });
+    }
+    return probes;
+}
+
+pub fn test_port<P: NolpPort>(
+    parameters: &PortParameters,
+    open: impl Fn(&PortParameters) -> Result<P>,
+    events: &EventSender,
+) {
+    // Opened as a session would be, then closed once the time is up (or the
+    // port fails), with nothing sent
+    let result = match open_port(&open, parameters) {
+        Ok(mut connection) => {
+            let deadline = Instant::now() + TEST_DURATION;
+            let mut received = 0;
+            while Instant::now() < deadline {
+                match read_data(&mut connection, false) {
+                    Ok(data) => received += data.len(),
+                    Err(_) => break,
+                }
+            }
+            Ok(received)
+        }
+        Err(e) => Err(get_open_error(&e)),
+    };
+    send_event(events, SerialEvent::Tested(result));
+}
+
+pub fn read_write_port<P: NolpPort>(
+    mut parameters: PortParameters,
+    open: impl Fn(&PortParameters) -> Result<P>,
+    commands: &CommandReceiver,
+    tx: &DataReceiver,
+    events: &EventSender,
+    stats: &SharedStats,
+    rx: &SharedRx,
+) -> bool {
+    // Runs the session on the calling thread until the port is closed, the
+    // return value is whether a shutdown was requested in the meantime
+    let mut connection = match open_port(&open, &parameters) {
+        Ok(c) => c,
+        Err(e) => {
+            send_event(events, SerialEvent::Failed(get_open_error(&e)));
+            return false;
+        }
+    };
+    let mut bridge = match open_bridge(&open, &parameters) {
+        Ok(b) => b,
+        Err(e) => {
+            let error = match get_open_error(&e) {
+                SerialErrorKind::OpenFailed { reason } => SerialErrorKind::OpenFailed {
+                    reason: format!("{} (bridge port)", reason),
+                },
+                error => error,
+            };
+            send_event(events, SerialEvent::Failed(error));
+            return false;
+        }
+    };
+    // Data queued while the port was closed belongs to an earlier session,
+    // both buffers are emptied before the terminal is told it is connected
+    clear_buffers(tx, rx);
+    if let Ok(mut r_mutex) = rx.lock() {
+        r_mutex.set_gap(get_gap(&parameters));
+    }
+    reset_stats(stats);
+    send_event(events, SerialEvent::Status(ConnectionStatus::Connected));
+    // The target is reset before the first read, so none of its boot output
+    // is missed
+    if parameters.reset_on_connect && parameters.read_only == false {
+        match pulse_reset(&mut connection) {
+            Ok(_) => send_event(events, SerialEvent::Reset),
+            Err(_) => {
+                let error = SerialErrorKind::Other(String::from("Reset failed"));
+                send_event(events, SerialEvent::Error(error));
+            }
+        }
+    }
+
+    let retry_limit = get_retry_limit(&parameters);
+    let mut pending = VecDeque::new();
+    let mut next_write = Instant::now();
+    let mut generator: Option<Generator> = None;
+    let mut lines = LineStatus::default();
+    let mut retries = 0_usize;
+    let mut failures = 0_usize;
+    let mut disconnected = false;
+    let mut shutdown = false;
+    let mut queued = VecDeque::new();
+    'session: loop {
+        loop {
+            // Those held while reconnecting come first, in the order sent
+            let command = match queued.pop_front() {
+                Some(command) => Ok(command),
+                None => commands.try_recv(),
+            };
+            match command {
+                Ok(SerialCommand::Close) => break 'session,
+                Ok(SerialCommand::Shutdown) | Err(TryRecvError::Disconnected) => {
+                    shutdown = true;
+                    break 'session;
+                }
+                Ok(SerialCommand::Open(_)) => {}
+                Ok(SerialCommand::Configure(updated)) => {
+                    if connection.configure(&updated).is_ok() {
+                        parameters = updated;
+                        send_event(events, SerialEvent::Reconfigured(parameters.clone()));
+                    } else {
+                        // The previous settings are restored, a partially
+                        // applied configuration would garble the data
+                        let _ = connection.configure(&parameters);
+                        send_event(events, SerialEvent::Error(SerialErrorKind::Other(String::from("Reconfigure failed"))));
+                    }
+                }
+                Ok(SerialCommand::Flush) => {
+                    // Anything not yet written is discarded along with the
+                    // driver buffers, so the next data shown is new
+                    pending.clear();
+                    retries = 0;
+                    while tx.try_recv().is_ok() {}
+                    let mut result = connection.clear_all();
+                    if let Some(b) = bridge.as_mut() {
+                        result = result.and(b.clear_all());
+                    }
+                    if let Ok(mut r_mutex) = rx.lock() {
+                        r_mutex.clear();
+                    }
+                    match result {
+                        Ok(_) => send_event(events, SerialEvent::Flushed),
+                        Err(_) => {
+                            let error = SerialErrorKind::Other(String::from("Flush failed"));
+                            send_event(events, SerialEvent::Error(error));
+                        }
+                    }
+                }
+                Ok(SerialCommand::Replay(control)) => connection.control_replay(control),
+                // The control lines are left as they are in read-only mode
+                Ok(_) if parameters.read_only => {}
+                Ok(SerialCommand::Generate(started)) => generator = Some(started),
+                Ok(SerialCommand::StopGenerating) => generator = None,
+                Ok(command) => {
+                    let message = match command {
+                        SerialCommand::Break(_) => "Break failed",
+                        _ => "Line control failed",
+                    };
+                    if apply_command(&mut connection, command).is_err() {
+                        let error = SerialErrorKind::Other(String::from(message));
+                        send_event(events, SerialEvent::Error(error));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        // Nothing is ever transmitted in read-only mode, including queued data
+        for data in tx.try_iter() {
+            if parameters.read_only == false {
+                pending.extend(data);
+            }
+        }
+        // The pattern only fills the gaps, so what is typed still goes out
+        if let Some(g) = generator.as_mut().filter(|_| pending.len() == 0) {
+            pending = VecDeque::from(g.take(Instant::now()));
+        }
+        if pending.len() > 0 {
+            let length = pending.len();
+            let result = match parameters.rs485 {
+                true => write_half_duplex(&mut connection, &mut pending, &mut retries, &mut next_write, &parameters),
+                false => {
+                    write_paced(&mut connection, &mut pending, &mut retries, retry_limit, &mut next_write, &parameters)
+                }
+            };
+            if let Some(error) = result {
+                update_stats(stats, |s| s.write_errors += 1);
+                if error == SerialErrorKind::Disconnected {
+                    disconnected = true;
+                } else {
+                    send_event(events, SerialEvent::Error(error));
+                    failures += 1;
+                }
+            } else {
+                update_stats(stats, |s| s.tx_bytes += (length - pending.len()) as u64);
+            }
+        }
+
+        // Each port forwards what it receives to the other while bridging,
+        // unless nothing may be transmitted
+        let forward = parameters.read_only == false;
+        let poll = bridge.is_some();
+        let mut idle = true;
+        match read_data(&mut connection, poll) {
+            Ok(data) if data.len() > 0 => {
+                if let Some(b) = bridge.as_mut().filter(|_| forward) {
+                    if b.write_all(&data).is_err() {
+                        send_event(events, SerialEvent::Error(SerialErrorKind::WriteFailed));
+                    }
+                }
+                receive_data(stats, rx, events, Origin::Primary, &data);
+                idle = false;
+                failures = 0;
+            }
+            Ok(_) => {}
+            Err(ref e) if is_disconnect(e) => {
+                update_stats(stats, |s| s.read_errors += 1);
+                disconnected = true;
+            }
+            Err(_) => {
+                update_stats(stats, |s| s.read_errors += 1);
+                send_event(events, SerialEvent::Error(SerialErrorKind::ReadFailed));
+                failures += 1;
+            }
+        }
+
+        for (origin, data) in connection.read_replayed() {
+            receive_data(stats, rx, events, origin, &data);
+        }
+
+        if let Some(b) = bridge.as_mut() {
+            match read_data(b, poll) {
+                Ok(data) if data.len() > 0 => {
+                    if forward && connection.write_all(&data).is_err() {
+                        send_event(events, SerialEvent::Error(SerialErrorKind::WriteFailed));
+                    }
+                    receive_data(stats, rx, events, Origin::Bridge, &data);
+                    idle = false;
+                }
+                Ok(_) => {}
+                Err(ref e) if is_disconnect(e) => {
+                    update_stats(stats, |s| s.read_errors += 1);
+                    disconnected = true;
+                }
+                Err(_) => {
+                    update_stats(stats, |s| s.read_errors += 1);
+                    send_event(events, SerialEvent::Error(SerialErrorKind::ReadFailed));
+                    failures += 1;
+                }
+            }
+            if idle {
+                thread::sleep(BRIDGE_POLL);
+            }
+        }
+
+        let current = connection.read_lines();
+        if current != lines {
+            lines = current;
+            send_event(events, SerialEvent::Lines(lines.clone()));
+        }
+
+        // Repeated failures are treated as the device having disappeared,
+        // so the port is closed and reopened once it is available again
+        if disconnected || failures >= DISCONNECT_LIMIT {
+            drop(connection);
+            drop(bridge);
+            pause_uptime(stats);
+            let status = match disconnected {
+                true => ConnectionStatus::Disconnected,
+                false => ConnectionStatus::Reconnecting,
+            };
+            send_event(events, SerialEvent::Status(status));
+            match reconnect(&open, &parameters, commands, &mut queued, &mut shutdown) {
+                Some((c, b)) => {
+                    connection = c;
+                    bridge = b;
+                    failures = 0;
+                    disconnected = false;
+                    update_stats(stats, |s| s.started = Some(Instant::now()));
+                    send_event(events, SerialEvent::Status(ConnectionStatus::Connected));
+                }
+                None => {
+                    send_event(events, SerialEvent::Status(ConnectionStatus::Closed));
+                    send_event(events, SerialEvent::Lines(LineStatus::default()));
+                    return shutdown;
+                }
+            }
+        }
+    }
+
+    // Restore the control lines to their state when the port was opened
+    if parameters.read_only == false {
+        let _ = connection.set_dtr(parameters.dtr_on_open.unwrap_or(true));
+        let _ = connection.set_rts(!parameters.rs485);
+        let _ = connection.flush();
+    }
+    drop(connection);
+    drop(bridge);
+    // Whatever the terminal did not pick up is discarded with the session
+    clear_buffers(tx, rx);
+    send_event(events, SerialEvent::Status(ConnectionStatus::Closed));
+    send_event(events, SerialEvent::Lines(LineStatus::default()));
+    return shutdown;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn apply_command(connection: &mut impl NolpPort, command: SerialCommand) -> Result<()> {
+    match command {
+        SerialCommand::SetDtr(level) => connection.set_dtr(level)?,
+        SerialCommand::SetRts(level) => connection.set_rts(level)?,
+        SerialCommand::Break(duration) => {
+            connection.set_break(true)?;
+            thread::sleep(duration);
+            connection.set_break(false)?;
+        }
+        _ => {}
+    }
+    return Ok(());
+}
+
+fn clear_buffers(tx: &DataReceiver, rx: &SharedRx) {
+    while tx.try_recv().is_ok() {}
+    if let Ok(mut r_mutex) = rx.lock() {
+        r_mutex.clear();
+        r_mutex.take_dropped();
+    }
+}
+
+fn get_gap(parameters: &PortParameters) -> Option<Duration> {
+    match parameters.gap_markers {
+        Some(GapMarkers::Shown) | Some(GapMarkers::Exported) => {
+            let gap_ms = parameters.gap_ms.unwrap_or(DEFAULT_GAP_MS);
+            return Some(Duration::from_millis(u64::from(gap_ms)));
+        }
+        _ => return None,
+    }
+}
+
+fn get_data_bits(parameters: &PortParameters) -> DataBits {
+    match parameters.data_bits.unwrap() {
         5 => DataBits::Five,
         6 => DataBits::Six,
         7 => DataBits::Seven,
         8 => DataBits::Eight,
         _ => unreachable!(),
+    }
+}
+
+fn get_flow_control(parameters: &PortParameters) -> SFlowControl {
+    match parameters.flow_control.clone().unwrap_or(FlowControl::None) {
+        FlowControl::Hardware => SFlowControl::Hardware,
+        FlowControl::Software => SFlowControl::Software,
+        FlowControl::None => SFlowControl::None,
+    }
+}
+
+fn get_info(port: SerialPortInfo) -> DeviceInfo {
+    let mut device = DeviceInfo {
+        name: port.port_name,
+        ..Default::default()
     };
-    let stop_bits = match parameters.stop_bits.unwrap() {
-        1 => StopBits::One,
-        2 => StopBits::Two,
-        _ => unreachable!(),
+    match port.port_type {
+        SerialPortType::UsbPort(usb) => {
+            // A blank string is as good as none
+            let text = |s: Option<String>| s.map(|s| String::from(s.trim())).filter(|s| s.len() > 0);
+            device.kind = DeviceKind::Usb;
+            device.vid_pid = Some((usb.vid, usb.pid));
+            device.manufacturer = text(usb.manufacturer);
+            device.product = text(usb.product);
+            device.serial_number = text(usb.serial_number);
+        }
+        SerialPortType::PciPort => device.kind = DeviceKind::Pci,
+        SerialPortType::BluetoothPort => device.kind = DeviceKind::Bluetooth,
+        SerialPortType::Unknown => device.kind = DeviceKind::Unknown,
+    }
+    return device;
+}
+
+fn get_open_error(error: &anyhow::Error) -> SerialErrorKind {
+    // Both serial and socket errors are mapped, anything else is described
+    // by the error itself
+    let kind = if let Some(e) = error.downcast_ref::<serialport::Error>() {
+        match e.kind() {
+            serialport::ErrorKind::Io(kind) => Some(kind),
+            serialport::ErrorKind::NoDevice => Some(ErrorKind::NotFound),
+            _ => None,
+        }
+    } else {
+        error.downcast_ref::<io::Error>().map(|e| e.kind())
     };
-    let parity = match parameters.parity.unwrap() {
+
+    let reason = error.to_string();
+    // The driver only reports an exclusively opened port in its description
+    if reason.to_lowercase().contains("busy") {
+        return SerialErrorKind::Busy;
+    }
+    match kind {
+        Some(ErrorKind::PermissionDenied) => return SerialErrorKind::PermissionDenied,
+        Some(ErrorKind::AddrInUse) => return SerialErrorKind::Busy,
+        _ => return SerialErrorKind::OpenFailed { reason },
+    }
+}
+
+fn get_parity(parameters: &PortParameters) -> SParity {
+    match parameters.parity.clone().unwrap() {
         Parity::Even => SParity::Even,
         Parity::Odd => SParity::Odd,
         Parity::None => SParity::None,
+    }
+}
+
+fn get_retry_limit(parameters: &PortParameters) -> usize {
+    let retries = WRITE_RETRY_MS / get_timeout(parameters);
+    return usize::try_from(retries.max(1)).unwrap_or(1);
+}
+
+fn get_stop_bits(parameters: &PortParameters) -> StopBits {
+    match parameters.stop_bits.unwrap() {
+        1 => StopBits::One,
+        2 => StopBits::Two,
+        _ => unreachable!(),
+    }
+}
+
+fn is_disconnect(error: &io::Error) -> bool {
+    // Unplugging an adapter is reported differently by each platform, these
+    // are the errors that mean the device is gone rather than the line quiet
+    match error.kind() {
+        ErrorKind::BrokenPipe
+        | ErrorKind::NotFound
+        | ErrorKind::NotConnected
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::ConnectionReset
+        | ErrorKind::UnexpectedEof => return true,
+        _ => {}
+    }
+    // i.e., EIO, ENXIO, and ENODEV
+    #[cfg(unix)]
+    let codes = [5, 6, 19];
+    // i.e., ERROR_ACCESS_DENIED, ERROR_BAD_COMMAND, ERROR_GEN_FAILURE,
+    // ERROR_OPERATION_ABORTED, and ERROR_DEVICE_NOT_CONNECTED
+    #[cfg(windows)]
+    let codes = [5, 22, 31, 995, 1167];
+    #[cfg(not(any(unix, windows)))]
+    let codes: [i32; 0] = [];
+    match error.raw_os_error() {
+        Some(code) => return codes.contains(&code),
+        None => return false,
+    }
+}
+
+fn open_bridge<P: NolpPort>(
+    open: &impl Fn(&PortParameters) -> Result<P>,
+    parameters: &PortParameters,
+) -> Result<Option<P>> {
+    // The bridge port shares the settings of the primary port
+    match &parameters.bridge {
+        Some(name) => {
+            let mut bridge_parameters = parameters.clone();
+            bridge_parameters.name = Some(name.clone());
+            bridge_parameters.bridge = None;
+            return Ok(Some(open_port(open, &bridge_parameters)?));
+        }
+        None => return Ok(None),
+    }
+}
+
+fn open_port<P: NolpPort>(
+    open: &impl Fn(&PortParameters) -> Result<P>,
+    parameters: &PortParameters,
+) -> Result<P> {
+    let mut connection = open(parameters)?;
+    // A read-only port is opened as is, so the bus being monitored is not
+    // disturbed
+    if parameters.read_only {
+        return Ok(connection);
+    }
+    // The operating system asserts DTR on open, which resets some boards
+    // (e.g., Arduino), so it is released as soon as possible when requested
+    if parameters.dtr_on_open == Some(false) {
+        connection.set_dtr(false)?;
+    }
+    // RS-485 transceivers are left receiving until there is data to write
+    if parameters.rs485 {
+        connection.set_rts(false)?;
+    }
+    return Ok(connection);
+}
+
+fn pause_uptime(stats: &SharedStats) {
+    update_stats(stats, |s| {
+        if let Some(started) = s.started.take() {
+            s.uptime += started.elapsed();
+        }
+    });
+}
+
+fn pulse_reset(connection: &mut impl NolpPort) -> Result<()> {
+    // The pulse ends with DTR asserted, regardless of its state on open
+    connection.set_dtr(false)?;
+    thread::sleep(RESET_PULSE);
+    connection.set_dtr(true)?;
+    return Ok(());
+}
+
+fn read_data(connection: &mut impl NolpPort, poll: bool) -> io::Result<Vec<u8>> {
+    // Everything already received is read at once, otherwise the read waits
+    // up to the timeout for the next byte (unless only polling)
+    let available = match connection.bytes_to_read() {
+        Ok(n) => n as usize,
+        Err(_) if poll => return Err(io::Error::from(ErrorKind::BrokenPipe)),
+        Err(_) => 0,
     };
+    if poll && available == 0 {
+        return Ok(Vec::new());
+    }
 
-    let port = serialport::new(parameters.name.unwrap(), parameters.baud_rate.unwrap())
-        .data_bits(data_bits)
-        .stop_bits(stop_bits)
-        .parity(parity)
-        .timeout(timeout);
+    let mut buffer = vec![0; available.clamp(1, READ_CHUNK)];
+    match connection.read(&mut buffer) {
+        Ok(n) => {
+            buffer.truncate(n);
+            return Ok(buffer);
+        }
+        Err(ref e) if e.kind() == ErrorKind::TimedOut => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    }
+}
 
-    return Ok(port);
+fn receive_data(
+    stats: &SharedStats,
+    rx: &SharedRx,
+    events: &EventSender,
+    origin: Origin,
+    data: &[u8],
+) {
+    match origin {
+        Origin::Sent => update_stats(stats, |s| s.tx_bytes += data.len() as u64),
+        _ => update_stats(stats, |s| s.rx_bytes += data.len() as u64),
+    }
+    let notify = match rx.lock() {
+        Ok(mut r_mutex) => r_mutex.push(origin, data),
+        Err(_) => false,
+    };
+    if notify {
+        send_event(events, SerialEvent::RxReady);
+    }
 }
 
-pub fn open_connection(
-    flag: &SerialFlag,
-    serial_params: &SerialParams,
-    port_params: PortParameters,
-) -> bool {
-    let mut success = false;
-    let mut p_lock = serial_params.try_lock();
-    let mut f_lock = flag.try_lock();
-    if let Ok(ref mut p_mutex) = p_lock {
-        if let Ok(ref mut f_mutex) = f_lock {
-            **p_mutex = port_params.clone();
-            **f_mutex = true;
-            success = true;
-        }         
-        drop(f_lock);
-        drop(p_lock);
-    }     
-    return success;
-}
-
-pub fn read_write_port(
-    port: SerialPortBuilder,
-    rx: &SerialBuffer,
-    tx: &SerialBuffer,
-    flag: &SerialFlag,
-    error: &SerialError,
-) -> thread::JoinHandle<()> {
-    let rx_handle = Arc::clone(&rx);
-    let tx_handle = Arc::clone(&tx);
-    let f_handle = Arc::clone(&flag);
-    let e_handle = Arc::clone(&error);
-    thread::spawn(move || {
-        let mut connection;
-        let mut f = true;
-        let f_lock = f_handle.try_lock();
-        if let Ok(ref f_mutex) = f_lock {
-            f = (**f_mutex).clone();
-            drop(f_lock);
-        }
-        match port.open() {
-            Ok(c) => connection = c,
-            Err(_) => {
-                let mut e_lock = e_handle.try_lock();
-                if let Ok(ref mut e_mutex) = e_lock {
-                    **e_mutex = Some(String::from(" Failed to open port "));
-                    drop(e_lock);
+fn reconnect<P: NolpPort>(
+    open: &impl Fn(&PortParameters) -> Result<P>,
+    parameters: &PortParameters,
+    commands: &CommandReceiver,
+    queued: &mut VecDeque<SerialCommand>,
+    shutdown: &mut bool,
+) -> Option<(P, Option<P>)> {
+    // The backoff is spent waiting on the command channel so that a close
+    // or shutdown request ends the attempt immediately, anything else (e.g.,
+    // a change of DTR) is held until the port is open again
+    let mut attempt = 0_usize;
+    loop {
+        let backoff = RECONNECT_BACKOFF[attempt.min(RECONNECT_BACKOFF.len() - 1)];
+        let deadline = Instant::now() + Duration::from_secs(backoff);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match commands.recv_timeout(remaining) {
+                Ok(SerialCommand::Close) => return None,
+                Ok(SerialCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                    *shutdown = true;
+                    return None;
                 }
-                return;
+                Ok(command) => queued.push_back(command),
+                Err(RecvTimeoutError::Timeout) => break,
             }
+        }
+
+        if let Ok(connection) = open_port(open, parameters) {
+            if let Ok(bridge) = open_bridge(open, parameters) {
+                return Some((connection, bridge));
+            }
+        }
+        attempt += 1;
+    }
+}
+
+fn reset_stats(stats: &SharedStats) {
+    if let Ok(mut s_mutex) = stats.lock() {
+        *s_mutex = SerialStats {
+            started: Some(Instant::now()),
+            ..SerialStats::default()
         };
+    }
+}
 
-        while f == true {
-            let mut tx_lock = tx_handle.try_lock();
-            if let Ok(ref mut tx_mutex) = tx_lock {
-                if (**tx_mutex).len() > 0 {
-                    match connection.write(tx_mutex) {
-                        Ok(_) => {
-                            (**tx_mutex).clear();
-                        }
-                        Err(ref e) if e.kind() == ErrorKind::TimedOut => {}
-                        Err(_) => {
-                            let mut e_lock = e_handle.try_lock();
-                            if let Ok(ref mut e_mutex) = e_lock {
-                                **e_mutex = Some(String::from(" Write failed "));
-                                drop(e_lock);
-                            }
-                        }
-                    };
-                    (**tx_mutex).clear();
-                }
-                drop(tx_lock);
+fn send_event(events: &EventSender, event: SerialEvent) {
+    // The receiver is only dropped once the application is stopping
+    let _ = events.send(event);
+}
+
+fn update_stats(stats: &SharedStats, update: impl FnOnce(&mut SerialStats)) {
+    // Blocking since the only other user is the UI taking a snapshot
+    if let Ok(mut s_mutex) = stats.lock() {
+        update(&mut s_mutex);
+    }
+}
+
+fn write_buffer(
+    writer: &mut impl Write,
+    buffer: &mut VecDeque<u8>,
+    retries: &mut usize,
+    limit: usize,
+) -> Option<SerialErrorKind> {
+    // Only the bytes reported as written are removed, the remainder is kept
+    // for the next attempt until the retry limit is reached
+    let timed_out = match writer.write(buffer.make_contiguous()) {
+        Ok(0) => true,
+        Ok(n) => {
+            buffer.drain(..n);
+            *retries = 0;
+            false
+        }
+        Err(ref e) if e.kind() == ErrorKind::TimedOut => true,
+        Err(ref e) if is_disconnect(e) => {
+            buffer.clear();
+            *retries = 0;
+            return Some(SerialErrorKind::Disconnected);
+        }
+        Err(_) => {
+            buffer.clear();
+            *retries = 0;
+            return Some(SerialErrorKind::WriteFailed);
+        }
+    };
+
+    if timed_out {
+        *retries += 1;
+        if *retries >= limit {
+            buffer.clear();
+            *retries = 0;
+            return Some(SerialErrorKind::WriteTimedOut);
+        }
+    }
+
+    return None;
+}
+
+fn write_half_duplex(
+    connection: &mut impl NolpPort,
+    buffer: &mut VecDeque<u8>,
+    retries: &mut usize,
+    next_write: &mut Instant,
+    parameters: &PortParameters,
+) -> Option<SerialErrorKind> {
+    if Instant::now() < *next_write {
+        return None;
+    }
+    // RTS drives the transceiver direction, so it is only asserted while
+    // writing and the flush waits for the data to leave the port
+    if connection.set_rts(true).is_err() {
+        return Some(SerialErrorKind::Other(String::from("Line control failed")));
+    }
+    let limit = get_retry_limit(parameters);
+    let result = write_paced(connection, buffer, retries, limit, next_write, parameters);
+    let _ = connection.flush();
+    if parameters.rs485_discard_echo {
+        let _ = connection.clear_input();
+    }
+    if connection.set_rts(false).is_err() {
+        return Some(SerialErrorKind::Other(String::from("Line control failed")));
+    }
+    return result;
+}
+
+fn write_paced(
+    writer: &mut impl Write,
+    buffer: &mut VecDeque<u8>,
+    retries: &mut usize,
+    limit: usize,
+    next_write: &mut Instant,
+    parameters: &PortParameters,
+) -> Option<SerialErrorKind> {
+    let char_delay = Duration::from_millis(u64::from(parameters.char_delay_ms.unwrap_or(0)));
+    let line_delay = Duration::from_millis(u64::from(parameters.line_delay_ms.unwrap_or(0)));
+    if char_delay.is_zero() && line_delay.is_zero() {
+        return write_buffer(writer, buffer, retries, limit);
+    }
+
+    // Slow devices are given time to process each byte (and line), so a
+    // single byte is written once the previous one is due, and the session
+    // keeps reading and taking commands in between
+    let now = Instant::now();
+    if now < *next_write {
+        return None;
+    }
+    let byte = buffer[0];
+    let mut single = VecDeque::from([byte]);
+    if let Some(error) = write_buffer(writer, &mut single, retries, limit) {
+        buffer.clear();
+        return Some(error);
+    }
+    if single.len() > 0 {
+        // Timed out, the byte is retried on the next attempt
+        return None;
+    }
+    buffer.pop_front();
+    *next_write = match byte {
+        b'\n' => now + char_delay + line_delay,
+        _ => now + char_delay,
+    };
+    return None;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::VecDeque,
+        io::{self, Read},
+        sync::{mpsc, Arc, Mutex},
+    };
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use crate::generator::Pattern;
+
+    struct MockWriter {
+        written: Vec<u8>,
+        responses: VecDeque<io::Result<usize>>,
+    }
+
+    impl Write for MockWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let response = self.responses.pop_front().unwrap_or(Ok(buf.len()));
+            if let Ok(n) = response {
+                let n = n.min(buf.len());
+                self.written.extend_from_slice(&buf[..n]);
+                return Ok(n);
             }
+            return response;
+        }
 
-            let mut rx_lock = rx_handle.try_lock();
-            if let Ok(ref mut rx_mutex) = rx_lock {
-                let mut buffer = vec![0; 1];
-                match connection.read(buffer.as_mut_slice()) {
-                    Ok(_) => {
-                        (**rx_mutex).append(&mut buffer);
-                    }
-                    Err(ref e) if e.kind() == ErrorKind::TimedOut => {}
-                    Err(_) => {
-                        let mut e_lock = e_handle.try_lock();
-                        if let Ok(ref mut e_mutex) = e_lock {
-                            **e_mutex = Some(String::from(" Read failed "));
-                        }
-                    }
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        reads: VecDeque<io::Result<Vec<u8>>>,
+        writes: VecDeque<io::Result<usize>>,
+        written: Vec<u8>,
+        flushed: usize,
+        dtr: Option<bool>,
+        rts: Option<bool>,
+        dtr_levels: Vec<bool>,
+        write_times: Vec<Instant>,
+    }
+
+    // Shares its state with the test, since the port itself is moved into
+    // the session loop
+    #[derive(Clone, Default)]
+    struct FakePort {
+        state: Arc<Mutex<FakeState>>,
+    }
+
+    impl Read for FakePort {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let response = self.state.lock().unwrap().reads.pop_front();
+            match response {
+                Some(Ok(data)) => {
+                    let n = data.len().min(buf.len());
+                    buf[..n].copy_from_slice(&data[..n]);
+                    return Ok(n);
+                }
+                Some(Err(e)) => return Err(e),
+                None => {
+                    thread::sleep(Duration::from_millis(1));
+                    return Err(io::Error::from(ErrorKind::TimedOut));
                 }
-                drop(rx_lock);
             }
+        }
+    }
 
-            let f_lock = f_handle.try_lock();
-            if let Ok(ref f_mutex) = f_lock {
-                f = (**f_mutex).clone();
-                drop(f_lock);
+    impl Write for FakePort {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut state = self.state.lock().unwrap();
+            let response = state.writes.pop_front().unwrap_or(Ok(buf.len()));
+            state.write_times.push(Instant::now());
+            if let Ok(n) = response {
+                let n = n.min(buf.len());
+                state.written.extend_from_slice(&buf[..n]);
+                return Ok(n);
             }
+            return response;
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    impl NolpPort for FakePort {
+        fn bytes_to_read(&self) -> Result<u32> {
+            let state = self.state.lock().unwrap();
+            match state.reads.front() {
+                Some(Ok(data)) => return Ok(data.len() as u32),
+                _ => return Ok(0),
+            }
+        }
+
+        fn clear_all(&mut self) -> Result<()> {
+            self.state.lock().unwrap().flushed += 1;
+            return Ok(());
+        }
+
+        fn clear_input(&mut self) -> Result<()> {
+            return Ok(());
+        }
+
+        fn configure(&mut self, _parameters: &PortParameters) -> Result<()> {
+            return Ok(());
+        }
+
+        fn read_lines(&mut self) -> LineStatus {
+            return LineStatus::default();
+        }
+
+        fn set_break(&mut self, _enabled: bool) -> Result<()> {
+            return Ok(());
+        }
+
+        fn set_dtr(&mut self, level: bool) -> Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.dtr = Some(level);
+            state.dtr_levels.push(level);
+            return Ok(());
+        }
+
+        fn set_rts(&mut self, level: bool) -> Result<()> {
+            self.state.lock().unwrap().rts = Some(level);
+            return Ok(());
+        }
+    }
+
+    struct Session {
+        commands: mpsc::Sender<SerialCommand>,
+        tx: mpsc::Sender<Vec<u8>>,
+        events: UnboundedReceiver<SerialEvent>,
+        stats: SharedStats,
+        rx: SharedRx,
+        handle: thread::JoinHandle<bool>,
+    }
+
+    fn fake_session(port: &FakePort, parameters: PortParameters) -> Session {
+        let (commands, command_receiver) = mpsc::channel();
+        let (tx, data_receiver) = mpsc::channel();
+        let (event_sender, events) = unbounded_channel();
+        let stats = SharedStats::default();
+        let rx = SharedRx::default();
+
+        let port = port.clone();
+        let (t_stats, t_rx) = (stats.clone(), rx.clone());
+        let handle = thread::spawn(move || {
+            let open = |_: &PortParameters| -> Result<FakePort> { Ok(port.clone()) };
+            return read_write_port(
+                parameters,
+                open,
+                &command_receiver,
+                &data_receiver,
+                &event_sender,
+                &t_stats,
+                &t_rx,
+            );
+        });
+
+        Session {
+            commands,
+            tx,
+            events,
+            stats,
+            rx,
+            handle,
+        }
+    }
+
+    fn fake_parameters() -> PortParameters {
+        let mut parameters = PortParameters::default().name(String::from("test/fake"));
+        parameters.baud_rate = Some(9600);
+        parameters.data_bits = Some(8);
+        parameters.stop_bits = Some(1);
+        parameters.parity = Some(Parity::None);
+        parameters.mode = Some(Mode::Ascii);
+        return parameters;
+    }
+
+    fn wait_for_event(session: &mut Session, expected: SerialEvent) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            match session.events.try_recv() {
+                Ok(event) if event == expected => return,
+                Ok(_) => {}
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        panic!("Did not receive {:?}", expected);
+    }
+
+    fn mock_writer(responses: Vec<io::Result<usize>>) -> MockWriter {
+        MockWriter {
+            written: Vec::new(),
+            responses: VecDeque::from(responses),
+        }
+    }
+
+    fn timeout() -> io::Result<usize> {
+        return Err(io::Error::from(ErrorKind::TimedOut));
+    }
+
+    #[test]
+    fn test_write_buffer_short_writes() {
+        let mut writer = mock_writer(vec![Ok(2), Ok(1), Ok(3)]);
+        let mut buffer = VecDeque::from(vec![1, 2, 3, 4, 5, 6]);
+        let mut retries = 0;
+
+        while buffer.len() > 0 {
+            let result = write_buffer(&mut writer, &mut buffer, &mut retries, 3);
+            assert_eq!(result, None);
+        }
+        assert_eq!(writer.written, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_write_buffer_timeout_retry() {
+        let mut writer = mock_writer(vec![timeout(), Ok(2), timeout(), timeout()]);
+        let mut buffer = VecDeque::from(vec![1, 2, 3, 4]);
+        let mut retries = 0;
+
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(buffer, vec![1, 2, 3, 4]);
+        assert_eq!(retries, 1);
+
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(buffer, vec![3, 4]);
+        assert_eq!(retries, 0);
+
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(writer.written, vec![1, 2, 3, 4]);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_write_buffer_retry_limit() {
+        let mut writer = mock_writer(vec![timeout(), Ok(0), timeout()]);
+        let mut buffer = VecDeque::from(vec![1, 2]);
+        let mut retries = 0;
+
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(write_buffer(&mut writer, &mut buffer, &mut retries, 3), None);
+        assert_eq!(
+            write_buffer(&mut writer, &mut buffer, &mut retries, 3),
+            Some(SerialErrorKind::WriteTimedOut)
+        );
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(retries, 0);
+    }
+
+    #[test]
+    fn test_write_buffer_failure() {
+        let mut writer = mock_writer(vec![
+            Err(io::Error::from(ErrorKind::InvalidInput)),
+            Err(io::Error::from(ErrorKind::BrokenPipe)),
+        ]);
+        let mut buffer = VecDeque::from(vec![1, 2]);
+        let mut retries = 0;
+
+        assert_eq!(
+            write_buffer(&mut writer, &mut buffer, &mut retries, 3),
+            Some(SerialErrorKind::WriteFailed)
+        );
+        assert_eq!(buffer.len(), 0);
+
+        buffer = VecDeque::from(vec![1, 2]);
+        assert_eq!(
+            write_buffer(&mut writer, &mut buffer, &mut retries, 3),
+            Some(SerialErrorKind::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_write_paced() {
+        let mut port = FakePort::default();
+        let mut parameters = fake_parameters();
+        let mut retries = 0;
+        let mut next_write = Instant::now();
+
+        // Without delays the buffer is written in bulk
+        let mut buffer = VecDeque::from(b"ab\nc".to_vec());
+        assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        assert_eq!(port.state.lock().unwrap().write_times.len(), 1);
+
+        // Otherwise a byte at a time, nothing is written before it is due
+        port = FakePort::default();
+        parameters.char_delay_ms = Some(5);
+        parameters.line_delay_ms = Some(20);
+        let mut buffer = VecDeque::from(b"ab\nc".to_vec());
+        assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        assert_eq!(buffer, b"b\nc".to_vec());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while buffer.len() > 0 && Instant::now() < deadline {
+            assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let state = port.state.lock().unwrap();
+        assert_eq!(state.written, b"ab\nc".to_vec());
+        let times = &state.write_times;
+        assert_eq!(times.len(), 4);
+        assert!(times[1] - times[0] >= Duration::from_millis(5));
+        assert!(times[2] - times[1] >= Duration::from_millis(5));
+        assert!(times[3] - times[2] >= Duration::from_millis(25));
+    }
+
+    #[test]
+    fn test_write_paced_timeout() {
+        let mut port = FakePort::default();
+        port.state.lock().unwrap().writes.extend([Ok(1), timeout(), Ok(1), Ok(1)]);
+        let mut parameters = fake_parameters();
+        parameters.line_delay_ms = Some(1);
+        let mut retries = 0;
+        let mut next_write = Instant::now();
+
+        // The byte is kept after a timeout, then written on the retry
+        let mut buffer = VecDeque::from(b"abc".to_vec());
+        assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        assert_eq!(buffer, b"bc".to_vec());
+        assert_eq!(retries, 1);
+        while buffer.len() > 0 {
+            assert_eq!(write_paced(&mut port, &mut buffer, &mut retries, 3, &mut next_write, &parameters), None);
+        }
+        assert_eq!(port.state.lock().unwrap().written, b"abc".to_vec());
+    }
+
+    #[test]
+    fn test_get_timeout() {
+        let mut parameters = PortParameters::default();
+        assert_eq!(get_timeout(&parameters), DEFAULT_TIMEOUT_MS);
+        assert_eq!(get_retry_limit(&parameters), 50);
+
+        parameters.timeout_ms = Some(0);
+        assert_eq!(get_timeout(&parameters), DEFAULT_TIMEOUT_MS);
+
+        parameters.timeout_ms = Some(20);
+        assert_eq!(get_timeout(&parameters), 20);
+        assert_eq!(get_retry_limit(&parameters), 250);
+
+        parameters.timeout_ms = Some(10000);
+        assert_eq!(get_retry_limit(&parameters), 1);
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = SharedStats::default();
+        update_stats(&stats, |s| s.tx_bytes += 10);
+        update_stats(&stats, |s| s.read_errors += 1);
+        assert_eq!(get_stats(&stats).tx_bytes, 10);
+        assert_eq!(get_stats(&stats).read_errors, 1);
+
+        reset_stats(&stats);
+        let current = get_stats(&stats);
+        assert_eq!(current.tx_bytes, 0);
+        assert_eq!(current.read_errors, 0);
+        assert!(current.started.is_some());
+    }
+
+    #[test]
+    fn test_reconnect_closed() {
+        let open = |_: &PortParameters| -> Result<FakePort> { Ok(FakePort::default()) };
+        let parameters = PortParameters::default();
+        let (sender, receiver) = mpsc::channel();
+        let mut queued = VecDeque::new();
+        let mut shutdown = false;
+
+        // What is asked for in the meantime is held for the port reopened
+        sender.send(SerialCommand::SetRts(false)).unwrap();
+        sender.send(SerialCommand::Flush).unwrap();
+        sender.send(SerialCommand::Close).unwrap();
+        assert!(reconnect(&open, &parameters, &receiver, &mut queued, &mut shutdown).is_none());
+        assert_eq!(queued, VecDeque::from([SerialCommand::SetRts(false), SerialCommand::Flush]));
+        assert!(!shutdown);
+
+        sender.send(SerialCommand::Shutdown).unwrap();
+        assert!(reconnect(&open, &parameters, &receiver, &mut queued, &mut shutdown).is_none());
+        assert!(shutdown);
+    }
+
+    #[test]
+    fn test_read_write_port_failed_open() {
+        let parameters = PortParameters {
+            name: Some(String::from("test/missing-port")),
+            baud_rate: Some(9600),
+            data_bits: Some(8),
+            stop_bits: Some(1),
+            parity: Some(Parity::None),
+            mode: Some(Mode::Ascii),
+            dtr_on_open: Some(false),
+            ..Default::default()
+        };
+        let (_sender, commands) = mpsc::channel();
+        let (_tx, data) = mpsc::channel();
+        let (events, mut receiver) = unbounded_channel();
+        let stats = SharedStats::default();
+        let rx = SharedRx::default();
+
+        assert!(!read_write_port(parameters, open_serial, &commands, &data, &events, &stats, &rx));
+        assert_eq!(get_stats(&stats), SerialStats::default());
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(SerialEvent::Failed(SerialErrorKind::OpenFailed { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_read_write_port_round_trip() {
+        let port = FakePort::default();
+        port.state.lock().unwrap().reads.push_back(Ok(b"pong".to_vec()));
+        let mut parameters = fake_parameters();
+        parameters.dtr_on_open = Some(false);
+        let mut session = fake_session(&port, parameters);
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        session.tx.send(b"ping".to_vec()).unwrap();
+        wait_for_event(&mut session, SerialEvent::RxReady);
+        assert_eq!(
+            session.rx.lock().unwrap().drain(),
+            vec![(Origin::Primary, b"pong".to_vec(), None)]
+        );
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while get_stats(&session.stats).tx_bytes < 4 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Closed));
+        assert!(!session.handle.join().unwrap());
+
+        let state = port.state.lock().unwrap();
+        assert_eq!(state.written, b"ping".to_vec());
+        assert_eq!(state.dtr, Some(false));
+        assert_eq!(state.rts, Some(true));
+        let stats = get_stats(&session.stats);
+        assert_eq!(stats.tx_bytes, 4);
+        assert_eq!(stats.rx_bytes, 4);
+    }
+
+    #[test]
+    fn test_read_write_port_generate() {
+        let port = FakePort::default();
+        let mut session = fake_session(&port, fake_parameters());
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        let generator = Generator::new(&Pattern::Increment, None);
+        session.commands.send(SerialCommand::Generate(generator)).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while get_stats(&session.stats).tx_bytes < 1000 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        session.commands.send(SerialCommand::StopGenerating).unwrap();
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+
+        // The pattern runs on from one chunk to the next, and stops
+        let state = port.state.lock().unwrap();
+        assert!(state.written.len() >= 1000);
+        assert!(state.written.iter().enumerate().all(|(i, value)| *value == i as u8));
+        assert_eq!(get_stats(&session.stats).tx_bytes, state.written.len() as u64);
+    }
+
+    #[test]
+    fn test_read_write_port_stale_data() {
+        let port = FakePort::default();
+        port.state.lock().unwrap().reads.push_back(Ok(b"stale".to_vec()));
+        let mut session = fake_session(&port, fake_parameters());
+
+        // The received data is never drained before the session is closed
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        wait_for_event(&mut session, SerialEvent::RxReady);
+        assert_eq!(session.rx.lock().unwrap().len(), 5);
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Closed));
+        assert!(!session.handle.join().unwrap());
+        assert_eq!(session.rx.lock().unwrap().len(), 0);
+        assert!(session.rx.lock().unwrap().drain().is_empty());
+    }
+
+    #[test]
+    fn test_read_write_port_reset() {
+        let port = FakePort::default();
+        port.state.lock().unwrap().reads.push_back(Ok(b"boot".to_vec()));
+        let mut parameters = fake_parameters();
+        parameters.dtr_on_open = Some(false);
+        parameters.reset_on_connect = true;
+        let mut session = fake_session(&port, parameters);
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        let start = Instant::now();
+        // The marker must precede the data read after the pulse
+        assert_eq!(session.events.blocking_recv(), Some(SerialEvent::Reset));
+        assert!(start.elapsed() >= RESET_PULSE / 2);
+        wait_for_event(&mut session, SerialEvent::RxReady);
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+        let state = port.state.lock().unwrap();
+        assert_eq!(&state.dtr_levels[..3], &[false, false, true]);
+    }
+
+    #[test]
+    fn test_read_write_port_read_only() {
+        let port = FakePort::default();
+        let mut parameters = fake_parameters();
+        parameters.dtr_on_open = Some(false);
+        parameters.read_only = true;
+        let mut session = fake_session(&port, parameters);
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        session.tx.send(b"ping".to_vec()).unwrap();
+        session.commands.send(SerialCommand::SetRts(false)).unwrap();
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+
+        let state = port.state.lock().unwrap();
+        assert_eq!(state.written.len(), 0);
+        assert_eq!(state.dtr, None);
+        assert_eq!(state.rts, None);
+    }
+
+    #[test]
+    fn test_read_write_port_bridge() {
+        let (port_a, port_b) = (FakePort::default(), FakePort::default());
+        port_a.state.lock().unwrap().reads.push_back(Ok(b"req".to_vec()));
+        port_b.state.lock().unwrap().reads.push_back(Ok(b"resp".to_vec()));
+        let mut parameters = fake_parameters();
+        parameters.bridge = Some(String::from("test/bridge"));
 
+        let (commands, command_receiver) = mpsc::channel();
+        let (_tx, data_receiver) = mpsc::channel();
+        let (event_sender, _events) = unbounded_channel();
+        let rx = SharedRx::default();
+        let (t_a, t_b, t_rx) = (port_a.clone(), port_b.clone(), rx.clone());
+        let handle = thread::spawn(move || {
+            let open = |p: &PortParameters| -> Result<FakePort> {
+                match p.name.as_deref() {
+                    Some("test/bridge") => return Ok(t_b.clone()),
+                    _ => return Ok(t_a.clone()),
+                }
+            };
+            let stats = SharedStats::default();
+            return read_write_port(
+                parameters,
+                open,
+                &command_receiver,
+                &data_receiver,
+                &event_sender,
+                &stats,
+                &t_rx,
+            );
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while rx.lock().unwrap().len() < 7 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            rx.lock().unwrap().drain(),
+            vec![
+                (Origin::Primary, b"req".to_vec(), None),
+                (Origin::Bridge, b"resp".to_vec(), None)
+            ]
+        );
+        commands.send(SerialCommand::Close).unwrap();
+        assert!(!handle.join().unwrap());
+
+        assert_eq!(port_a.state.lock().unwrap().written, b"resp".to_vec());
+        assert_eq!(port_b.state.lock().unwrap().written, b"req".to_vec());
+    }
+
+    #[test]
+    fn test_read_write_port_flush() {
+        let port = FakePort::default();
+        let mut session = fake_session(&port, fake_parameters());
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        session.rx.lock().unwrap().push(Origin::Primary, b"stale");
+
+        session.commands.send(SerialCommand::Flush).unwrap();
+        wait_for_event(&mut session, SerialEvent::Flushed);
+        assert_eq!(session.rx.lock().unwrap().len(), 0);
+        assert_eq!(port.state.lock().unwrap().flushed, 1);
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_read_write_port_write_timeout() {
+        let port = FakePort::default();
+        port.state.lock().unwrap().writes.push_back(timeout());
+        let mut parameters = fake_parameters();
+        parameters.timeout_ms = Some(WRITE_RETRY_MS);
+        let mut session = fake_session(&port, parameters);
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        session.tx.send(vec![1, 2, 3]).unwrap();
+        wait_for_event(&mut session, SerialEvent::Error(SerialErrorKind::WriteTimedOut));
+        assert_eq!(get_stats(&session.stats).write_errors, 1);
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+        assert_eq!(port.state.lock().unwrap().written.len(), 0);
+    }
+
+    #[test]
+    fn test_read_write_port_read_error() {
+        let port = FakePort::default();
+        let error = io::Error::from(ErrorKind::InvalidData);
+        port.state.lock().unwrap().reads.push_back(Err(error));
+        let mut session = fake_session(&port, fake_parameters());
+
+        wait_for_event(&mut session, SerialEvent::Error(SerialErrorKind::ReadFailed));
+        assert_eq!(get_stats(&session.stats).read_errors, 1);
+
+        session.commands.send(SerialCommand::Close).unwrap();
+        assert!(!session.handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_read_write_port_disconnect() {
+        let port = FakePort::default();
+        let error = io::Error::from_raw_os_error(if cfg!(windows) { 1167 } else { 6 });
+        port.state.lock().unwrap().reads.push_back(Err(error));
+        let mut session = fake_session(&port, fake_parameters());
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Disconnected));
+        let stats = get_stats(&session.stats);
+        assert_eq!(stats.read_errors, 1);
+        assert_eq!(stats.started, None);
+
+        // The session waits for the device to return until it is closed
+        session.commands.send(SerialCommand::Close).unwrap();
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Closed));
+        assert!(!session.handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_get_open_error() {
+        let kind = serialport::ErrorKind::Io(ErrorKind::PermissionDenied);
+        let error = anyhow::Error::new(serialport::Error::new(kind, "Permission denied"));
+        assert_eq!(get_open_error(&error), SerialErrorKind::PermissionDenied);
+
+        let kind = serialport::ErrorKind::Unknown;
+        let error = anyhow::Error::new(serialport::Error::new(kind, "Device or resource busy"));
+        assert_eq!(get_open_error(&error), SerialErrorKind::Busy);
+
+        let error = anyhow::Error::new(io::Error::from(ErrorKind::AddrInUse));
+        assert_eq!(get_open_error(&error), SerialErrorKind::Busy);
+
+        let error = anyhow::anyhow!("Invalid address");
+        let reason = String::from("Invalid address");
+        assert_eq!(get_open_error(&error), SerialErrorKind::OpenFailed { reason });
+    }
+
+    #[test]
+    fn test_probe_devices() {
+        // Each port is probed by the few workers, whether it opens or not,
+        // but for the session and the pseudo-ports
+        let missing = |i: usize| format!("/dev/nolp-missing-{:02}", i);
+        let mut names: Vec<String> = (0..PROBE_WORKERS * 2 + 1).map(missing).collect();
+        let expected = names.clone();
+        names.extend([LOOPBACK_PORT, "tcp://localhost:1", "replay:missing.log", "/dev/nolp-session"].map(String::from));
+        let probes = probe_devices(names, Some("/dev/nolp-session"));
+        let start = Instant::now();
+        while probes.lock().unwrap().len() < expected.len() && start.elapsed() < Duration::from_secs(5) {
             thread::sleep(Duration::from_millis(10));
         }
+        thread::sleep(Duration::from_millis(50));
+        let mut probes = probes.lock().unwrap().clone();
+        probes.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(probes.iter().map(|(name, _)| name.clone()).collect::<Vec<String>>(), expected);
+        assert!(probes.iter().all(|(_, result)| result.is_err()));
+        assert_eq!(probe_devices(Vec::new(), None).lock().unwrap().len(), 0);
+    }
 
-        drop(connection);
-    })
+    #[test]
+    fn test_get_info() {
+        let usb = serialport::UsbPortInfo {
+            vid: 0x10C4,
+            pid: 0xEA60,
+            serial_number: Some(String::from("0001")),
+            manufacturer: Some(String::from("Silicon Labs ")),
+            product: Some(String::new()),
+        };
+        let port = SerialPortInfo {
+            port_name: String::from("/dev/ttyUSB0"),
+            port_type: SerialPortType::UsbPort(usb),
+        };
+        let device = get_info(port);
+        assert_eq!((device.name.as_str(), device.kind), ("/dev/ttyUSB0", DeviceKind::Usb));
+        assert_eq!(device.vid_pid, Some((0x10C4, 0xEA60)));
+        assert_eq!(device.manufacturer, Some(String::from("Silicon Labs")));
+        assert_eq!((device.product, device.serial_number), (None, Some(String::from("0001"))));
+
+        // Anything else only has its kind
+        let port = SerialPortInfo {
+            port_name: String::from("COM1"),
+            port_type: SerialPortType::PciPort,
+        };
+        let expected = DeviceInfo {
+            name: String::from("COM1"),
+            kind: DeviceKind::Pci,
+            ..Default::default()
+        };
+        assert_eq!(get_info(port), expected);
+    }
+
+    #[test]
+    fn test_is_disconnect() {
+        assert!(is_disconnect(&io::Error::from(ErrorKind::NotFound)));
+        assert!(is_disconnect(&io::Error::from(ErrorKind::BrokenPipe)));
+        assert!(!is_disconnect(&io::Error::from(ErrorKind::TimedOut)));
+        assert!(!is_disconnect(&io::Error::from(ErrorKind::InvalidData)));
+    }
+
+    #[test]
+    fn test_read_write_port_shutdown() {
+        let port = FakePort::default();
+        let mut session = fake_session(&port, fake_parameters());
+
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Connected));
+        session.commands.send(SerialCommand::Shutdown).unwrap();
+        wait_for_event(&mut session, SerialEvent::Status(ConnectionStatus::Closed));
+        assert!(session.handle.join().unwrap());
+    }
 }
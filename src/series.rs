@@ -0,0 +1,200 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the series graphed from the received lines, the first
+* number of each line in the order it arrived. The data is split into lines as
+* it arrives, so that a line split across reads is still read whole.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::collections::VecDeque;
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    values: VecDeque<f64>,
+    capacity: usize,
+    // What arrived since the last line break
+    line: Vec<u8>,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// The longest line kept until its break, the rest of a longer one is dropped
+// (e.g., binary data read as text)
+const LINE_LIMIT: usize = 256;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Series {
+    pub fn new(capacity: usize) -> Series {
+        Series {
+            values: VecDeque::new(),
+            capacity: capacity.max(1),
+            line: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.line.clear();
+    }
+
+    pub fn get_values(&self) -> &VecDeque<f64> {
+        return &self.values;
+    }
+
+    pub fn get_current(&self) -> Option<f64> {
+        return self.values.back().copied();
+    }
+
+    pub fn get_min(&self) -> Option<f64> {
+        return self.values.iter().copied().reduce(f64::min);
+    }
+
+    pub fn get_max(&self) -> Option<f64> {
+        return self.values.iter().copied().reduce(f64::max);
+    }
+
+    // The values added, a line that holds no number is skipped
+    pub fn update(&mut self, data: &[u8]) -> usize {
+        let mut added = 0;
+        for value in data.iter().copied() {
+            if value != b'\r' && value != b'\n' {
+                if self.line.len() < LINE_LIMIT {
+                    self.line.push(value);
+                }
+                continue;
+            }
+            if let Some(number) = get_number(&self.line) {
+                self.values.push_back(number);
+                added += 1;
+            }
+            self.line.clear();
+        }
+
+        let excess = self.values.len().saturating_sub(self.capacity);
+        self.values.drain(..excess);
+        return added;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_number(line: &[u8]) -> Option<f64> {
+    // The first run of digits, with its fraction, and a sign right before
+    // it unless that is part of a word (e.g., `ID-5` is 5, `t=-5` is -5)
+    let line = &get_text(line);
+    let start = line.iter().enumerate().position(|(i, value)| {
+        value.is_ascii_digit()
+            || (*value == b'.' && line.get(i + 1).is_some_and(|next| next.is_ascii_digit()))
+    })?;
+    let mut end = start;
+    let mut point = false;
+    while end < line.len() {
+        match line[end] {
+            b'0'..=b'9' => {}
+            b'.' if point == false && line.get(end + 1).is_some_and(|next| next.is_ascii_digit()) => {
+                point = true;
+            }
+            _ => break,
+        }
+        end += 1;
+    }
+
+    let signed = start > 0
+        && line[start - 1] == b'-'
+        && (start < 2 || line[start - 2].is_ascii_alphanumeric() == false);
+    let text = String::from_utf8_lossy(&line[start..end]);
+    let number = text.parse::<f64>().ok()?;
+    return match signed {
+        true => Some(-number),
+        false => Some(number),
+    };
+}
+
+fn get_text(line: &[u8]) -> Vec<u8> {
+    // The escape sequences of a colored log are dropped, their parameters
+    // are numbers too
+    let mut text = Vec::with_capacity(line.len());
+    let mut values = line.iter().copied().peekable();
+    while let Some(value) = values.next() {
+        if value != 0x1B {
+            text.push(value);
+            continue;
+        }
+        if values.next_if_eq(&b'[').is_some() {
+            for parameter in values.by_ref() {
+                if (0x40..=0x7E).contains(&parameter) {
+                    break;
+                }
+            }
+        }
+    }
+    return text;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_number() {
+        assert_eq!(get_number(b"temp=23.4C"), Some(23.4));
+        assert_eq!(get_number(b"42"), Some(42.0));
+        assert_eq!(get_number(b"  t=-5.25 degC"), Some(-5.25));
+        assert_eq!(get_number(b"-7"), Some(-7.0));
+        assert_eq!(get_number(b"ID-5"), Some(5.0));
+        assert_eq!(get_number(b"v: .5V"), Some(0.5));
+        assert_eq!(get_number(b"rpm 1200. ok"), Some(1200.0));
+        assert_eq!(get_number(b"1.2.3"), Some(1.2));
+        assert_eq!(get_number(b"\x1B[32mhum 40%\x1B[0m"), Some(40.0));
+        assert_eq!(get_number(b"ready."), None);
+        assert_eq!(get_number(b"\xFF\xFE"), None);
+        assert_eq!(get_number(b""), None);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut series = Series::new(3);
+        assert_eq!(series.get_current(), None);
+
+        // A line split across reads is read once its break arrives
+        assert_eq!(series.update(b"temp=23."), 0);
+        assert_eq!(series.update(b"4C\r\nboot\r\n"), 1);
+        assert_eq!(series.update(b"temp=19.0C\r\ntemp=25.5C\n\n"), 2);
+        assert_eq!(series.get_current(), Some(25.5));
+        assert_eq!(series.get_min(), Some(19.0));
+        assert_eq!(series.get_max(), Some(25.5));
+
+        // Only the latest values are kept
+        series.update(b"1\r\n");
+        assert_eq!(series.get_values(), &VecDeque::from(vec![19.0, 25.5, 1.0]));
+        assert_eq!(series.get_min(), Some(1.0));
+
+        // A line that never breaks is cut short, rather than kept whole
+        series.update(&[b'x'; LINE_LIMIT * 2]);
+        assert_eq!(series.line.len(), LINE_LIMIT);
+        series.clear();
+        assert_eq!(series.update(b"9\r\n"), 1);
+        assert_eq!(series.get_values().len(), 1);
+    }
+}
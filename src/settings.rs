@@ -0,0 +1,246 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the settings kept between runs, which is the last
+* connection started from the menu, kept as the fields it was entered in
+* (`key = "value"` lines, a small subset of TOML) in the configuration
+* directory of the platform, and the recent connections next to it (the same
+* lines, under a `[[recent]]` header each), as well as the ports hidden from
+* the device list (a `port` line each, or a `group` line for a whole group).
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::Result;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const SETTINGS_DIR: &str = "nolp";
+const SETTINGS_FILE: &str = "last.toml";
+const RECENT_FILE: &str = "recent.toml";
+const RECENT_HEADER: &str = "[[recent]]";
+const HIDDEN_FILE: &str = "hidden.toml";
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_settings_path() -> Option<PathBuf> {
+    // As in %APPDATA%, ~/Library/Application Support, or ~/.config
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let config = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.map(|h| h.join("Library").join("Application Support"))
+    } else {
+        match env::var_os("XDG_CONFIG_HOME") {
+            Some(c) if c.len() > 0 => Some(PathBuf::from(c)),
+            _ => home.map(|h| h.join(".config")),
+        }
+    };
+    return config.map(|c| c.join(SETTINGS_DIR).join(SETTINGS_FILE));
+}
+
+pub fn get_recent_path(settings: &Path) -> PathBuf {
+    return settings.with_file_name(RECENT_FILE);
+}
+
+pub fn get_hidden_path(settings: &Path) -> PathBuf {
+    return settings.with_file_name(HIDDEN_FILE);
+}
+
+pub fn read_entries(path: &Path) -> Option<Vec<Vec<(String, String)>>> {
+    let text = fs::read_to_string(path).ok()?;
+    return get_entries(&text);
+}
+
+pub fn read_values(path: &Path) -> Option<Vec<(String, String)>> {
+    // A missing (or unreadable) file is as good as none
+    let text = fs::read_to_string(path).ok()?;
+    return get_values(&text);
+}
+
+pub fn write_entries(path: &Path, entries: &[Vec<(String, String)>]) -> Result<()> {
+    return write_text(path, &get_entries_text(entries));
+}
+
+pub fn write_hidden(path: &Path, values: &[(String, String)]) -> Result<()> {
+    let text = String::from("# The ports hidden from the device list\n");
+    return write_text(path, &(text + &get_lines_text(values)));
+}
+
+pub fn write_values(path: &Path, values: &[(String, String)]) -> Result<()> {
+    return write_text(path, &get_values_text(values));
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_entries(text: &str) -> Option<Vec<Vec<(String, String)>>> {
+    // As for the values, where a value before the first header is corrupt
+    let mut entries: Vec<Vec<(String, String)>> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.len() == 0 || line.starts_with('#') {
+            continue;
+        }
+        if line == RECENT_HEADER {
+            entries.push(Vec::new());
+            continue;
+        }
+        entries.last_mut()?.push(get_value(line)?);
+    }
+    return Some(entries);
+}
+
+fn get_entries_text(entries: &[Vec<(String, String)>]) -> String {
+    let mut text = String::from("# The recent connections, the latest first\n");
+    for values in entries {
+        text += &format!("\n{}\n", RECENT_HEADER);
+        text += &get_lines_text(values);
+    }
+    return text;
+}
+
+fn get_lines_text(values: &[(String, String)]) -> String {
+    let mut text = String::new();
+    for (key, value) in values {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        text += &format!("{} = \"{}\"\n", key, value);
+    }
+    return text;
+}
+
+fn get_value(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    return Some((String::from(key.trim()), get_unescaped(value)?));
+}
+
+fn get_values(text: &str) -> Option<Vec<(String, String)>> {
+    // Any line that is not a quoted value makes the whole file corrupt,
+    // rather than restoring part of it
+    let mut values = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.len() == 0 || line.starts_with('#') {
+            continue;
+        }
+        values.push(get_value(line)?);
+    }
+    return Some(values);
+}
+
+fn get_values_text(values: &[(String, String)]) -> String {
+    let text = String::from("# The last connection started from the menu\n");
+    return text + &get_lines_text(values);
+}
+
+fn write_text(path: &Path, text: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, text)?;
+    return Ok(());
+}
+
+fn get_unescaped(value: &str) -> Option<String> {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                e @ ('\\' | '"') => unescaped.push(e),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => unescaped.push(c),
+        }
+    }
+    return Some(unescaped);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_values() {
+        let values = vec![
+            (String::from("port"), String::from("C:\\ports\\\"odd\"")),
+            (String::from("baudrate"), String::from("115200")),
+        ];
+        let text = get_values_text(&values);
+        assert_eq!(text.lines().nth(1), Some("port = \"C:\\\\ports\\\\\\\"odd\\\"\""));
+        assert_eq!(get_values(&text), Some(values));
+
+        // Anything else is taken as a corrupt file
+        assert_eq!(get_values("port = COM4"), None);
+        assert_eq!(get_values("port"), None);
+        assert_eq!(get_values("port = \"a\\q\""), None);
+        assert_eq!(get_values("\n# nothing yet\n"), Some(vec![]));
+    }
+
+    #[test]
+    fn test_read_write_values() {
+        let dir = env::temp_dir().join(format!("nolp-settings-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(read_values(&path), None);
+
+        let values = vec![(String::from("port"), String::from("/dev/ttyUSB0"))];
+        write_values(&path, &values).unwrap();
+        assert_eq!(read_values(&path), Some(values));
+        fs::write(&path, [0xFF, 0xFE]).unwrap();
+        assert_eq!(read_values(&path), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_entries() {
+        let entries = vec![
+            vec![(String::from("port"), String::from("/dev/ttyUSB0"))],
+            vec![],
+            vec![(String::from("port"), String::from("COM4")), (String::from("parity"), String::from("Even"))],
+        ];
+        let text = get_entries_text(&entries);
+        assert_eq!(text.lines().nth(2), Some("[[recent]]"));
+        assert_eq!(get_entries(&text), Some(entries));
+
+        // A value outside an entry is as corrupt as an unquoted one
+        assert_eq!(get_entries("port = \"COM4\"\n[[recent]]"), None);
+        assert_eq!(get_entries("[[recent]]\nport = COM4"), None);
+        assert_eq!(get_entries(""), Some(vec![]));
+        let path = get_recent_path(Path::new("/home/nolp/.config/nolp/last.toml"));
+        assert_eq!(path, Path::new("/home/nolp/.config/nolp/recent.toml"));
+    }
+
+    #[test]
+    fn test_read_write_hidden() {
+        let dir = env::temp_dir().join(format!("nolp-hidden-{}", std::process::id()));
+        let path = get_hidden_path(&dir.join(SETTINGS_FILE));
+        assert_eq!(path, dir.join("hidden.toml"));
+
+        // Read back as any other values
+        let values = vec![
+            (String::from("port"), String::from("/dev/ttyS0")),
+            (String::from("group"), String::from("native")),
+        ];
+        write_hidden(&path, &values).unwrap();
+        assert_eq!(read_values(&path), Some(values));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the SLIP framing of RFC 1055, where each packet ends on
+* an END byte and the END and ESC bytes within it are escaped.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+pub const END: u8 = 0xC0;
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const ESC: u8 = 0xDB;
+const ESC_END: u8 = 0xDC;
+const ESC_ESC: u8 = 0xDD;
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+pub fn encode(packet: &[u8]) -> Vec<u8> {
+    // An END is sent ahead as well, ending any noise the receiver has read
+    // since the last packet
+    let mut frame = Vec::with_capacity(packet.len() + 2);
+    frame.push(END);
+    for value in packet.iter().copied() {
+        match value {
+            END => frame.extend_from_slice(&[ESC, ESC_END]),
+            ESC => frame.extend_from_slice(&[ESC, ESC_ESC]),
+            _ => frame.push(value),
+        }
+    }
+    frame.push(END);
+    return frame;
+}
+
+pub fn decode(frame: &[u8]) -> Result<Vec<u8>, String> {
+    // The bytes between two ENDs, an ESC followed by anything else is not a
+    // packet that was encoded
+    let mut packet = Vec::with_capacity(frame.len());
+    let mut values = frame.iter().copied();
+    while let Some(value) = values.next() {
+        match value {
+            END => return Err(String::from("END within a frame")),
+            ESC => match values.next() {
+                Some(ESC_END) => packet.push(END),
+                Some(ESC_ESC) => packet.push(ESC),
+                Some(other) => return Err(format!("invalid escape 0x{:02X}", other)),
+                None => return Err(String::from("frame ends on an escape")),
+            },
+            _ => packet.push(value),
+        }
+    }
+    return Ok(packet);
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(b"hi"), vec![END, b'h', b'i', END]);
+        assert_eq!(encode(&[0x01, END, ESC, 0x02]), vec![END, 0x01, ESC, ESC_END, ESC, ESC_ESC, 0x02, END]);
+        assert_eq!(encode(&[]), vec![END, END]);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"hi"), Ok(b"hi".to_vec()));
+        assert_eq!(decode(&[0x01, ESC, ESC_END, ESC, ESC_ESC, 0x02]), Ok(vec![0x01, END, ESC, 0x02]));
+        assert_eq!(decode(&[]), Ok(vec![]));
+
+        let packet: Vec<u8> = (0..=255).collect();
+        let frame = encode(&packet);
+        assert_eq!(decode(&frame[1..frame.len() - 1]), Ok(packet));
+
+        assert!(decode(&[0x01, ESC, 0x02]).is_err());
+        assert!(decode(&[0x01, ESC]).is_err());
+        assert!(decode(&[0x01, END, 0x02]).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the TCP 'port', which connects to a raw socket (e.g.,
+* ser2net, ESP-Link) as if it was a serial device.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use anyhow::{anyhow, Result};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::common::*;
+use crate::serial::{get_timeout, NolpPort};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Debug)]
+pub struct TcpPort {
+    stream: TcpStream,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const PEEK_LENGTH: usize = 1024;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Read for TcpPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // The remote end closed the connection, which is reported as an
+            // error so that it is handled like a disconnected device
+            Ok(0) if buf.len() > 0 => return Err(io::Error::from(ErrorKind::UnexpectedEof)),
+            Ok(n) => return Ok(n),
+            Err(e) => return Err(get_error(e)),
+        }
+    }
+}
+
+impl Write for TcpPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return self.stream.write(buf).map_err(get_error);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return self.stream.flush();
+    }
+}
+
+impl NolpPort for TcpPort {
+    fn bytes_to_read(&self) -> Result<u32> {
+        // Peeking without blocking, the read waits for the timeout instead
+        let mut buffer = [0; PEEK_LENGTH];
+        self.stream.set_nonblocking(true)?;
+        let result = self.stream.peek(&mut buffer);
+        self.stream.set_nonblocking(false)?;
+        match result {
+            Ok(n) => return Ok(n as u32),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => return Ok(0),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    // Whatever is in flight on the network is not affected
+    fn clear_all(&mut self) -> Result<()> {
+        return Ok(());
+    }
+
+    fn clear_input(&mut self) -> Result<()> {
+        return Ok(());
+    }
+
+    // A socket has no framing or control lines, so these are accepted and
+    // ignored to keep the terminal behavior the same as for a serial port
+    fn configure(&mut self, _parameters: &PortParameters) -> Result<()> {
+        return Ok(());
+    }
+
+    fn read_lines(&mut self) -> LineStatus {
+        return LineStatus::default();
+    }
+
+    fn set_break(&mut self, _enabled: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn set_dtr(&mut self, _level: bool) -> Result<()> {
+        return Ok(());
+    }
+
+    fn set_rts(&mut self, _level: bool) -> Result<()> {
+        return Ok(());
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface | Implementation
+*******************************************************************************/
+/******************************************************************************/
+pub fn get_address(name: &str) -> Option<&str> {
+    // Expects `tcp://host:port`, the host itself is resolved when connecting
+    let address = name.strip_prefix(TCP_PREFIX)?;
+    let (host, port) = address.rsplit_once(':')?;
+    if host.len() == 0 || port.parse::<u16>().is_err() {
+        return None;
+    }
+    return Some(address);
+}
+
+pub fn is_tcp(parameters: &PortParameters) -> bool {
+    match &parameters.name {
+        Some(name) => return name.starts_with(TCP_PREFIX),
+        None => return false,
+    }
+}
+
+pub fn open_tcp(parameters: &PortParameters) -> Result<TcpPort> {
+    let name = parameters.name.clone().unwrap_or_default();
+    let address = get_address(&name).ok_or(anyhow!("Invalid address {}", name))?;
+    let socket = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or(anyhow!("Unresolved address {}", address))?;
+
+    let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)?;
+    let timeout = Duration::from_millis(u64::from(get_timeout(parameters)));
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+    return Ok(TcpPort { stream });
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_error(error: io::Error) -> io::Error {
+    // Depending on the platform, a socket timeout is reported as either kind
+    match error.kind() {
+        ErrorKind::WouldBlock => return io::Error::from(ErrorKind::TimedOut),
+        _ => return error,
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn tcp_parameters(name: String) -> PortParameters {
+        let mut parameters = PortParameters::default().name(name);
+        parameters.mode = Some(Mode::Ascii);
+        parameters.timeout_ms = Some(50);
+        return parameters;
+    }
+
+    #[test]
+    fn test_get_address() {
+        assert_eq!(get_address("tcp://localhost:2000"), Some("localhost:2000"));
+        assert_eq!(get_address("tcp://[::1]:2000"), Some("[::1]:2000"));
+        assert_eq!(get_address("tcp://localhost"), None);
+        assert_eq!(get_address("tcp://:2000"), None);
+        assert_eq!(get_address("/dev/ttyUSB0"), None);
+        assert!(is_tcp(&tcp_parameters(String::from("tcp://localhost:2000"))));
+    }
+
+    #[test]
+    fn test_open_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let name = format!("tcp://{}", listener.local_addr().unwrap());
+        let mut port = open_tcp(&tcp_parameters(name)).unwrap();
+        let (mut remote, _) = listener.accept().unwrap();
+
+        let mut buffer = [0; 8];
+        assert_eq!(port.read(&mut buffer).unwrap_err().kind(), ErrorKind::TimedOut);
+
+        port.write_all(b"ping").unwrap();
+        remote.read_exact(&mut buffer[..4]).unwrap();
+        assert_eq!(&buffer[..4], b"ping");
+
+        remote.write_all(b"pong").unwrap();
+        remote.flush().unwrap();
+        assert_eq!(port.read(&mut buffer).unwrap(), 4);
+        assert_eq!(&buffer[..4], b"pong");
+
+        drop(remote);
+        assert_eq!(port.read(&mut buffer).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -6,16 +6,36 @@
 * DATE: 01/01/24
 ********************************************************************************/
 /*******************************************************************************/
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Padding, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Gauge, Padding, Paragraph, Sparkline},
     Frame,
 };
-use std::rc::Rc;
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
+};
 
+use crate::at::{AtEvent, AtSession, Exchange, Verdict};
+use crate::capture::get_default_path;
 use crate::common::*;
+use crate::configure::ConfigureModel;
+use crate::framing::{Deframer, Packet};
+use crate::generator::{Generator, Pattern, Verifier};
+use crate::ihex::{get_records, is_hex_path, HexSender, HexState, Pacing};
+use crate::latency::{get_ms_text, Latency};
+use crate::nmea::{get_sentences, Check, NmeaReader};
+use crate::replay::is_replay;
+use crate::script::{get_steps, Script, ScriptState};
+use crate::series::Series;
+use crate::trigger::{Trigger, TriggerMatch, TriggerMatcher};
+use crate::xmodem::{Xmodem, XmodemDirection, XmodemState};
 /******************************************************************************/
 /*******************************************************************************
 * Public Interface
@@ -26,8 +46,78 @@ pub struct TerminalModel {
     state: State,
     bounds: Rect,
     input: String,
+    cursor: usize,
+    invalid: Option<String>,
+    notice: Option<String>,
+    history: VecDeque<String>,
+    recalled: Option<usize>,
+    draft: String,
+    passthrough: bool,
+    escaping: bool,
+    prompt: Option<(Prompt, String)>,
+    upload: Option<Upload>,
+    hex: Option<HexUpload>,
+    transfer: Option<Transfer>,
+    script: Option<ScriptRun>,
+    // The round trips being measured, kept to be shown once done
+    latency: Option<Latency>,
+    generating: Option<Generating>,
+    // How a replay is played back, while the port is one
+    replay_speed: u32,
+    replay_paused: bool,
+    repeat: Option<Repeat>,
+    triggers: TriggerMatcher,
+    bell: bool,
+    capture_marker: Option<String>,
+    // What the last clear discarded, until the next key press
+    cleared: Option<Cleared>,
+    rules: Vec<Rule>,
+    responder: TriggerMatcher,
+    responding: bool,
+    // Control bytes are shown by name in Ascii, rather than as dots
+    mnemonics: bool,
+    // The numbers of the received lines, while graphed
+    series: Option<Series>,
+    // The NMEA sentences received, read while they are checked
+    nmea: NmeaReader,
+    // The frame still arriving, while the data is framed
+    deframer: Option<Deframer>,
+    // The AT commands sent, while they are followed, and whether their
+    // history is shown
+    at: AtSession,
+    at_history: bool,
+    responses: VecDeque<Response>,
+    response_times: VecDeque<Instant>,
+    search: Option<Search>,
     out: Vec<u8>,
-    buffer: Vec<DataByte>,
+    buffer: VecDeque<DataEntry>,
+    // The first line shown while the view is locked, or None to follow the
+    // data as it arrives
+    scroll: Option<usize>,
+    // The bytes received since the view was locked
+    unseen: usize,
+    frozen: usize,
+    // The bytes no longer in the buffer, so the dump offsets run on from the
+    // connection
+    dropped_bytes: u64,
+    paused_bytes: usize,
+    text: VecDeque<TextEntry>,
+    text_frozen: usize,
+    text_size: usize,
+    after_cr: bool,
+    connection: ConnectionStatus,
+    lines: LineStatus,
+    dtr: bool,
+    rts: bool,
+    commands: Vec<SerialCommand>,
+    configure: Option<ConfigureModel>,
+    error: Option<SerialErrorKind>,
+    stats: SerialStats,
+    rx_samples: VecDeque<(Instant, usize)>,
+    tx_samples: VecDeque<(Instant, usize)>,
+    rx_rate: f64,
+    tx_rate: f64,
+    opened: Instant,
     pub parameters: PortParameters,
 }
 
@@ -40,12 +130,166 @@ pub struct TerminalModel {
 enum DataDirection {
     Input,
     Output,
+    Bridge,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 struct DataByte {
     value: u8,
     direction: DataDirection,
+    matched: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DataEntry {
+    Byte(DataByte),
+    Marker(String),
+    // The silence before the next data received
+    Gap(Duration),
+    // The start of a framed packet of this length, or of the bytes of a
+    // frame that could not be decoded
+    Frame(Option<usize>),
+    // A marker in the color of what it notes
+    Note(String, Color),
+}
+
+// A file being sent, a chunk at a time once the previous one is written
+#[derive(Clone, Debug, PartialEq)]
+struct Upload {
+    path: String,
+    total: u64,
+    offset: u64,
+    base: u64,
+}
+
+// A HEX file being sent a record at a time, each line once the one before
+// it is written (and answered, or waited for, as set in the menu)
+#[derive(Clone, Debug, PartialEq)]
+struct HexUpload {
+    sender: HexSender,
+    path: String,
+    base: u64,
+    queued: u64,
+}
+
+// What the input is asked for while a prompt borrows it
+#[derive(Clone, Debug, PartialEq)]
+enum Prompt {
+    SendFile,
+    SendByte,
+    Xmodem,
+    Script,
+    Latency,
+    Generator,
+    Repeat,
+    Trigger,
+    // Asked before esc leaves the terminal, rather than for anything typed
+    Disconnect,
+}
+
+// The input sent again on every interval, until it is disarmed
+#[derive(Clone, Debug, PartialEq)]
+struct Repeat {
+    bytes: Vec<u8>,
+    interval: Duration,
+    next: Instant,
+}
+
+// An XMODEM transfer, which has the port to itself until it ends
+#[derive(Clone, Debug, PartialEq)]
+struct Transfer {
+    engine: Xmodem,
+    path: String,
+}
+
+// A script being run, which shares the port with what is typed
+#[derive(Clone, Debug, PartialEq)]
+struct ScriptRun {
+    script: Script,
+    path: String,
+}
+
+// A pattern being sent by the serial thread, and the check of what comes
+// back while the link is looped
+#[derive(Clone, Debug, PartialEq)]
+struct Generating {
+    pattern: Pattern,
+    rate: Option<u64>,
+    duration: Option<Duration>,
+    started: Instant,
+    // The bytes sent before the pattern started
+    base: u64,
+    verifier: Verifier,
+}
+
+// The display as it was before a clear, so it can be restored once
+#[derive(Clone, Debug, PartialEq)]
+struct Cleared {
+    buffer: VecDeque<DataEntry>,
+    text: VecDeque<TextEntry>,
+    text_size: usize,
+    bytes: u64,
+    frozen: usize,
+    text_frozen: usize,
+}
+
+// The reply to a pattern, matched by the trigger of the same index
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    response: Vec<u8>,
+    delay: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Response {
+    bytes: Vec<u8>,
+    due: Instant,
+}
+
+// The query typed in the search overlay, matched in the display mode
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Search {
+    query: String,
+    current: Option<usize>,
+    // The rendered line of each match, from the last encoding
+    lines: Vec<usize>,
+}
+
+// The data in the text view of Ascii, split on its line breaks
+#[derive(Clone, Debug, PartialEq)]
+struct TerminalLine {
+    data: Vec<u8>,
+    direction: DataDirection,
+    complete: bool,
+    // The break the line ended on, shown with the mnemonics
+    ending: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TextEntry {
+    Line(TerminalLine),
+    Marker(String),
+    Gap(Duration),
+    Note(String, Color),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum AnsiState {
+    Text,
+    Escape,
+    Csi(String),
+    Osc,
+    OscEscape,
+}
+
+// Builds the wrapped lines of the text view, a span per run of a style
+struct TextWriter<'a> {
+    lines: Vec<Line<'a>>,
+    spans: Vec<Span<'a>>,
+    text: String,
+    style: Style,
+    width: usize,
+    limit: usize,
 }
 
 /******************************************************************************/
@@ -54,19 +298,180 @@ struct DataByte {
 *******************************************************************************/
 /******************************************************************************/
 const PADDING: u16 = 1;
+// Includes the row occupied by the frame border
+const STATUS_HEIGHT: u16 = 2;
+const BREAK_DURATION: Duration = Duration::from_millis(250);
+const OPEN_TIMEOUT: Duration = Duration::from_secs(5);
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+const HISTORY_LENGTH: usize = 50;
+const UPLOAD_CHUNK: u64 = 4096;
+const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_millis(1000);
+const REPLAY_SPEEDS: [u32; 3] = [1, 2, 10];
+// Beyond this many replies a second, the rules are taken to be answering
+// another responder and are turned off
+const RESPONSE_LIMIT: usize = 10;
+const RESPONSE_WINDOW: Duration = Duration::from_secs(1);
+// The bytes on a row of the dump, the most that fit the width
+const DUMP_ROW_LENGTHS: [usize; 3] = [32, 16, 8];
+// The values graphed, and the rows taken by the graph (with its border)
+const SERIES_LENGTH: usize = 512;
+const GRAPH_HEIGHT: u16 = 6;
+const GRAPH_LEVELS: u64 = 100;
+// The bytes on a row of Hex, Decimal, and Octal, where zero packs them to
+// the width
+const ROW_LENGTHS: [usize; 5] = [0, 4, 8, 16, 32];
+// The values on each line of an export outside of the text view
+const EXPORT_ROW_LENGTH: usize = 16;
+const DEFAULT_INPUT_LIMIT: usize = 512;
+// The wait for the result of an AT command, long enough for most but a
+// network scan (e.g., AT+COPS=?)
+const DEFAULT_AT_TIMEOUT: Duration = Duration::from_millis(5000);
+// The exchanges listed in the AT history, the latest last
+const AT_HISTORY_ROWS: usize = 10;
+// The expects listed under the step being run
+const SCRIPT_RESULT_ROWS: usize = 5;
+// The probes sent when measuring the latency, unless typed otherwise, and
+// the wait for each echo before it is counted as lost
+const DEFAULT_PROBE_COUNT: usize = 10;
+const DEFAULT_PROBE: &[u8] = b"nolp-probe";
+const PROBE_TIMEOUT: Duration = Duration::from_millis(2000);
+// The entries kept for scrolling back, the oldest are dropped beyond this
+const DEFAULT_SCROLLBACK: usize = 100_000;
 
 /******************************************************************************/
 /*******************************************************************************
 * Implementation
 *******************************************************************************/
 /******************************************************************************/
+impl<'a> TextWriter<'a> {
+    fn new(limit: usize) -> TextWriter<'a> {
+        TextWriter {
+            lines: Vec::new(),
+            spans: Vec::new(),
+            text: String::from(""),
+            style: Style::default(),
+            width: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.text.len() > 0 {
+            let text = std::mem::take(&mut self.text);
+            self.spans.push(Span::styled(text, self.style));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line<'a>> {
+        self.flush();
+        if self.spans.len() > 0 {
+            self.lines.push(Line::from(self.spans));
+        }
+        return self.lines;
+    }
+
+    fn new_line(&mut self) {
+        self.flush();
+        self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        self.width = 0;
+    }
+
+    fn push(&mut self, c: char, style: Style) {
+        if self.width >= self.limit {
+            self.new_line();
+        }
+        if style != self.style {
+            self.flush();
+            self.style = style;
+        }
+        self.text.push(c);
+        self.width += 1;
+    }
+
+    fn push_cell(&mut self, cell: &str, style: Style) {
+        // Kept whole on a line, unless it is wider than the line itself
+        if self.width + cell.len() > self.limit && self.width > 0 {
+            self.new_line();
+        }
+        for c in cell.chars() {
+            self.push(c, style);
+        }
+    }
+
+    fn push_span(&mut self, span: Span<'a>) {
+        let width = span.content.len();
+        if self.width + width > self.limit && self.width > 0 {
+            self.new_line();
+        }
+        self.flush();
+        self.spans.push(span);
+        self.width += width;
+    }
+}
+
 impl Default for TerminalModel {
     fn default() -> TerminalModel {
         TerminalModel {
             out: Vec::new(),
-            buffer: Vec::new(),
+            buffer: VecDeque::new(),
+            scroll: None,
+            unseen: 0,
+            frozen: 0,
+            dropped_bytes: 0,
+            paused_bytes: 0,
+            text: VecDeque::new(),
+            text_frozen: 0,
+            text_size: 0,
+            after_cr: false,
             state: State::Running,
+            connection: ConnectionStatus::Connected,
+            lines: LineStatus::default(),
+            dtr: true,
+            rts: true,
+            commands: Vec::new(),
+            configure: None,
+            error: None,
+            stats: SerialStats::default(),
+            rx_samples: VecDeque::new(),
+            tx_samples: VecDeque::new(),
+            rx_rate: 0.0,
+            tx_rate: 0.0,
+            opened: Instant::now(),
             input: String::from(""),
+            cursor: 0,
+            invalid: None,
+            notice: None,
+            history: VecDeque::new(),
+            recalled: None,
+            draft: String::from(""),
+            passthrough: false,
+            escaping: false,
+            prompt: None,
+            upload: None,
+            hex: None,
+            transfer: None,
+            script: None,
+            latency: None,
+            generating: None,
+            replay_speed: 1,
+            replay_paused: false,
+            repeat: None,
+            triggers: TriggerMatcher::default(),
+            bell: false,
+            capture_marker: None,
+            cleared: None,
+            rules: Vec::new(),
+            responder: TriggerMatcher::default(),
+            responding: false,
+            mnemonics: false,
+            series: None,
+            nmea: NmeaReader::default(),
+            deframer: None,
+            at: AtSession::default(),
+            at_history: false,
+            responses: VecDeque::new(),
+            response_times: VecDeque::new(),
+            search: None,
             bounds: Rect::default(),
             parameters: PortParameters::default(),
         }
@@ -75,7 +480,16 @@ impl Default for TerminalModel {
 
 impl TerminalModel {
     pub fn new(parameters: PortParameters) -> TerminalModel {
-        let mut model = TerminalModel::default();
+        let mut model = TerminalModel {
+            // The live view is only shown once the serial thread reports the open
+            connection: ConnectionStatus::Connecting,
+            dtr: parameters.dtr_on_open.unwrap_or(true),
+            rts: !parameters.rs485,
+            ..Default::default()
+        };
+        if let Some(path) = &parameters.response_rules {
+            load_rules(&mut model, path);
+        }
         model.parameters = parameters;
         return model;
     }
@@ -85,8 +499,75 @@ impl TerminalModel {
     }
 
     pub fn clear_output_buffer(&mut self) {
+        // The buffer is only cleared once it has been handed to the port
+        record_sample(&mut self.tx_samples, Instant::now(), self.out.len());
         self.out.clear();
     }
+
+    pub fn get_commands(&self) -> Vec<SerialCommand> {
+        return self.commands.clone();
+    }
+
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        // An error is still acknowledged with enter, and the keys are only
+        // forwarded while there is a device to take them
+        let error = matches!(self.state, State::Error(_));
+        return self.passthrough && error == false && self.connection == ConnectionStatus::Connected;
+    }
+
+    pub fn get_connection(&self) -> ConnectionStatus {
+        return self.connection.clone();
+    }
+
+    pub fn get_error(&self) -> Option<SerialErrorKind> {
+        return self.error.clone();
+    }
+
+    pub fn get_stats(&self) -> SerialStats {
+        return self.stats.clone();
+    }
+
+    // The entries held for display, and the bytes dropped to make room
+    pub fn get_buffered(&self) -> (usize, u64) {
+        return (self.buffer.len(), self.dropped_bytes);
+    }
+
+    // The port and its settings, shown in the border while connected
+    pub fn get_summary(&self) -> Option<String> {
+        if self.connection != ConnectionStatus::Connected {
+            return None;
+        }
+        let mut summary = get_port_summary(&self.parameters);
+        if is_replay(&self.parameters) {
+            match self.replay_paused {
+                true => summary += " (paused)",
+                false => summary += &format!(" ({}x)", self.replay_speed),
+            }
+        }
+        return Some(summary);
+    }
+
+    pub fn take_notice(&mut self) -> Option<String> {
+        return self.notice.take();
+    }
+
+    pub fn take_capture_marker(&mut self) -> Option<String> {
+        return self.capture_marker.take();
+    }
+
+    pub fn take_bell(&mut self) -> bool {
+        return std::mem::take(&mut self.bell);
+    }
+
+    pub fn set_error(&mut self, error: SerialErrorKind) {
+        // The kind is kept for the guidance shown with the error
+        self.state = State::Error(error.to_string());
+        self.error = Some(error);
+    }
 }
 
 impl Nolp for TerminalModel {
@@ -101,37 +582,541 @@ impl Nolp for TerminalModel {
 
 impl Tea for TerminalModel {
     fn update(&mut self, msg: Message) -> State {
+        // A clear can only be undone by the key press after it
+        if msg != Message::Clear && is_background(&msg) == false {
+            self.cleared = None;
+        }
+
+        // Editing input is captured by the popup while it is displayed
+        if self.configure.is_some() && is_configure_input(&msg) {
+            let configure = self.configure.as_mut().unwrap();
+            if let State::Switching(_, Some(parameters)) = configure.update(msg) {
+                self.commands.push(SerialCommand::Configure(*parameters));
+                self.configure = None;
+            }
+            return self.get_state();
+        }
+
+        // As is the editing input by the search overlay
+        if self.search.is_some() && is_search_input(&msg) {
+            update_search(self, msg);
+            return self.get_state();
+        }
+
+        // Nothing can be sent until the port is open, and a failed open can
+        // only be acknowledged by returning to the menu
+        match self.connection {
+            ConnectionStatus::Connecting => match msg {
+                Message::Connection(ConnectionStatus::Closed) => return self.get_state(),
+                Message::Connection(_) | Message::Tick => {}
+                _ => return self.get_state(),
+            },
+            ConnectionStatus::Failed(_) => {
+                if msg == Message::Enter {
+                    let parameters = Some(Box::new(self.parameters.clone()));
+                    self.state = State::Switching(Screen::Menu, parameters);
+                }
+                return self.get_state();
+            }
+            _ => {}
+        }
+
+        // Anything that would transmit is ignored in read-only mode, which
+        // can still be left with esc
+        let disconnecting = matches!(self.prompt, Some((Prompt::Disconnect, _))) && msg == Message::Enter;
+        if self.parameters.read_only && is_transmit_input(&msg) && disconnecting == false {
+            return self.get_state();
+        }
+
+        // Anything typed would end up in the middle of the packets
+        if self.transfer.is_some() && is_transmit_input(&msg) {
+            return self.get_state();
+        }
+
         match msg {
-            Message::Input(input) => {
-                if self.state != State::Pausing && self.input.len() < 50 {
-                    self.input.push(input);
+            Message::Tick => {
+                update_upload(self);
+                let now = Instant::now();
+                update_hex(self, now);
+                update_transfer(self, &[], now);
+                update_script(self, &[], now);
+                update_latency(self, &[], now);
+                update_generating(self, now);
+                update_repeat(self, now);
+                send_responses(self, now);
+                if let Some(exchange) = self.at.check(now, get_at_timeout(self)) {
+                    push_exchange(self, &exchange);
                 }
+                self.rx_rate = get_rate(&mut self.rx_samples, now);
+                self.tx_rate = get_rate(&mut self.tx_samples, now);
+                if self.connection == ConnectionStatus::Connecting
+                    && self.opened.elapsed() >= OPEN_TIMEOUT
+                {
+                    // The open may still complete, so the serial thread is
+                    // asked to close whatever it ends up with
+                    self.commands.push(SerialCommand::Close);
+                    let reason = String::from("timed out");
+                    self.connection = ConnectionStatus::Failed(SerialErrorKind::OpenFailed { reason });
+                }
+            }
+            Message::Input(input)
+                if self.state != State::Pausing && self.input.chars().count() < get_input_limit(self) =>
+            {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Insert(input));
+                self.invalid = None;
+            }
+            // Deletes the character before the cursor
+            Message::Backspace if self.cursor > 0 && self.state != State::Pausing => {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Backspace);
+                self.invalid = None;
+            }
+            // Deletes the character under the cursor
+            Message::Delete if self.cursor < self.input.chars().count() && self.state != State::Pausing => {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Delete);
+                self.invalid = None;
             }
-            Message::Backspace => {
-                if self.input.len() > 0 && self.state != State::Pausing {
-                    self.input.pop();
+            Message::CursorLeft => {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Left);
+            }
+            Message::CursorRight => {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Right);
+            }
+            Message::CursorHome => {
+                edit_text(&mut self.input, &mut self.cursor, Edit::Home);
+            }
+            Message::CursorEnd => {
+                // Once at the end of the input, the view follows the data again
+                if self.cursor == self.input.chars().count() {
+                    self.scroll = None;
                 }
+                edit_text(&mut self.input, &mut self.cursor, Edit::End);
             }
-            Message::Pause => {
-                if self.state != State::Pausing {
-                    // Clear the buffer
-                    self.buffer = Vec::new();
-                    self.state = State::Pausing;
+            Message::HistoryPrev => {
+                if self.history.len() == 0 || self.state == State::Pausing {
+                    return self.get_state();
                 }
+                // The input being typed is kept, to return to it afterwards
+                let index = match self.recalled {
+                    None => {
+                        self.draft = self.input.clone();
+                        self.history.len() - 1
+                    }
+                    Some(i) => i.saturating_sub(1),
+                };
+                self.recalled = Some(index);
+                self.input = self.history[index].clone();
+                self.cursor = self.input.chars().count();
+                self.invalid = None;
             }
-            Message::Resume => {
-                if self.state != State::Running {
-                    self.state = State::Running;
+            Message::HistoryNext => {
+                if self.state == State::Pausing {
+                    return self.get_state();
+                }
+                match self.recalled {
+                    Some(i) if i + 1 < self.history.len() => {
+                        self.recalled = Some(i + 1);
+                        self.input = self.history[i + 1].clone();
+                    }
+                    Some(_) => {
+                        self.recalled = None;
+                        self.input = self.draft.clone();
+                    }
+                    None => return self.get_state(),
                 }
+                self.cursor = self.input.chars().count();
+                self.invalid = None;
+            }
+            Message::Pause if self.state != State::Pausing => {
+                // The view is frozen on what is displayed, while the
+                // data keeps being added after it
+                self.frozen = self.buffer.len();
+                self.text_frozen = self.text.len();
+                self.paused_bytes = 0;
+                self.state = State::Pausing;
+            }
+            Message::Resume if self.state != State::Running => {
+                self.state = State::Running;
+                self.scroll = None;
             }
             Message::Enter => {
-                if self.input.len() > 0 {
-                    update_buffer_input(self);
-                    self.input = String::from("");
+                // An invalid entry leaves the prompt open, to correct it
+                if let Some(e) = self.prompt.as_ref().and_then(|(prompt, _)| get_prompt_error(prompt, &self.input)) {
+                    self.invalid = Some(e);
+                    return self.get_state();
+                }
+                // The prompt hands what was typed over, and restores the input
+                if let Some((prompt, input)) = self.prompt.take() {
+                    let typed = std::mem::replace(&mut self.input, input);
+                    self.cursor = self.input.chars().count();
+                    match prompt {
+                        Prompt::SendFile => start_upload(self, typed.trim()),
+                        Prompt::SendByte => send_byte(self, typed.trim()),
+                        Prompt::Xmodem => start_transfer(self, typed.trim()),
+                        Prompt::Script => start_script(self, typed.trim()),
+                        // Spaces may well be part of the probe
+                        Prompt::Latency => start_latency(self, typed.trim_start()),
+                        Prompt::Generator => start_generating(self, typed.trim()),
+                        Prompt::Repeat => start_repeat(self, typed.trim()),
+                        // Spaces may well be part of the pattern
+                        Prompt::Trigger => add_trigger(self, &typed),
+                        Prompt::Disconnect => {
+                            let parameters = Some(Box::new(self.parameters.clone()));
+                            self.state = State::Switching(Screen::Menu, parameters);
+                        }
+                    }
+                } else {
+                    submit_input(self);
+                }
+            }
+            Message::SendFile
+            | Message::SendByte
+            | Message::Xmodem
+            | Message::RunScript
+            | Message::MeasureLatency => {
+                // One script (or measurement) runs at a time
+                let running = match msg {
+                    Message::RunScript => self.script.is_some(),
+                    Message::MeasureLatency => is_measuring(self),
+                    _ => false,
+                };
+                if running {
+                    return self.get_state();
+                }
+                if self.upload.is_none() && self.hex.is_none() && self.prompt.is_none() {
+                    let prompt = match msg {
+                        Message::SendFile => Prompt::SendFile,
+                        Message::SendByte => Prompt::SendByte,
+                        Message::RunScript => Prompt::Script,
+                        Message::MeasureLatency => Prompt::Latency,
+                        _ => Prompt::Xmodem,
+                    };
+                    self.prompt = Some((prompt, std::mem::take(&mut self.input)));
+                    self.cursor = 0;
+                    self.invalid = None;
+                }
+            }
+            Message::Repeat => {
+                // The input is checked before the interval is asked for
+                if self.repeat.take().is_some() || self.prompt.is_some() {
+                    return self.get_state();
+                }
+                if self.input.len() == 0 {
+                    self.notice = Some(String::from(" Type the input to repeat first "));
+                    return self.get_state();
+                }
+                let mode = self.parameters.mode.clone().unwrap_or(Mode::Ascii);
+                match get_input_bytes(&self.input, mode.clone()) {
+                    Ok(_) => {
+                        self.prompt = Some((Prompt::Repeat, std::mem::take(&mut self.input)));
+                        self.cursor = 0;
+                        self.invalid = None;
+                    }
+                    Err(e) if mode == Mode::Ascii => self.invalid = Some(e),
+                    Err(e) => self.state = State::Error(e),
+                }
+            }
+            Message::Generate => {
+                // Typed again, the pattern is stopped
+                if self.generating.is_some() {
+                    stop_generating(self);
+                } else if self.prompt.is_none() {
+                    self.prompt = Some((Prompt::Generator, std::mem::take(&mut self.input)));
+                    self.cursor = 0;
+                    self.invalid = None;
+                }
+            }
+            Message::Trigger if self.prompt.is_none() => {
+                self.prompt = Some((Prompt::Trigger, std::mem::take(&mut self.input)));
+                self.cursor = 0;
+                self.invalid = None;
+            }
+            Message::Search => {
+                self.search = Some(Search::default());
+            }
+            Message::ToggleAtHistory => {
+                if self.parameters.at_commands == false && self.at_history == false {
+                    self.notice = Some(String::from(" AT commands are not followed (see the menu) "));
+                    return self.get_state();
+                }
+                self.at_history = self.at_history == false;
+            }
+            Message::ToggleResponses => {
+                if self.rules.len() == 0 {
+                    self.notice = Some(String::from(" No response rules loaded "));
+                    return self.get_state();
+                }
+                self.responding = self.responding == false;
+                self.responses.clear();
+                self.response_times.clear();
+                let status = if self.responding { "on" } else { "off" };
+                self.notice = Some(format!(" Auto-response {} ", status));
+            }
+            Message::Cancel => {
+                if let Some((_, input)) = self.prompt.take() {
+                    self.input = input;
+                    self.cursor = self.input.chars().count();
+                    self.invalid = None;
+                } else if self.upload.take().is_some() {
+                    push_marker(self, "<SEND CANCELLED>");
+                } else if self.hex.take().is_some() {
+                    push_marker(self, "<HEX CANCELLED>");
+                } else if let Some(mut transfer) = self.transfer.take() {
+                    // The other end is told, rather than left to time out
+                    self.out.extend(transfer.engine.cancel());
+                    push_marker(self, "<XMODEM CANCELLED>");
+                } else if self.script.take().is_some() {
+                    push_marker(self, "<SCRIPT CANCELLED>");
+                } else if is_measuring(self) {
+                    self.latency = None;
+                    push_marker(self, "<LATENCY CANCELLED>");
+                } else if self.generating.is_some() {
+                    stop_generating(self);
+                } else if self.at_history {
+                    self.at_history = false;
+                } else if self.latency.is_some() {
+                    self.latency = None;
+                } else if self.input.len() > 0 {
+                    self.input.clear();
+                    self.cursor = 0;
+                } else {
+                    // With nothing left to cancel, esc goes back to the menu
+                    self.prompt = Some((Prompt::Disconnect, String::new()));
+                    self.invalid = None;
+                }
+            }
+            // Follows the data, since the device echoes what is typed
+            Message::TogglePassThrough if self.configure.is_none() => {
+                self.passthrough = self.passthrough == false;
+                self.escaping = false;
+                self.state = State::Running;
+                self.scroll = None;
+            }
+            Message::PassThrough(key) => {
+                if self.escaping {
+                    self.escaping = false;
+                    if key.code == KeyCode::Char(QUIT_CHAR) && key.modifiers == KeyModifiers::NONE {
+                        self.passthrough = false;
+                        return self.get_state();
+                    }
+                } else if is_passthrough_escape(&key) {
+                    self.escaping = true;
+                    return self.get_state();
+                }
+                self.out.extend(get_key_bytes(&key));
+            }
+            Message::Paste(text) if self.passthrough => {
+                // Sent as typed, where Enter is a carriage return
+                let text = text.replace("\r\n", "\r").replace('\n', "\r");
+                self.out.extend_from_slice(text.as_bytes());
+            }
+            Message::Paste(text) => {
+                if self.state == State::Pausing {
+                    return self.get_state();
+                }
+                let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                if self.parameters.paste_lines {
+                    // Every complete line is sent, the last is left to be
+                    // finished, stopping at the first that is invalid
+                    let mut lines = text.split('\n').peekable();
+                    while let Some(line) = lines.next() {
+                        paste_input(self, line);
+                        if lines.peek().is_some() && submit_input(self) == false {
+                            break;
+                        }
+                    }
+                } else {
+                    paste_input(self, &text.replace('\n', ""));
                 }
             }
             Message::Rx(data) => {
-                update_buffer_output(self, data)
+                record_sample(&mut self.rx_samples, Instant::now(), data.len());
+                if self.transfer.is_some() {
+                    update_transfer(self, &data, Instant::now());
+                } else {
+                    if let Some(series) = self.series.as_mut() {
+                        series.update(&data);
+                    }
+                    if is_nmea_checked(self) {
+                        self.nmea.update(&data);
+                    }
+                    if let Some(generating) = self.generating.as_mut() {
+                        generating.verifier.update(&data);
+                    }
+                    // The patterns are matched in the packets, as shown
+                    let data = match get_framing(self) {
+                        Some(framing) => update_buffer_frames(self, framing, &data),
+                        None if self.parameters.at_commands => {
+                            update_at(self, &data, Instant::now());
+                            data
+                        }
+                        None => {
+                            update_buffer_output(self, data.clone(), DataDirection::Output);
+                            data
+                        }
+                    };
+                    update_triggers(self, &data);
+                    update_responses(self, &data, Instant::now());
+                    update_script(self, &data, Instant::now());
+                    update_latency(self, &data, Instant::now());
+                    if let Some(hex) = self.hex.as_mut() {
+                        hex.sender.update(&data);
+                        update_hex(self, Instant::now());
+                    }
+                }
+            }
+            // A transfer replaces the data shown, so it is not marked
+            Message::Gap(gap) if self.transfer.is_none() => {
+                push_gap(self, gap);
+            }
+            Message::BridgeRx(data) => {
+                record_sample(&mut self.rx_samples, Instant::now(), data.len());
+                update_buffer_output(self, data, DataDirection::Bridge)
+            }
+            Message::Sent(data) => {
+                record_sample(&mut self.tx_samples, Instant::now(), data.len());
+                update_buffer_output(self, data, DataDirection::Input)
+            }
+            Message::PauseReplay | Message::CycleReplaySpeed | Message::StepReplay => {
+                if is_replay(&self.parameters) == false {
+                    self.notice = Some(String::from(" Not a replay (see replay: in the README) "));
+                    return self.get_state();
+                }
+                update_replay(self, msg);
+            }
+            Message::Break => {
+                self.commands.push(SerialCommand::Break(BREAK_DURATION));
+                push_marker(self, "<BREAK>");
+            }
+            Message::Reset => {
+                // The pulse leaves DTR asserted, shown where it occurred
+                self.dtr = true;
+                push_marker(self, "<RESET>");
+            }
+            Message::ToggleDtr => {
+                self.dtr = !self.dtr;
+                self.commands.push(SerialCommand::SetDtr(self.dtr));
+            }
+            Message::ToggleRts => {
+                self.rts = !self.rts;
+                self.commands.push(SerialCommand::SetRts(self.rts));
+            }
+            Message::Clear => match self.cleared.take() {
+                Some(cleared) => restore_display(self, cleared),
+                None => clear_display(self),
+            },
+            Message::Flush => {
+                // The display is cleared along with the port buffers
+                self.commands.push(SerialCommand::Flush);
+                self.dropped_bytes += get_byte_count(self.buffer.iter()) as u64;
+                self.buffer.clear();
+                self.text.clear();
+                self.scroll = None;
+                self.frozen = 0;
+                self.text_frozen = 0;
+                self.text_size = 0;
+                self.after_cr = false;
+                if let Some(series) = self.series.as_mut() {
+                    series.clear();
+                }
+                self.nmea.clear();
+                self.deframer = None;
+            }
+            Message::Lines(lines) => {
+                self.lines = lines;
+            }
+            Message::Export => {
+                let path = get_default_path(&self.parameters, SystemTime::now(), "txt");
+                export_buffer(self, &path);
+            }
+            Message::ScrollUp => {
+                let (bottom, height) = get_scroll_bounds(self);
+                let top = self.scroll.unwrap_or(bottom);
+                if bottom > 0 {
+                    lock_scroll(self, top.saturating_sub(height));
+                }
+            }
+            Message::ScrollDown => {
+                // Reaching the bottom follows the data again
+                let (bottom, height) = get_scroll_bounds(self);
+                self.scroll = match self.scroll {
+                    Some(top) if top + height < bottom => Some(top + height),
+                    _ => None,
+                };
+            }
+            Message::ScrollTop => {
+                let (bottom, _) = get_scroll_bounds(self);
+                if bottom > 0 {
+                    lock_scroll(self, 0);
+                }
+            }
+            Message::ScrollBottom => {
+                // Locks the view where it is, when it is following the data
+                if self.scroll.is_some() {
+                    self.scroll = None;
+                } else {
+                    let (bottom, _) = get_scroll_bounds(self);
+                    lock_scroll(self, bottom);
+                }
+            }
+            Message::CycleLineEnding => {
+                let line_ending = get_next_line_ending(&self.parameters.line_ending);
+                self.parameters.line_ending = Some(line_ending);
+            }
+            Message::CycleMode => {
+                // Only the display changes, the buffer is encoded again with
+                // the new widths, so the view returns to the latest data
+                self.parameters.mode = Some(get_next_mode(&self.parameters.mode));
+                self.scroll = None;
+            }
+            Message::ToggleMnemonics => {
+                // As for the mode, the widths change with it
+                self.mnemonics = self.mnemonics == false;
+                self.scroll = None;
+            }
+            Message::ToggleEcho => {
+                // Only what is sent from now on, what was shown stays
+                self.parameters.echo = self.parameters.echo == false;
+                let status = if self.parameters.echo { "on" } else { "off" };
+                self.notice = Some(format!(" Local echo {} ", status));
+            }
+            Message::ToggleGraph if self.series.is_some() => {
+                self.series = None;
+            }
+            // Only text has numbers to be read from its lines
+            Message::ToggleGraph => match self.parameters.mode {
+                Some(Mode::Ascii) => self.series = Some(Series::new(SERIES_LENGTH)),
+                _ => self.notice = Some(String::from(" The graph is only shown in Ascii ")),
+            },
+            Message::LongerRows => {
+                self.parameters.row_length = get_next_row_length(self.parameters.row_length, true);
+                self.scroll = None;
+            }
+            Message::ShorterRows => {
+                self.parameters.row_length = get_next_row_length(self.parameters.row_length, false);
+                self.scroll = None;
+            }
+            Message::Configure => {
+                self.configure = match self.configure {
+                    Some(_) => None,
+                    None => Some(ConfigureModel::new(self.parameters.clone())),
+                };
+            }
+            Message::Reconfigured(parameters) => {
+                self.parameters = parameters;
+            }
+            Message::Stats(stats) => {
+                self.stats = stats;
+            }
+            Message::Connection(status) => {
+                // Losing the device supersedes the errors that led to it
+                if status == ConnectionStatus::Reconnecting
+                    || status == ConnectionStatus::Disconnected
+                {
+                    if let State::Error(_) = self.state {
+                        self.state = State::Running;
+                    }
+                }
+                self.connection = status;
             }
             _ => {}
         }
@@ -140,15 +1125,55 @@ impl Tea for TerminalModel {
 
     fn view(&mut self, frame: &mut Frame) {
         self.bounds = frame.size();
-        let layout = get_layout(self.bounds);
+        let layout = get_layout(self.bounds, self.passthrough, is_graph_shown(self), is_fix_shown(self));
 
-        if self.state == State::Pausing {
-            render_pause(frame, self.bounds);
+        if self.connection == ConnectionStatus::Connecting {
+            render_connecting(frame, self.bounds, self);
+        } else if let ConnectionStatus::Failed(_) = self.connection {
+            render_error(frame, self.bounds, self);
+        } else if self.connection == ConnectionStatus::Reconnecting {
+            render_reconnecting(frame, self.bounds, self);
         } else if let State::Error(_) = self.state {
             render_error(frame, self.bounds, self);
         } else {
-            render_terminal(frame, layout[0], self);
-            render_input(frame, layout[1], self);
+            render_lines(frame, layout[0], self);
+            if self.transfer.is_some() {
+                render_transfer(frame, layout[3], self);
+                render_transfer_progress(frame, layout[4], self);
+            } else {
+                if let Some(series) = self.series.as_ref().filter(|_| layout[1].height > 0) {
+                    render_graph(frame, layout[1], series);
+                }
+                if layout[2].height > 0 {
+                    render_fix(frame, layout[2], self);
+                }
+                render_terminal(frame, layout[3], self);
+                if self.search.is_some() {
+                    render_search(frame, layout[3], self);
+                } else if self.at_history {
+                    render_at_history(frame, layout[3], self);
+                } else if self.script.is_some() {
+                    render_script(frame, layout[3], self);
+                } else if self.latency.is_some() {
+                    render_latency(frame, layout[3], self);
+                } else if self.generating.is_some() {
+                    render_generating(frame, layout[3], self);
+                }
+                if self.upload.is_some() {
+                    render_upload(frame, layout[4], self);
+                } else if self.hex.is_some() {
+                    render_hex(frame, layout[4], self);
+                } else if self.passthrough == false {
+                    render_input(frame, layout[4], self);
+                }
+            }
+            if self.connection == ConnectionStatus::Disconnected {
+                render_disconnected(frame, layout[3], self);
+            } else if self.state == State::Pausing && self.transfer.is_none() {
+                render_pause(frame, layout[3], self);
+            } else if let Some(configure) = self.configure.as_mut() {
+                configure.view(frame);
+            }
         }
     }
 }
@@ -158,177 +1183,4756 @@ impl Tea for TerminalModel {
 * Utility functions
 *******************************************************************************/
 /******************************************************************************/
-fn get_encoding(model: &mut TerminalModel, area: Rect) -> Vec<Line> {
+fn add_trigger(model: &mut TerminalModel, pattern: &str) {
+    if pattern.len() == 0 {
+        if model.triggers.get_triggers().len() > 0 {
+            model.triggers.clear();
+            model.notice = Some(String::from(" Triggers cleared "));
+        }
+        return;
+    }
+    match get_trigger(pattern) {
+        Ok(trigger) => {
+            model.notice = Some(format!(" Trigger added: {} ", pattern));
+            model.triggers.add(trigger);
+        }
+        Err(e) => model.state = State::Error(e),
+    }
+}
+
+fn export_buffer(model: &mut TerminalModel, path: &str) {
+    // Everything kept is written, including what is scrolled out of view
+    let lines = get_export_lines(model);
+    if lines.len() == 0 {
+        model.notice = Some(String::from(" Nothing to export "));
+        return;
+    }
+    match fs::write(path, lines.join("\n") + "\n") {
+        Ok(()) => model.notice = Some(format!(" Exported {} lines to {} ", lines.len(), path)),
+        Err(e) => model.state = State::Error(format!(" Failed to export to {}: {} ", path, e)),
+    }
+}
+
+fn get_ansi_color(code: u16) -> Color {
+    match code {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+fn get_ansi_state(state: AnsiState, value: u8, sgr: &mut Style) -> AnsiState {
+    match state {
+        AnsiState::Text => match value {
+            0x1B => return AnsiState::Escape,
+            _ => return AnsiState::Text,
+        },
+        AnsiState::Escape => match value {
+            b'[' => return AnsiState::Csi(String::from("")),
+            b']' => return AnsiState::Osc,
+            _ => return AnsiState::Text,
+        },
+        AnsiState::Csi(mut params) => match value {
+            0x40..=0x7E => {
+                if value == b'm' {
+                    *sgr = get_sgr_style(*sgr, &params);
+                }
+                return AnsiState::Text;
+            }
+            _ => {
+                params.push(value as char);
+                return AnsiState::Csi(params);
+            }
+        },
+        // An OSC ends with BEL or ST (ESC \)
+        AnsiState::Osc => match value {
+            0x07 => return AnsiState::Text,
+            0x1B => return AnsiState::OscEscape,
+            _ => return AnsiState::Osc,
+        },
+        AnsiState::OscEscape => match value {
+            b'\\' => return AnsiState::Text,
+            _ => return AnsiState::Osc,
+        },
+    }
+}
+
+fn get_encoding(model: &mut TerminalModel, area: Rect) -> Vec<Line<'_>> {
     let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let marker_style = style.add_modifier(Modifier::BOLD);
+    let gap_style = style.add_modifier(Modifier::DIM);
+    let bridge_style = Style::default().fg(crate::SELECTED_COLOR);
+    let trigger_style = Style::default().fg(Color::Black).bg(crate::TRIGGER_COLOR);
     let mode = model.parameters.mode.clone().unwrap();
+    if is_text_view(model) {
+        return get_text_encoding(model, area);
+    }
+    if mode == Mode::HexDump {
+        return get_dump_encoding(model, area);
+    }
     let mut encoding: Vec<Line> = Vec::new();
     let mut current: Vec<Span> = Vec::new();
-    for data_byte in model.buffer.iter() {
-        let (width, text) = match mode {
-            Mode::Hex => (5, format!("{:#04X} ", data_byte.value)),
-            Mode::Octal => (6, format!("{:#05o} ", data_byte.value)),
-            Mode::Ascii => {
-                if data_byte.value >= 32 && data_byte.value <= 126 {
-                    (2, (data_byte.value as char).to_string() + " ")
-                } else {
-                    (2, String::from(". "))
+    let mut current_width = 0_usize;
+    let visible = match model.state {
+        State::Pausing => model.frozen,
+        _ => model.buffer.len(),
+    };
+    // The bytes on the current row, when they are laid out in fixed rows
+    let row_length = get_row_length(model);
+    let (cell_width, _) = get_byte_text(&mode, 0, false);
+    let mut count = 0;
+
+    // The markers are skipped over by the search, matching only the data
+    let pattern = get_search_pattern(model).unwrap_or_default();
+    let values: Vec<u8> = model
+        .buffer
+        .iter()
+        .take(visible)
+        .filter_map(|entry| match entry {
+            DataEntry::Byte(data_byte) => Some(data_byte.value),
+            DataEntry::Marker(_) | DataEntry::Note(..) | DataEntry::Gap(_) | DataEntry::Frame(_) => None,
+        })
+        .collect();
+    let marks = get_search_marks(&values, &pattern, &mut 0);
+    let selected = model.search.as_ref().and_then(|search| search.current);
+    let mut search_lines = Vec::new();
+    let mut value_index = 0;
+    // The rows of a packet line up after its length, and the bytes of a
+    // frame that could not be decoded are dimmed
+    let mut indent = 0;
+    let mut garbage = false;
+
+    for entry in model.buffer.iter().take(visible) {
+        let mut mark = None;
+        let (width, span) = match entry {
+            DataEntry::Byte(data_byte) => {
+                mark = marks[value_index];
+                value_index += 1;
+                let (width, text) = get_byte_text(&mode, data_byte.value, model.mnemonics);
+                let span = match data_byte.direction {
+                    DataDirection::Output if data_byte.matched => Span::styled(text, trigger_style),
+                    DataDirection::Output if garbage => Span::styled(text, gap_style),
+                    DataDirection::Output => Span::from(text),
+                    DataDirection::Input => Span::styled(text, style),
+                    DataDirection::Bridge => Span::styled(text, bridge_style),
+                };
+                // A mnemonic is dimmed, so it stands apart from the text
+                match model.mnemonics && mode == Mode::Ascii && is_printable(data_byte.value) == false {
+                    true => (width, Span::styled(span.content, span.style.add_modifier(Modifier::DIM))),
+                    false => (width, span),
+                }
+            }
+            // Also on a row of its own in fixed rows, keeping the columns
+            DataEntry::Marker(marker) | DataEntry::Note(marker, _) if row_length.is_some() => {
+                if current.len() > 0 {
+                    pad_row(&mut current, row_length, count, cell_width);
+                    encoding.push(Line::from(std::mem::take(&mut current)));
+                    current_width = 0;
+                    count = 0;
+                }
+                encoding.push(Line::from(Span::styled(marker.clone(), get_note_style(entry, marker_style))));
+                continue;
+            }
+            DataEntry::Marker(marker) | DataEntry::Note(marker, _) => {
+                let text = format!("{} ", marker);
+                (text.len(), Span::styled(text, get_note_style(entry, marker_style)))
+            }
+            DataEntry::Gap(gap) => {
+                // On a row of its own, so the data after it starts a new row
+                if current.len() > 0 {
+                    pad_row(&mut current, row_length, count, cell_width);
+                    encoding.push(Line::from(std::mem::take(&mut current)));
+                    current_width = 0;
+                    count = 0;
+                }
+                encoding.push(Line::from(Span::styled(get_gap_text(*gap), gap_style)));
+                (indent, garbage) = (0, false);
+                continue;
+            }
+            DataEntry::Frame(length) => {
+                // Each packet starts a row of its own
+                if current.len() > 0 {
+                    pad_row(&mut current, row_length, count, cell_width);
+                    encoding.push(Line::from(std::mem::take(&mut current)));
+                    count = 0;
                 }
+                let prefix = get_frame_prefix(*length);
+                (indent, garbage) = (prefix.len(), length.is_none());
+                current.push(Span::styled(prefix, style));
+                current_width = indent;
+                continue;
             }
-            Mode::Decimal => (4, format!("{: >3} ", data_byte.value)),
         };
 
-        if usize::from(area.width) <= (current.len() * width) + width {
+        let full = row_length.is_some_and(|length| count == length);
+        if (full || usize::from(area.width) <= current_width + width) && current_width > indent {
             encoding.push(Line::from(current));
             current = Vec::new();
+            current_width = 0;
+            count = 0;
+            if indent > 0 {
+                current.push(Span::from(" ".repeat(indent)));
+                current_width = indent;
+            }
         }
 
-        match data_byte.direction {
-            DataDirection::Output => current.push(Span::from(text)),
-            DataDirection::Input => current.push(Span::styled(text, style)),
-        }
+        let span = match mark {
+            Some(number) => {
+                if search_lines.len() == number {
+                    search_lines.push(encoding.len());
+                }
+                Span::styled(span.content, get_search_style(number, selected))
+            }
+            None => span,
+        };
+        current.push(span);
+        current_width += width;
+        count += 1;
     }
 
     if current.len() > 0 {
+        pad_row(&mut current, row_length, count, cell_width);
         encoding.push(Line::from(current));
     }
+    if let Some(search) = model.search.as_mut() {
+        search.lines = search_lines;
+    }
 
     return encoding;
 }
 
-fn get_layout(fsize: Rect) -> Rc<[Rect]> {
-    Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(3)])
-        .split(fsize)
+fn get_byte_count<'a>(entries: impl Iterator<Item = &'a DataEntry>) -> usize {
+    return entries.filter(|entry| matches!(entry, DataEntry::Byte(_))).count();
 }
 
-fn get_number_from_char(input: char) -> u8 {
-    match input {
-        '0' => 0_u8,
-        '1' => 1_u8,
-        '2' => 2_u8,
-        '3' => 3_u8,
-        '4' => 4_u8,
-        '5' => 5_u8,
-        '6' => 6_u8,
-        '7' => 7_u8,
-        '8' => 8_u8,
-        '9' => 9_u8,
-        _ => input as u8
+fn get_byte_text(mode: &Mode, value: u8, mnemonics: bool) -> (usize, String) {
+    match mode {
+        Mode::Hex | Mode::HexDump => (5, format!("{:#04X} ", value)),
+        Mode::Octal => (6, format!("{:#05o} ", value)),
+        Mode::Ascii => {
+            if is_printable(value) {
+                (2, (value as char).to_string() + " ")
+            } else if mnemonics {
+                let text = get_mnemonic(value);
+                (text.len() + 1, text + " ")
+            } else {
+                (2, String::from(". "))
+            }
+        }
+        Mode::Decimal => (4, format!("{: >3} ", value)),
     }
 }
 
-fn render_error(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
-    let bounds = get_center_bounds(50, 50, area);
-    let style = Style::default()
-        .fg(crate::INVALID_COLOR)
-        .add_modifier(Modifier::BOLD);
-    let message = format!(
-        "There was an error connecting to {}",
-        model.parameters.name.clone().unwrap()
-    );
-    let text = Text::styled(message, style);
-    let pause = Paragraph::new(text).alignment(Alignment::Center);
-    frame.render_widget(pause, bounds);
+fn get_rules(text: &str) -> Result<Vec<(Trigger, Rule)>, String> {
+    // A rule a line, as `pattern => response`, optionally followed by
+    // `=> delay` in ms, where blank lines and those starting with # are
+    // skipped
+    let mut rules = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().len() == 0 || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(" => ").collect();
+        let delay = match fields.len() {
+            2 => Duration::ZERO,
+            3 => match fields[2].trim().parse::<u64>() {
+                Ok(ms) => Duration::from_millis(ms),
+                Err(_) => return Err(format!(" Line {}: invalid delay '{}' ", number + 1, fields[2])),
+            },
+            _ => return Err(format!(" Line {}: expected pattern => response ", number + 1)),
+        };
+        let error = |e: String| format!(" Line {}:{}", number + 1, e);
+        let trigger = get_trigger(fields[0]).map_err(error)?;
+        let response = get_escaped_bytes(fields[1]).map_err(error)?;
+        rules.push((trigger, Rule { response, delay }));
+    }
+    return Ok(rules);
 }
 
-fn render_input(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
-    let text = if model.input.len() > 0 {
-        Text::styled(
-            model.input.clone(),
-            Style::default().fg(crate::SELECTED_COLOR),
-        )
-    } else {
-        Text::styled("...", Style::default().fg(crate::PLACEHOLDER_COLOR))
-    };
-    let block = Block::default()
-        .title(" Input ")
-        .title_alignment(Alignment::Left)
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded);
-    let input = Paragraph::new(text).block(block);
-    frame.render_widget(input, area);
+fn get_direction_prefix(direction: &DataDirection) -> &'static str {
+    match direction {
+        DataDirection::Input => return "> ",
+        DataDirection::Output => return "< ",
+        DataDirection::Bridge => return "= ",
+    }
 }
 
-fn render_pause(frame: &mut Frame, area: Rect) {
-    let bounds = get_center_bounds(50, 50, area);
-    let style = Style::default()
-        .fg(crate::PLACEHOLDER_COLOR)
-        .add_modifier(Modifier::BOLD);
-    let text = Text::styled("PAUSED", style);
-    let pause = Paragraph::new(text).alignment(Alignment::Center);
-    frame.render_widget(pause, bounds);
+fn get_dump_encoding(model: &mut TerminalModel, area: Rect) -> Vec<Line<'_>> {
+    // The rows are laid out first, and only those in view are formatted, the
+    // others are left blank so the scrollback stays cheap to draw
+    let visible = match model.state {
+        State::Pausing => model.frozen,
+        _ => model.buffer.len(),
+    };
+    let width = usize::from(area.width.saturating_sub(PADDING * 2));
+    let length = DUMP_ROW_LENGTHS
+        .into_iter()
+        .find(|length| get_dump_width(*length) <= width)
+        .unwrap_or(DUMP_ROW_LENGTHS[DUMP_ROW_LENGTHS.len() - 1]);
+
+    let pattern = get_search_pattern(model).unwrap_or_default();
+    let values: Vec<u8> = model
+        .buffer
+        .iter()
+        .take(visible)
+        .filter_map(|entry| match entry {
+            DataEntry::Byte(data_byte) => Some(data_byte.value),
+            DataEntry::Marker(_) | DataEntry::Note(..) | DataEntry::Gap(_) | DataEntry::Frame(_) => None,
+        })
+        .collect();
+    let marks = get_search_marks(&values, &pattern, &mut 0);
+    let selected = model.search.as_ref().and_then(|search| search.current);
+    let mut search_lines = Vec::new();
+
+    // Each row as its first entry and the index of its first byte
+    let mut rows: Vec<(usize, usize)> = Vec::new();
+    let mut count = 0;
+    let mut value_index = 0;
+    for (index, entry) in model.buffer.iter().take(visible).enumerate() {
+        match entry {
+            DataEntry::Byte(_) => {
+                if count == 0 || count == length {
+                    rows.push((index, value_index));
+                    count = 0;
+                }
+                if let Some(number) = marks[value_index] {
+                    if search_lines.len() == number {
+                        search_lines.push(rows.len() - 1);
+                    }
+                }
+                count += 1;
+                value_index += 1;
+            }
+            DataEntry::Marker(_) | DataEntry::Note(..) | DataEntry::Gap(_) => {
+                rows.push((index, value_index));
+                count = 0;
+            }
+            // The packet starts a row, with the offset of its first byte
+            DataEntry::Frame(_) => count = 0,
+        }
+    }
+    if let Some(search) = model.search.as_mut() {
+        search.lines = search_lines;
+    }
+
+    let height = usize::from(area.height.saturating_sub(PADDING * 2));
+    let bottom = rows.len().saturating_sub(height);
+    let top = model.scroll.unwrap_or(bottom).min(bottom);
+    let mut encoding = Vec::with_capacity(rows.len());
+    for (row, (index, value_index)) in rows.iter().copied().enumerate() {
+        if row < top || row >= top + height {
+            encoding.push(Line::default());
+            continue;
+        }
+        let offset = model.dropped_bytes + value_index as u64;
+        let line = get_dump_line(model, index, offset, length, &marks[value_index..], selected);
+        encoding.push(line);
+    }
+    return encoding;
 }
 
-fn render_terminal(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
-    let block = Block::default().padding(Padding::uniform(PADDING));
-    let data = get_encoding(model, area);
-    let terminal = Paragraph::new(data).block(block);
-    frame.render_widget(terminal, area);
+fn get_dump_line<'a>(
+    model: &TerminalModel,
+    index: usize,
+    offset: u64,
+    length: usize,
+    marks: &[Option<usize>],
+    selected: Option<usize>,
+) -> Line<'a> {
+    // The offset, the bytes in Hex grouped by eight, then their characters
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let trigger_style = Style::default().fg(Color::Black).bg(crate::TRIGGER_COLOR);
+    match model.buffer.get(index) {
+        Some(entry @ (DataEntry::Marker(marker) | DataEntry::Note(marker, _))) => {
+            let marker_style = get_note_style(entry, style.add_modifier(Modifier::BOLD));
+            return Line::from(Span::styled(marker.clone(), marker_style));
+        }
+        Some(DataEntry::Gap(gap)) => {
+            return Line::from(Span::styled(get_gap_text(*gap), style.add_modifier(Modifier::DIM)));
+        }
+        _ => {}
+    }
+
+    let mut hex = vec![Span::styled(format!("{:08X}  ", offset), style)];
+    let mut ascii = vec![Span::styled(" |", style)];
+    let mut count = 0;
+    for entry in model.buffer.iter().skip(index).take(length) {
+        let data_byte = match entry {
+            DataEntry::Byte(data_byte) => data_byte,
+            DataEntry::Marker(_) | DataEntry::Note(..) | DataEntry::Gap(_) | DataEntry::Frame(_) => break,
+        };
+        let byte_style = match (marks[count], &data_byte.direction) {
+            (Some(number), _) => get_search_style(number, selected),
+            (None, DataDirection::Output) if data_byte.matched => trigger_style,
+            (None, DataDirection::Output) => Style::default(),
+            (None, DataDirection::Input) => style,
+            (None, DataDirection::Bridge) => Style::default().fg(crate::SELECTED_COLOR),
+        };
+        let character = match data_byte.value {
+            32..=126 => data_byte.value as char,
+            _ => '.',
+        };
+        hex.push(Span::styled(format!("{:02X}", data_byte.value), byte_style));
+        hex.push(Span::from(get_dump_gap(count, length)));
+        ascii.push(Span::styled(character.to_string(), byte_style));
+        count += 1;
+    }
+
+    // A short row is padded, keeping the characters in their column
+    let padding: String = (count..length).map(|i| format!("  {}", get_dump_gap(i, length))).collect();
+    hex.push(Span::from(padding));
+    ascii.push(Span::styled("|", style));
+    hex.append(&mut ascii);
+    return Line::from(hex);
 }
 
-fn update_buffer_input(model: &mut TerminalModel) {
-    let input_bytes: Vec<u8> = model.input.clone().into_bytes().iter().map(|&value| {
-        // Necessary due to the manner by which crossterm sends number-key input
-        get_number_from_char(value as char)
-    }).collect();
-    let mut input_handle = input_bytes.clone();
-    model.out.append(&mut input_handle);
-    let mode = model.parameters.mode.as_ref().unwrap();
-    let text_width = match mode {
-        Mode::Hex => 5,
-        Mode::Octal => 6,
-        Mode::Ascii => 2,
-        _ => 4,
+fn get_dump_gap(index: usize, length: usize) -> &'static str {
+    if (index + 1).is_multiple_of(8) && index + 1 < length {
+        return "  ";
+    }
+    return " ";
+}
+
+fn get_dump_width(length: usize) -> usize {
+    // The offset, three columns a byte with a gap between the groups, and
+    // the characters between bars
+    return 10 + length * 3 + (length / 8 - 1) + 2 + length + 1;
+}
+
+fn get_export_lines(model: &TerminalModel) -> Vec<String> {
+    // In the mode displayed, a line per run of a direction (or text line),
+    // which starts with the direction
+    let mode = model.parameters.mode.clone().unwrap_or(Mode::Ascii);
+    let gaps = model.parameters.gap_markers == Some(GapMarkers::Exported);
+    let mut lines = Vec::new();
+    if is_text_view(model) {
+        let ansi = model.parameters.ascii_view == Some(AsciiView::Ansi);
+        let mut state = AnsiState::Text;
+        let mut sgr = Style::default();
+        for entry in model.text.iter() {
+            let line = match entry {
+                TextEntry::Line(line) => line,
+                TextEntry::Marker(marker) | TextEntry::Note(marker, _) => {
+                    lines.push(marker.clone());
+                    continue;
+                }
+                TextEntry::Gap(gap) => {
+                    if gaps {
+                        lines.push(get_gap_text(*gap));
+                    }
+                    continue;
+                }
+            };
+            let interpret = ansi && line.direction == DataDirection::Output;
+            let mut text = String::from(get_direction_prefix(&line.direction));
+            for value in line.data.iter().copied() {
+                if interpret {
+                    let in_sequence = state != AnsiState::Text;
+                    state = get_ansi_state(state, value, &mut sgr);
+                    if in_sequence || state != AnsiState::Text {
+                        continue;
+                    }
+                }
+                match value {
+                    32..=126 => text.push(value as char),
+                    b'\t' if interpret || model.mnemonics == false => text.push(value as char),
+                    0..=31 | 127 if interpret => {}
+                    _ if model.mnemonics => text.push_str(&get_mnemonic(value)),
+                    _ => text.push('.'),
+                }
+            }
+            if model.mnemonics && interpret == false {
+                line.ending.iter().for_each(|value| text.push_str(&get_mnemonic(*value)));
+            }
+            lines.push(text);
+        }
+        return lines;
+    }
+
+    let mut current: Option<(DataDirection, String, usize)> = None;
+    // The length of a packet, put ahead of its first row
+    let mut frame = None;
+    for entry in model.buffer.iter() {
+        let data_byte = match entry {
+            DataEntry::Byte(data_byte) => data_byte,
+            DataEntry::Marker(marker) | DataEntry::Note(marker, _) => {
+                if let Some((_, text, _)) = current.take() {
+                    lines.push(text);
+                }
+                lines.push(marker.clone());
+                continue;
+            }
+            DataEntry::Gap(gap) => {
+                if gaps {
+                    if let Some((_, text, _)) = current.take() {
+                        lines.push(text);
+                    }
+                    lines.push(get_gap_text(*gap));
+                }
+                continue;
+            }
+            DataEntry::Frame(length) => {
+                if let Some((_, text, _)) = current.take() {
+                    lines.push(text);
+                }
+                frame = Some(get_frame_prefix(*length));
+                continue;
+            }
+        };
+        let (_, value) = get_byte_text(&mode, data_byte.value, model.mnemonics);
+        match current.as_mut() {
+            Some((direction, text, count))
+                if *direction == data_byte.direction && *count < EXPORT_ROW_LENGTH =>
+            {
+                text.push_str(&value);
+                *count += 1;
+            }
+            _ => {
+                if let Some((_, text, _)) = current.take() {
+                    lines.push(text);
+                }
+                let prefix = frame.take().unwrap_or_default();
+                let text = String::from(get_direction_prefix(&data_byte.direction)) + &prefix + &value;
+                current = Some((data_byte.direction.clone(), text, 1));
+            }
+        }
+    }
+    if let Some((_, text, _)) = current {
+        lines.push(text);
+    }
+    return lines.into_iter().map(|line| line.trim_end().to_string()).collect();
+}
+
+fn get_upload_written(model: &TerminalModel, upload: &Upload) -> u64 {
+    // What the port has written since the transfer started, which the
+    // statistics count for the whole connection
+    let written = model.stats.tx_bytes.saturating_sub(upload.base);
+    return written.min(upload.offset);
+}
+
+fn get_trigger(pattern: &str) -> Result<Trigger, String> {
+    // A pattern between slashes is a regex, anything else is matched as
+    // typed, with the escapes of the input
+    if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return Trigger::regex(pattern, &pattern[1..pattern.len() - 1]);
+    }
+    return get_escaped_bytes(pattern).map(|bytes| Trigger::bytes(pattern, &bytes));
+}
+
+fn get_input_bytes(input: &str, mode: Mode) -> Result<Vec<u8>, String> {
+    // Apart from Ascii, the input is the space separated byte values in the
+    // notation of the mode (e.g., `DE AD BE EF` in Hex)
+    let radix = match mode {
+        Mode::Ascii => return get_escaped_bytes(input),
+        Mode::Hex | Mode::HexDump => 16,
+        Mode::Decimal => 10,
+        Mode::Octal => 8,
     };
-    let width = usize::from(model.bounds.width);
-    let height = usize::from(model.bounds.height);
+    let name = mode.to_string().to_lowercase();
+    let mut bytes = Vec::new();
+    for group in input.split_whitespace() {
+        let invalid = format!(" Invalid {} input '{}' ", name, group);
+        if group.chars().all(|c| c.is_digit(radix)) == false {
+            return Err(invalid);
+        }
+        if radix == 16 {
+            // Hex digits are taken in pairs, so that `DEAD` is two bytes
+            if group.len() % 2 != 0 {
+                return Err(invalid);
+            }
+            for i in (0..group.len()).step_by(2) {
+                let value = u8::from_str_radix(&group[i..i + 2], 16);
+                bytes.push(value.map_err(|_| invalid.clone())?);
+            }
+        } else {
+            bytes.push(u8::from_str_radix(group, radix).map_err(|_| invalid)?);
+        }
+    }
+    return Ok(bytes);
+}
 
-    let rel_height = height - 5;
-    let rel_width = width / text_width;
-    let mut rel_length = (model.buffer.len() + input_bytes.len()) / rel_width;
-    if width % text_width != 0 {
-        rel_length += 1;
+fn get_prompt_error(prompt: &Prompt, input: &str) -> Option<String> {
+    // Only those that can be checked as typed, the others fail once started
+    match prompt {
+        Prompt::SendByte if u8::from_str_radix(input.trim(), 16).is_err() => {
+            return Some(String::from(" Invalid hex byte "));
+        }
+        _ => return None,
     }
+}
 
-    if rel_length > rel_height {
-        model.buffer = Vec::new();
+fn get_row_length(model: &TerminalModel) -> Option<usize> {
+    // Only the modes of a cell a byte are laid out in fixed rows
+    match model.parameters.mode {
+        Some(Mode::Hex) | Some(Mode::Decimal) | Some(Mode::Octal) => {
+            return model.parameters.row_length.filter(|length| *length > 0);
+        }
+        _ => return None,
     }
+}
 
-    for value in input_bytes.iter() {
-        model.buffer.push(DataByte {
-            value: *value,
-            direction: DataDirection::Input,
-        });
+fn get_next_row_length(row_length: Option<usize>, longer: bool) -> Option<usize> {
+    // Stops at either end, rather than wrapping around
+    let index = ROW_LENGTHS.iter().position(|length| *length == row_length.unwrap_or(0)).unwrap_or(0);
+    let next = match longer {
+        true => (index + 1).min(ROW_LENGTHS.len() - 1),
+        false => index.saturating_sub(1),
+    };
+    return Some(ROW_LENGTHS[next]).filter(|length| *length > 0);
+}
+
+fn get_graph_level(value: f64, min: f64, max: f64) -> u64 {
+    // The lowest value keeps a sliver, so that it is told from no data, and
+    // a flat series is drawn halfway
+    if max > min {
+        return 1 + ((value - min) / (max - min) * (GRAPH_LEVELS - 1) as f64).round() as u64;
+    }
+    return GRAPH_LEVELS / 2;
+}
+
+fn get_framing(model: &TerminalModel) -> Option<Framing> {
+    return model.parameters.framing.clone().filter(|framing| *framing != Framing::Off);
+}
+
+fn get_frame_prefix(length: Option<usize>) -> String {
+    // As in `[  12] `, the same width for the bytes that were not decoded
+    match length {
+        Some(length) => return format!("[{:>4}] ", length),
+        None => return String::from("[  ??] "),
+    }
+}
+
+fn get_note_style(entry: &DataEntry, marker_style: Style) -> Style {
+    match entry {
+        DataEntry::Note(_, color) => return marker_style.fg(*color),
+        _ => return marker_style,
+    }
+}
+
+fn get_verdict_color(verdict: &Verdict) -> Color {
+    match verdict {
+        Verdict::Ok => return crate::ACTIVE_COLOR,
+        Verdict::Error | Verdict::CmeError(_) => return crate::INVALID_COLOR,
+        Verdict::Timeout | Verdict::Interrupted => return crate::WARNING_COLOR,
+    }
+}
+
+fn get_at_timeout(model: &TerminalModel) -> Duration {
+    match model.parameters.at_timeout_ms {
+        Some(ms) if ms > 0 => return Duration::from_millis(u64::from(ms)),
+        _ => return DEFAULT_AT_TIMEOUT,
+    }
+}
+
+fn get_generating_summary(model: &TerminalModel, generating: &Generating) -> String {
+    // As in `sent 1.2 MB, checked 1.1 MB, 3 errors`
+    let sent = model.stats.tx_bytes.saturating_sub(generating.base);
+    let verifier = &generating.verifier;
+    if verifier.get_checked() == 0 {
+        return format!("sent {}, nothing looped back", get_size_text(sent));
+    }
+    return format!(
+        "sent {}, checked {}, {} errors",
+        get_size_text(sent),
+        get_size_text(verifier.get_checked()),
+        verifier.get_errors()
+    );
+}
+
+fn get_gap_text(gap: Duration) -> String {
+    return format!("—— {} ms ——", gap.as_millis());
+}
+
+fn get_input_limit(model: &TerminalModel) -> usize {
+    if let Some((Prompt::SendByte, _)) = model.prompt {
+        return 2;
+    }
+    return model.parameters.input_limit.unwrap_or(DEFAULT_INPUT_LIMIT);
+}
+
+fn get_input_spans<'a>(model: &TerminalModel, width: usize, style: Style) -> Vec<Span<'a>> {
+    return get_cursor_spans(&model.input, model.cursor, width, style);
+}
+
+fn get_input_title(model: &TerminalModel) -> String {
+    let mut status = Vec::new();
+    match model.parameters.mode.clone() {
+        Some(Mode::Ascii) => match model.parameters.ascii_view.clone() {
+            Some(AsciiView::Text) | None => status.push(String::from("Ascii")),
+            Some(view) => status.push(format!("Ascii {}", view)),
+        },
+        Some(mode) => status.push(mode.to_string()),
+        None => {}
+    }
+    if let Some(row_length) = get_row_length(model) {
+        status.push(format!("ROW {}", row_length));
+    }
+    if let Some(framing) = get_framing(model) {
+        status.push(framing.to_string());
+    }
+    let triggers = model.triggers.get_triggers().len();
+    if triggers > 0 {
+        status.push(format!("TRIG {}", triggers));
+    }
+    if model.responding {
+        status.push(String::from("AUTO"));
+    }
+    if model.parameters.read_only {
+        status.push(String::from("RO"));
+    } else {
+        match model.parameters.line_ending.clone() {
+            Some(LineEnding::None) | None => {}
+            Some(_) if get_framing(model).is_some() => {}
+            Some(line_ending) => status.push(line_ending.to_string()),
+        }
+        if let Some(repeat) = &model.repeat {
+            status.push(format!("REPEAT {}ms", repeat.interval.as_millis()));
+        }
+        let level = |asserted: bool| if asserted { "on" } else { "off" };
+        status.push(format!("DTR {}", level(model.dtr)));
+        status.push(format!("RTS {}", level(model.rts)));
+        if model.input.len() > 0 {
+            let count = model.input.chars().count();
+            status.push(format!("{}/{}", count, get_input_limit(model)));
+        }
+    }
+    return format!(" Input [{}] ", status.join(" | "));
+}
+
+fn get_next_line_ending(line_ending: &Option<LineEnding>) -> LineEnding {
+    match line_ending {
+        Some(LineEnding::None) | None => LineEnding::Cr,
+        Some(LineEnding::Cr) => LineEnding::Lf,
+        Some(LineEnding::Lf) => LineEnding::CrLf,
+        Some(LineEnding::CrLf) => LineEnding::None,
+    }
+}
+
+fn get_mnemonic(value: u8) -> String {
+    // The ASCII names of the control characters, other bytes by value
+    const NAMES: [&str; 32] = [
+        "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR", "SO", "SI",
+        "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC", "FS", "GS", "RS", "US",
+    ];
+    match value {
+        0..=31 => return format!("<{}>", NAMES[usize::from(value)]),
+        127 => return String::from("<DEL>"),
+        _ => return format!("\\x{:02X}", value),
+    }
+}
+
+fn get_next_mode(mode: &Option<Mode>) -> Mode {
+    match mode {
+        Some(Mode::Ascii) => Mode::Hex,
+        Some(Mode::Hex) => Mode::Decimal,
+        Some(Mode::Decimal) => Mode::Octal,
+        Some(Mode::Octal) => Mode::HexDump,
+        Some(Mode::HexDump) | None => Mode::Ascii,
+    }
+}
+
+fn get_line_spans<'a>(lines: &LineStatus) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let states = [
+        ("CTS", lines.cts),
+        ("DSR", lines.dsr),
+        ("CD", lines.cd),
+        ("RI", lines.ri),
+    ];
+    for (name, state) in states {
+        let span = match state {
+            Some(true) => Span::styled(
+                format!("{} ● ", name),
+                Style::default().fg(crate::ACTIVE_COLOR),
+            ),
+            Some(false) => Span::styled(
+                format!("{} ● ", name),
+                Style::default().fg(crate::PLACEHOLDER_COLOR),
+            ),
+            None => Span::styled(
+                format!("{} - ", name),
+                Style::default().fg(crate::PLACEHOLDER_COLOR),
+            ),
+        };
+        spans.push(span);
+    }
+    return spans;
+}
+
+fn is_passthrough_escape(key: &KeyEvent) -> bool {
+    let escape = [KeyCode::Char(PASSTHROUGH_ESCAPE_CHAR), KeyCode::Char(PASSTHROUGH_ESCAPE_ALIAS)];
+    return key.modifiers == KeyModifiers::CONTROL && escape.contains(&key.code);
+}
+
+fn get_key_bytes(key: &KeyEvent) -> Vec<u8> {
+    // Encoded as a VT100 compatible terminal would send the key
+    let mut bytes = Vec::new();
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        bytes.push(0x1B);
+    }
+    match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => match c {
+            'a'..='z' | 'A'..='Z' => bytes.push(c.to_ascii_lowercase() as u8 - b'a' + 1),
+            '@' | ' ' => bytes.push(0x00),
+            '[' => bytes.push(0x1B),
+            '\\' => bytes.push(0x1C),
+            ']' => bytes.push(0x1D),
+            '^' => bytes.push(0x1E),
+            '_' => bytes.push(0x1F),
+            // As 0x1C - 0x1F are read back from a Unix terminal
+            '4'..='7' => bytes.push(c as u8 - b'4' + 0x1C),
+            _ => return Vec::new(),
+        },
+        KeyCode::Char(c) => {
+            let mut encoded = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+        }
+        KeyCode::Enter => bytes.push(b'\r'),
+        KeyCode::Backspace => bytes.push(0x7F),
+        KeyCode::Tab => bytes.push(b'\t'),
+        KeyCode::Esc => bytes.push(0x1B),
+        KeyCode::BackTab => bytes.extend_from_slice(b"\x1B[Z"),
+        KeyCode::Up => bytes.extend_from_slice(b"\x1B[A"),
+        KeyCode::Down => bytes.extend_from_slice(b"\x1B[B"),
+        KeyCode::Right => bytes.extend_from_slice(b"\x1B[C"),
+        KeyCode::Left => bytes.extend_from_slice(b"\x1B[D"),
+        KeyCode::Home => bytes.extend_from_slice(b"\x1B[H"),
+        KeyCode::End => bytes.extend_from_slice(b"\x1B[F"),
+        KeyCode::Insert => bytes.extend_from_slice(b"\x1B[2~"),
+        KeyCode::Delete => bytes.extend_from_slice(b"\x1B[3~"),
+        KeyCode::PageUp => bytes.extend_from_slice(b"\x1B[5~"),
+        KeyCode::PageDown => bytes.extend_from_slice(b"\x1B[6~"),
+        KeyCode::F(n @ 1..=4) => bytes.extend_from_slice(&[0x1B, b'O', b'P' + n - 1]),
+        _ => return Vec::new(),
     }
+    return bytes;
 }
 
-fn update_buffer_output(model: &mut TerminalModel, data: Vec<u8>) {
-    let mode = model.parameters.mode.as_ref().unwrap();
-    let text_width = match mode {
-        Mode::Hex => 5,
-        Mode::Octal => 6,
-        Mode::Ascii => 2,
-        _ => 4,
+fn get_layout(fsize: Rect, passthrough: bool, graph: bool, fix: bool) -> Rc<[Rect]> {
+    // The input is hidden in pass-through mode, where keys are sent as typed,
+    // and the graph when it would leave little room for the data
+    let input_height = if passthrough { 0 } else { 3 };
+    let graph_height = match graph && fsize.height >= GRAPH_HEIGHT * 3 {
+        true => GRAPH_HEIGHT,
+        false => 0,
     };
-    let width = usize::from(model.bounds.width);
-    let height = usize::from(model.bounds.height);
+    let fix_height = if fix { 1 } else { 0 };
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(STATUS_HEIGHT),
+            Constraint::Length(graph_height),
+            Constraint::Length(fix_height),
+            Constraint::Min(3),
+            Constraint::Length(input_height),
+        ])
+        .split(fsize)
+}
+
+fn get_scroll_bounds(model: &mut TerminalModel) -> (usize, usize) {
+    // The first line shown when following the data, and the lines that fit
+    let area = get_layout(model.bounds, model.passthrough, is_graph_shown(model), is_fix_shown(model))[3];
+    let height = usize::from(area.height.saturating_sub(PADDING * 2));
+    let total = get_encoding(model, area).len();
+    return (total.saturating_sub(height), height.max(1));
+}
+
+fn get_scrollback(model: &TerminalModel) -> usize {
+    return model.parameters.scrollback.unwrap_or(DEFAULT_SCROLLBACK);
+}
+
+pub fn get_port_summary(parameters: &PortParameters) -> String {
+    // As in `/dev/ttyUSB0 115200 8N1 [Hex]`, without the framing that a
+    // network port (or a replay) does not have
+    let mut summary = vec![parameters.name.clone().unwrap_or_default()];
+    if crate::tcp::is_tcp(parameters) == false && is_replay(parameters) == false {
+        if let Some(baud_rate) = parameters.baud_rate {
+            summary.push(baud_rate.to_string());
+        }
+        let parity = parameters.parity.clone().map(|p| p.to_string()).unwrap_or(String::from("N"));
+        summary.push(format!(
+            "{}{}{}",
+            parameters.data_bits.unwrap_or(8),
+            &parity[..1],
+            parameters.stop_bits.unwrap_or(1)
+        ));
+    }
+    if let Some(mode) = parameters.mode.clone() {
+        summary.push(format!("[{}]", mode));
+    }
+    return summary.join(" ");
+}
 
-    let rel_height = height - 5;
-    let rel_width = width / text_width;
-    let mut rel_length = (model.buffer.len() + data.len()) / rel_width;
-    if width % text_width != 0 {
-        rel_length += 1;
+fn get_rate(samples: &mut VecDeque<(Instant, usize)>, now: Instant) -> f64 {
+    // Samples older than the window are discarded, so the rate decays to
+    // zero once the traffic stops
+    while let Some((time, _)) = samples.front() {
+        if now.duration_since(*time) <= RATE_WINDOW {
+            break;
+        }
+        samples.pop_front();
     }
+    let total: usize = samples.iter().map(|(_, size)| size).sum();
+    return total as f64 / RATE_WINDOW.as_secs_f64();
+}
+
+fn get_rate_text(rx_rate: f64, tx_rate: f64) -> String {
+    return format!(
+        "RX {:.1} kB/s  TX {:.1} kB/s",
+        rx_rate / 1000.0,
+        tx_rate / 1000.0
+    );
+}
 
-    if rel_length > rel_height {
-        model.buffer = Vec::new();
+fn get_range_text(range: Option<(u32, u32)>) -> String {
+    match range {
+        Some((first, last)) => return format!("0x{:08X}-0x{:08X}", first, last),
+        None => return String::from("no data"),
     }
+}
 
-    for d in data {
-        model.buffer.push(DataByte {
-            value: d,
-            direction: DataDirection::Output,
-        });
+fn get_size_text(bytes: u64) -> String {
+    let value = bytes as f64;
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    } else if bytes < 1024 * 1024 {
+        return format!("{:.1} KB", value / 1024.0);
+    }
+    return format!("{:.1} MB", value / (1024.0 * 1024.0));
+}
+
+fn get_sgr_style(style: Style, params: &str) -> Style {
+    // An empty parameter is a zero, so `ESC[m` resets like `ESC[0m`
+    let codes: Vec<u16> = params
+        .split(';')
+        .map(|code| code.parse::<u16>().unwrap_or(0))
+        .collect();
+    let mut style = style;
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            c @ 30..=37 => style = style.fg(get_ansi_color(c - 30)),
+            39 => style.fg = None,
+            c @ 40..=47 => style = style.bg(get_ansi_color(c - 40)),
+            49 => style.bg = None,
+            c @ 90..=97 => style = style.fg(get_ansi_color(c - 90 + 8)),
+            c @ 100..=107 => style = style.bg(get_ansi_color(c - 100 + 8)),
+            // The extended colors are skipped over, beyond the basic 16
+            38 | 48 => match codes.get(i + 1) {
+                Some(5) => i += 2,
+                Some(2) => i += 4,
+                _ => {}
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    return style;
+}
+
+fn get_text_encoding(model: &mut TerminalModel, area: Rect) -> Vec<Line<'_>> {
+    // Each line is laid out on its own, wrapped at the width. In the ANSI
+    // view, the received text is styled by its SGR sequences and any other
+    // sequence or control byte is dropped
+    let ansi = model.parameters.ascii_view == Some(AsciiView::Ansi);
+    let input_style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let marker_style = input_style.add_modifier(Modifier::BOLD);
+    let bridge_style = Style::default().fg(crate::SELECTED_COLOR);
+    let trigger_style = Style::default().fg(Color::Black).bg(crate::TRIGGER_COLOR);
+    let mut writer = TextWriter::new(usize::from(area.width));
+    let mut state = AnsiState::Text;
+    let mut sgr = Style::default();
+    let visible = match model.state {
+        State::Pausing => model.text_frozen,
+        _ => model.text.len(),
+    };
+    let pattern = get_search_pattern(model).unwrap_or_default();
+    let selected = model.search.as_ref().and_then(|search| search.current);
+    let mut search_lines = Vec::new();
+    let mut count = 0;
+    for entry in model.text.iter().take(visible) {
+        let line = match entry {
+            TextEntry::Line(line) => line,
+            TextEntry::Marker(marker) => {
+                writer.push_span(Span::styled(marker.clone(), marker_style));
+                writer.new_line();
+                continue;
+            }
+            TextEntry::Note(note, color) => {
+                writer.push_span(Span::styled(note.clone(), marker_style.fg(*color)));
+                writer.new_line();
+                continue;
+            }
+            TextEntry::Gap(gap) => {
+                writer.push_span(Span::styled(get_gap_text(*gap), input_style.add_modifier(Modifier::DIM)));
+                writer.new_line();
+                continue;
+            }
+        };
+        let interpret = ansi && line.direction == DataDirection::Output;
+        // Matched again as displayed, whichever reads the line came from
+        let mut matched = vec![false; line.data.len()];
+        if line.direction == DataDirection::Output {
+            let rules = match model.responding {
+                true => model.responder.get_triggers(),
+                false => &[],
+            };
+            for trigger in model.triggers.get_triggers().iter().chain(rules) {
+                for (start, end) in trigger.find(&line.data) {
+                    matched[start..end].fill(true);
+                }
+            }
+        }
+        // A sentence still arriving is only marked once its line is done
+        let mut broken = vec![false; line.data.len()];
+        if line.direction == DataDirection::Output && is_nmea_checked(model) {
+            for sentence in get_sentences(&line.data) {
+                let pending = line.complete == false && sentence.end == line.data.len();
+                match sentence.check {
+                    Check::Invalid => broken[sentence.start..sentence.end].fill(true),
+                    Check::Truncated if pending == false => broken[sentence.start..sentence.end].fill(true),
+                    _ => {}
+                }
+            }
+        }
+        let marks = get_search_marks(&line.data, &pattern, &mut count);
+        for (index, value) in line.data.iter().copied().enumerate() {
+            // Taken before a sequence is skipped, so every match has a line
+            if let Some(number) = marks[index] {
+                if search_lines.len() == number {
+                    search_lines.push(writer.lines.len());
+                }
+            }
+            if interpret {
+                // A byte within a sequence is never displayed, even the last
+                let in_sequence = state != AnsiState::Text;
+                state = get_ansi_state(state, value, &mut sgr);
+                if in_sequence || state != AnsiState::Text {
+                    continue;
+                }
+            }
+            let style = match line.direction {
+                _ if marks[index].is_some() => get_search_style(marks[index].unwrap(), selected),
+                DataDirection::Output if matched[index] => trigger_style,
+                DataDirection::Output if broken[index] => sgr.fg(crate::INVALID_COLOR),
+                DataDirection::Output => sgr,
+                DataDirection::Input => input_style,
+                DataDirection::Bridge => bridge_style,
+            };
+            match value {
+                32..=126 => writer.push(value as char, style),
+                b'\t' if interpret || model.mnemonics == false => {
+                    for _ in 0..(8 - writer.width % 8) {
+                        writer.push(' ', style);
+                    }
+                }
+                0..=31 | 127 if interpret => {}
+                _ if model.mnemonics => writer.push_cell(&get_mnemonic(value), style.add_modifier(Modifier::DIM)),
+                _ => writer.push('.', style),
+            }
+        }
+        if model.mnemonics && interpret == false {
+            let style = match line.direction {
+                DataDirection::Output => Style::default(),
+                DataDirection::Input => input_style,
+                DataDirection::Bridge => bridge_style,
+            };
+            for value in line.ending.iter().copied() {
+                writer.push_cell(&get_mnemonic(value), style.add_modifier(Modifier::DIM));
+            }
+        }
+        writer.new_line();
+    }
+    if let Some(search) = model.search.as_mut() {
+        search.lines = search_lines;
+    }
+    return writer.finish();
+}
+
+fn get_search_marks(data: &[u8], pattern: &[u8], count: &mut usize) -> Vec<Option<usize>> {
+    // The match each byte is part of, numbered on from those before it
+    let mut marks = vec![None; data.len()];
+    if pattern.len() == 0 {
+        return marks;
+    }
+    let mut index = 0;
+    while index + pattern.len() <= data.len() {
+        if data[index..index + pattern.len()] == *pattern {
+            marks[index..index + pattern.len()].fill(Some(*count));
+            *count += 1;
+            index += pattern.len();
+        } else {
+            index += 1;
+        }
+    }
+    return marks;
+}
+
+fn get_search_pattern(model: &TerminalModel) -> Option<Vec<u8>> {
+    // Typed like the input, as text in Ascii and byte values otherwise
+    let search = model.search.as_ref()?;
+    let mode = model.parameters.mode.clone().unwrap_or(Mode::Ascii);
+    match get_input_bytes(&search.query, mode) {
+        Ok(bytes) if bytes.len() > 0 => return Some(bytes),
+        _ => return None,
+    }
+}
+
+fn get_search_style(number: usize, selected: Option<usize>) -> Style {
+    let style = Style::default().fg(Color::Black);
+    if Some(number) == selected {
+        return style.bg(crate::WARNING_COLOR);
+    }
+    return style.bg(crate::SELECTED_COLOR);
+}
+
+fn get_stats_text(stats: &SerialStats) -> String {
+    let uptime = stats.get_uptime().as_secs();
+    return format!(
+        "TX {}  RX {}  ERR {}  up {:02}:{:02}:{:02}",
+        get_size_text(stats.tx_bytes),
+        get_size_text(stats.rx_bytes),
+        stats.write_errors + stats.read_errors,
+        uptime / 3600,
+        (uptime / 60) % 60,
+        uptime % 60
+    );
+}
+
+fn is_configure_input(msg: &Message) -> bool {
+    match msg {
+        Message::Input(_)
+        | Message::Paste(_)
+        | Message::Backspace
+        | Message::Enter
+        | Message::Delete
+        | Message::HistoryPrev
+        | Message::HistoryNext
+        | Message::CursorLeft
+        | Message::CursorRight
+        | Message::CursorHome
+        | Message::CursorEnd
+        | Message::NextElement
+        | Message::PreviousElement => return true,
+        _ => return false,
+    }
+}
+
+fn is_background(msg: &Message) -> bool {
+    // Those not from a key press
+    match msg {
+        Message::Tick
+        | Message::Rx(_)
+        | Message::Sent(_)
+        | Message::Gap(_)
+        | Message::Connection(_)
+        | Message::Lines(_)
+        | Message::Stats(_)
+        | Message::Reconfigured(_) => return true,
+        _ => return false,
+    }
+}
+
+fn is_graph_shown(model: &TerminalModel) -> bool {
+    return model.series.is_some() && model.parameters.mode == Some(Mode::Ascii) && model.transfer.is_none();
+}
+
+fn is_fix_shown(model: &TerminalModel) -> bool {
+    return model.parameters.nmea == Some(NmeaDecode::Fix)
+        && model.parameters.mode == Some(Mode::Ascii)
+        && model.transfer.is_none();
+}
+
+fn is_text_view(model: &TerminalModel) -> bool {
+    // Framed data is laid out a packet a row, which has no lines of text
+    return matches!(model.parameters.mode, Some(Mode::Ascii) | None)
+        && model.parameters.ascii_view != Some(AsciiView::Raw)
+        && get_framing(model).is_none();
+}
+
+fn is_measuring(model: &TerminalModel) -> bool {
+    return model.latency.as_ref().is_some_and(|latency| latency.is_finished() == false);
+}
+
+fn is_nmea_checked(model: &TerminalModel) -> bool {
+    return matches!(model.parameters.nmea, Some(NmeaDecode::Checked) | Some(NmeaDecode::Fix));
+}
+
+fn is_printable(value: u8) -> bool {
+    return (32..=126).contains(&value);
+}
+
+fn is_search_input(msg: &Message) -> bool {
+    match msg {
+        Message::Input(_)
+        | Message::Paste(_)
+        | Message::Backspace
+        | Message::Enter
+        | Message::HistoryPrev
+        | Message::HistoryNext
+        | Message::Search
+        | Message::Cancel => return true,
+        _ => return false,
+    }
+}
+
+fn is_transmit_input(msg: &Message) -> bool {
+    match msg {
+        Message::Input(_)
+        | Message::Paste(_)
+        | Message::Backspace
+        | Message::Enter
+        | Message::Delete
+        | Message::HistoryPrev
+        | Message::HistoryNext
+        | Message::PassThrough(_)
+        | Message::TogglePassThrough
+        | Message::SendFile
+        | Message::SendByte
+        | Message::Xmodem
+        | Message::RunScript
+        | Message::MeasureLatency
+        | Message::Generate
+        | Message::Repeat
+        | Message::Break
+        | Message::ToggleDtr
+        | Message::ToggleRts => return true,
+        _ => return false,
+    }
+}
+
+fn load_rules(model: &mut TerminalModel, path: &str) {
+    // Shown once connected, the terminal still opens without the rules
+    let rules = fs::read_to_string(path)
+        .map_err(|e| format!(" Failed to read {}: {} ", path, e))
+        .and_then(|text| get_rules(&text));
+    match rules {
+        Ok(rules) => {
+            for (trigger, rule) in rules {
+                model.responder.add(trigger);
+                model.rules.push(rule);
+            }
+            model.responding = model.rules.len() > 0;
+        }
+        Err(e) => model.state = State::Error(e),
+    }
+}
+
+fn clear_display(model: &mut TerminalModel) {
+    // Only what is shown is cleared, the port and the session are left as is
+    let bytes = get_byte_count(model.buffer.iter()) as u64;
+    model.cleared = Some(Cleared {
+        buffer: std::mem::take(&mut model.buffer),
+        text: std::mem::take(&mut model.text),
+        text_size: std::mem::take(&mut model.text_size),
+        bytes,
+        frozen: model.frozen,
+        text_frozen: model.text_frozen,
+    });
+    model.dropped_bytes += bytes;
+    model.scroll = None;
+    model.frozen = 0;
+    model.text_frozen = 0;
+    model.capture_marker = Some(String::from("<CLEARED>"));
+    model.notice = Some(format!(" Cleared [ctrl+{} restores] ", crate::CLEAR_CHAR));
+}
+
+fn restore_display(model: &mut TerminalModel, mut cleared: Cleared) {
+    // Put back ahead of anything received since, which is behind the view
+    // when paused, as no key has been pressed since
+    model.frozen = cleared.frozen;
+    model.text_frozen = cleared.text_frozen;
+    cleared.buffer.append(&mut model.buffer);
+    cleared.text.append(&mut model.text);
+    model.buffer = cleared.buffer;
+    model.text = cleared.text;
+    model.text_size += cleared.text_size;
+    model.dropped_bytes -= cleared.bytes;
+    model.scroll = None;
+    model.notice = Some(String::from(" Restored "));
+    trim_buffer(model);
+}
+
+fn lock_scroll(model: &mut TerminalModel, top: usize) {
+    // The received bytes are counted from when it stops following
+    if model.scroll.is_none() {
+        model.unseen = 0;
+    }
+    model.scroll = Some(top);
+}
+
+fn mark_matches(model: &mut TerminalModel, matches: &[TriggerMatch], end: u64) {
+    // The bytes are marked from the latest received, since part of a match
+    // may have arrived with an earlier read
+    let first = matches.iter().map(|m| m.start).min().unwrap_or(end);
+    let mut offset = end;
+    for entry in model.buffer.iter_mut().rev() {
+        if offset <= first {
+            break;
+        }
+        if let DataEntry::Byte(data_byte) = entry {
+            if data_byte.direction == DataDirection::Output {
+                offset -= 1;
+                data_byte.matched |= matches.iter().any(|m| m.start <= offset && offset < m.end);
+            }
+        }
+    }
+}
+
+fn paste_input(model: &mut TerminalModel, text: &str) {
+    // Inserted at the cursor like typing, what does not fit is dropped
+    let limit = get_input_limit(model);
+    for input in text.chars().filter(|c| c.is_control() == false) {
+        if model.input.chars().count() >= limit {
+            model.notice = Some(format!(" Paste truncated to {} characters ", limit));
+            break;
+        }
+        edit_text(&mut model.input, &mut model.cursor, Edit::Insert(input));
+    }
+    model.invalid = None;
+}
+
+fn read_chunk(path: &str, offset: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut chunk = Vec::new();
+    file.take(UPLOAD_CHUNK).read_to_end(&mut chunk)?;
+    return Ok(chunk);
+}
+
+fn push_marker(model: &mut TerminalModel, marker: &str) {
+    model.buffer.push_back(DataEntry::Marker(String::from(marker)));
+    model.text.push_back(TextEntry::Marker(String::from(marker)));
+}
+
+fn push_note(model: &mut TerminalModel, note: &str, color: Color) {
+    model.buffer.push_back(DataEntry::Note(String::from(note), color));
+    model.text.push_back(TextEntry::Note(String::from(note), color));
+}
+
+fn push_exchange(model: &mut TerminalModel, exchange: &Exchange) {
+    // As in `<AT+CSQ OK 48 ms>`, after the reply it ends
+    let note = format!(
+        "<{} {} {} ms>",
+        exchange.command,
+        exchange.verdict.get_label(),
+        exchange.elapsed.as_millis()
+    );
+    push_note(model, &note, get_verdict_color(&exchange.verdict));
+}
+
+fn pad_row(row: &mut Vec<Span>, row_length: Option<usize>, count: usize, cell_width: usize) {
+    // A short row is filled out to the length of the others
+    if let Some(length) = row_length.filter(|length| count < *length) {
+        row.push(Span::from(" ".repeat((length - count) * cell_width)));
+    }
+}
+
+fn push_gap(model: &mut TerminalModel, gap: Duration) {
+    model.buffer.push_back(DataEntry::Gap(gap));
+    model.text.push_back(TextEntry::Gap(gap));
+    if model.parameters.gap_markers == Some(GapMarkers::Exported) {
+        model.capture_marker = Some(get_gap_text(gap));
+    }
+    trim_buffer(model);
+}
+
+fn record_sample(samples: &mut VecDeque<(Instant, usize)>, now: Instant, size: usize) {
+    if size > 0 {
+        samples.push_back((now, size));
+    }
+}
+
+fn render_at_history(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the top right of the data, the latest exchange last
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let history = model.at.get_history();
+    let mut lines: Vec<Line> = history
+        .iter()
+        .skip(history.len().saturating_sub(AT_HISTORY_ROWS))
+        .map(|exchange| {
+            Line::from(vec![
+                Span::from(format!("{:<16} ", exchange.command)),
+                Span::styled(
+                    format!("{:<14} ", exchange.verdict.get_label()),
+                    Style::default().fg(get_verdict_color(&exchange.verdict)),
+                ),
+                Span::styled(format!("{:>6} ms", exchange.elapsed.as_millis()), style),
+            ])
+        })
+        .collect();
+    if model.at.is_pending() {
+        lines.push(Line::styled("waiting for a result...", style));
+    } else if lines.len() == 0 {
+        lines.push(Line::styled("no commands sent yet", style));
+    }
+
+    let width = 50.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let bounds = Rect::new(area.x + area.width - width, area.y, width, height);
+    let block = Block::default()
+        .title(" AT history [esc] ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(PADDING));
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(lines).block(block), bounds);
+}
+
+fn render_connecting(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let bounds = get_center_bounds(50, 50, area);
+    let style = Style::default()
+        .fg(crate::PLACEHOLDER_COLOR)
+        .add_modifier(Modifier::BOLD);
+    let text = Text::styled(
+        format!(
+            "Connecting to {}...",
+            model.parameters.name.clone().unwrap()
+        ),
+        style,
+    );
+    let connecting = Paragraph::new(text).alignment(Alignment::Center);
+    frame.render_widget(connecting, bounds);
+}
+
+fn render_disconnected(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the data, which stays visible around the banner
+    let text = vec![
+        Line::styled(
+            "DISCONNECTED",
+            Style::default()
+                .fg(crate::INVALID_COLOR)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Line::styled(
+            format!(
+                "{} was unplugged, waiting for it to return",
+                model.parameters.name.clone().unwrap_or_default()
+            ),
+            Style::default().fg(crate::PLACEHOLDER_COLOR),
+        ),
+    ];
+    let width = (text[1].width() as u16 + 4).min(area.width);
+    let height = 4.min(area.height);
+    let bounds = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(crate::INVALID_COLOR));
+    let banner = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(block);
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(banner, bounds);
+}
+
+fn render_error(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let bounds = get_center_bounds(50, 50, area);
+    let style = Style::default()
+        .fg(crate::INVALID_COLOR)
+        .add_modifier(Modifier::BOLD);
+    let (reason, guidance) = match (&model.connection, &model.state) {
+        (ConnectionStatus::Failed(e), _) => (e.to_string(), e.get_guidance()),
+        (_, State::Error(m)) => match &model.error {
+            Some(e) => (m.clone(), e.get_guidance()),
+            None => (m.clone(), None),
+        },
+        _ => (String::from(""), None),
+    };
+    let message = format!(
+        "There was an error communicating with {}",
+        model.parameters.name.clone().unwrap()
+    );
+    let mut text = Text::styled(message, style);
+    text.extend([
+        Line::from(""),
+        Line::styled(
+            String::from(reason.trim()),
+            Style::default().fg(crate::INVALID_COLOR),
+        ),
+    ]);
+    if let Some(g) = guidance {
+        text.extend([Line::styled(g, Style::default().fg(crate::WARNING_COLOR))]);
+    }
+    text.extend([
+        Line::from(""),
+        Line::styled(
+            "Press enter to continue",
+            Style::default().fg(crate::PLACEHOLDER_COLOR),
+        ),
+    ]);
+    let error = Paragraph::new(text).alignment(Alignment::Center);
+    frame.render_widget(error, bounds);
+}
+
+fn render_graph(frame: &mut Frame, area: Rect, series: &Series) {
+    // The latest values that fit, scaled between the lowest and highest
+    let title = match (series.get_current(), series.get_min(), series.get_max()) {
+        (Some(current), Some(min), Some(max)) => format!(" Graph [{} | min {} | max {}] ", current, min, max),
+        _ => String::from(" Graph [waiting for a line with a number] "),
+    };
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let values = series.get_values();
+    let (min, max) = (series.get_min().unwrap_or(0.0), series.get_max().unwrap_or(0.0));
+    let width = usize::from(area.width.saturating_sub(2));
+    let levels: Vec<u64> = values
+        .iter()
+        .skip(values.len().saturating_sub(width))
+        .map(|value| get_graph_level(*value, min, max))
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(block)
+        .data(&levels)
+        .max(GRAPH_LEVELS)
+        .style(Style::default().fg(crate::SELECTED_COLOR));
+    frame.render_widget(sparkline, area);
+}
+
+fn render_fix(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // The latest fix on the left, and the sentences checked on the right
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let fix = match model.nmea.get_fix() {
+        Some(fix) => Span::from(fix.get_summary()),
+        None => Span::styled("waiting for an RMC or GGA sentence", style),
+    };
+    let block = Block::default().padding(Padding::horizontal(PADDING * 2));
+    let line = Line::from(vec![Span::styled("Fix ", style.add_modifier(Modifier::BOLD)), fix]);
+    frame.render_widget(Paragraph::new(line).block(block), area);
+
+    let invalid_style = match model.nmea.get_invalid() > 0 {
+        true => Style::default().fg(crate::INVALID_COLOR),
+        false => style,
+    };
+    let line = Line::from(vec![
+        Span::styled(format!("{} valid  ", model.nmea.get_valid()), style),
+        Span::styled(format!("{} invalid", model.nmea.get_invalid()), invalid_style),
+    ]);
+    let block = Block::default().padding(Padding::horizontal(PADDING * 2));
+    frame.render_widget(Paragraph::new(line).alignment(Alignment::Right).block(block), area);
+}
+
+fn render_generating(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the top right of the data, what was sent first and what
+    // came back after it
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let generating = model.generating.as_ref().unwrap();
+    let elapsed = generating.started.elapsed();
+    let sent = model.stats.tx_bytes.saturating_sub(generating.base);
+    let rate = match generating.rate {
+        Some(rate) => format!("{}/s", get_size_text(rate)),
+        None => String::from("full speed"),
+    };
+    let mut lines = vec![Line::styled(format!("{} at {}", generating.pattern, rate), style)];
+    let average = sent as f64 / elapsed.as_secs_f64().max(1.0);
+    let text = format!("TX {} at {}/s", get_size_text(sent), get_size_text(average as u64));
+    lines.push(Line::styled(text, Style::default().fg(crate::SELECTED_COLOR)));
+    lines.push(Line::styled(
+        match generating.duration {
+            Some(duration) => format!("{} s of {} s", elapsed.as_secs(), duration.as_secs()),
+            None => format!("{} s, until stopped", elapsed.as_secs()),
+        },
+        style,
+    ));
+
+    let verifier = &generating.verifier;
+    match verifier.get_ber() {
+        Some(ber) => {
+            let error_style = match verifier.get_errors() > 0 {
+                true => Style::default().fg(crate::INVALID_COLOR),
+                false => Style::default().fg(crate::ACTIVE_COLOR),
+            };
+            let mut spans = vec![
+                Span::styled(format!("RX {} checked, ", get_size_text(verifier.get_checked())), style),
+                Span::styled(format!("{} errors", verifier.get_errors()), error_style),
+            ];
+            // The pattern slipped, and is being looked for again
+            if verifier.is_locked() == false {
+                spans.push(Span::styled(" (lost)", Style::default().fg(crate::WARNING_COLOR)));
+            }
+            lines.push(Line::from(spans));
+            let text = format!(
+                "BER {:.1e} ({} bits), {} resyncs",
+                ber,
+                verifier.get_bit_errors(),
+                verifier.get_resyncs()
+            );
+            lines.push(Line::styled(text, style));
+        }
+        None => lines.push(Line::styled("RX waiting for the pattern...", style)),
+    }
+
+    let width = 44.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let bounds = Rect::new(area.x + area.width - width, area.y, width, height);
+    let block = Block::default()
+        .title(format!(" Generator [F{} or esc stops] ", GENERATOR_KEY))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(PADDING));
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(lines).block(block), bounds);
+}
+
+fn render_input(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let text = if model.parameters.read_only {
+        Text::styled(
+            "Transmission is disabled in read-only mode",
+            Style::default().fg(crate::WARNING_COLOR),
+        )
+    } else if let Some(invalid) = &model.invalid {
+        let width = usize::from(area.width.saturating_sub(2)).saturating_sub(invalid.len());
+        let mut spans = get_input_spans(model, width, Style::default().fg(crate::INVALID_COLOR));
+        spans.push(Span::styled(invalid.clone(), Style::default().fg(crate::PLACEHOLDER_COLOR)));
+        Text::from(Line::from(spans))
+    } else if model.input.len() > 0 {
+        let width = usize::from(area.width.saturating_sub(2));
+        let spans = get_input_spans(model, width, Style::default().fg(crate::SELECTED_COLOR));
+        Text::from(Line::from(spans))
+    } else if let Some((prompt, _)) = &model.prompt {
+        let placeholder = match prompt {
+            Prompt::SendFile => "Path of the file to send",
+            Prompt::SendByte => "Byte in Hex (e.g., 03, 1B)",
+            Prompt::Xmodem => "send <path> or receive <path>",
+            Prompt::Script => "Path of the script to run",
+            Prompt::Latency => "Count and probe (10 of nolp-probe if blank)",
+            Prompt::Generator => "inc, 55aa, prbs, or text:<bytes>, then B/s and seconds",
+            Prompt::Repeat => "Interval in ms (1000 if blank)",
+            Prompt::Trigger => "Text to match, /regex/, or blank to clear them",
+            Prompt::Disconnect => "Close the port and return to the menu",
+        };
+        Text::styled(placeholder, Style::default().fg(crate::PLACEHOLDER_COLOR))
+    } else {
+        Text::styled("...", Style::default().fg(crate::PLACEHOLDER_COLOR))
+    };
+    let title = match model.prompt {
+        Some((Prompt::SendFile, _)) => String::from(" Send file [enter sends | esc cancels] "),
+        Some((Prompt::SendByte, _)) => String::from(" Send byte [enter sends | esc cancels] "),
+        Some((Prompt::Xmodem, _)) => String::from(" XMODEM [enter starts | esc cancels] "),
+        Some((Prompt::Script, _)) => String::from(" Run script [enter runs | esc cancels] "),
+        Some((Prompt::Latency, _)) => String::from(" Latency [enter starts | esc cancels] "),
+        Some((Prompt::Generator, _)) => String::from(" Generator [enter starts | esc cancels] "),
+        Some((Prompt::Repeat, _)) => String::from(" Repeat [enter starts | esc cancels] "),
+        Some((Prompt::Trigger, _)) => String::from(" Trigger [enter adds | esc cancels] "),
+        Some((Prompt::Disconnect, _)) => String::from(" Disconnect? [enter disconnects | esc stays] "),
+        None => get_input_title(model),
+    };
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let input = Paragraph::new(text).block(block);
+    frame.render_widget(input, area);
+}
+
+fn render_latency(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the top right of the data, and left there once done
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let latency = model.latency.as_ref().unwrap();
+    let stats = latency.get_stats();
+    let lost_style = match stats.lost > 0 {
+        true => Style::default().fg(crate::WARNING_COLOR),
+        false => style,
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("{} of {} sent  ", stats.sent, latency.get_count()), style),
+        Span::styled(format!("{} lost", stats.lost), lost_style),
+    ])];
+    match latency.get_last() {
+        Some(last) => {
+            let text = format!(
+                "min {}  avg {}  max {} ms",
+                get_ms_text(stats.min),
+                get_ms_text(stats.avg),
+                get_ms_text(stats.max)
+            );
+            lines.push(Line::styled(text, Style::default().fg(crate::SELECTED_COLOR)));
+            let text = format!("jitter {} ms  last {} ms", get_ms_text(stats.jitter), get_ms_text(last));
+            lines.push(Line::styled(text, style));
+        }
+        None => lines.push(Line::styled("waiting for an echo...", style)),
+    }
+
+    let title = match latency.is_finished() {
+        true => " Latency [esc closes] ",
+        false => " Latency [esc cancels] ",
+    };
+    let width = 40.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let bounds = Rect::new(area.x + area.width - width, area.y, width, height);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(PADDING));
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(lines).block(block), bounds);
+}
+
+fn render_lines(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let block = Block::default().padding(Padding::new(PADDING * 2, 0, STATUS_HEIGHT - 1, 0));
+    let mut spans = get_line_spans(&model.lines);
+    if model.passthrough {
+        let style = Style::default().fg(crate::WARNING_COLOR).add_modifier(Modifier::BOLD);
+        let text = format!(" PASS-THROUGH (ctrl+{} {} exits)", PASSTHROUGH_ESCAPE_CHAR, QUIT_CHAR);
+        spans.push(Span::styled(text, style));
+    }
+    let lines = Paragraph::new(Line::from(spans)).block(block);
+    frame.render_widget(lines, area);
+
+    // The statistics share the status row, aligned opposite the lines
+    let block = Block::default().padding(Padding::new(0, PADDING * 2, STATUS_HEIGHT - 1, 0));
+    let text = format!(
+        "{}  {}",
+        get_rate_text(model.rx_rate, model.tx_rate),
+        get_stats_text(&model.stats)
+    );
+    let stats = Paragraph::new(Line::styled(
+        text,
+        Style::default().fg(crate::PLACEHOLDER_COLOR),
+    ))
+    .alignment(Alignment::Right)
+    .block(block);
+    frame.render_widget(stats, area);
+}
+
+fn render_pause(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the frozen data, like the disconnected banner
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let text = vec![
+        Line::styled("PAUSED", style.add_modifier(Modifier::BOLD)),
+        Line::styled(
+            format!("{} bytes received since pausing", model.paused_bytes),
+            style,
+        ),
+    ];
+    let width = (text[1].width() as u16 + 4).min(area.width);
+    let height = 4.min(area.height);
+    let bounds = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(style);
+    let banner = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(block);
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(banner, bounds);
+}
+
+fn render_reconnecting(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let bounds = get_center_bounds(50, 50, area);
+    let style = Style::default()
+        .fg(crate::PLACEHOLDER_COLOR)
+        .add_modifier(Modifier::BOLD);
+    let mut text = Text::styled("RECONNECTING...", style);
+    text.extend([
+        Line::from(""),
+        Line::styled(
+            format!(
+                "{} disconnected, waiting for it to return",
+                model.parameters.name.clone().unwrap()
+            ),
+            Style::default().fg(crate::PLACEHOLDER_COLOR),
+        ),
+    ]);
+    let reconnecting = Paragraph::new(text).alignment(Alignment::Center);
+    frame.render_widget(reconnecting, bounds);
+}
+
+fn render_scroll(frame: &mut Frame, area: Rect, unseen: usize) {
+    let style = Style::default()
+        .fg(crate::PLACEHOLDER_COLOR)
+        .add_modifier(Modifier::BOLD);
+    let text = format!("[SCROLL LOCKED — {} new bytes]", unseen);
+    let width = (text.chars().count() as u16).min(area.width);
+    let bounds = Rect::new(
+        area.x + area.width - width,
+        area.y + area.height.saturating_sub(1),
+        width,
+        1.min(area.height),
+    );
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(text).style(style), bounds);
+}
+
+fn render_script(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the top right of the data, the step being run first and
+    // the latest expects after it
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let run = model.script.as_ref().unwrap();
+    let script = &run.script;
+    let mut lines = Vec::new();
+    if let Some(step) = script.get_current() {
+        lines.push(Line::styled(
+            format!("Line {} (step {} of {})", step.number, script.get_index() + 1, script.get_length()),
+            style,
+        ));
+        lines.push(Line::styled(step.line.clone(), Style::default().fg(crate::SELECTED_COLOR)));
+    }
+    let results = script.get_results();
+    for (number, passed) in results.iter().skip(results.len().saturating_sub(SCRIPT_RESULT_ROWS)) {
+        let (label, color) = match passed {
+            true => ("passed", crate::ACTIVE_COLOR),
+            false => ("failed", crate::INVALID_COLOR),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("expect on line {:<4} ", number), style),
+            Span::styled(label, Style::default().fg(color)),
+        ]));
+    }
+
+    let width = 50.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let bounds = Rect::new(area.x + area.width - width, area.y, width, height);
+    let block = Block::default()
+        .title(format!(" Script {} [esc cancels] ", run.path))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(PADDING));
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(lines).block(block), bounds);
+}
+
+fn render_search(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Drawn over the top right of the data, which is left in view
+    let search = model.search.clone().unwrap();
+    let mode = model.parameters.mode.clone().unwrap_or(Mode::Ascii);
+    let count = search.lines.len();
+    let status = if search.query.len() == 0 {
+        String::from("")
+    } else if get_input_bytes(&search.query, mode).is_err() {
+        String::from("invalid")
+    } else if count == 0 {
+        String::from("no matches")
+    } else {
+        match search.current {
+            Some(i) if i < count => format!("{}/{}", i + 1, count),
+            _ => format!("{} matches", count),
+        }
+    };
+    let width = 44.min(area.width);
+    let bounds = Rect::new(area.x + area.width - width, area.y, width, 3.min(area.height));
+    let inner = usize::from(width.saturating_sub(4));
+    let query = match search.query.chars().count() + status.len() + 1 > inner {
+        // The end of a long query is kept in view, where it is typed
+        true => {
+            let keep = inner.saturating_sub(status.len() + 1);
+            let skip = search.query.chars().count().saturating_sub(keep);
+            search.query.chars().skip(skip).collect()
+        }
+        false => search.query.clone(),
+    };
+    let gap = inner.saturating_sub(query.chars().count() + status.len());
+    let text = Line::from(vec![
+        Span::styled(query, Style::default().fg(crate::SELECTED_COLOR)),
+        Span::from(" ".repeat(gap)),
+        Span::styled(status, Style::default().fg(crate::PLACEHOLDER_COLOR)),
+    ]);
+    let block = Block::default()
+        .title(" Search [enter/up/down | esc] ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(PADDING));
+    frame.render_widget(Clear, bounds);
+    frame.render_widget(Paragraph::new(text).block(block), bounds);
+}
+
+fn render_terminal(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let block = Block::default().padding(Padding::uniform(PADDING));
+    let scroll = model.scroll;
+    let data = get_encoding(model, area);
+    // Only the visible window is shown, the latest data unless scrolled up
+    let height = usize::from(area.height.saturating_sub(PADDING * 2));
+    let bottom = data.len().saturating_sub(height);
+    let offset = scroll.unwrap_or(bottom).min(bottom);
+    let terminal = Paragraph::new(data)
+        .scroll((u16::try_from(offset).unwrap_or(u16::MAX), 0))
+        .block(block);
+    frame.render_widget(terminal, area);
+    if scroll.is_some() {
+        render_scroll(frame, area, model.unseen);
+    }
+}
+
+fn render_transfer(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Shown in place of the data, which would only be the packets
+    let transfer = model.transfer.clone().unwrap();
+    let engine = &transfer.engine;
+    let (title, waiting) = match engine.get_direction() {
+        XmodemDirection::Send => (format!(" XMODEM sending {} ", transfer.path), "Waiting for the receiver"),
+        XmodemDirection::Receive => (format!(" XMODEM receiving {} ", transfer.path), "Waiting for the sender"),
+    };
+    let status = match engine.get_state() {
+        XmodemState::Starting => waiting,
+        XmodemState::Ending => "Ending",
+        _ => "Transferring",
+    };
+    let style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+    let text = vec![
+        Line::from(vec![Span::styled("Status   ", style), Span::from(status)]),
+        Line::from(vec![Span::styled("Blocks   ", style), Span::from(engine.get_block().to_string())]),
+        Line::from(vec![Span::styled("Retries  ", style), Span::from(engine.get_retries().to_string())]),
+    ];
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::uniform(PADDING));
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn render_transfer_progress(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // Only the sender knows the size, the receiver counts what has arrived
+    let engine = &model.transfer.as_ref().unwrap().engine;
+    let transferred = engine.get_transferred() as u64;
+    let block = Block::default()
+        .title(" [esc cancels] ")
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    match engine.get_total() {
+        Some(total) => {
+            let label = format!("{} / {}", get_size_text(transferred), get_size_text(total as u64));
+            let gauge = Gauge::default()
+                .block(block)
+                .gauge_style(Style::default().fg(crate::SELECTED_COLOR))
+                .ratio(transferred as f64 / total as f64)
+                .label(label);
+            frame.render_widget(gauge, area);
+        }
+        None => {
+            let text = format!("Received {}", get_size_text(transferred));
+            frame.render_widget(Paragraph::new(text).block(block), area);
+        }
+    }
+}
+
+fn render_upload(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    let upload = model.upload.clone().unwrap();
+    let written = get_upload_written(model, &upload);
+    let block = Block::default()
+        .title(format!(" Sending {} [esc cancels] ", upload.path))
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let label = format!("{} / {}", get_size_text(written), get_size_text(upload.total));
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(crate::SELECTED_COLOR))
+        .ratio(written as f64 / upload.total as f64)
+        .label(label);
+    frame.render_widget(gauge, area);
+}
+
+fn start_hex(model: &mut TerminalModel, path: &str) {
+    // The whole file is checked up front, so that a bad record is shown
+    // before anything is sent
+    let records = fs::read_to_string(path)
+        .map_err(|e| format!(" Failed to read {}: {} ", path, e))
+        .and_then(|text| get_records(&text));
+    match records {
+        Ok(records) => {
+            let pacing = Pacing {
+                delay: model.parameters.hex_line_ms.map(|ms| Duration::from_millis(ms as u64)),
+                prompt: model.parameters.hex_prompt,
+            };
+            push_marker(model, &format!("<HEX {}>", path));
+            model.hex = Some(HexUpload {
+                sender: HexSender::new(records, pacing),
+                path: String::from(path),
+                base: model.stats.tx_bytes,
+                queued: 0,
+            });
+            update_hex(model, Instant::now());
+        }
+        Err(e) => model.state = State::Error(e),
+    }
+}
+
+fn render_hex(frame: &mut Frame, area: Rect, model: &mut TerminalModel) {
+    // The address reached is shown against the range of the whole file
+    let hex = model.hex.as_ref().unwrap();
+    let sender = &hex.sender;
+    let block = Block::default()
+        .title(format!(" Sending {} [esc cancels] ", hex.path))
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let mut label = format!("Line {} / {}", sender.get_index(), sender.get_length());
+    if let Some(address) = sender.get_address() {
+        label += &format!(", 0x{:08X} of {}", address, get_range_text(sender.get_range()));
+    }
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(crate::SELECTED_COLOR))
+        .ratio(sender.get_index() as f64 / sender.get_length() as f64)
+        .label(label);
+    frame.render_widget(gauge, area);
+}
+
+fn start_upload(model: &mut TerminalModel, path: &str) {
+    // Only the size is taken here, the file is read as it is sent
+    if path.len() == 0 {
+        return;
+    }
+    if is_hex_path(path) {
+        start_hex(model, path);
+        return;
+    }
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() == false => {
+            model.state = State::Error(format!(" {} is not a file ", path));
+        }
+        Ok(metadata) if metadata.len() == 0 => {
+            model.notice = Some(format!(" {} is empty ", path));
+        }
+        Ok(metadata) => {
+            push_marker(model, &format!("<SEND {}>", path));
+            model.upload = Some(Upload {
+                path: String::from(path),
+                total: metadata.len(),
+                offset: 0,
+                base: model.stats.tx_bytes,
+            });
+        }
+        Err(e) => model.state = State::Error(format!(" Failed to open {}: {} ", path, e)),
+    }
+}
+
+fn start_repeat(model: &mut TerminalModel, interval: &str) {
+    // The input was checked when prompted, and is sent on the next tick
+    let interval = match interval {
+        "" => DEFAULT_REPEAT_INTERVAL,
+        _ => match interval.parse::<u64>() {
+            Ok(ms) if ms > 0 => Duration::from_millis(ms),
+            _ => {
+                model.state = State::Error(format!(" Invalid repeat interval: {} ", interval));
+                return;
+            }
+        },
+    };
+    let mode = model.parameters.mode.clone().unwrap_or(Mode::Ascii);
+    if let Ok(bytes) = get_input_bytes(&model.input, mode) {
+        model.repeat = Some(Repeat {
+            bytes,
+            interval,
+            next: Instant::now(),
+        });
+    }
+}
+
+fn start_transfer(model: &mut TerminalModel, input: &str) {
+    // Typed as the direction followed by the path (e.g., `send fw.bin`)
+    if input.len() == 0 {
+        return;
+    }
+    let (direction, path) = input.split_once(' ').unwrap_or((input, ""));
+    let path = path.trim();
+    let now = Instant::now();
+    let engine = match direction {
+        "send" | "s" if path.len() > 0 => match fs::read(path) {
+            Ok(data) if data.len() == 0 => {
+                model.notice = Some(format!(" {} is empty ", path));
+                return;
+            }
+            Ok(data) => Xmodem::send(data, now),
+            Err(e) => {
+                model.state = State::Error(format!(" Failed to open {}: {} ", path, e));
+                return;
+            }
+        },
+        "receive" | "r" if path.len() > 0 => Xmodem::receive(now),
+        _ => {
+            model.state = State::Error(String::from(" Expected send <path> or receive <path> "));
+            return;
+        }
+    };
+    let marker = match engine.get_direction() {
+        XmodemDirection::Send => format!("<XMODEM SEND {}>", path),
+        XmodemDirection::Receive => format!("<XMODEM RECEIVE {}>", path),
+    };
+    push_marker(model, &marker);
+    model.transfer = Some(Transfer {
+        engine,
+        path: String::from(path),
+    });
+}
+
+fn start_generating(model: &mut TerminalModel, input: &str) {
+    // Typed as the pattern, then the bytes a second and the seconds to run
+    // (e.g., `prbs 11520 60`), where 0 or nothing is no limit
+    if input.len() == 0 {
+        return;
+    }
+    let fields: Vec<&str> = input.split_whitespace().collect();
+    let pattern = match fields[0] {
+        "inc" => Pattern::Increment,
+        "55aa" => Pattern::Alternate,
+        "prbs" => Pattern::Prbs,
+        field => match field.strip_prefix("text:").map(get_escaped_bytes) {
+            Some(Ok(text)) if text.len() > 0 => Pattern::Text(text),
+            Some(Err(e)) => {
+                model.state = State::Error(e);
+                return;
+            }
+            _ => {
+                model.state = State::Error(format!(" Unknown pattern: {} ", field));
+                return;
+            }
+        },
+    };
+    let mut limits = Vec::new();
+    for field in fields.iter().skip(1).take(2) {
+        match field.parse::<u64>() {
+            Ok(0) => limits.push(None),
+            Ok(limit) => limits.push(Some(limit)),
+            Err(_) => {
+                model.state = State::Error(format!(" Invalid limit: {} ", field));
+                return;
+            }
+        }
+    }
+    let rate = limits.first().copied().flatten();
+    let duration = limits.get(1).copied().flatten().map(Duration::from_secs);
+    push_marker(model, &format!("<GENERATE {}>", pattern));
+    model.commands.push(SerialCommand::Generate(Generator::new(&pattern, rate)));
+    model.generating = Some(Generating {
+        verifier: Verifier::new(&pattern),
+        pattern,
+        rate,
+        duration,
+        started: Instant::now(),
+        base: model.stats.tx_bytes,
+    });
+}
+
+fn start_latency(model: &mut TerminalModel, input: &str) {
+    // Typed as the count followed by the probe (e.g., `20 PING\r\n`),
+    // where either may be left out
+    let (count, probe) = input.split_once(' ').unwrap_or((input, ""));
+    let count = match count {
+        "" => DEFAULT_PROBE_COUNT,
+        _ => match count.parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                model.state = State::Error(String::from(" Expected <count> <probe> "));
+                return;
+            }
+        },
+    };
+    let probe = match get_escaped_bytes(probe) {
+        Ok(probe) if probe.len() == 0 => DEFAULT_PROBE.to_vec(),
+        Ok(probe) => probe,
+        Err(e) => {
+            model.state = State::Error(e);
+            return;
+        }
+    };
+    model.latency = Some(Latency::new(probe, count, PROBE_TIMEOUT));
+    update_latency(model, &[], Instant::now());
+}
+
+fn start_script(model: &mut TerminalModel, path: &str) {
+    // The whole script is read up front, so that a mistake on any line is
+    // shown before anything is sent
+    if path.len() == 0 {
+        return;
+    }
+    let steps = fs::read_to_string(path)
+        .map_err(|e| format!(" Failed to read {}: {} ", path, e))
+        .and_then(|text| get_steps(&text));
+    match steps {
+        Ok(steps) => {
+            push_marker(model, &format!("<SCRIPT {}>", path));
+            let now = Instant::now();
+            model.script = Some(ScriptRun {
+                script: Script::new(steps, now),
+                path: String::from(path),
+            });
+            update_script(model, &[], now);
+        }
+        Err(e) => model.state = State::Error(e),
+    }
+}
+
+fn stop_generating(model: &mut TerminalModel) {
+    let generating = match model.generating.take() {
+        Some(generating) => generating,
+        None => return,
+    };
+    model.commands.push(SerialCommand::StopGenerating);
+    let summary = get_generating_summary(model, &generating);
+    push_marker(model, &format!("<GENERATED {}>", summary));
+    model.notice = Some(format!(" Generator: {} ", summary));
+}
+
+fn submit_input(model: &mut TerminalModel) -> bool {
+    if model.input.len() == 0 {
+        return true;
+    }
+    // Invalid input is kept, so that it can be corrected
+    let mode = model.parameters.mode.clone().unwrap_or(Mode::Ascii);
+    match get_input_bytes(&model.input, mode.clone()) {
+        Ok(bytes) => {
+            update_buffer_input(model, bytes);
+            update_history(model);
+            model.input = String::from("");
+            model.cursor = 0;
+            return true;
+        }
+        // An escape is flagged on the input itself
+        Err(e) if mode == Mode::Ascii => model.invalid = Some(e),
+        Err(e) => model.state = State::Error(e),
+    }
+    return false;
+}
+
+fn trim_buffer(model: &mut TerminalModel) {
+    // The oldest entries are dropped, including from the frozen view
+    let scrollback = get_scrollback(model);
+    if model.buffer.len() > scrollback {
+        let excess = model.buffer.len() - scrollback;
+        model.dropped_bytes += get_byte_count(model.buffer.iter().take(excess)) as u64;
+        model.buffer.drain(..excess);
+        model.frozen = model.frozen.saturating_sub(excess);
+    }
+    // The latest line is kept whole, even when it is longer on its own
+    while model.text_size > scrollback && model.text.len() > 1 {
+        if let Some(TextEntry::Line(line)) = model.text.pop_front() {
+            model.text_size -= line.data.len() + 1;
+        }
+        model.text_frozen = model.text_frozen.saturating_sub(1);
+    }
+}
+
+fn update_buffer_input(model: &mut TerminalModel, mut input_bytes: Vec<u8>) {
+    // A framed input is sent as a packet, where the frame takes the place
+    // of the terminator
+    if let Some(framing) = get_framing(model) {
+        model.out.append(&mut crate::framing::encode(&framing, &input_bytes));
+        if model.parameters.echo {
+            model.buffer.push_back(DataEntry::Frame(Some(input_bytes.len())));
+            push_bytes(model, &input_bytes, DataDirection::Input);
+        }
+        return;
+    }
+    if model.parameters.at_commands {
+        let command = String::from_utf8_lossy(&input_bytes).to_string();
+        if crate::at::is_command(&command) {
+            if let Some(exchange) = model.at.send(&command, Instant::now()) {
+                push_exchange(model, &exchange);
+            }
+        }
+    }
+    // The terminator is sent (and shown) along with the input
+    if let Some(line_ending) = &model.parameters.line_ending {
+        input_bytes.extend_from_slice(line_ending.get_bytes());
+    }
+    send_bytes(model, input_bytes);
+}
+
+fn send_byte(model: &mut TerminalModel, input: &str) {
+    // Sent as it is, without the line ending or a place in the history
+    if let Ok(value) = u8::from_str_radix(input, 16) {
+        send_bytes(model, vec![value]);
+    }
+}
+
+fn send_bytes(model: &mut TerminalModel, input_bytes: Vec<u8>) {
+    // Without the local echo, the device is left to show what it got (the
+    // capture and the counts still have it)
+    let mut input_handle = input_bytes.clone();
+    model.out.append(&mut input_handle);
+    if model.parameters.echo {
+        update_text(model, &input_bytes, DataDirection::Input);
+        push_bytes(model, &input_bytes, DataDirection::Input);
+    }
+}
+
+fn push_bytes(model: &mut TerminalModel, data: &[u8], direction: DataDirection) {
+    for value in data.iter().copied() {
+        model.buffer.push_back(DataEntry::Byte(DataByte {
+            value,
+            direction: direction.clone(),
+            matched: false,
+        }));
+    }
+    trim_buffer(model);
+}
+
+fn is_port_lost(model: &TerminalModel) -> bool {
+    // A failed write (or a lost device) ends any transfer or script
+    return matches!(model.state, State::Error(_)) || model.connection != ConnectionStatus::Connected;
+}
+
+fn update_upload(model: &mut TerminalModel) {
+    let upload = match model.upload.clone() {
+        Some(upload) => upload,
+        None => return,
+    };
+    if is_port_lost(model) {
+        model.upload = None;
+        push_marker(model, "<SEND FAILED>");
+        return;
+    }
+    let written = get_upload_written(model, &upload);
+    if written >= upload.total {
+        model.upload = None;
+        push_marker(model, "<SENT>");
+        model.notice = Some(format!(" Sent {} from {} ", get_size_text(upload.total), upload.path));
+        return;
+    }
+    // The next chunk waits for the port, so the TX buffer only ever holds
+    // one of them
+    if written < upload.offset {
+        return;
+    }
+    match read_chunk(&upload.path, upload.offset) {
+        Ok(chunk) if chunk.len() > 0 => {
+            model.out.extend_from_slice(&chunk);
+            model.upload.as_mut().unwrap().offset += chunk.len() as u64;
+        }
+        // The file was cut short while it was being sent
+        Ok(_) => model.upload.as_mut().unwrap().total = upload.offset,
+        Err(e) => {
+            model.upload = None;
+            model.state = State::Error(format!(" Failed to read {}: {} ", upload.path, e));
+        }
+    }
+}
+
+fn update_hex(model: &mut TerminalModel, now: Instant) {
+    let mut hex = match model.hex.take() {
+        Some(hex) => hex,
+        None => return,
+    };
+    if is_port_lost(model) {
+        push_marker(model, "<HEX FAILED>");
+        return;
+    }
+    // Held while a transfer has the port, and until the last line is
+    // written, so a delay runs from when the line went out
+    let written = model.stats.tx_bytes.saturating_sub(hex.base);
+    if model.transfer.is_some() || written < hex.queued {
+        model.hex = Some(hex);
+        return;
+    }
+    let output = hex.sender.tick(now);
+    if output.len() > 0 {
+        hex.queued += output.len() as u64;
+        send_bytes(model, output);
+    }
+    match hex.sender.get_state() {
+        HexState::Done => {
+            push_marker(model, "<HEX SENT>");
+            let range = get_range_text(hex.sender.get_range());
+            model.notice = Some(format!(" Sent {} records ({}) from {} ", hex.sender.get_length(), range, hex.path));
+        }
+        HexState::Failed(number) => {
+            push_marker(model, "<HEX FAILED>");
+            model.state = State::Error(format!(" No prompt after line {} of {} ", number, hex.path));
+        }
+        HexState::Running => model.hex = Some(hex),
+    }
+}
+
+fn update_replay(model: &mut TerminalModel, msg: Message) {
+    let control = match msg {
+        Message::PauseReplay => {
+            model.replay_paused = model.replay_paused == false;
+            ReplayControl::Pause(model.replay_paused)
+        }
+        Message::CycleReplaySpeed => {
+            let index = REPLAY_SPEEDS.iter().position(|speed| *speed == model.replay_speed).unwrap_or(0);
+            model.replay_speed = REPLAY_SPEEDS[(index + 1) % REPLAY_SPEEDS.len()];
+            ReplayControl::Speed(model.replay_speed)
+        }
+        // A step pauses the replay first, so that it stops after the record
+        _ => {
+            if model.replay_paused == false {
+                model.replay_paused = true;
+                model.commands.push(SerialCommand::Replay(ReplayControl::Pause(true)));
+            }
+            ReplayControl::Step
+        }
+    };
+    model.commands.push(SerialCommand::Replay(control));
+}
+
+fn update_repeat(model: &mut TerminalModel, now: Instant) {
+    // Held while paused (or while anything else has the port), then sent
+    // as soon as it is free again
+    let repeat = match model.repeat.clone() {
+        Some(repeat) => repeat,
+        None => return,
+    };
+    let busy = model.transfer.is_some() || model.connection != ConnectionStatus::Connected;
+    if busy || model.state != State::Running || now < repeat.next {
+        return;
+    }
+    update_buffer_input(model, repeat.bytes);
+    model.repeat.as_mut().unwrap().next = now + repeat.interval;
+}
+
+fn send_responses(model: &mut TerminalModel, now: Instant) {
+    // Held while anything else has the port, in the order they were queued
+    let busy = model.transfer.is_some() || model.connection != ConnectionStatus::Connected;
+    if busy || model.parameters.read_only {
+        return;
+    }
+    while model.responses.front().is_some_and(|response| response.due <= now) {
+        while model.response_times.front().is_some_and(|t| now.duration_since(*t) >= RESPONSE_WINDOW) {
+            model.response_times.pop_front();
+        }
+        if model.response_times.len() >= RESPONSE_LIMIT {
+            model.responding = false;
+            model.responses.clear();
+            model.notice = Some(format!(" Over {} replies a second, auto-response off ", RESPONSE_LIMIT));
+            return;
+        }
+        let response = model.responses.pop_front().unwrap();
+        model.response_times.push_back(now);
+        push_marker(model, "<AUTO>");
+        send_bytes(model, response.bytes);
+    }
+}
+
+fn update_search(model: &mut TerminalModel, msg: Message) {
+    // A new query starts from the latest match again
+    let search = model.search.as_mut().unwrap();
+    match msg {
+        Message::Input(c) => {
+            search.query.push(c);
+            search.current = None;
+        }
+        Message::Paste(text) => {
+            search.query.extend(text.chars().filter(|c| c.is_control() == false));
+            search.current = None;
+        }
+        Message::Backspace => {
+            search.query.pop();
+            search.current = None;
+        }
+        Message::Enter | Message::HistoryNext => update_search_scroll(model, true),
+        Message::HistoryPrev => update_search_scroll(model, false),
+        _ => model.search = None,
+    }
+}
+
+fn update_search_scroll(model: &mut TerminalModel, forward: bool) {
+    // The matches are found again as laid out, and the view is scrolled to
+    // have the next one in the middle
+    let (bottom, height) = get_scroll_bounds(model);
+    let search = model.search.as_mut().unwrap();
+    let count = search.lines.len();
+    if count == 0 {
+        return;
+    }
+    let index = match (search.current, forward) {
+        (Some(i), true) if i < count => (i + 1) % count,
+        (Some(i), false) if i < count => (i + count - 1) % count,
+        _ => count - 1,
+    };
+    search.current = Some(index);
+    let top = search.lines[index].saturating_sub(height / 2);
+    match top >= bottom {
+        true => model.scroll = None,
+        false => lock_scroll(model, top),
+    }
+}
+
+fn update_responses(model: &mut TerminalModel, data: &[u8], now: Instant) {
+    // Always matched, so that the offsets follow the buffer while it is off
+    let matches = model.responder.update(data);
+    if model.responding == false || matches.len() == 0 {
+        return;
+    }
+    mark_matches(model, &matches, model.responder.get_offset());
+    for m in matches.iter() {
+        let rule = model.rules[m.index].clone();
+        model.responses.push_back(Response {
+            bytes: rule.response,
+            due: now + rule.delay,
+        });
+    }
+    send_responses(model, now);
+}
+
+fn update_at(model: &mut TerminalModel, data: &[u8], now: Instant) {
+    // The data is added a line at a time, so that each note follows the
+    // line it is about
+    for line in data.split_inclusive(|value| *value == b'\r' || *value == b'\n') {
+        update_buffer_output(model, line.to_vec(), DataDirection::Output);
+        for event in model.at.update(line, now) {
+            match event {
+                AtEvent::Done(exchange) => push_exchange(model, &exchange),
+                AtEvent::Urc(_) => push_note(model, "<URC>", crate::SELECTED_COLOR),
+            }
+        }
+    }
+}
+
+fn update_triggers(model: &mut TerminalModel, data: &[u8]) {
+    let matches = model.triggers.update(data);
+    if matches.len() == 0 {
+        return;
+    }
+    mark_matches(model, &matches, model.triggers.get_offset());
+
+    let mut labels: Vec<&str> = Vec::new();
+    for m in matches.iter() {
+        let label = model.triggers.get_triggers()[m.index].get_label();
+        if labels.contains(&label) == false {
+            labels.push(label);
+        }
+    }
+    model.notice = Some(format!(" Triggered: {} ", labels.join(", ")));
+    model.bell = model.parameters.trigger_bell;
+}
+
+fn update_transfer(model: &mut TerminalModel, input: &[u8], now: Instant) {
+    let mut transfer = match model.transfer.take() {
+        Some(transfer) => transfer,
+        None => return,
+    };
+    if is_port_lost(model) {
+        push_marker(model, "<XMODEM FAILED>");
+        return;
+    }
+    let output = transfer.engine.update(input, now);
+    model.out.extend(output);
+    let output = transfer.engine.tick(now);
+    model.out.extend(output);
+
+    match transfer.engine.get_state().clone() {
+        XmodemState::Done => {
+            let size = get_size_text(transfer.engine.get_transferred() as u64);
+            if *transfer.engine.get_direction() == XmodemDirection::Send {
+                push_marker(model, "<XMODEM SENT>");
+                model.notice = Some(format!(" Sent {} from {} ", size, transfer.path));
+                return;
+            }
+            match fs::write(&transfer.path, transfer.engine.take_data()) {
+                Ok(()) => {
+                    push_marker(model, "<XMODEM RECEIVED>");
+                    model.notice = Some(format!(" Received {} to {} ", size, transfer.path));
+                }
+                Err(e) => {
+                    push_marker(model, "<XMODEM FAILED>");
+                    model.state = State::Error(format!(" Failed to write {}: {} ", transfer.path, e));
+                }
+            }
+        }
+        XmodemState::Failed(reason) => {
+            push_marker(model, "<XMODEM FAILED>");
+            model.state = State::Error(format!(" XMODEM transfer failed: {} ", reason));
+        }
+        _ => model.transfer = Some(transfer),
+    }
+}
+
+fn update_script(model: &mut TerminalModel, input: &[u8], now: Instant) {
+    let mut run = match model.script.take() {
+        Some(run) => run,
+        None => return,
+    };
+    if is_port_lost(model) {
+        push_marker(model, "<SCRIPT FAILED>");
+        return;
+    }
+    // Held while a transfer has the port
+    if model.transfer.is_some() {
+        model.script = Some(run);
+        return;
+    }
+    // What it sends is shown (and captured) as if it were typed
+    let output = run.script.update(input, now);
+    if output.len() > 0 {
+        send_bytes(model, output);
+    }
+    match run.script.get_state() {
+        ScriptState::Done => {
+            push_marker(model, "<SCRIPT DONE>");
+            let passed = run.script.get_results().len();
+            model.notice = Some(format!(" Ran {} ({} expects passed) ", run.path, passed));
+        }
+        ScriptState::Failed => {
+            let step = run.script.get_current().unwrap();
+            push_marker(model, "<SCRIPT FAILED>");
+            model.state = State::Error(format!(" Script timed out on line {}: {} ", step.number, step.line));
+        }
+        ScriptState::Running => model.script = Some(run),
+    }
+}
+
+fn update_latency(model: &mut TerminalModel, input: &[u8], now: Instant) {
+    // Held while anything else has the port, where a probe still waiting
+    // times out as it would otherwise
+    let busy = model.transfer.is_some() || model.connection != ConnectionStatus::Connected;
+    if busy || is_measuring(model) == false {
+        return;
+    }
+    let latency = model.latency.as_mut().unwrap();
+    let output = latency.update(input, now);
+    let finished = latency.is_finished();
+    let summary = latency.get_stats().get_summary();
+    if output.len() > 0 {
+        send_bytes(model, output);
+    }
+    // The result is kept in the capture, along with the probes
+    if finished {
+        let marker = format!("<LATENCY {}>", summary);
+        push_marker(model, &marker);
+        model.capture_marker = Some(marker);
+        model.notice = Some(format!(" Latency: {} ", summary));
+    }
+}
+
+fn update_generating(model: &mut TerminalModel, now: Instant) {
+    let due = match model.generating.as_ref().and_then(|generating| Some(generating.started + generating.duration?)) {
+        Some(due) => due,
+        None => return,
+    };
+    if now >= due {
+        stop_generating(model);
+    }
+}
+
+fn update_history(model: &mut TerminalModel) {
+    // A recalled entry that is sent again becomes the latest one
+    if model.history.back() != Some(&model.input) {
+        model.history.push_back(model.input.clone());
+    }
+    if model.history.len() > HISTORY_LENGTH {
+        model.history.pop_front();
+    }
+    model.recalled = None;
+    model.draft = String::from("");
+}
+
+fn update_buffer_output(model: &mut TerminalModel, data: Vec<u8>, direction: DataDirection) {
+    // The data accumulates behind the frozen view until it is resumed
+    if model.state == State::Pausing {
+        model.paused_bytes += data.len();
+    }
+    if model.scroll.is_some() {
+        model.unseen += data.len();
+    }
+
+    update_text(model, &data, direction.clone());
+    push_bytes(model, &data, direction);
+}
+
+fn update_buffer_frames(model: &mut TerminalModel, framing: Framing, data: &[u8]) -> Vec<u8> {
+    // Each packet is shown after its length, rather than the data as it was
+    // received, and the decoder starts over when the framing is changed
+    if model.deframer.as_ref().map(|deframer| deframer.get_framing()) != Some(&framing) {
+        model.deframer = Some(Deframer::new(framing));
+    }
+    let packets = model.deframer.as_mut().unwrap().update(data);
+    let mut shown = Vec::new();
+    for packet in packets {
+        let (length, packet) = match packet {
+            Packet::Decoded(packet) => (Some(packet.len()), packet),
+            Packet::Garbage(bytes) => (None, bytes),
+        };
+        model.buffer.push_back(DataEntry::Frame(length));
+        update_buffer_output(model, packet.clone(), DataDirection::Output);
+        shown.extend(packet);
+    }
+    return shown;
+}
+
+fn update_text(model: &mut TerminalModel, data: &[u8], direction: DataDirection) {
+    // Lines end on CR, LF, or CRLF, and each direction is kept on its own
+    // lines so the sent data is not mixed into what is received
+    for value in data.iter().copied() {
+        let after_cr = model.after_cr;
+        model.after_cr = value == b'\r';
+        if value == b'\n' && after_cr {
+            // The LF of a CRLF is kept with the line the CR ended
+            let last = model.text.iter_mut().rev().find_map(|entry| match entry {
+                TextEntry::Line(line) if line.direction == direction => Some(line),
+                _ => None,
+            });
+            if let Some(line) = last {
+                line.ending.push(value);
+            }
+            continue;
+        }
+        let open = match model.text.back_mut() {
+            Some(TextEntry::Line(line)) if line.complete == false && line.direction == direction => {
+                Some(line)
+            }
+            _ => None,
+        };
+        let line_break = value == b'\r' || value == b'\n';
+        match open {
+            Some(line) if line_break => {
+                line.complete = true;
+                line.ending.push(value);
+            }
+            Some(line) => {
+                line.data.push(value);
+                model.text_size += 1;
+            }
+            None => {
+                let (data, ending) = if line_break { (Vec::new(), vec![value]) } else { (vec![value], Vec::new()) };
+                model.text_size += data.len() + 1;
+                model.text.push_back(TextEntry::Line(TerminalLine {
+                    data,
+                    direction: direction.clone(),
+                    complete: line_break,
+                    ending,
+                }));
+            }
+        }
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_connection() {
+        let mut test_model = TerminalModel::default();
+        assert_eq!(test_model.connection, ConnectionStatus::Connected);
+
+        test_model.set_state(State::Error(String::from(" Read failed ")));
+        test_model.update(Message::Connection(ConnectionStatus::Reconnecting));
+        assert_eq!(test_model.connection, ConnectionStatus::Reconnecting);
+        assert_eq!(test_model.get_state(), State::Running);
+
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+        assert_eq!(test_model.connection, ConnectionStatus::Connected);
+
+        test_model.set_state(State::Error(String::from(" Read failed ")));
+        test_model.update(Message::Connection(ConnectionStatus::Disconnected));
+        assert_eq!(test_model.connection, ConnectionStatus::Disconnected);
+        assert_eq!(test_model.get_state(), State::Running);
+    }
+
+    #[test]
+    fn test_update_connecting() {
+        let parameters = PortParameters::default().name(String::from("test/port"));
+        let mut test_model = TerminalModel::new(parameters.clone());
+        assert_eq!(test_model.connection, ConnectionStatus::Connecting);
+
+        test_model.update(Message::ToggleDtr);
+        test_model.update(Message::Connection(ConnectionStatus::Closed));
+        assert_eq!(test_model.get_commands().len(), 0);
+        assert_eq!(test_model.connection, ConnectionStatus::Connecting);
+
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+        assert_eq!(test_model.connection, ConnectionStatus::Connected);
+
+        test_model = TerminalModel::new(parameters.clone());
+        test_model.opened = Instant::now() - OPEN_TIMEOUT;
+        test_model.update(Message::Tick);
+        let reason = String::from("timed out");
+        let error = SerialErrorKind::OpenFailed { reason };
+        assert_eq!(test_model.connection, ConnectionStatus::Failed(error));
+        assert_eq!(test_model.get_commands(), vec![SerialCommand::Close]);
+
+        assert_eq!(
+            test_model.update(Message::Enter),
+            State::Switching(Screen::Menu, Some(Box::new(parameters)))
+        );
+    }
+
+    #[test]
+    fn test_update_read_only() {
+        let mut parameters = PortParameters::default().name(String::from("test/port"));
+        parameters.mode = Some(Mode::Ascii);
+        parameters.read_only = true;
+        let mut test_model = TerminalModel::new(parameters);
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+        assert_eq!(get_input_title(&test_model), " Input [Ascii | RO] ");
+
+        test_model.update(Message::Input('a'));
+        test_model.update(Message::Enter);
+        test_model.update(Message::Break);
+        test_model.update(Message::ToggleDtr);
+        test_model.update(Message::ToggleRts);
+        assert_eq!(test_model.input, "");
+        assert_eq!(test_model.get_output_buffer().len(), 0);
+        assert_eq!(test_model.get_commands().len(), 0);
+    }
+
+    #[test]
+    fn test_update_control_lines() {
+        let mut test_model = TerminalModel::default();
+        assert_eq!(get_input_title(&test_model), " Input [DTR on | RTS on] ");
+
+        test_model.update(Message::ToggleDtr);
+        test_model.update(Message::ToggleRts);
+        test_model.update(Message::ToggleRts);
+        assert_eq!(
+            test_model.get_commands(),
+            vec![
+                SerialCommand::SetDtr(false),
+                SerialCommand::SetRts(false),
+                SerialCommand::SetRts(true),
+            ]
+        );
+        assert_eq!(get_input_title(&test_model), " Input [DTR off | RTS on] ");
+
+        test_model.clear_commands();
+        assert_eq!(test_model.get_commands().len(), 0);
+    }
+
+    #[test]
+    fn test_update_configure() {
+        let parameters = PortParameters {
+            baud_rate: Some(9600),
+            data_bits: Some(8),
+            stop_bits: Some(1),
+            parity: Some(Parity::None),
+            ..Default::default()
+        };
+        let mut test_model = TerminalModel::new(parameters.clone());
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+
+        test_model.update(Message::Configure);
+        assert!(test_model.configure.is_some());
+        test_model.update(Message::Input('0'));
+        assert_eq!(test_model.input, "");
+
+        test_model.update(Message::Enter);
+        let mut expected = parameters.clone();
+        expected.baud_rate = Some(96000);
+        expected.flow_control = Some(FlowControl::None);
+        assert!(test_model.configure.is_none());
+        assert_eq!(
+            test_model.get_commands(),
+            vec![SerialCommand::Configure(expected.clone())]
+        );
+
+        // The parameters only change once the port has accepted them
+        assert_eq!(test_model.parameters, parameters);
+        test_model.update(Message::Reconfigured(expected.clone()));
+        assert_eq!(test_model.parameters, expected);
+
+        test_model.update(Message::Configure);
+        test_model.update(Message::Configure);
+        assert!(test_model.configure.is_none());
+    }
+
+    #[test]
+    fn test_new_dtr_on_open() {
+        let parameters = PortParameters { dtr_on_open: Some(false), ..Default::default() };
+        let test_model = TerminalModel::new(parameters);
+        assert_eq!(get_input_title(&test_model), " Input [DTR off | RTS on] ");
+    }
+
+    #[test]
+    fn test_update_reset() {
+        let parameters = PortParameters { dtr_on_open: Some(false), ..Default::default() };
+        let mut test_model = TerminalModel::new(parameters);
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+
+        test_model.update(Message::Reset);
+        assert_eq!(test_model.get_commands(), vec![]);
+        assert_eq!(
+            test_model.buffer,
+            vec![DataEntry::Marker(String::from("<RESET>"))]
+        );
+        assert_eq!(get_input_title(&test_model), " Input [DTR on | RTS on] ");
+    }
+
+    #[test]
+    fn test_update_break() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+
+        test_model.update(Message::Break);
+        assert_eq!(
+            test_model.get_commands(),
+            vec![SerialCommand::Break(BREAK_DURATION)]
+        );
+        assert_eq!(
+            test_model.buffer,
+            vec![DataEntry::Marker(String::from("<BREAK>"))]
+        );
+
+        let area = Rect::new(0, 0, 80, 24);
+        let encoding = get_encoding(&mut test_model, area);
+        assert_eq!(encoding, vec![Line::from(Span::styled(
+            "<BREAK> ",
+            Style::default()
+                .fg(crate::PLACEHOLDER_COLOR)
+                .add_modifier(Modifier::BOLD),
+        ))]);
+    }
+
+    #[test]
+    fn test_update_pause() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Raw);
+        test_model.bounds = Rect::new(0, 0, 20, 10);
+        let area = Rect::new(0, 0, 20, 10);
+
+        test_model.update(Message::Rx(b"ab".to_vec()));
+        test_model.update(Message::Pause);
+        assert_eq!(test_model.get_state(), State::Pausing);
+
+        // The view stays as it was, while the data is kept for later
+        test_model.update(Message::Rx(b"cdefghijklmnopqrstuvwxyz".to_vec()));
+        assert_eq!(test_model.paused_bytes, 24);
+        assert_eq!(test_model.buffer.len(), 26);
+        assert_eq!(get_encoding(&mut test_model, area), vec![Line::from(vec![
+            Span::from("a "),
+            Span::from("b "),
+        ])]);
+
+        test_model.update(Message::Resume);
+        assert_eq!(test_model.get_state(), State::Running);
+        assert_eq!(test_model.buffer.len(), 26);
+        assert_eq!(get_encoding(&mut test_model, area).len(), 3);
+    }
+
+    #[test]
+    fn test_update_pause_capacity() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        test_model.bounds = Rect::new(0, 0, 20, 10);
+
+        test_model.update(Message::Rx(vec![1, 2]));
+        test_model.update(Message::Pause);
+        test_model.update(Message::Rx(vec![0; DEFAULT_SCROLLBACK]));
+        assert_eq!(test_model.buffer.len(), DEFAULT_SCROLLBACK);
+        assert_eq!(test_model.frozen, 0);
+        assert_eq!(test_model.paused_bytes, DEFAULT_SCROLLBACK);
+    }
+
+    #[test]
+    fn test_update_scroll() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        // Three bytes per line and five visible lines
+        test_model.bounds = Rect::new(0, 0, 20, 12);
+
+        test_model.update(Message::ScrollUp);
+        assert_eq!(test_model.scroll, None);
+
+        test_model.update(Message::Rx(vec![0; 60]));
+        assert_eq!(get_scroll_bounds(&mut test_model), (15, 5));
+        test_model.update(Message::ScrollUp);
+        assert_eq!(test_model.scroll, Some(10));
+
+        // New data does not move the view while scrolled up
+        test_model.update(Message::Rx(vec![0; 30]));
+        assert_eq!(test_model.scroll, Some(10));
+
+        test_model.update(Message::ScrollDown);
+        test_model.update(Message::ScrollDown);
+        assert_eq!(test_model.scroll, Some(20));
+        test_model.update(Message::ScrollDown);
+        assert_eq!(test_model.scroll, None);
+
+        test_model.update(Message::ScrollTop);
+        assert_eq!(test_model.scroll, Some(0));
+        test_model.update(Message::ScrollBottom);
+        assert_eq!(test_model.scroll, None);
+    }
+
+    #[test]
+    fn test_update_scrollback() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Decimal);
+        test_model.parameters.scrollback = Some(4);
+
+        // Only the latest entries are kept, nothing is cleared at once
+        test_model.update(Message::Rx(vec![1, 2, 3]));
+        test_model.update(Message::Rx(vec![4, 5]));
+        let values: Vec<u8> = test_model.buffer.iter().map(|entry| match entry {
+            DataEntry::Byte(data_byte) => data_byte.value,
+            DataEntry::Marker(_) | DataEntry::Note(..) | DataEntry::Gap(_) | DataEntry::Frame(_) => 0,
+        }).collect();
+        assert_eq!(values, vec![2, 3, 4, 5]);
+
+        test_model.update(Message::Flush);
+        assert_eq!(test_model.buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_get_dump_encoding() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::HexDump);
+        let text = |line: &Line| line.spans.iter().map(|span| span.content.to_string()).collect::<String>();
+        test_model.update(Message::Rx(b"0123456789ABCDEFGHIJ".to_vec()));
+        push_marker(&mut test_model, "<RESET>");
+        test_model.update(Message::Rx(vec![0x00, 0xFF]));
+
+        // As many bytes a row as fit, with a short row padded out
+        let lines = get_encoding(&mut test_model, Rect::new(0, 0, 80, 24));
+        let row = "00000000  30 31 32 33 34 35 36 37  38 39 41 42 43 44 45 46  |0123456789ABCDEF|";
+        assert_eq!(text(&lines[0]), row);
+        assert_eq!(text(&lines[1]), format!("00000010  47 48 49 4A{}|GHIJ|", " ".repeat(39)));
+        assert_eq!(text(&lines[2]), "<RESET>");
+        assert!(text(&lines[3]).starts_with("00000014  00 FF "));
+        assert!(text(&lines[3]).ends_with("|..|"));
+        assert_eq!(text(&lines[1]).find('|'), row.find('|'));
+        assert_eq!(get_encoding(&mut test_model, Rect::new(0, 0, 60, 24)).len(), 5);
+        assert_eq!(get_encoding(&mut test_model, Rect::new(0, 0, 160, 24)).len(), 3);
+
+        // The sent bytes are styled apart
+        test_model.update(Message::Connection(ConnectionStatus::Connected));
+        update_buffer_input(&mut test_model, b"OK".to_vec());
+        let lines = get_encoding(&mut test_model, Rect::new(0, 0, 80, 24));
+        let sent: Vec<&Span> = lines[3].spans.iter().filter(|span| span.content == "4F").collect();
+        assert_eq!(sent[0].style.fg, Some(crate::PLACEHOLDER_COLOR));
+
+        // The offsets run on past the dropped bytes, and only the rows in
+        // view are formatted
+        test_model.parameters.scrollback = Some(1600);
+        test_model.update(Message::Flush);
+        test_model.update(Message::Rx(vec![0x55; 1604]));
+        let lines = get_encoding(&mut test_model, Rect::new(0, 0, 80, 12));
+        assert_eq!(lines.len(), 100);
+        assert!(lines[0].spans.is_empty());
+        assert!(text(&lines[99]).starts_with("0000064C  55 "));
+    }
+
+    #[test]
+    fn test_update_scroll_lock() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 16)).unwrap();
+        let mut shown = |model: &mut TerminalModel| -> Vec<String> {
+            terminal.draw(|frame| model.view(frame)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let rows: Vec<String> = buffer
+                .content
+                .chunks(usize::from(buffer.area.width))
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .collect();
+            return rows.into_iter().filter(|row| row.contains("line") || row.contains("LOCKED")).collect();
+        };
+        for i in 0..40 {
+            test_model.update(Message::Rx(format!("line {:02}\r\n", i).into_bytes()));
+        }
+        let following = shown(&mut test_model);
+        assert!(following.last().unwrap().contains("line 39"));
+
+        // Scrolling up locks the view, which stays put as the data arrives
+        test_model.update(Message::ScrollUp);
+        let locked = shown(&mut test_model);
+        for i in 40..50 {
+            test_model.update(Message::Rx(format!("line {:02}\r\n", i).into_bytes()));
+        }
+        let after = shown(&mut test_model);
+        assert_eq!(after[..after.len() - 1], locked[..locked.len() - 1]);
+        assert!(after.last().unwrap().contains("[SCROLL LOCKED — 90 new bytes]"));
+
+        // End follows the data again, and ctrl+End locks it where it is
+        test_model.update(Message::CursorEnd);
+        assert_eq!(test_model.scroll, None);
+        assert!(shown(&mut test_model).last().unwrap().contains("line 49"));
+        test_model.update(Message::ScrollBottom);
+        let locked = shown(&mut test_model);
+        assert!(locked.last().unwrap().contains("0 new bytes"));
+        test_model.update(Message::Rx(b"line 50\r\n".to_vec()));
+        let after = shown(&mut test_model);
+        assert_eq!(after[..after.len() - 1], locked[..locked.len() - 1]);
+        test_model.update(Message::ScrollBottom);
+        assert_eq!(test_model.scroll, None);
+    }
+
+    #[test]
+    fn test_view_small() {
+        // Drawn and scrolled at any size, even those the main screen
+        // replaces with a placeholder
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::Rx(b"hello world\r\n".repeat(20)));
+        for (width, height) in (1..=10).flat_map(|w| (1..=5).map(move |h| (w, h))) {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+            for mode in [Mode::Ascii, Mode::HexDump] {
+                test_model.parameters.mode = Some(mode);
+                test_model.update(Message::Search);
+                test_model.update(Message::Input('o'));
+                terminal.draw(|frame| test_model.view(frame)).unwrap();
+                test_model.update(Message::Enter);
+                test_model.update(Message::Cancel);
+                test_model.update(Message::ScrollUp);
+                test_model.update(Message::ScrollTop);
+                terminal.draw(|frame| test_model.view(frame)).unwrap();
+                test_model.update(Message::ScrollDown);
+                test_model.update(Message::Pause);
+                terminal.draw(|frame| test_model.view(frame)).unwrap();
+                test_model.update(Message::Resume);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_clear() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::Rx(b"one\r\n".to_vec()));
+        test_model.update(Message::Input('a'));
+
+        // Only the display is cleared, which is noted in the capture
+        test_model.update(Message::Clear);
+        assert!(test_model.buffer.is_empty());
+        assert!(test_model.text.is_empty());
+        assert_eq!(test_model.input, "a");
+        assert_eq!(test_model.dropped_bytes, 5);
+        assert_eq!(test_model.take_capture_marker(), Some(String::from("<CLEARED>")));
+
+        // Restored ahead of what arrived since, by the next key press
+        test_model.update(Message::Rx(b"two\r\n".to_vec()));
+        test_model.update(Message::Clear);
+        assert_eq!(get_export_lines(&test_model), vec!["< one", "< two"]);
+        assert_eq!(test_model.dropped_bytes, 0);
+        assert_eq!(test_model.take_capture_marker(), None);
+
+        // But not by any later one
+        test_model.update(Message::Clear);
+        test_model.update(Message::ScrollUp);
+        test_model.update(Message::Clear);
+        assert!(test_model.buffer.is_empty());
+        assert_eq!(test_model.dropped_bytes, 10);
+    }
+
+    #[test]
+    fn test_update_mnemonics() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Raw);
+        test_model.update(Message::Rx(vec![b'A', 0x0D, 0x0A, 0x00, 0x1B, 0xFF]));
+        test_model.update(Message::ToggleMnemonics);
+
+        // The names are wrapped whole, however wide they are
+        let lines = get_encoding(&mut test_model, Rect::new(0, 0, 12, 10));
+        let text: Vec<Vec<String>> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.to_string()).collect())
+            .collect();
+        assert_eq!(text, vec![vec!["A ", "<CR> "], vec!["<LF> ", "<NUL> "], vec!["<ESC> ", "\\xFF "]]);
+        assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::DIM));
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::DIM) == false);
+
+        // The text view shows the line breaks as well, as does the export
+        test_model.parameters.ascii_view = Some(AsciiView::Text);
+        test_model.update(Message::Flush);
+        test_model.update(Message::Rx(b"a\x00b\r\nc\n".to_vec()));
+        let lines = get_text_encoding(&mut test_model, Rect::new(0, 0, 40, 10));
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.to_string()).collect())
+            .collect();
+        assert_eq!(text, vec!["a<NUL>b<CR><LF>", "c<LF>"]);
+        let export = get_export_lines(&test_model);
+        assert!(export[0].ends_with("a<NUL>b<CR><LF>"));
+
+        test_model.update(Message::ToggleMnemonics);
+        let export = get_export_lines(&test_model);
+        assert!(export[0].ends_with("a.b"));
+    }
+
+    #[test]
+    fn test_update_cycle_mode() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Raw);
+        test_model.update(Message::Rx(vec![0x41, 0x0A]));
+        let area = Rect::new(0, 0, 80, 24);
+
+        let expected = [
+            (Mode::Hex, vec!["0x41 ", "0x0A "]),
+            (Mode::Decimal, vec![" 65 ", " 10 "]),
+            (Mode::Octal, vec!["0o101 ", "0o012 "]),
+            (Mode::HexDump, vec![]),
+            (Mode::Ascii, vec!["A ", ". "]),
+        ];
+        for (mode, text) in expected {
+            test_model.update(Message::CycleMode);
+            assert_eq!(test_model.parameters.mode, Some(mode.clone()));
+            let name = match mode {
+                Mode::Ascii => String::from("Ascii Raw"),
+                _ => mode.clone().to_string(),
+            };
+            assert_eq!(
+                get_input_title(&test_model),
+                format!(" Input [{} | DTR on | RTS on] ", name)
+            );
+            if mode == Mode::HexDump {
+                // Laid out in rows of its own, see test_get_dump_encoding
+                continue;
+            }
+            let spans: Vec<Span> = text.into_iter().map(Span::from).collect();
+            assert_eq!(get_encoding(&mut test_model, area), vec![Line::from(spans)]);
+        }
+        assert_eq!(test_model.get_commands().len(), 0);
+    }
+
+    #[test]
+    fn test_update_graph() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        test_model.update(Message::ToggleGraph);
+        assert!(test_model.series.is_none());
+        assert!(test_model.take_notice().is_some());
+
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::ToggleGraph);
+        test_model.update(Message::Rx(b"temp=23.4C\r\nboot\r\ntemp=2".to_vec()));
+        test_model.update(Message::Rx(b"5C\r\ntemp=24.5C\r\n".to_vec()));
+
+        // Drawn above the data, which keeps the rest of the height
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 24)).unwrap();
+        terminal.draw(|frame| test_model.view(frame)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let rows: Vec<String> = buffer
+            .content
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect();
+        let title = rows.iter().position(|row| row.contains(" Graph [24.5 | min 23.4 | max 25] ")).unwrap();
+        let data = rows.iter().position(|row| row.contains("temp=23.4C")).unwrap();
+        assert_eq!(data, title + usize::from(GRAPH_HEIGHT) + 1);
+        assert_eq!(get_graph_level(23.4, 23.4, 25.0), 1);
+        assert_eq!(get_graph_level(25.0, 23.4, 25.0), GRAPH_LEVELS);
+
+        test_model.update(Message::ToggleGraph);
+        assert!(test_model.series.is_none());
+    }
+
+    #[test]
+    fn test_update_nmea() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Text);
+        test_model.parameters.nmea = Some(NmeaDecode::Fix);
+
+        // A sentence split across reads is checked whole
+        test_model.update(Message::Rx(b"$GPGGA,123520,4807.038,N,01131.0".to_vec()));
+        test_model.update(Message::Rx(b"00,E,1,08,0.9,545.4,M,46.9,M,,*4D\r\n".to_vec()));
+        test_model.update(Message::Rx(b"$GPGGA,123520,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*4E\r\n".to_vec()));
+        test_model.update(Message::Rx(b"$GPRMC,123519,A,48".to_vec()));
+        assert_eq!((test_model.nmea.get_valid(), test_model.nmea.get_invalid()), (1, 1));
+
+        // Only the bad sentence is colored, not the one still arriving
+        let lines = get_encoding(&mut test_model, Rect::new(0, 0, 100, 24));
+        let invalid = |line: &Line| line.spans.iter().any(|span| span.style.fg == Some(crate::INVALID_COLOR));
+        assert_eq!(lines.iter().map(invalid).collect::<Vec<bool>>(), vec![false, true, false]);
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| test_model.view(frame)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let rows: Vec<String> = buffer
+            .content
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect();
+        let fix = rows.iter().position(|row| row.contains("Fix 12:35:20 | 48.11730 N, 11.51667 E | GPS, 8 sats")).unwrap();
+        assert!(rows[fix].contains("1 valid  1 invalid"));
+        let data = rows.iter().position(|row| row.contains("$GPGGA")).unwrap();
+        assert_eq!(data, fix + 2);
+
+        // Nothing is checked once turned off, and a flush starts over
+        test_model.parameters.nmea = Some(NmeaDecode::Off);
+        test_model.update(Message::Rx(b"$GPGGA*00\r\n".to_vec()));
+        assert_eq!(test_model.nmea.get_invalid(), 1);
+        test_model.update(Message::Flush);
+        assert_eq!(test_model.nmea.get_fix(), None);
+    }
+
+    #[test]
+    fn test_update_framing() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        test_model.parameters.framing = Some(Framing::Slip);
+        test_model.parameters.line_ending = Some(LineEnding::CrLf);
+        let area = Rect::new(0, 0, 80, 24);
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.spans.iter().map(|span| span.content.to_string()).collect()).collect()
+        };
+
+        // A frame split across reads is shown once decoded, a packet a row
+        test_model.update(Message::Rx(vec![0xC0, 0x01, 0xDB]));
+        assert_eq!(get_encoding(&mut test_model, area).len(), 0);
+        test_model.update(Message::Rx(vec![0xDC, 0xC0, 0xC0, 0x02, 0xDB, 0x03, 0xC0]));
+        let lines = text(get_encoding(&mut test_model, area));
+        assert_eq!(lines, vec!["[   2] 0x01 0xC0 ", "[  ??] 0x02 0xDB 0x03 "]);
+        let garbage = get_encoding(&mut test_model, area)[1].spans[1].style;
+        assert!(garbage.add_modifier.contains(Modifier::DIM));
+
+        // The input is sent as a packet, without the line ending
+        test_model.input = String::from("C0 05");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), vec![0xC0, 0xDB, 0xDC, 0x05, 0xC0]);
+        assert!(get_input_title(&test_model).contains("SLIP"));
+        assert!(get_input_title(&test_model).contains("CRLF") == false);
+
+        // A long packet wraps under its first byte, and Ascii keeps the rows
+        test_model.clear_output_buffer();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::Rx(crate::slip::encode(&[b'a'; 40])));
+        let lines = text(get_encoding(&mut test_model, Rect::new(0, 0, 60, 24)));
+        assert_eq!(lines[2], "[   2] . . ");
+        assert!(lines[3].starts_with("[  40] a a"));
+        assert!(lines[4].starts_with("       a a"));
+
+        test_model.update(Message::Flush);
+        assert!(test_model.deframer.is_none());
+    }
+
+    #[test]
+    fn test_update_echo() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let area = Rect::new(0, 0, 80, 24);
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.spans.iter().map(|span| span.content.to_string()).collect()).collect()
+        };
+
+        // Off, what is sent is still sent, but only the device shows it
+        test_model.update(Message::ToggleEcho);
+        assert_eq!(test_model.notice, Some(String::from(" Local echo off ")));
+        test_model.input = String::from("ab");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), b"ab".to_vec());
+        assert_eq!((test_model.buffer.len(), test_model.text.len()), (0, 0));
+        assert_eq!(get_encoding(&mut test_model, area).len(), 0);
+        test_model.update(Message::Rx(b"ab".to_vec()));
+        assert_eq!(text(get_encoding(&mut test_model, area)), vec!["ab"]);
+
+        // As for a packet
+        test_model.clear_output_buffer();
+        test_model.parameters.framing = Some(Framing::Slip);
+        test_model.input = String::from("c");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), vec![0xC0, b'c', 0xC0]);
+        assert_eq!(test_model.buffer.len(), 2);
+
+        // On, it is shown with the data again
+        test_model.parameters.framing = None;
+        test_model.update(Message::ToggleEcho);
+        assert_eq!(test_model.notice, Some(String::from(" Local echo on ")));
+        test_model.input = String::from("d");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.buffer.len(), 3);
+        assert_eq!(test_model.text.len(), 2);
+    }
+
+    #[test]
+    fn test_update_at() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.line_ending = Some(LineEnding::Cr);
+        test_model.update(Message::ToggleAtHistory);
+        assert!(test_model.at_history == false);
+        assert!(test_model.take_notice().is_some());
+
+        // The echo is read with the reply, and the result noted after it
+        test_model.parameters.at_commands = true;
+        test_model.input = String::from("AT+CSQ");
+        test_model.update(Message::Enter);
+        test_model.update(Message::Rx(b"AT+CSQ\r\r\n+CSQ: 20,99\r\n".to_vec()));
+        test_model.update(Message::Rx(b"\r\nOK\r\n\r\n+CREG: 1\r\n".to_vec()));
+        let notes: Vec<(String, Color)> = test_model
+            .buffer
+            .iter()
+            .filter_map(|entry| match entry {
+                DataEntry::Note(note, color) => Some((note.clone(), *color)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].0.starts_with("<AT+CSQ OK "));
+        assert_eq!(notes[0].1, crate::ACTIVE_COLOR);
+        assert_eq!(notes[1], (String::from("<URC>"), crate::SELECTED_COLOR));
+
+        // Each note has a line of its own in the text view
+        let lines: Vec<String> = get_encoding(&mut test_model, Rect::new(0, 0, 80, 24))
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.to_string()).collect())
+            .collect();
+        let note = lines.iter().position(|line| line.starts_with("<AT+CSQ OK")).unwrap();
+        assert_eq!(lines[note - 1], "OK");
+        assert_eq!(lines[note + 1..note + 4], ["", "+CREG: 1", "<URC>"]);
+
+        // A command without its result is ended by the next
+        test_model.input = String::from("ATD123;");
+        test_model.update(Message::Enter);
+        test_model.input = String::from("ATH");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.at.get_history()[1].verdict, Verdict::Interrupted);
+
+        test_model.update(Message::ToggleAtHistory);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| test_model.view(frame)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let rows: Vec<String> = buffer
+            .content
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect();
+        assert!(rows.iter().any(|row| row.contains(" AT history [esc] ")));
+        assert!(rows.iter().any(|row| row.contains("ATD123;") && row.contains("NO RESULT")));
+        assert!(rows.iter().any(|row| row.contains("waiting for a result...")));
+        test_model.update(Message::Cancel);
+        assert!(test_model.at_history == false);
+    }
+
+    #[test]
+    fn test_update_row_length() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        let area = Rect::new(0, 0, 80, 24);
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.spans.iter().map(|span| span.content.to_string()).collect()).collect()
+        };
+        test_model.update(Message::Rx((0..10).collect()));
+        assert_eq!(get_encoding(&mut test_model, area).len(), 1);
+
+        // The rows are cut at the length, and the last is padded out to it
+        test_model.update(Message::LongerRows);
+        test_model.update(Message::LongerRows);
+        assert_eq!(test_model.parameters.row_length, Some(8));
+        assert_eq!(get_input_title(&test_model), " Input [Hex | ROW 8 | DTR on | RTS on] ");
+        test_model.update(Message::Break);
+        test_model.update(Message::Rx(vec![10]));
+        let lines = text(get_encoding(&mut test_model, area));
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "0x00 0x01 0x02 0x03 0x04 0x05 0x06 0x07 ");
+        assert_eq!(lines[1], format!("0x08 0x09 {}", " ".repeat(30)));
+        assert_eq!(lines[2], "<BREAK>");
+        assert_eq!(lines[3].len(), lines[0].len());
+
+        // Not in Ascii, which has cells of its own widths
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Raw);
+        assert_eq!(get_encoding(&mut test_model, area).len(), 1);
+        assert_eq!(get_input_title(&test_model), " Input [Ascii Raw | DTR on | RTS on] ");
+
+        // Either end is kept, back to the width
+        for _ in 0..5 {
+            test_model.update(Message::LongerRows);
+        }
+        assert_eq!(test_model.parameters.row_length, Some(32));
+        for _ in 0..5 {
+            test_model.update(Message::ShorterRows);
+        }
+        assert_eq!(test_model.parameters.row_length, None);
+    }
+
+    #[test]
+    fn test_get_summary() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.name = Some(String::from("/dev/ttyUSB0"));
+        test_model.parameters.baud_rate = Some(115200);
+        test_model.parameters.data_bits = Some(7);
+        test_model.parameters.parity = Some(Parity::Even);
+        test_model.parameters.stop_bits = Some(2);
+        test_model.parameters.mode = Some(Mode::Hex);
+        assert_eq!(test_model.get_summary(), Some(String::from("/dev/ttyUSB0 115200 7E2 [Hex]")));
+
+        // Follows the port as it is reconfigured, while it is connected
+        let mut parameters = test_model.parameters.clone();
+        parameters.parity = None;
+        parameters.baud_rate = Some(9600);
+        test_model.update(Message::Reconfigured(parameters));
+        assert_eq!(test_model.get_summary(), Some(String::from("/dev/ttyUSB0 9600 7N2 [Hex]")));
+        test_model.update(Message::Connection(ConnectionStatus::Disconnected));
+        assert_eq!(test_model.get_summary(), None);
+
+        let parameters = PortParameters {
+            name: Some(format!("{}localhost:4000", TCP_PREFIX)),
+            baud_rate: Some(9600),
+            ..Default::default()
+        };
+        assert_eq!(get_port_summary(&parameters), "tcp://localhost:4000");
+    }
+
+    #[test]
+    fn test_update_replay() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::PauseReplay);
+        assert!(test_model.take_notice().is_some());
+        assert_eq!(test_model.get_commands(), vec![]);
+
+        // The speed cycles, and a step pauses the replay first
+        test_model.parameters.name = Some(format!("{}session.log", REPLAY_PREFIX));
+        test_model.update(Message::CycleReplaySpeed);
+        test_model.update(Message::CycleReplaySpeed);
+        assert_eq!(test_model.get_summary(), Some(String::from("replay:session.log [Ascii] (10x)")));
+        test_model.update(Message::StepReplay);
+        test_model.update(Message::StepReplay);
+        let expected = vec![
+            SerialCommand::Replay(ReplayControl::Speed(2)),
+            SerialCommand::Replay(ReplayControl::Speed(10)),
+            SerialCommand::Replay(ReplayControl::Pause(true)),
+            SerialCommand::Replay(ReplayControl::Step),
+            SerialCommand::Replay(ReplayControl::Step),
+        ];
+        assert_eq!(test_model.get_commands(), expected);
+        assert_eq!(test_model.get_summary(), Some(String::from("replay:session.log [Ascii] (paused)")));
+        test_model.clear_commands();
+        test_model.update(Message::PauseReplay);
+        test_model.update(Message::CycleReplaySpeed);
+        let expected = vec![
+            SerialCommand::Replay(ReplayControl::Pause(false)),
+            SerialCommand::Replay(ReplayControl::Speed(1)),
+        ];
+        assert_eq!(test_model.get_commands(), expected);
+
+        // What was sent at the time is shown as sent
+        test_model.update(Message::Sent(b"AT".to_vec()));
+        test_model.update(Message::Rx(b"OK".to_vec()));
+        assert_eq!(get_export_lines(&test_model), vec!["> AT", "< OK"]);
+    }
+
+    #[test]
+    fn test_get_input_bytes() {
+        assert_eq!(get_input_bytes("hi 9", Mode::Ascii), Ok(b"hi 9".to_vec()));
+
+        let expected = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        assert_eq!(get_input_bytes("DE AD BE EF", Mode::Hex), Ok(expected.clone()));
+        assert_eq!(get_input_bytes("dead  beef", Mode::Hex), Ok(expected));
+        let error = String::from(" Invalid hex input 'ADB' ");
+        assert_eq!(get_input_bytes("DE ADB", Mode::Hex), Err(error));
+        assert!(get_input_bytes("ZZ", Mode::Hex).is_err());
+        assert!(get_input_bytes("+1", Mode::Hex).is_err());
+        assert!(get_input_bytes("éé", Mode::Hex).is_err());
+
+        assert_eq!(get_input_bytes("0 10 255", Mode::Decimal), Ok(vec![0, 10, 255]));
+        let error = String::from(" Invalid decimal input '256' ");
+        assert_eq!(get_input_bytes("1 256", Mode::Decimal), Err(error));
+        assert!(get_input_bytes("-1", Mode::Decimal).is_err());
+
+        assert_eq!(get_input_bytes("0 12 377", Mode::Octal), Ok(vec![0, 10, 255]));
+        assert!(get_input_bytes("400", Mode::Octal).is_err());
+        assert!(get_input_bytes("8", Mode::Octal).is_err());
+        assert_eq!(get_input_bytes("   ", Mode::Octal), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_update_enter_escape() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        for c in "AT\\q".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.invalid, Some(String::from(" Invalid escape '\\q' ")));
+        assert_eq!(test_model.get_state(), State::Running);
+        assert_eq!(test_model.get_output_buffer().len(), 0);
+
+        // Editing the input clears the flag, and the decoded bytes are shown
+        test_model.update(Message::Backspace);
+        assert_eq!(test_model.invalid, None);
+        test_model.update(Message::Input('r'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), vec![0x41, 0x54, 0x0D]);
+        assert_eq!(test_model.buffer.len(), 3);
+    }
+
+    #[test]
+    fn test_update_line_ending() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+
+        let expected = [
+            (LineEnding::Cr, b"AT\r".to_vec(), " Input [Ascii | CR | DTR on | RTS on] "),
+            (LineEnding::Lf, b"AT\n".to_vec(), " Input [Ascii | LF | DTR on | RTS on] "),
+            (LineEnding::CrLf, b"AT\r\n".to_vec(), " Input [Ascii | CRLF | DTR on | RTS on] "),
+            (LineEnding::None, b"AT".to_vec(), " Input [Ascii | DTR on | RTS on] "),
+        ];
+        for (line_ending, bytes, title) in expected {
+            test_model.update(Message::CycleLineEnding);
+            assert_eq!(test_model.parameters.line_ending, Some(line_ending));
+            assert_eq!(get_input_title(&test_model), title);
+
+            test_model.buffer.clear();
+            test_model.update(Message::Input('A'));
+            test_model.update(Message::Input('T'));
+            test_model.update(Message::Enter);
+            assert_eq!(test_model.get_output_buffer(), bytes);
+            assert_eq!(test_model.buffer.len(), bytes.len());
+            test_model.clear_output_buffer();
+        }
+    }
+
+    #[test]
+    fn test_update_history() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let send = |model: &mut TerminalModel, text: &str| {
+            for c in text.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.input, "");
+
+        send(&mut test_model, "AT");
+        send(&mut test_model, "ATI");
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.input, "ATI");
+        test_model.update(Message::HistoryPrev);
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.input, "AT");
+        test_model.update(Message::HistoryNext);
+        assert_eq!(test_model.input, "ATI");
+        test_model.update(Message::HistoryNext);
+        assert_eq!(test_model.input, "x");
+
+        // An edited entry is added as a new one, the original is kept
+        test_model.update(Message::HistoryPrev);
+        test_model.update(Message::HistoryPrev);
+        send(&mut test_model, "+GMR");
+        assert_eq!(test_model.history, vec!["AT", "ATI", "AT+GMR"]);
+
+        test_model.update(Message::Pause);
+        test_model.update(Message::Resume);
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.input, "AT+GMR");
+        test_model.update(Message::HistoryNext);
+        assert_eq!(test_model.input, "");
+
+        for i in 0..HISTORY_LENGTH {
+            send(&mut test_model, &i.to_string());
+        }
+        assert_eq!(test_model.history.len(), HISTORY_LENGTH);
+        assert_eq!(test_model.history.front(), Some(&String::from("0")));
+    }
+
+    #[test]
+    fn test_update_cursor() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.input_limit = Some(50);
+
+        // Nothing happens at either end of an empty input
+        test_model.update(Message::Backspace);
+        test_model.update(Message::Delete);
+        test_model.update(Message::CursorLeft);
+        test_model.update(Message::CursorRight);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("", 0));
+
+        for c in "ATé".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::CursorHome);
+        test_model.update(Message::Backspace);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("ATé", 0));
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::Delete);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("xTé", 1));
+
+        test_model.update(Message::CursorEnd);
+        test_model.update(Message::Delete);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("xTé", 3));
+        test_model.update(Message::CursorLeft);
+        test_model.update(Message::Backspace);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("xé", 1));
+        test_model.update(Message::CursorRight);
+        test_model.update(Message::CursorRight);
+        test_model.update(Message::Input('!'));
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("xé!", 3));
+
+        // The limit applies wherever the cursor is
+        test_model.update(Message::CursorHome);
+        for _ in 0..60 {
+            test_model.update(Message::Input('a'));
+        }
+        assert_eq!(test_model.input.chars().count(), 50);
+        assert_eq!(test_model.cursor, 47);
+
+        test_model.update(Message::CursorEnd);
+        test_model.update(Message::Enter);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("", 0));
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.cursor, 50);
+    }
+
+    #[test]
+    fn test_update_input_limit() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        for _ in 0..600 {
+            test_model.update(Message::Input('a'));
+        }
+        assert_eq!(test_model.input.len(), DEFAULT_INPUT_LIMIT);
+        assert_eq!(get_input_title(&test_model), " Input [Ascii | DTR on | RTS on | 512/512] ");
+    }
+
+    #[test]
+    fn test_get_input_spans() {
+        let mut test_model = TerminalModel::default();
+        let style = Style::default();
+        let text = |spans: Vec<Span>| -> Vec<String> {
+            spans.iter().map(|span| span.content.to_string()).collect()
+        };
+        test_model.input = String::from("0123456789");
+
+        // The window follows the cursor once it passes the width
+        test_model.cursor = 10;
+        assert_eq!(text(get_input_spans(&test_model, 4, style)), vec!["789", " ", ""]);
+        test_model.cursor = 5;
+        assert_eq!(text(get_input_spans(&test_model, 4, style)), vec!["234", "5", ""]);
+        test_model.cursor = 1;
+        assert_eq!(text(get_input_spans(&test_model, 4, style)), vec!["0", "1", "23"]);
+        test_model.cursor = 0;
+        assert_eq!(text(get_input_spans(&test_model, 20, style)), vec!["", "0", "123456789"]);
+        assert_eq!(text(get_input_spans(&test_model, 0, style)), vec!["", "0", ""]);
+    }
+
+    #[test]
+    fn test_update_enter() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::Input('9'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), vec![0x39]);
+
+        test_model.clear_output_buffer();
+        test_model.parameters.mode = Some(Mode::Hex);
+        for c in "0a f".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Enter);
+        let error = String::from(" Invalid hex input 'f' ");
+        assert_eq!(test_model.get_state(), State::Error(error));
+        assert_eq!(test_model.input, "0a f");
+        assert_eq!(test_model.get_output_buffer().len(), 0);
+
+        test_model.set_state(State::Running);
+        test_model.update(Message::Input('f'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), vec![0x0A, 0xFF]);
+        assert_eq!(test_model.input, "");
+    }
+
+    #[test]
+    fn test_update_paste() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.input_limit = Some(8);
+
+        // Newlines are dropped by default, joining the lines
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::CursorHome);
+        test_model.update(Message::Paste(String::from("ab\r\ncd")));
+        assert_eq!(test_model.input, "abcdx");
+        assert_eq!(test_model.cursor, 4);
+        assert_eq!(test_model.take_notice(), None);
+
+        // What does not fit is dropped with a notice, which is shown once
+        test_model.update(Message::Paste(String::from("efghij")));
+        assert_eq!(test_model.input, "abcdefgx");
+        let notice = String::from(" Paste truncated to 8 characters ");
+        assert_eq!(test_model.take_notice(), Some(notice));
+        assert_eq!(test_model.take_notice(), None);
+
+        // Each complete line is sent, the rest is left in the input
+        test_model.update(Message::CursorEnd);
+        test_model.update(Message::Enter);
+        test_model.clear_output_buffer();
+        test_model.parameters.paste_lines = true;
+        test_model.update(Message::Paste(String::from("AT\rATI\n\nAT+")));
+        assert_eq!(test_model.get_output_buffer(), b"ATATI".to_vec());
+        assert_eq!(test_model.input, "AT+");
+
+        // Sending stops at an invalid line, which is kept to be corrected
+        test_model.input = String::from("");
+        test_model.cursor = 0;
+        test_model.clear_output_buffer();
+        test_model.update(Message::Paste(String::from("a\\q\nb\n")));
+        assert_eq!(test_model.get_output_buffer().len(), 0);
+        assert_eq!(test_model.input, "a\\q");
+        assert!(test_model.invalid.is_some());
+
+        // Nothing is pasted while paused
+        test_model.update(Message::Pause);
+        test_model.update(Message::Paste(String::from("zz")));
+        assert_eq!(test_model.input, "a\\q");
+    }
+
+    #[test]
+    fn test_update_passthrough() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.line_ending = Some(LineEnding::CrLf);
+        let key = |code, modifiers| Message::PassThrough(KeyEvent::new(code, modifiers));
+
+        test_model.update(Message::TogglePassThrough);
+        assert!(test_model.is_passthrough());
+        test_model.update(key(KeyCode::Char('l'), KeyModifiers::NONE));
+        test_model.update(key(KeyCode::Char('s'), KeyModifiers::NONE));
+        test_model.update(key(KeyCode::Enter, KeyModifiers::NONE));
+        test_model.update(key(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        test_model.update(Message::Paste(String::from("a\nb")));
+        assert_eq!(test_model.get_output_buffer(), b"ls\r\x03a\rb".to_vec());
+        // Nothing is echoed locally or left in the input
+        assert_eq!(test_model.buffer.len(), 0);
+        assert_eq!(test_model.input, "");
+
+        // The escape is sent by typing it twice, otherwise q leaves the mode
+        test_model.clear_output_buffer();
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_CHAR), KeyModifiers::CONTROL));
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_CHAR), KeyModifiers::CONTROL));
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_CHAR), KeyModifiers::CONTROL));
+        assert!(test_model.is_passthrough());
+        test_model.update(key(KeyCode::Char(QUIT_CHAR), KeyModifiers::NONE));
+        assert!(test_model.is_passthrough() == false);
+        assert_eq!(test_model.get_output_buffer(), vec![0x1D]);
+
+        // As the escape arrives from a Unix terminal
+        test_model.clear_output_buffer();
+        test_model.update(Message::TogglePassThrough);
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_ALIAS), KeyModifiers::CONTROL));
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_ALIAS), KeyModifiers::CONTROL));
+        test_model.update(key(KeyCode::Char(PASSTHROUGH_ESCAPE_ALIAS), KeyModifiers::CONTROL));
+        test_model.update(key(KeyCode::Char(QUIT_CHAR), KeyModifiers::NONE));
+        assert!(test_model.is_passthrough() == false);
+        assert_eq!(test_model.get_output_buffer(), vec![0x1D]);
+
+        // An error is acknowledged as usual
+        test_model.update(Message::TogglePassThrough);
+        test_model.set_state(State::Error(String::from(" Error ")));
+        assert!(test_model.is_passthrough() == false);
+
+        test_model = TerminalModel::default();
+        test_model.parameters.read_only = true;
+        test_model.update(Message::TogglePassThrough);
+        assert!(test_model.is_passthrough() == false);
+    }
+
+    #[test]
+    fn test_get_key_bytes() {
+        let bytes = |code, modifiers| get_key_bytes(&KeyEvent::new(code, modifiers));
+        assert_eq!(bytes(KeyCode::Char('é'), KeyModifiers::NONE), "é".as_bytes().to_vec());
+        assert_eq!(bytes(KeyCode::Char('A'), KeyModifiers::SHIFT), b"A".to_vec());
+        assert_eq!(bytes(KeyCode::Char('a'), KeyModifiers::CONTROL), vec![0x01]);
+        assert_eq!(bytes(KeyCode::Char('Z'), KeyModifiers::CONTROL), vec![0x1A]);
+        assert_eq!(bytes(KeyCode::Char('['), KeyModifiers::CONTROL), vec![0x1B]);
+        assert_eq!(bytes(KeyCode::Char('1'), KeyModifiers::CONTROL), Vec::<u8>::new());
+        assert_eq!(bytes(KeyCode::Char('4'), KeyModifiers::CONTROL), vec![0x1C]);
+        assert_eq!(bytes(KeyCode::Char('7'), KeyModifiers::CONTROL), vec![0x1F]);
+        assert_eq!(bytes(KeyCode::Char('x'), KeyModifiers::ALT), b"\x1Bx".to_vec());
+        assert_eq!(bytes(KeyCode::Backspace, KeyModifiers::NONE), vec![0x7F]);
+        assert_eq!(bytes(KeyCode::Up, KeyModifiers::NONE), b"\x1B[A".to_vec());
+        assert_eq!(bytes(KeyCode::Delete, KeyModifiers::NONE), b"\x1B[3~".to_vec());
+        assert_eq!(bytes(KeyCode::F(2), KeyModifiers::NONE), b"\x1BOQ".to_vec());
+        assert_eq!(bytes(KeyCode::F(5), KeyModifiers::NONE), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_get_text_encoding() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.bounds = Rect::new(0, 0, 20, 10);
+        let area = Rect::new(0, 0, 6, 10);
+        let text = |model: &mut TerminalModel| -> Vec<String> {
+            get_text_encoding(model, area)
+                .iter()
+                .map(|line| line.spans.iter().map(|span| span.content.to_string()).collect())
+                .collect()
+        };
+
+        // CRLF is a single break, split across reads or not
+        test_model.update(Message::Rx(b"boot\r".to_vec()));
+        test_model.update(Message::Rx(b"\nok\rwrapped\n\n\x1B[0m".to_vec()));
+        assert_eq!(text(&mut test_model), vec!["boot", "ok", "wrappe", "d", "", ".[0m"]);
+
+        // The sent data is on its own lines, styled apart
+        test_model.update(Message::Input('h'));
+        test_model.update(Message::Input('i'));
+        test_model.update(Message::Enter);
+        test_model.update(Message::Rx(b"!".to_vec()));
+        let lines = get_text_encoding(&mut test_model, area);
+        let input_style = Style::default().fg(crate::PLACEHOLDER_COLOR);
+        assert_eq!(lines[6], Line::from(Span::styled("hi", input_style)));
+        assert_eq!(lines[7], Line::from(Span::from("!")));
+
+        // A marker is a line of its own, and the pause freezes the lines
+        test_model.update(Message::Pause);
+        test_model.update(Message::Rx(b"more".to_vec()));
+        assert_eq!(text(&mut test_model).len(), 8);
+        test_model.update(Message::Resume);
+        test_model.update(Message::Break);
+        test_model.update(Message::Rx(b"x".to_vec()));
+        assert_eq!(text(&mut test_model)[7..], ["!more", "<BREAK>", "x"]);
+
+        test_model.update(Message::Flush);
+        assert_eq!(text(&mut test_model).len(), 0);
+        assert_eq!(test_model.text_size, 0);
+    }
+
+    #[test]
+    fn test_update_text_scrollback() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.scrollback = Some(10);
+
+        // The oldest lines are dropped, but never the latest
+        test_model.update(Message::Rx(b"one\ntwo\nthree\n".to_vec()));
+        assert_eq!(test_model.text.len(), 2);
+        assert_eq!(test_model.text_size, 10);
+        test_model.update(Message::Rx(b"a long line".to_vec()));
+        assert_eq!(test_model.text.len(), 1);
+    }
+
+    #[test]
+    fn test_get_export_lines() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Ansi);
+        test_model.update(Message::Rx(b"\x1B[32mboot\x1B[0m\r\nready".to_vec()));
+        test_model.update(Message::Input('h'));
+        test_model.update(Message::Enter);
+        test_model.update(Message::Break);
+        test_model.update(Message::BridgeRx(b"\t\x01".to_vec()));
+        let expected = vec!["< boot", "< ready", "> h", "<BREAK>", "= \t."];
+        assert_eq!(get_export_lines(&test_model), expected);
+
+        // Otherwise the bytes are split into rows, as displayed
+        test_model.parameters.mode = Some(Mode::Hex);
+        let expected = vec![
+            "< 0x1B 0x5B 0x33 0x32 0x6D 0x62 0x6F 0x6F 0x74 0x1B 0x5B 0x30 0x6D 0x0D 0x0A 0x72",
+            "< 0x65 0x61 0x64 0x79",
+            "> 0x68",
+            "<BREAK>",
+            "= 0x09 0x01",
+        ];
+        assert_eq!(get_export_lines(&test_model), expected);
+    }
+
+    #[test]
+    fn test_update_gap() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Hex);
+        test_model.parameters.gap_markers = Some(GapMarkers::Shown);
+        test_model.update(Message::Rx(vec![1, 2, 3]));
+        test_model.update(Message::Gap(Duration::from_millis(312)));
+        test_model.update(Message::Rx(vec![4]));
+
+        // The gap is a row of its own, and the data after it starts a new row
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.spans.iter().map(|span| span.content.to_string()).collect()).collect()
+        };
+        let lines = text(get_encoding(&mut test_model, Rect::new(0, 0, 80, 24)));
+        assert_eq!(lines, vec!["0x01 0x02 0x03 ", "—— 312 ms ——", "0x04 "]);
+        assert_eq!(get_byte_count(test_model.buffer.iter()), 4);
+        assert_eq!(test_model.take_capture_marker(), None);
+        assert_eq!(get_export_lines(&test_model), vec!["< 0x01 0x02 0x03 0x04"]);
+
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::Rx(b"ab".to_vec()));
+        test_model.update(Message::Gap(Duration::from_millis(50)));
+        test_model.update(Message::Rx(b"c\r\n".to_vec()));
+        let lines = text(get_text_encoding(&mut test_model, Rect::new(0, 0, 80, 24)));
+        assert_eq!(lines[lines.len() - 3..], [".ab", "—— 50 ms ——", "c"]);
+
+        // Only kept in the exports when asked for
+        test_model.parameters.gap_markers = Some(GapMarkers::Exported);
+        test_model.update(Message::Gap(Duration::from_millis(75)));
+        assert_eq!(test_model.take_capture_marker(), Some(String::from("—— 75 ms ——")));
+        let expected = vec!["< ...", "—— 312 ms ——", "< .ab", "—— 50 ms ——", "< c", "—— 75 ms ——"];
+        assert_eq!(get_export_lines(&test_model), expected);
+    }
+
+    #[test]
+    fn test_export_buffer() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Decimal);
+        let path = std::env::temp_dir().join(format!("nolp-export-{}.txt", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+
+        export_buffer(&mut test_model, &path);
+        assert_eq!(test_model.take_notice(), Some(String::from(" Nothing to export ")));
+
+        test_model.update(Message::Rx(vec![1, 255]));
+        export_buffer(&mut test_model, &path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<   1 255\n");
+        let notice = format!(" Exported 1 lines to {} ", path);
+        assert_eq!(test_model.take_notice(), Some(notice));
+        fs::remove_file(&path).unwrap();
+
+        // A failure is shown as an error, to be acknowledged
+        export_buffer(&mut test_model, "/nonexistent/nolp/export.txt");
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+    }
+
+    #[test]
+    fn test_update_send_file() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let path = std::env::temp_dir().join(format!("nolp-send-{}.bin", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let data: Vec<u8> = (0..10_000_u32).map(|i| i as u8).collect();
+        fs::write(&path, &data).unwrap();
+        let type_text = |model: &mut TerminalModel, text: &str| {
+            for c in text.chars() {
+                model.update(Message::Input(c));
+            }
+        };
+
+        // The prompt borrows the input box, which is restored after
+        type_text(&mut test_model, "draft");
+        test_model.update(Message::SendFile);
+        assert_eq!(test_model.input, "");
+        type_text(&mut test_model, &path);
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.input, "draft");
+        assert!(test_model.upload.is_some());
+
+        // Each chunk waits until the previous one is written
+        let mut sent = Vec::new();
+        for _ in 0..3 {
+            test_model.update(Message::Tick);
+            test_model.update(Message::Tick);
+            let chunk = test_model.get_output_buffer();
+            test_model.clear_output_buffer();
+            assert!(chunk.len() as u64 <= UPLOAD_CHUNK);
+            sent.extend(chunk);
+            let mut stats = test_model.stats.clone();
+            stats.tx_bytes = sent.len() as u64;
+            test_model.update(Message::Stats(stats));
+        }
+        assert_eq!(sent, data);
+        test_model.update(Message::Tick);
+        assert!(test_model.upload.is_none());
+        assert!(test_model.take_notice().is_some());
+
+        // Cancelling stops the transfer, and leaves a marker
+        test_model.update(Message::SendFile);
+        type_text(&mut test_model, &path);
+        test_model.update(Message::Enter);
+        test_model.update(Message::Tick);
+        test_model.update(Message::Cancel);
+        assert!(test_model.upload.is_none());
+        let marker = DataEntry::Marker(String::from("<SEND CANCELLED>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        fs::remove_file(&path).unwrap();
+
+        // A missing file is an error, and an escaped prompt sends nothing
+        test_model.update(Message::SendFile);
+        type_text(&mut test_model, &path);
+        test_model.update(Message::Enter);
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        test_model.set_state(State::Running);
+        test_model.update(Message::SendFile);
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.input, "draft");
+        assert!(test_model.upload.is_none());
+    }
+
+    #[test]
+    fn test_update_hex() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.hex_prompt = Some(b'>');
+        let path = std::env::temp_dir().join(format!("nolp-send-{}.hex", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let send = |model: &mut TerminalModel, text: &str| {
+            fs::write(&path, text).unwrap();
+            model.update(Message::SendFile);
+            for c in path.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+        let written = |model: &mut TerminalModel, bytes: u64| {
+            let mut stats = model.stats.clone();
+            stats.tx_bytes += bytes;
+            model.update(Message::Stats(stats));
+        };
+
+        // Each line waits until the one before is written and answered
+        send(&mut test_model, ":04001000DEADBEEFB4\n:00000001FF\n");
+        assert_eq!(test_model.get_output_buffer(), b":04001000DEADBEEFB4\r\n");
+        test_model.clear_output_buffer();
+        test_model.update(Message::Rx(b">".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"");
+        written(&mut test_model, 21);
+        test_model.update(Message::Rx(b"\r\n>".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b":00000001FF\r\n");
+        test_model.clear_output_buffer();
+        written(&mut test_model, 13);
+        test_model.update(Message::Rx(b">".to_vec()));
+        assert!(test_model.hex.is_none());
+        let marker = DataEntry::Marker(String::from("<HEX SENT>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        assert!(test_model.take_notice().is_some_and(|notice| notice.contains("0x00000010-0x00000013")));
+
+        // A bad record is shown before anything is sent
+        send(&mut test_model, ":04001000DEADBEEFB5\n:00000001FF\n");
+        assert!(matches!(test_model.get_state(), State::Error(e) if e.contains("bad checksum")));
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.set_state(State::Running);
+
+        // Cancelling leaves a marker
+        send(&mut test_model, ":00000001FF\n");
+        test_model.update(Message::Cancel);
+        assert!(test_model.hex.is_none());
+        let marker = DataEntry::Marker(String::from("<HEX CANCELLED>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_update_script() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let path = std::env::temp_dir().join(format!("nolp-script-{}.txt", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let run = |model: &mut TerminalModel, script: &str| {
+            fs::write(&path, script).unwrap();
+            model.update(Message::RunScript);
+            for c in path.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+
+        // The first send goes out at once, and the reply ends the expect
+        run(&mut test_model, "send AT\\r\\n\nexpect OK 1000\n");
+        assert_eq!(test_model.get_output_buffer(), b"AT\r\n");
+        test_model.clear_output_buffer();
+        assert!(test_model.script.is_some());
+        test_model.update(Message::Rx(b"AT\r\r\nOK\r\n".to_vec()));
+        assert!(test_model.script.is_none());
+        let marker = DataEntry::Marker(String::from("<SCRIPT DONE>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        assert!(test_model.take_notice().is_some());
+
+        // An expect that times out stops the script, naming its line
+        run(&mut test_model, "send ATI\\r\\n\nexpect never 0\nsend AT\\r\\n\n");
+        assert_eq!(test_model.get_output_buffer(), b"ATI\r\n");
+        test_model.clear_output_buffer();
+        assert!(test_model.script.is_none());
+        assert!(matches!(test_model.get_state(), State::Error(e) if e.contains("line 2")));
+        test_model.set_state(State::Running);
+
+        // Cancelling leaves a marker, and a mistake is shown before the run
+        run(&mut test_model, "expect OK 1000\nsend AT\n");
+        test_model.update(Message::Cancel);
+        assert!(test_model.script.is_none());
+        let marker = DataEntry::Marker(String::from("<SCRIPT CANCELLED>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        run(&mut test_model, "send AT\nwait 10\n");
+        assert!(matches!(test_model.get_state(), State::Error(e) if e.contains("Line 2")));
+        assert_eq!(test_model.get_output_buffer(), b"");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_update_latency() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.update(Message::MeasureLatency);
+        for c in "2 ab".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Enter);
+
+        // The next probe goes once the echo of the last is found, even when
+        // it arrives split across reads
+        assert_eq!(test_model.get_output_buffer(), b"ab");
+        test_model.clear_output_buffer();
+        test_model.update(Message::Rx(b"a".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.update(Message::Rx(b"b".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"ab");
+        test_model.clear_output_buffer();
+        test_model.update(Message::MeasureLatency);
+        assert!(test_model.prompt.is_none());
+        test_model.update(Message::Rx(b"ab".to_vec()));
+
+        // The result is marked in the data and in the capture, and stays
+        // shown until closed
+        let marker = test_model.take_capture_marker().unwrap();
+        assert!(marker.starts_with("<LATENCY 2 sent, 0 lost, min/avg/max "));
+        assert_eq!(test_model.buffer.back(), Some(&DataEntry::Marker(marker)));
+        assert!(test_model.take_notice().is_some());
+        test_model.update(Message::Cancel);
+        assert!(test_model.latency.is_none());
+
+        // A measurement cancelled midway leaves a marker
+        test_model.update(Message::MeasureLatency);
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), DEFAULT_PROBE);
+        test_model.update(Message::Cancel);
+        let marker = DataEntry::Marker(String::from("<LATENCY CANCELLED>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+
+        test_model.update(Message::MeasureLatency);
+        for c in "none".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Enter);
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+    }
+
+    #[test]
+    fn test_update_generating() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        let start = |model: &mut TerminalModel, input: &str| {
+            model.update(Message::Generate);
+            for c in input.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+
+        // The serial thread is handed the pattern, and what comes back is
+        // checked against it
+        start(&mut test_model, "prbs 1000");
+        let generator = Generator::new(&Pattern::Prbs, Some(1000));
+        assert_eq!(test_model.get_commands(), vec![SerialCommand::Generate(generator)]);
+        test_model.clear_commands();
+        let marker = DataEntry::Marker(String::from("<GENERATE PRBS-15>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        let mut looped = crate::generator::get_period(&Pattern::Prbs)[100..300].to_vec();
+        looped[150] ^= 0x10;
+        test_model.update(Message::Rx(looped));
+        let mut stats = test_model.stats.clone();
+        stats.tx_bytes = 2048;
+        test_model.update(Message::Stats(stats));
+
+        test_model.update(Message::Generate);
+        assert_eq!(test_model.get_commands(), vec![SerialCommand::StopGenerating]);
+        test_model.clear_commands();
+        let marker = DataEntry::Marker(String::from("<GENERATED sent 2.0 KB, checked 196 B, 1 errors>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        assert!(test_model.take_notice().is_some());
+
+        // A run of the given seconds stops on its own
+        start(&mut test_model, "text:U\\x00 0 1");
+        assert_eq!(test_model.generating.as_ref().unwrap().pattern, Pattern::Text(vec![b'U', 0]));
+        test_model.update(Message::Tick);
+        assert!(test_model.generating.is_some());
+        test_model.generating.as_mut().unwrap().started -= Duration::from_secs(2);
+        test_model.update(Message::Tick);
+        assert!(test_model.generating.is_none());
+        let marker = DataEntry::Marker(String::from("<GENERATED sent 0 B, nothing looped back>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+
+        start(&mut test_model, "walk");
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        test_model.set_state(State::Running);
+        start(&mut test_model, "inc fast");
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        assert!(test_model.generating.is_none());
+    }
+
+    #[test]
+    fn test_update_repeat() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.line_ending = Some(LineEnding::Cr);
+        test_model.update(Message::Repeat);
+        assert!(test_model.take_notice().is_some());
+
+        // The input is kept in the box, and sent at once once armed
+        for c in "?".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Repeat);
+        assert_eq!(test_model.input, "");
+        for c in "250".chars() {
+            test_model.update(Message::Input(c));
+        }
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.input, "?");
+        assert!(get_input_title(&test_model).contains("REPEAT 250ms"));
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"?\r");
+        test_model.clear_output_buffer();
+
+        // Nothing more is sent until the interval has passed
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.repeat.as_mut().unwrap().next = Instant::now();
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"?\r");
+        test_model.clear_output_buffer();
+        let sent = test_model.buffer.iter().filter(|e| matches!(e, DataEntry::Byte(_))).count();
+        assert_eq!(sent, 4);
+
+        // Pausing holds the repeat, and the key disarms it
+        test_model.repeat.as_mut().unwrap().next = Instant::now();
+        test_model.update(Message::Pause);
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.update(Message::Resume);
+        test_model.update(Message::Repeat);
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"");
+        assert!(test_model.repeat.is_none());
+
+        // A blank interval is the default, an invalid one is an error
+        test_model.update(Message::Repeat);
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.repeat.clone().unwrap().interval, DEFAULT_REPEAT_INTERVAL);
+        test_model.update(Message::Repeat);
+        test_model.update(Message::Repeat);
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::Enter);
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        assert!(test_model.repeat.is_none());
+    }
+
+    #[test]
+    fn test_update_send_byte() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.line_ending = Some(LineEnding::CrLf);
+        for c in "AT".chars() {
+            test_model.update(Message::Input(c));
+        }
+
+        // Two digits at most, sent alone once entered
+        test_model.update(Message::SendByte);
+        assert_eq!(test_model.input, "");
+        for c in "1b2".chars() {
+            test_model.update(Message::Input(c));
+        }
+        assert_eq!(test_model.input, "1b");
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.get_output_buffer(), b"\x1B");
+        assert_eq!(test_model.input, "AT");
+        assert_eq!(test_model.history.len(), 0);
+        assert_eq!(get_byte_count(test_model.buffer.iter()), 1);
+        test_model.clear_output_buffer();
+
+        // Invalid hex is flagged, and the prompt is left open to correct it
+        test_model.update(Message::SendByte);
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.invalid, Some(String::from(" Invalid hex byte ")));
+        assert!(test_model.prompt.is_some());
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.input, "AT");
+        assert_eq!(test_model.invalid, None);
+        assert_eq!(test_model.get_state(), State::Running);
+    }
+
+    #[test]
+    fn test_update_trigger() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Text);
+        test_model.parameters.trigger_bell = true;
+        let add = |model: &mut TerminalModel, pattern: &str| {
+            model.update(Message::Trigger);
+            for c in pattern.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+        add(&mut test_model, "ERROR");
+        add(&mut test_model, "/rc=\\d+/");
+        assert!(get_input_title(&test_model).contains("TRIG 2"));
+        test_model.take_notice();
+
+        // A match split across two reads is found, and marked on both sides
+        test_model.update(Message::Rx(b"ok\r\nER".to_vec()));
+        assert_eq!(test_model.take_notice(), None);
+        test_model.update(Message::Rx(b"ROR rc=12\r\n".to_vec()));
+        let notice = String::from(" Triggered: ERROR, /rc=\\d+/ ");
+        assert_eq!(test_model.take_notice(), Some(notice));
+        assert!(test_model.take_bell());
+        assert!(test_model.take_bell() == false);
+
+        let matched: Vec<u8> = test_model
+            .buffer
+            .iter()
+            .filter_map(|entry| match entry {
+                DataEntry::Byte(data_byte) if data_byte.matched => Some(data_byte.value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(matched, b"ERRORrc=12");
+
+        // The text view highlights the same span
+        let lines = get_text_encoding(&mut test_model, Rect::new(0, 0, 40, 10));
+        let highlighted: Vec<String> = lines
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .filter(|span| span.style.bg == Some(crate::TRIGGER_COLOR))
+            .map(|span| span.content.to_string())
+            .collect();
+        assert_eq!(highlighted, vec!["ERROR", "rc=12"]);
+
+        // A blank pattern clears them, an invalid one is an error
+        add(&mut test_model, "");
+        assert!(test_model.triggers.get_triggers().is_empty());
+        add(&mut test_model, "/(/");
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+    }
+
+    #[test]
+    fn test_update_search() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Text);
+        test_model.bounds = Rect::new(0, 0, 60, 14);
+        let open = |model: &mut TerminalModel, query: &str| {
+            model.update(Message::Search);
+            for c in query.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+
+        // Nothing to find in an empty buffer, nor in one without a match
+        open(&mut test_model, "hit");
+        assert_eq!(test_model.search.as_ref().unwrap().current, None);
+        assert_eq!(test_model.scroll, None);
+        for i in 0..40 {
+            let line = if i % 15 == 5 { format!("hit {}\r\n", i) } else { format!("line {}\r\n", i) };
+            test_model.update(Message::Rx(line.into_bytes()));
+        }
+        test_model.update(Message::Backspace);
+        test_model.update(Message::Input('x'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.search.as_ref().unwrap().current, None);
+
+        // The first jump is to the latest match, then back through the rest
+        test_model.update(Message::Backspace);
+        test_model.update(Message::Input('t'));
+        test_model.update(Message::Enter);
+        assert_eq!(test_model.search.as_ref().unwrap().lines.len(), 3);
+        assert_eq!(test_model.search.as_ref().unwrap().current, Some(2));
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.search.as_ref().unwrap().current, Some(1));
+        let scroll = test_model.scroll.unwrap();
+        assert!(scroll <= 20 && 20 < scroll + 10);
+        test_model.update(Message::HistoryPrev);
+        test_model.update(Message::HistoryPrev);
+        assert_eq!(test_model.search.as_ref().unwrap().current, Some(2));
+
+        // The current match is highlighted apart from the others
+        let lines = get_text_encoding(&mut test_model, Rect::new(0, 0, 40, 10));
+        let highlighted: Vec<(String, Option<Color>)> = lines
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .filter(|span| span.style.fg == Some(Color::Black))
+            .map(|span| (span.content.to_string(), span.style.bg))
+            .collect();
+        assert_eq!(highlighted.len(), 3);
+        assert_eq!(highlighted[2], (String::from("hit"), Some(crate::WARNING_COLOR)));
+
+        // Bytes are typed as values in the other modes, and the search is
+        // closed like a prompt
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.search, None);
+        test_model.parameters.mode = Some(Mode::Hex);
+        test_model.update(Message::Rx(vec![0x0A, 0xFF, 0x00, 0x0A, 0xFF]));
+        open(&mut test_model, "0A FF");
+        assert_eq!(test_model.search.as_ref().unwrap().lines.len(), 2);
+        test_model.update(Message::Input('Z'));
+        test_model.update(Message::Enter);
+        assert!(test_model.search.as_ref().unwrap().lines.is_empty());
+        test_model.update(Message::Search);
+        assert_eq!(test_model.search, None);
+    }
+
+    #[test]
+    fn test_get_rules() {
+        let rules = get_rules("# ping\n\nPING\\r\\n => PONG\\r\\n\n/AT\\+\\w+/ => OK => 100\n").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].0.get_label(), "PING\\r\\n");
+        assert_eq!(rules[0].1.response, b"PONG\r\n");
+        assert_eq!(rules[1].1.delay, Duration::from_millis(100));
+
+        assert_eq!(get_rules("a").err(), Some(String::from(" Line 1: expected pattern => response ")));
+        assert_eq!(get_rules("a => b => c").err(), Some(String::from(" Line 1: invalid delay 'c' ")));
+        assert!(get_rules("/(/ => b").is_err());
+    }
+
+    #[test]
+    fn test_update_responses() {
+        let path = std::env::temp_dir().join(format!("nolp-rules-{}.txt", std::process::id()));
+        fs::write(&path, "PING\\r\\n => PONG\\r\\n\nAT => OK => 100\n").unwrap();
+        let parameters = PortParameters {
+            response_rules: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let mut test_model = TerminalModel::new(parameters);
+        test_model.connection = ConnectionStatus::Connected;
+        fs::remove_file(&path).unwrap();
+        assert!(get_input_title(&test_model).contains("AUTO"));
+
+        // A match split across reads is answered once complete, after a
+        // marker, and highlighted like a trigger
+        test_model.update(Message::Rx(b"PI".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.update(Message::Rx(b"NG\r\n".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"PONG\r\n");
+        test_model.clear_output_buffer();
+        let marker = DataEntry::Marker(String::from("<AUTO>"));
+        assert!(test_model.buffer.contains(&marker));
+        let matched = test_model.buffer.iter().filter(|e| matches!(e, DataEntry::Byte(b) if b.matched)).count();
+        assert_eq!(matched, 6);
+
+        // A delayed reply waits for its tick
+        test_model.update(Message::Rx(b"AT".to_vec()));
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"");
+        test_model.responses[0].due = Instant::now();
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), b"OK");
+        test_model.clear_output_buffer();
+
+        // Too many replies a second turns the rules off
+        test_model.update(Message::Rx(b"PING\r\n".repeat(RESPONSE_LIMIT)));
+        assert!(test_model.responding == false);
+        assert!(test_model.take_notice().is_some());
+        assert_eq!(test_model.get_output_buffer(), b"PONG\r\n".repeat(RESPONSE_LIMIT - 2));
+        test_model.clear_output_buffer();
+        test_model.update(Message::Rx(b"PING\r\n".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"");
+
+        // And the key turns them back on
+        test_model.update(Message::ToggleResponses);
+        test_model.update(Message::Rx(b"PING\r\n".to_vec()));
+        assert_eq!(test_model.get_output_buffer(), b"PONG\r\n");
+        let mut test_model = TerminalModel::default();
+        test_model.update(Message::ToggleResponses);
+        assert_eq!(test_model.take_notice(), Some(String::from(" No response rules loaded ")));
+    }
+
+    #[test]
+    fn test_update_xmodem() {
+        let mut test_model = TerminalModel::default();
+        let path = std::env::temp_dir().join(format!("nolp-xmodem-{}.bin", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, b"firmware").unwrap();
+        let start = |model: &mut TerminalModel, text: &str| {
+            model.update(Message::Xmodem);
+            for c in text.chars() {
+                model.update(Message::Input(c));
+            }
+            model.update(Message::Enter);
+        };
+
+        // The received data goes to the transfer rather than the display,
+        // and nothing typed is sent while it lasts
+        start(&mut test_model, &format!("send {}", path));
+        assert!(test_model.transfer.is_some());
+        test_model.update(Message::Rx(vec![b'C']));
+        let packet = test_model.get_output_buffer();
+        assert_eq!(&packet[..4], &[0x01, 0x01, 0xFE, b'f']);
+        assert_eq!(packet.len(), 133);
+        test_model.clear_output_buffer();
+        test_model.update(Message::Input('a'));
+        assert_eq!(test_model.input, "");
+        test_model.update(Message::Rx(vec![0x06]));
+        assert_eq!(test_model.get_output_buffer(), vec![0x04]);
+        test_model.clear_output_buffer();
+        test_model.update(Message::Rx(vec![0x06]));
+        assert!(test_model.transfer.is_none());
+        assert!(test_model.take_notice().is_some());
+        let marker = DataEntry::Marker(String::from("<XMODEM SENT>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        assert_eq!(test_model.buffer.iter().filter(|e| matches!(e, DataEntry::Byte(_))).count(), 0);
+
+        // The receiver asks for the first block, and is cancelled with CAN
+        start(&mut test_model, &format!("receive {}", path));
+        test_model.update(Message::Tick);
+        assert_eq!(test_model.get_output_buffer(), vec![b'C']);
+        test_model.clear_output_buffer();
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.get_output_buffer(), vec![0x18, 0x18, 0x18]);
+        assert!(test_model.transfer.is_none());
+        let marker = DataEntry::Marker(String::from("<XMODEM CANCELLED>"));
+        assert_eq!(test_model.buffer.back(), Some(&marker));
+        test_model.clear_output_buffer();
+
+        // An abort by the other end is shown as an error
+        start(&mut test_model, &format!("r {}", path));
+        test_model.update(Message::Rx(vec![0x18, 0x18]));
+        assert!(test_model.transfer.is_none());
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        test_model.set_state(State::Running);
+        assert_eq!(fs::read(&path).unwrap(), b"firmware");
+        fs::remove_file(&path).unwrap();
+
+        start(&mut test_model, "upload");
+        assert!(matches!(test_model.get_state(), State::Error(_)));
+        assert!(test_model.transfer.is_none());
+    }
+
+    #[test]
+    fn test_get_text_encoding_ansi() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.mode = Some(Mode::Ascii);
+        test_model.parameters.ascii_view = Some(AsciiView::Ansi);
+        let area = Rect::new(0, 0, 10, 10);
+        let bytes = b"\x1B[1;32mI (12)\x1B[0m ok\r\n\x1B]0;title\x07\x1B[2Ka\tb\x01\n0123456789AB";
+        test_model.update(Message::Rx(bytes.to_vec()));
+
+        let lines = get_text_encoding(&mut test_model, area);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.to_string()).collect())
+            .collect();
+        assert_eq!(text, vec!["I (12) ok", "a       b", "0123456789", "AB"]);
+
+        let green = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
+        assert_eq!(lines[0].spans[0].style, green);
+        assert_eq!(lines[0].spans[1].style, Style::default());
+
+        // The raw display is kept when the option is off
+        test_model.parameters.ascii_view = Some(AsciiView::Raw);
+        assert_eq!(get_encoding(&mut test_model, area)[0].spans[0].content, ". ");
+    }
+
+    #[test]
+    fn test_get_sgr_style() {
+        let base = Style::default();
+        assert_eq!(get_sgr_style(base, "31"), base.fg(Color::Red));
+        let bold = base.add_modifier(Modifier::BOLD);
+        assert_eq!(get_sgr_style(base, "1;94;41"), bold.fg(Color::LightBlue).bg(Color::Red));
+        assert_eq!(get_sgr_style(bold.fg(Color::Red), ""), base);
+        let style = get_sgr_style(bold.fg(Color::Red), "39;22");
+        assert_eq!(style.fg, None);
+        assert!(style.add_modifier.contains(Modifier::BOLD) == false);
+        assert_eq!(get_sgr_style(base, "38;5;200;4"), base.add_modifier(Modifier::UNDERLINED));
+        assert_eq!(get_sgr_style(base, "38;2;1;2;3;33"), base.fg(Color::Yellow));
+    }
+
+    #[test]
+    fn test_get_rate() {
+        let start = Instant::now();
+        let mut samples = VecDeque::new();
+        record_sample(&mut samples, start, 0);
+        assert_eq!(samples.len(), 0);
+
+        record_sample(&mut samples, start, 20000);
+        record_sample(&mut samples, start + Duration::from_secs(1), 3000);
+        assert_eq!(get_rate(&mut samples, start + Duration::from_secs(1)), 11500.0);
+        assert_eq!(get_rate_text(11500.0, 0.0), "RX 11.5 kB/s  TX 0.0 kB/s");
+
+        // The first sample leaves the window, then the rate decays to zero
+        assert_eq!(get_rate(&mut samples, start + Duration::from_millis(2500)), 1500.0);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(get_rate(&mut samples, start + Duration::from_secs(4)), 0.0);
+        assert_eq!(samples.len(), 0);
+    }
+
+    #[test]
+    fn test_get_stats_text() {
+        let mut stats = SerialStats::default();
+        assert_eq!(get_stats_text(&stats), "TX 0 B  RX 0 B  ERR 0  up 00:00:00");
+
+        stats.tx_bytes = 1229;
+        stats.rx_bytes = 46388;
+        stats.write_errors = 1;
+        stats.read_errors = 2;
+        assert_eq!(
+            get_stats_text(&stats),
+            "TX 1.2 KB  RX 45.3 KB  ERR 3  up 00:00:00"
+        );
+
+        // Only the time spent connected is counted
+        stats.uptime = Duration::from_secs(3723);
+        assert_eq!(
+            get_stats_text(&stats),
+            "TX 1.2 KB  RX 45.3 KB  ERR 3  up 01:02:03"
+        );
+        assert_eq!(get_size_text(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn test_get_line_spans() {
+        let lines = LineStatus {
+            cts: Some(true),
+            dsr: Some(false),
+            cd: None,
+            ri: Some(false),
+        };
+        let spans = get_line_spans(&lines);
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[0].content, "CTS ● ");
+        assert_eq!(spans[0].style.fg, Some(crate::ACTIVE_COLOR));
+        assert_eq!(spans[1].content, "DSR ● ");
+        assert_eq!(spans[1].style.fg, Some(crate::PLACEHOLDER_COLOR));
+        assert_eq!(spans[2].content, "CD - ");
+        assert_eq!(spans[3].content, "RI ● ");
+    }
+
+    #[test]
+    fn test_update_cancel() {
+        let mut test_model = TerminalModel::default();
+        test_model.parameters.read_only = true;
+        test_model.input = String::from("AT");
+        test_model.cursor = 2;
+
+        // The input is cleared first, then esc asks before disconnecting
+        test_model.update(Message::Cancel);
+        assert_eq!((test_model.input.as_str(), test_model.cursor), ("", 0));
+        assert!(test_model.prompt.is_none());
+        test_model.update(Message::Cancel);
+        assert_eq!(test_model.prompt, Some((Prompt::Disconnect, String::new())));
+        test_model.update(Message::Cancel);
+        assert!(test_model.prompt.is_none());
+        assert_eq!(test_model.get_state(), State::Running);
+
+        // Even read-only, which otherwise ignores enter
+        test_model.update(Message::Cancel);
+        test_model.update(Message::Enter);
+        let parameters = Some(Box::new(test_model.parameters.clone()));
+        assert_eq!(test_model.get_state(), State::Switching(Screen::Menu, parameters));
     }
 }
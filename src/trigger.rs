@@ -0,0 +1,182 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the triggers, patterns looked for in the received data.
+* The data is matched as it arrives, over a window of what came before, so that
+* a match split across reads is still found.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use regex::bytes::Regex;
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    label: String,
+    regex: Regex,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerMatcher {
+    triggers: Vec<Trigger>,
+    window: Vec<u8>,
+    offset: u64,
+    // Where the last match of each trigger ended, so none is reported twice
+    matched: Vec<u64>,
+}
+
+// The bytes matched, as offsets into everything received
+#[derive(Clone, Debug, PartialEq)]
+pub struct TriggerMatch {
+    pub index: usize,
+    pub start: u64,
+    pub end: u64,
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+// The longest match found across reads, a longer one is only found when it
+// arrives in a single read
+const WINDOW_LENGTH: usize = 256;
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Trigger {
+    pub fn bytes(label: &str, bytes: &[u8]) -> Trigger {
+        // Matched as is, including any byte that is not valid UTF-8
+        let escaped: Vec<String> = bytes.iter().map(|b| format!("\\x{:02X}", b)).collect();
+        let regex = Regex::new(&format!("(?-u){}", escaped.join(""))).unwrap();
+        return Trigger {
+            label: String::from(label),
+            regex,
+        };
+    }
+
+    pub fn regex(label: &str, pattern: &str) -> Result<Trigger, String> {
+        // The error of the regex spans several lines, too many to be shown
+        let regex = Regex::new(pattern).map_err(|_| format!(" Invalid pattern: {} ", pattern))?;
+        return Ok(Trigger {
+            label: String::from(label),
+            regex,
+        });
+    }
+
+    pub fn get_label(&self) -> &str {
+        return &self.label;
+    }
+
+    // The byte ranges of a line that match, as displayed
+    pub fn find(&self, data: &[u8]) -> Vec<(usize, usize)> {
+        return self
+            .regex
+            .find_iter(data)
+            .filter(|m| m.len() > 0)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+    }
+}
+
+impl TriggerMatcher {
+    pub fn add(&mut self, trigger: Trigger) {
+        self.triggers.push(trigger);
+        self.matched.push(self.offset);
+    }
+
+    pub fn clear(&mut self) {
+        self.triggers.clear();
+        self.matched.clear();
+        self.window.clear();
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        return self.offset;
+    }
+
+    pub fn get_triggers(&self) -> &[Trigger] {
+        return &self.triggers;
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Vec<TriggerMatch> {
+        // Only the matches that end in the new data are reported, those that
+        // end before it were when it arrived
+        let base = self.offset - self.window.len() as u64;
+        self.offset += data.len() as u64;
+        if self.triggers.len() == 0 {
+            return Vec::new();
+        }
+        self.window.extend_from_slice(data);
+
+        let mut matches = Vec::new();
+        let new_start = self.offset - data.len() as u64;
+        for (index, trigger) in self.triggers.iter().enumerate() {
+            for (start, end) in trigger.find(&self.window) {
+                let (start, end) = (base + start as u64, base + end as u64);
+                if end > new_start && start >= self.matched[index] {
+                    self.matched[index] = end;
+                    matches.push(TriggerMatch { index, start, end });
+                }
+            }
+        }
+
+        let excess = self.window.len().saturating_sub(WINDOW_LENGTH);
+        self.window.drain(..excess);
+        return matches;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_split() {
+        let mut matcher = TriggerMatcher::default();
+        matcher.update(b"boot");
+        matcher.add(Trigger::bytes("ERROR", b"ERROR"));
+        matcher.add(Trigger::regex("/E\\d+/", "E\\d+").unwrap());
+
+        // A match split across two reads is found once the rest arrives
+        assert_eq!(matcher.update(b"..ER"), vec![]);
+        let expected = vec![TriggerMatch {
+            index: 0,
+            start: 6,
+            end: 11,
+        }];
+        assert_eq!(matcher.update(b"ROR\r\n"), expected);
+
+        // As is a regex, but a match is never reported twice
+        assert_eq!(matcher.update(b"E4"), vec![TriggerMatch { index: 1, start: 13, end: 15 }]);
+        assert_eq!(matcher.update(b"2 "), vec![]);
+        assert_eq!(matcher.get_offset(), 17);
+    }
+
+    #[test]
+    fn test_update_bytes() {
+        let mut matcher = TriggerMatcher::default();
+        matcher.add(Trigger::bytes("\\xFF\\x00", &[0xFF, 0x00]));
+        assert_eq!(matcher.update(&[0x01, 0xFF]), vec![]);
+        assert_eq!(matcher.update(&[0x00]), vec![TriggerMatch { index: 0, start: 1, end: 3 }]);
+
+        // The window only holds so much, and is dropped with the triggers
+        matcher.update(&[0xAA; WINDOW_LENGTH * 2]);
+        assert_eq!(matcher.window.len(), WINDOW_LENGTH);
+        matcher.clear();
+        assert_eq!(matcher.update(&[0xFF, 0x00]), vec![]);
+        assert!(Trigger::regex("bad", "(").is_err());
+    }
+}
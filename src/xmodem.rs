@@ -0,0 +1,512 @@
+/*******************************************************************************/
+/********************************************************************************
+* DESCRIPTION: Defines the XMODEM transfer, which is driven by the terminal with
+* the data it receives and the ticks in between. It answers with the data to be
+* sent, so that it never touches the port itself.
+* AUTHOR: jb
+* DATE: 10/17/26
+********************************************************************************/
+/*******************************************************************************/
+use std::time::{Duration, Instant};
+
+/******************************************************************************/
+/*******************************************************************************
+* Public Interface
+*******************************************************************************/
+/******************************************************************************/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Xmodem {
+    direction: XmodemDirection,
+    data: Vec<u8>,
+    pending: Vec<u8>,
+    block: usize,
+    crc: bool,
+    retries: u32,
+    state: XmodemState,
+    deadline: Instant,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmodemDirection {
+    Send,
+    Receive,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmodemState {
+    Starting,
+    Transferring,
+    Ending,
+    Done,
+    Failed(String),
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Local Constants
+*******************************************************************************/
+/******************************************************************************/
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1A;
+const CRC_START: u8 = b'C';
+const BLOCK_SIZE: usize = 128;
+const MAX_RETRIES: u32 = 10;
+// The receiver asks for CRC this many times, before falling back to the
+// checksum for senders that do not support it
+const CRC_TRIES: u32 = 3;
+const START_INTERVAL: Duration = Duration::from_secs(3);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/******************************************************************************/
+/*******************************************************************************
+* Implementation
+*******************************************************************************/
+/******************************************************************************/
+impl Xmodem {
+    pub fn send(data: Vec<u8>, now: Instant) -> Xmodem {
+        // Waits for the receiver to ask for the first block
+        Xmodem {
+            direction: XmodemDirection::Send,
+            data,
+            pending: Vec::new(),
+            block: 0,
+            crc: false,
+            retries: 0,
+            state: XmodemState::Starting,
+            deadline: now + TIMEOUT,
+        }
+    }
+
+    pub fn receive(now: Instant) -> Xmodem {
+        // The first request is sent on the first tick
+        Xmodem {
+            direction: XmodemDirection::Receive,
+            data: Vec::new(),
+            pending: Vec::new(),
+            block: 0,
+            crc: true,
+            retries: 0,
+            state: XmodemState::Starting,
+            deadline: now,
+        }
+    }
+
+    pub fn cancel(&mut self) -> Vec<u8> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+        self.state = XmodemState::Failed(String::from("Cancelled"));
+        return vec![CAN, CAN, CAN];
+    }
+
+    pub fn get_block(&self) -> usize {
+        return self.block;
+    }
+
+    pub fn get_direction(&self) -> &XmodemDirection {
+        return &self.direction;
+    }
+
+    pub fn get_retries(&self) -> u32 {
+        return self.retries;
+    }
+
+    pub fn get_state(&self) -> &XmodemState {
+        return &self.state;
+    }
+
+    // The size of the file, only known when sending
+    pub fn get_total(&self) -> Option<usize> {
+        match self.direction {
+            XmodemDirection::Send => return Some(self.data.len()),
+            XmodemDirection::Receive => return None,
+        }
+    }
+
+    pub fn get_transferred(&self) -> usize {
+        match self.direction {
+            XmodemDirection::Send => return (self.block * BLOCK_SIZE).min(self.data.len()),
+            XmodemDirection::Receive => return self.data.len(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        return matches!(self.state, XmodemState::Done | XmodemState::Failed(_));
+    }
+
+    pub fn take_data(&mut self) -> Vec<u8> {
+        return std::mem::take(&mut self.data);
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Vec<u8> {
+        if self.is_finished() || now < self.deadline {
+            return Vec::new();
+        }
+        match self.direction {
+            XmodemDirection::Send => return self.tick_send(now),
+            XmodemDirection::Receive => return self.tick_receive(now),
+        }
+    }
+
+    pub fn update(&mut self, input: &[u8], now: Instant) -> Vec<u8> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+        self.pending.extend_from_slice(input);
+        match self.direction {
+            XmodemDirection::Send => return self.update_send(now),
+            XmodemDirection::Receive => return self.update_receive(now),
+        }
+    }
+
+    fn fail(&mut self, reason: &str) -> Vec<u8> {
+        self.state = XmodemState::Failed(String::from(reason));
+        return vec![CAN, CAN, CAN];
+    }
+
+    fn get_packet(&self) -> Vec<u8> {
+        // The last block is padded out with SUB
+        let start = self.block * BLOCK_SIZE;
+        let end = (start + BLOCK_SIZE).min(self.data.len());
+        let mut payload = self.data[start..end].to_vec();
+        payload.resize(BLOCK_SIZE, SUB);
+
+        let number = (self.block + 1) as u8;
+        let mut packet = vec![SOH, number, 255 - number];
+        packet.extend_from_slice(&payload);
+        if self.crc {
+            packet.extend_from_slice(&get_crc(&payload).to_be_bytes());
+        } else {
+            packet.push(get_checksum(&payload));
+        }
+        return packet;
+    }
+
+    fn retry(&mut self, now: Instant) -> bool {
+        self.retries += 1;
+        self.deadline = now + TIMEOUT;
+        return self.retries <= MAX_RETRIES;
+    }
+
+    fn send_next(&mut self, now: Instant) -> Vec<u8> {
+        self.deadline = now + TIMEOUT;
+        if self.block * BLOCK_SIZE >= self.data.len() {
+            self.state = XmodemState::Ending;
+            return vec![EOT];
+        }
+        self.state = XmodemState::Transferring;
+        return self.get_packet();
+    }
+
+    fn tick_receive(&mut self, now: Instant) -> Vec<u8> {
+        if self.retry(now) == false {
+            return self.fail("Timed out");
+        }
+        if self.state == XmodemState::Starting {
+            self.deadline = now + START_INTERVAL;
+            self.crc = self.retries <= CRC_TRIES;
+            return vec![if self.crc { CRC_START } else { NAK }];
+        }
+        // Whatever was partially received is dropped for the retry
+        self.pending.clear();
+        return vec![NAK];
+    }
+
+    fn tick_send(&mut self, now: Instant) -> Vec<u8> {
+        if self.retry(now) == false {
+            return self.fail("Timed out");
+        }
+        match self.state {
+            XmodemState::Transferring => return self.get_packet(),
+            XmodemState::Ending => return vec![EOT],
+            _ => return Vec::new(),
+        }
+    }
+
+    fn update_receive(&mut self, now: Instant) -> Vec<u8> {
+        let mut output = Vec::new();
+        while self.pending.len() > 0 && self.is_finished() == false {
+            match self.pending[0] {
+                SOH | STX => {
+                    let size = if self.pending[0] == SOH { BLOCK_SIZE } else { 8 * BLOCK_SIZE };
+                    let length = 3 + size + if self.crc { 2 } else { 1 };
+                    if self.pending.len() < length {
+                        break;
+                    }
+                    let packet: Vec<u8> = self.pending.drain(..length).collect();
+                    output.extend(self.receive_packet(&packet, size, now));
+                }
+                EOT => {
+                    // The padding of the last block is not part of the file
+                    self.pending.clear();
+                    while self.data.last() == Some(&SUB) {
+                        self.data.pop();
+                    }
+                    self.state = XmodemState::Done;
+                    output.push(ACK);
+                }
+                CAN => {
+                    if self.pending.len() < 2 {
+                        break;
+                    }
+                    if self.pending[1] == CAN {
+                        self.state = XmodemState::Failed(String::from("Cancelled by the sender"));
+                    }
+                    self.pending.remove(0);
+                }
+                // Noise between the packets
+                _ => {
+                    self.pending.remove(0);
+                }
+            }
+        }
+        return output;
+    }
+
+    fn receive_packet(&mut self, packet: &[u8], size: usize, now: Instant) -> Vec<u8> {
+        let number = packet[1];
+        let payload = &packet[3..3 + size];
+        let valid = match self.crc {
+            true => packet[3 + size..] == get_crc(payload).to_be_bytes(),
+            false => packet[3 + size] == get_checksum(payload),
+        };
+        if number != 255 - packet[2] || valid == false {
+            if self.retry(now) == false {
+                return self.fail("Too many errors");
+            }
+            self.pending.clear();
+            return vec![NAK];
+        }
+
+        self.state = XmodemState::Transferring;
+        self.deadline = now + TIMEOUT;
+        let expected = (self.block + 1) as u8;
+        if number == expected {
+            self.data.extend_from_slice(payload);
+            self.block += 1;
+            self.retries = 0;
+            return vec![ACK];
+        } else if number == expected.wrapping_sub(1) {
+            // The ACK of the previous block was lost, so it was sent again
+            return vec![ACK];
+        }
+        return self.fail("Block out of sequence");
+    }
+
+    fn update_send(&mut self, now: Instant) -> Vec<u8> {
+        let mut output = Vec::new();
+        let input: Vec<u8> = self.pending.drain(..).collect();
+        let mut cancels = 0;
+        for value in input {
+            if self.is_finished() {
+                break;
+            }
+            if value == CAN {
+                cancels += 1;
+                if cancels >= 2 {
+                    self.state = XmodemState::Failed(String::from("Cancelled by the receiver"));
+                }
+                continue;
+            }
+            cancels = 0;
+            match (&self.state, value) {
+                // The receiver picks the check, CRC if it asks with 'C'
+                (XmodemState::Starting, CRC_START | NAK) => {
+                    self.crc = value == CRC_START;
+                    output.extend(self.send_next(now));
+                }
+                (XmodemState::Transferring, ACK) => {
+                    self.block += 1;
+                    self.retries = 0;
+                    output.extend(self.send_next(now));
+                }
+                (XmodemState::Transferring, NAK) => {
+                    if self.retry(now) == false {
+                        output.extend(self.fail("Too many errors"));
+                    } else {
+                        output.extend(self.get_packet());
+                    }
+                }
+                (XmodemState::Ending, ACK) => self.state = XmodemState::Done,
+                (XmodemState::Ending, NAK) => {
+                    if self.retry(now) == false {
+                        output.extend(self.fail("Too many errors"));
+                    } else {
+                        output.push(EOT);
+                    }
+                }
+                _ => {}
+            }
+        }
+        return output;
+    }
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Utility Functions
+*******************************************************************************/
+/******************************************************************************/
+fn get_checksum(payload: &[u8]) -> u8 {
+    return payload.iter().fold(0_u8, |sum, b| sum.wrapping_add(*b));
+}
+
+fn get_crc(payload: &[u8]) -> u16 {
+    // CRC-16/XMODEM, polynomial 0x1021 from zero
+    let mut crc = 0_u16;
+    for b in payload {
+        crc ^= u16::from(*b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    return crc;
+}
+
+/******************************************************************************/
+/*******************************************************************************
+* Tests
+*******************************************************************************/
+/******************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(number: u8, payload: &[u8], crc: bool) -> Vec<u8> {
+        let mut payload = payload.to_vec();
+        payload.resize(BLOCK_SIZE, SUB);
+        let mut packet = vec![SOH, number, 255 - number];
+        packet.extend_from_slice(&payload);
+        if crc {
+            packet.extend_from_slice(&get_crc(&payload).to_be_bytes());
+        } else {
+            packet.push(get_checksum(&payload));
+        }
+        return packet;
+    }
+
+    #[test]
+    fn test_get_crc() {
+        assert_eq!(get_crc(b"123456789"), 0x31C3);
+        assert_eq!(get_checksum(&[0xFF, 0x02]), 0x01);
+    }
+
+    #[test]
+    fn test_send() {
+        let now = Instant::now();
+        let data: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let mut xmodem = Xmodem::send(data.clone(), now);
+        assert_eq!(xmodem.get_total(), Some(200));
+
+        // Nothing is sent until the receiver asks with 'C'
+        assert_eq!(xmodem.update(b"x", now), vec![]);
+        assert_eq!(xmodem.update(&[CRC_START], now), packet(1, &data[..128], true));
+
+        // A NAK sends the block again, as does the timeout
+        assert_eq!(xmodem.update(&[NAK], now), packet(1, &data[..128], true));
+        assert_eq!(xmodem.tick(now + TIMEOUT), packet(1, &data[..128], true));
+        assert_eq!(xmodem.get_retries(), 2);
+
+        assert_eq!(xmodem.update(&[ACK], now), packet(2, &data[128..], true));
+        assert_eq!(xmodem.get_transferred(), 128);
+        assert_eq!(xmodem.update(&[ACK], now), vec![EOT]);
+        assert_eq!(xmodem.update(&[NAK], now), vec![EOT]);
+        assert_eq!(xmodem.update(&[ACK], now), vec![]);
+        assert_eq!(xmodem.get_state(), &XmodemState::Done);
+        assert_eq!(xmodem.get_transferred(), 200);
+    }
+
+    #[test]
+    fn test_send_checksum() {
+        let now = Instant::now();
+        let mut xmodem = Xmodem::send(b"hi".to_vec(), now);
+        assert_eq!(xmodem.update(&[NAK], now), packet(1, b"hi", false));
+
+        // Giving up cancels the transfer on the other end too
+        for i in 0..MAX_RETRIES {
+            assert_eq!(xmodem.update(&[NAK], now), packet(1, b"hi", false), "retry {}", i);
+        }
+        assert_eq!(xmodem.update(&[NAK], now), vec![CAN, CAN, CAN]);
+        assert!(xmodem.is_finished());
+    }
+
+    #[test]
+    fn test_send_cancel() {
+        let now = Instant::now();
+        let mut xmodem = Xmodem::send(b"hi".to_vec(), now);
+        xmodem.update(&[CRC_START], now);
+        assert_eq!(xmodem.update(&[CAN, CAN], now), vec![]);
+        let reason = String::from("Cancelled by the receiver");
+        assert_eq!(xmodem.get_state(), &XmodemState::Failed(reason));
+
+        xmodem = Xmodem::send(b"hi".to_vec(), now);
+        assert_eq!(xmodem.cancel(), vec![CAN, CAN, CAN]);
+        assert_eq!(xmodem.cancel(), vec![]);
+    }
+
+    #[test]
+    fn test_receive() {
+        let now = Instant::now();
+        let mut xmodem = Xmodem::receive(now);
+        assert_eq!(xmodem.tick(now), vec![CRC_START]);
+        assert_eq!(xmodem.tick(now + Duration::from_secs(1)), vec![]);
+
+        // A packet may arrive split across reads, after some noise
+        let first = packet(1, &[0x41; 128], true);
+        assert_eq!(xmodem.update(&[0x00], now), vec![]);
+        assert_eq!(xmodem.update(&first[..50], now), vec![]);
+        assert_eq!(xmodem.update(&first[50..], now), vec![ACK]);
+
+        // A duplicate is acknowledged but not kept, a corrupt one is NAKed
+        assert_eq!(xmodem.update(&first, now), vec![ACK]);
+        let mut second = packet(2, b"end", true);
+        second[10] ^= 0xFF;
+        assert_eq!(xmodem.update(&second, now), vec![NAK]);
+        assert_eq!(xmodem.get_retries(), 1);
+        assert_eq!(xmodem.update(&packet(2, b"end", true), now), vec![ACK]);
+
+        assert_eq!(xmodem.update(&[EOT], now), vec![ACK]);
+        assert_eq!(xmodem.get_state(), &XmodemState::Done);
+        let mut expected = vec![0x41; 128];
+        expected.extend_from_slice(b"end");
+        assert_eq!(xmodem.take_data(), expected);
+    }
+
+    #[test]
+    fn test_receive_checksum() {
+        // The sender never answers the CRC requests, so it falls back
+        let now = Instant::now();
+        let mut xmodem = Xmodem::receive(now);
+        let mut requests = Vec::new();
+        for i in 0..4 {
+            requests.extend(xmodem.tick(now + START_INTERVAL * i));
+        }
+        assert_eq!(requests, vec![CRC_START, CRC_START, CRC_START, NAK]);
+        assert_eq!(xmodem.update(&packet(1, b"a", false), now), vec![ACK]);
+        assert_eq!(xmodem.update(&[EOT], now), vec![ACK]);
+        assert_eq!(xmodem.take_data(), b"a");
+    }
+
+    #[test]
+    fn test_receive_abort() {
+        let now = Instant::now();
+        let mut xmodem = Xmodem::receive(now);
+        xmodem.tick(now);
+        assert_eq!(xmodem.update(&packet(2, b"a", true), now), vec![CAN, CAN, CAN]);
+        let reason = String::from("Block out of sequence");
+        assert_eq!(xmodem.get_state(), &XmodemState::Failed(reason));
+
+        xmodem = Xmodem::receive(now);
+        xmodem.tick(now);
+        assert_eq!(xmodem.update(&[CAN], now), vec![]);
+        assert!(xmodem.is_finished() == false);
+        xmodem.update(&[CAN], now);
+        let reason = String::from("Cancelled by the sender");
+        assert_eq!(xmodem.get_state(), &XmodemState::Failed(reason));
+    }
+}